@@ -0,0 +1,520 @@
+use std::{collections::HashMap, time::Duration};
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "position")]
+use crate::{position::Position, trade::{Order, OrderQuery}};
+use crate::{query, AccountType, BybitRequest, Category, Client, IntoGetRequest, MarginMode};
+
+// Also what the private websocket wallet topic pushes. Margin rates and totals are only filled in for
+// unified accounts, fields Bybit has deprecated or only sends for some account types default to empty
+#[derive(Debug, Clone, Deserialize)]
+pub struct WalletBalance {
+    #[serde(rename = "accountType")]
+    pub account_type: AccountType,
+    #[serde(rename = "totalEquity")]
+    pub total_equity: String,
+    #[serde(rename = "totalWalletBalance")]
+    pub total_wallet_balance: String,
+    #[serde(rename = "totalMarginBalance")]
+    pub total_margin_balance: String,
+    #[serde(rename = "totalAvailableBalance")]
+    pub total_available_balance: String,
+    #[serde(rename = "totalPerpUPL")]
+    pub total_perp_upl: String,
+    #[serde(rename = "totalInitialMargin")]
+    pub total_initial_margin: String,
+    #[serde(rename = "totalMaintenanceMargin")]
+    pub total_maintenance_margin: String,
+    #[serde(rename = "accountIMRate")]
+    pub account_im_rate: String,
+    #[serde(rename = "accountMMRate")]
+    pub account_mm_rate: String,
+    #[serde(rename = "accountLTV", default)]
+    pub account_ltv: String,
+    pub coin: Vec<CoinBalance>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CoinBalance {
+    pub coin: String,
+    pub equity: String,
+    #[serde(rename = "usdValue")]
+    pub usd_value: String,
+    #[serde(rename = "walletBalance")]
+    pub wallet_balance: String,
+    #[serde(rename = "availableToWithdraw", default)]
+    pub available_to_withdraw: String,
+    #[serde(rename = "availableToBorrow", default)]
+    pub available_to_borrow: String,
+    #[serde(rename = "borrowAmount")]
+    pub borrow_amount: String,
+    #[serde(rename = "accruedInterest")]
+    pub accrued_interest: String,
+    pub locked: String,
+    #[serde(default)]
+    pub bonus: String,
+    #[serde(rename = "totalOrderIM")]
+    pub total_order_im: String,
+    #[serde(rename = "totalPositionIM")]
+    pub total_position_im: String,
+    #[serde(rename = "totalPositionMM")]
+    pub total_position_mm: String,
+    #[serde(rename = "unrealisedPnl")]
+    pub unrealised_pnl: String,
+    #[serde(rename = "cumRealisedPnl")]
+    pub cum_realised_pnl: String,
+    #[serde(rename = "collateralSwitch", default)]
+    pub collateral_switch: bool,
+    #[serde(rename = "marginCollateral", default)]
+    pub margin_collateral: bool,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WalletBalances {
+    pub list: Vec<WalletBalance>,
+}
+
+// which account generation the key belongs to, classic accounts lack the unified wallet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(from = "i32")]
+pub enum UnifiedMarginStatus {
+    Classic,
+    Unified1,
+    Unified1Pro,
+    Unified2,
+    Unified2Pro,
+    Other(i32)
+}
+
+impl From<i32> for UnifiedMarginStatus {
+    fn from(code: i32) -> Self {
+        match code {
+            1 => Self::Classic,
+            3 => Self::Unified1,
+            4 => Self::Unified1Pro,
+            5 => Self::Unified2,
+            6 => Self::Unified2Pro,
+            code => Self::Other(code),
+        }
+    }
+}
+
+impl UnifiedMarginStatus {
+    pub fn is_unified(&self) -> bool {
+        matches!(self, Self::Unified1 | Self::Unified1Pro | Self::Unified2 | Self::Unified2Pro)
+    }
+}
+
+// Bybit reports these switches as "ON"/"OFF"
+fn on_off<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    Ok(String::deserialize(deserializer)? == "ON")
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AccountInfo {
+    #[serde(rename = "unifiedMarginStatus")]
+    pub unified_margin_status: UnifiedMarginStatus,
+    #[serde(rename = "marginMode")]
+    pub margin_mode: MarginMode,
+    #[serde(rename = "isMasterTrader")]
+    pub is_master_trader: bool,
+    #[serde(rename = "spotHedgingStatus", deserialize_with = "on_off", default)]
+    pub spot_hedging: bool,
+    // disconnect-cancel-all, see dcp
+    #[serde(rename = "dcpStatus", deserialize_with = "on_off", default)]
+    pub dcp_enabled: bool,
+    // dcp window in seconds
+    #[serde(rename = "timeWindow", default)]
+    pub dcp_window: u64,
+    // 0 when the account isn't in an SMP group
+    #[serde(rename = "smpGroup", default)]
+    pub smp_group: i64,
+    #[serde(rename = "updatedTime")]
+    pub updated_time: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl Client {
+    pub fn get_account_info(&self, recv_window: &Duration) -> crate::Result<BybitRequest<AccountInfo>> {
+        #[derive(Serialize, Debug)]
+        struct AccountInfoRequest {}
+
+        impl IntoGetRequest for AccountInfoRequest {
+            const ENDPOINT: &'static str = "/v5/account/info";
+            type Response = AccountInfo;
+        }
+
+        AccountInfoRequest {}.as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+    // coins is optional for UNIFIED (all non zero balances) and required for CONTRACT
+    pub fn get_wallet_balance(&self, account_type: AccountType, coins: Vec<String>, recv_window: &Duration) -> crate::Result<BybitRequest<WalletBalances>> {
+        #[derive(Serialize, Debug)]
+        struct WalletBalanceRequest {
+            #[serde(rename = "accountType")]
+            account_type: AccountType,
+            coin: Vec<String>,
+        }
+
+        impl IntoGetRequest for WalletBalanceRequest {
+            const ENDPOINT: &'static str = "/v5/account/wallet-balance";
+            type Response = WalletBalances;
+        }
+
+        let request = WalletBalanceRequest {
+            account_type,
+            coin: coins,
+        };
+
+        request.as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+}
+
+// per coin borrowing and collateral terms of the unified account
+#[derive(Debug, Clone, Deserialize)]
+pub struct CollateralInfo {
+    pub currency: String,
+    #[serde(rename = "hourlyBorrowRate")]
+    pub hourly_borrow_rate: String,
+    #[serde(rename = "maxBorrowingAmount")]
+    pub max_borrowing_amount: String,
+    #[serde(rename = "borrowAmount")]
+    pub borrow_amount: String,
+    #[serde(rename = "availableToBorrow")]
+    pub available_to_borrow: String,
+    pub borrowable: bool,
+    #[serde(rename = "marginCollateral")]
+    pub margin_collateral: bool,
+    #[serde(rename = "collateralSwitch")]
+    pub collateral_switch: bool,
+    // share of the coin's value that counts as margin, tiered by amount on Bybit's side
+    #[serde(rename = "collateralRatio")]
+    pub collateral_ratio: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CollateralInfoList {
+    pub list: Vec<CollateralInfo>,
+}
+
+// Account level margin figures for the account's margin mode. Regular margin adds up per position IM and MM, under
+// portfolio margin Bybit derives both from stress scenarios over the whole portfolio, so per position estimates
+// (notional / leverage) are wrong there and only the wallet's totals can be trusted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarginRisk {
+    pub margin_mode: MarginMode,
+    pub margin_balance: Decimal,
+    pub initial_margin: Decimal,
+    pub maintenance_margin: Decimal,
+    pub im_rate: Decimal,
+    pub mm_rate: Decimal,
+}
+
+impl MarginRisk {
+    pub fn new(info: &AccountInfo, wallet: &WalletBalance) -> Self {
+        let decimal = |value: &str| value.parse().unwrap_or_default();
+        Self {
+            margin_mode: info.margin_mode,
+            margin_balance: decimal(&wallet.total_margin_balance),
+            initial_margin: decimal(&wallet.total_initial_margin),
+            maintenance_margin: decimal(&wallet.total_maintenance_margin),
+            im_rate: decimal(&wallet.account_im_rate),
+            mm_rate: decimal(&wallet.account_mm_rate),
+        }
+    }
+
+    pub fn is_portfolio(&self) -> bool {
+        self.margin_mode == MarginMode::Portfolio
+    }
+
+    // margin left for new orders
+    pub fn available_margin(&self) -> Decimal {
+        self.margin_balance - self.initial_margin
+    }
+
+    // loss the account can take before maintenance margin reaches the margin balance (mm_rate 100%) and
+    // liquidation starts, in every margin mode
+    pub fn liquidation_buffer(&self) -> Decimal {
+        self.margin_balance - self.maintenance_margin
+    }
+
+    pub fn is_liquidatable(&self) -> bool {
+        self.mm_rate >= Decimal::ONE
+    }
+
+    // IM an extra position of notional takes, None under portfolio margin where it depends on the rest of the
+    // portfolio and can even be negative for hedges
+    pub fn order_initial_margin(&self, notional: Decimal, leverage: Decimal) -> Option<Decimal> {
+        if self.is_portfolio() {
+            return None;
+        }
+        notional.abs().checked_div(leverage)
+    }
+}
+
+// usd value of the coins that count as margin, haircut by their collateral ratio
+pub fn collateral_value(coins: &[CoinBalance], collateral: &[CollateralInfo]) -> Decimal {
+    coins.iter()
+        .filter_map(|coin| {
+            let info = collateral.iter().find(|info| info.currency == coin.coin)?;
+            if !info.margin_collateral || !info.collateral_switch {
+                return None;
+            }
+            let value: Decimal = coin.usd_value.parse().ok()?;
+            Some(value * info.collateral_ratio.parse::<Decimal>().ok()?)
+        })
+        .sum()
+}
+
+impl Client {
+    // every coin without a currency
+    pub fn get_collateral_info(&self, currency: Option<String>, recv_window: &Duration) -> crate::Result<BybitRequest<CollateralInfoList>> {
+        #[derive(Serialize, Debug)]
+        struct CollateralInfoRequest {
+            currency: Option<String>,
+        }
+
+        impl IntoGetRequest for CollateralInfoRequest {
+            const ENDPOINT: &'static str = "/v5/account/collateral-info";
+            type Response = CollateralInfoList;
+        }
+
+        CollateralInfoRequest { currency }.as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+    pub async fn margin_risk<F, R, E>(&self, recv_window: &Duration, send: F) -> crate::Result<MarginRisk>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let send = &send;
+        let info = async move { self.get_account_info(recv_window)?.send(send).await };
+        let wallets = async move { self.get_wallet_balance(AccountType::UNIFIED, Vec::new(), recv_window)?.send(send).await };
+        let (info, wallets) = futures::future::try_join(info, wallets).await?;
+        let wallet = wallets.list.first().ok_or_else(|| crate::Error::Unexpected("no unified wallet".into()))?;
+        Ok(MarginRisk::new(&info, wallet))
+    }
+}
+
+// One balance change of the account: trades, funding settlements, fees, transfers, deliveries. Which fields are set
+// depends on the type, the rest come as empty strings
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TransactionLogEntry {
+    pub id: String,
+    #[serde(default)]
+    pub symbol: String,
+    #[serde(default)]
+    pub category: String,
+    #[serde(default)]
+    pub side: String,
+    // unix millis
+    #[serde(rename = "transactionTime")]
+    pub transaction_time: String,
+    // TRADE, SETTLEMENT (funding), DELIVERY, TRANSFER_IN, TRANSFER_OUT, LIQUIDATION, BONUS, FEE_REFUND, INTEREST, ...
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub qty: String,
+    // position size after the change
+    #[serde(default)]
+    pub size: String,
+    pub currency: String,
+    #[serde(rename = "tradePrice", default)]
+    pub trade_price: String,
+    // paid negative, received positive
+    #[serde(default)]
+    pub funding: String,
+    // paid positive, rebates negative
+    #[serde(default)]
+    pub fee: String,
+    #[serde(rename = "cashFlow", default)]
+    pub cash_flow: String,
+    // cash_flow - fee + funding, what the wallet balance moved by
+    pub change: String,
+    #[serde(rename = "cashBalance")]
+    pub cash_balance: String,
+    #[serde(rename = "feeRate", default)]
+    pub fee_rate: String,
+    #[serde(rename = "tradeId", default)]
+    pub trade_id: String,
+    #[serde(rename = "orderId", default)]
+    pub order_id: String,
+    #[serde(rename = "orderLinkId", default)]
+    pub order_link_id: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl TransactionLogEntry {
+    pub fn time(&self) -> Option<DateTime<Utc>> {
+        self.transaction_time.parse().ok().and_then(DateTime::from_timestamp_millis)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransactionLogPage {
+    pub list: Vec<TransactionLogEntry>,
+    #[serde(rename = "nextPageCursor")]
+    pub next_page_cursor: String,
+}
+
+impl TransactionLogPage {
+    pub fn next_cursor(&self) -> Option<&str> {
+        Some(self.next_page_cursor.as_str()).filter(|cursor| !cursor.is_empty())
+    }
+}
+
+// without a time range Bybit returns the last 24 hours, a range can't span more than 7 days
+#[derive(Debug, Clone, Serialize)]
+pub struct TransactionLogQuery {
+    // UNIFIED when None
+    #[serde(rename = "accountType")]
+    pub account_type: Option<AccountType>,
+    pub category: Option<Category>,
+    pub currency: Option<String>,
+    #[serde(rename = "baseCoin")]
+    pub base_coin: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    // unix millis
+    #[serde(rename = "startTime")]
+    pub start_time: Option<i64>,
+    #[serde(rename = "endTime")]
+    pub end_time: Option<i64>,
+    pub limit: Option<u32>,
+    pub cursor: Option<String>,
+}
+
+impl TransactionLogQuery {
+    pub fn new() -> Self {
+        Self {
+            account_type: None,
+            category: None,
+            currency: None,
+            base_coin: None,
+            kind: None,
+            start_time: None,
+            end_time: None,
+            limit: None,
+            cursor: None,
+        }
+    }
+
+    pub fn with_category(mut self, category: Category) -> Self {
+        self.category = Some(category);
+        self
+    }
+
+    pub fn with_currency(mut self, currency: impl Into<String>) -> Self {
+        self.currency = Some(currency.into());
+        self
+    }
+
+    pub fn with_type(mut self, kind: impl Into<String>) -> Self {
+        self.kind = Some(kind.into());
+        self
+    }
+
+    pub fn with_time_range(mut self, start_time: i64, end_time: i64) -> Self {
+        self.start_time = Some(start_time);
+        self.end_time = Some(end_time);
+        self
+    }
+
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn with_cursor(mut self, cursor: Option<&str>) -> Self {
+        self.cursor = cursor.map(query::decode);
+        self
+    }
+}
+
+impl Default for TransactionLogQuery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Client {
+    pub fn get_transaction_log(&self, query: &TransactionLogQuery, recv_window: &Duration) -> crate::Result<BybitRequest<TransactionLogPage>> {
+        #[derive(Serialize, Debug)]
+        struct TransactionLogRequest<'a>(&'a TransactionLogQuery);
+
+        impl IntoGetRequest for TransactionLogRequest<'_> {
+            const ENDPOINT: &'static str = "/v5/account/transaction-log";
+            type Response = TransactionLogPage;
+        }
+
+        TransactionLogRequest(query).as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+    pub async fn get_all_transaction_log<F, R, E>(&self, query: &TransactionLogQuery, recv_window: &Duration, send: F) -> crate::Result<Vec<TransactionLogEntry>>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let mut query = query.clone();
+        let mut entries = Vec::new();
+        loop {
+            let page = self.get_transaction_log(&query, recv_window)?.send(&send).await?;
+            query = query.with_cursor(page.next_cursor());
+            entries.extend(page.list);
+            if query.cursor.is_none() {
+                return Ok(entries);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "position")]
+#[derive(Debug, Clone)]
+pub struct AccountSnapshot {
+    // when the snapshot was requested, every part is fetched after this
+    pub taken_at: DateTime<Utc>,
+    pub wallets: Vec<WalletBalance>,
+    pub positions: Vec<Position>,
+    pub open_orders: Vec<Order>,
+}
+
+#[cfg(feature = "position")]
+impl Client {
+    // Fetches the unified wallet, positions and open orders for every scope concurrently. Scopes are a category plus
+    // the settle coin linear and inverse need (see position::FlattenAll), spot scopes only contribute open orders
+    pub async fn snapshot<F, R, E>(&self, scopes: &[(Category, Option<String>)], recv_window: &Duration, send: F) -> crate::Result<AccountSnapshot>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let taken_at = self.clock.now();
+        let send = &send;
+        let wallets = async move { self.get_wallet_balance(AccountType::UNIFIED, Vec::new(), recv_window)?.send(send).await };
+        let positions = futures::future::try_join_all(scopes.iter()
+            .filter(|(category, _)| *category != Category::Spot)
+            .map(|(category, settle_coin)| self.get_all_positions(*category, settle_coin.clone(), recv_window, send)));
+        let open_orders = futures::future::try_join_all(scopes.iter().map(|(category, settle_coin)| {
+            let mut query = OrderQuery::new(*category);
+            query.settle_coin = settle_coin.clone();
+            async move { self.get_all_open_orders(&query, recv_window, send).await }
+        }));
+        let (wallets, positions, open_orders) = futures::future::try_join3(wallets, positions, open_orders).await?;
+        Ok(AccountSnapshot {
+            taken_at,
+            wallets: wallets.list,
+            positions: positions.into_iter().flatten().collect(),
+            open_orders: open_orders.into_iter().flatten().collect(),
+        })
+    }
+}
@@ -0,0 +1,5 @@
+mod candles;
+mod flow;
+
+pub use candles::*;
+pub use flow::*;
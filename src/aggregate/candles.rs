@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candle {
+    pub start: DateTime<Utc>,
+    pub interval: Duration,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    pub volume: Decimal,
+    pub turnover: Decimal,
+    pub trades: u64,
+}
+
+impl Candle {
+    pub fn end(&self) -> DateTime<Utc> {
+        self.start + self.interval
+    }
+
+    fn open_at(start: DateTime<Utc>, interval: Duration, price: Decimal) -> Self {
+        Self {
+            start,
+            interval,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: Decimal::ZERO,
+            turnover: Decimal::ZERO,
+            trades: 0,
+        }
+    }
+
+    fn add(&mut self, price: Decimal, qty: Decimal) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.volume += qty;
+        self.turnover += price * qty;
+        self.trades += 1;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CandleUpdate {
+    Forming(Candle),
+    Closed(Candle),
+}
+
+// Builds OHLCV candles of any interval from individual trades, intervals are aligned to the unix epoch
+// like Bybit's own klines. Trades older than the forming candle are ignored
+#[derive(Debug, Clone)]
+pub struct CandleBuilder {
+    interval: Duration,
+    fill_gaps: bool,
+    current: Option<Candle>,
+}
+
+impl CandleBuilder {
+    pub fn new(interval: Duration) -> Self {
+        Self { interval, fill_gaps: false, current: None }
+    }
+
+    // emit flat candles at the previous close for intervals without trades
+    pub fn with_gap_filling(mut self) -> Self {
+        self.fill_gaps = true;
+        self
+    }
+
+    pub fn current(&self) -> Option<&Candle> {
+        self.current.as_ref()
+    }
+
+    pub fn on_trade(&mut self, time: DateTime<Utc>, price: Decimal, qty: Decimal) -> Vec<CandleUpdate> {
+        let start = self.bucket(time);
+        let mut updates = self.roll(start);
+        let interval = self.interval;
+        match &mut self.current {
+            Some(candle) if candle.start > start => return updates,
+            Some(candle) => candle.add(price, qty),
+            None => {
+                let mut candle = Candle::open_at(start, interval, price);
+                candle.add(price, qty);
+                self.current = Some(candle);
+            }
+        }
+        updates.extend(self.current.clone().map(CandleUpdate::Forming));
+        updates
+    }
+
+    // closes the forming candle once its interval has passed, call this on a timer so quiet markets still close candles
+    pub fn flush(&mut self, now: DateTime<Utc>) -> Vec<CandleUpdate> {
+        let start = self.bucket(now);
+        self.roll(start)
+    }
+
+    fn roll(&mut self, start: DateTime<Utc>) -> Vec<CandleUpdate> {
+        let mut updates = Vec::new();
+        let Some(closed) = self.current.take_if(|current| current.start < start) else {
+            return updates;
+        };
+        let close = closed.close;
+        let mut next = closed.end();
+        updates.push(CandleUpdate::Closed(closed));
+        if self.fill_gaps {
+            while next < start {
+                let flat = Candle::open_at(next, self.interval, close);
+                next = flat.end();
+                updates.push(CandleUpdate::Closed(flat));
+            }
+        }
+        updates
+    }
+
+    fn bucket(&self, time: DateTime<Utc>) -> DateTime<Utc> {
+        let interval = (self.interval.as_millis() as i64).max(1);
+        let millis = time.timestamp_millis();
+        DateTime::from_timestamp_millis(millis - millis.rem_euclid(interval)).unwrap_or(time)
+    }
+}
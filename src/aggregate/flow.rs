@@ -0,0 +1,111 @@
+use std::{collections::VecDeque, time::Duration};
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+use crate::Side;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlowStats {
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub trades: u64,
+    pub volume: Decimal,
+    pub turnover: Decimal,
+    pub buy_volume: Decimal,
+    pub sell_volume: Decimal,
+}
+
+impl FlowStats {
+    pub fn vwap(&self) -> Option<Decimal> {
+        if self.volume.is_zero() {
+            return None;
+        }
+        Some(self.turnover / self.volume)
+    }
+
+    // (buy - sell) / (buy + sell), from -1 (all selling) to 1 (all buying)
+    pub fn imbalance(&self) -> Option<Decimal> {
+        if self.volume.is_zero() {
+            return None;
+        }
+        Some((self.buy_volume - self.sell_volume) / self.volume)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FlowTrade {
+    time: DateTime<Utc>,
+    side: Side,
+    price: Decimal,
+    qty: Decimal,
+}
+
+// Rolling window over the trade stream keeping running totals, so stats are cheap to read no matter how busy the symbol is
+#[derive(Debug, Clone)]
+pub struct TradeFlow {
+    window: Duration,
+    emit_every: Duration,
+    trades: VecDeque<FlowTrade>,
+    volume: Decimal,
+    turnover: Decimal,
+    buy_volume: Decimal,
+    last_emit: Option<DateTime<Utc>>,
+}
+
+impl TradeFlow {
+    pub fn new(window: Duration, emit_every: Duration) -> Self {
+        Self {
+            window,
+            emit_every,
+            trades: VecDeque::new(),
+            volume: Decimal::ZERO,
+            turnover: Decimal::ZERO,
+            buy_volume: Decimal::ZERO,
+            last_emit: None,
+        }
+    }
+
+    // returns stats whenever emit_every has passed since the last emission
+    pub fn on_trade(&mut self, time: DateTime<Utc>, side: Side, price: Decimal, qty: Decimal) -> Option<FlowStats> {
+        self.volume += qty;
+        self.turnover += price * qty;
+        if side == Side::Buy {
+            self.buy_volume += qty;
+        }
+        self.trades.push_back(FlowTrade { time, side, price, qty });
+        self.tick(time)
+    }
+
+    // same as on_trade without a trade, drive it from a timer so quiet periods still emit
+    pub fn tick(&mut self, now: DateTime<Utc>) -> Option<FlowStats> {
+        match self.last_emit {
+            Some(last) if now < last + self.emit_every => None,
+            _ => {
+                self.last_emit = Some(now);
+                Some(self.stats(now))
+            }
+        }
+    }
+
+    pub fn stats(&mut self, now: DateTime<Utc>) -> FlowStats {
+        let window_start = now - self.window;
+        while self.trades.front().is_some_and(|trade| trade.time < window_start) {
+            let Some(trade) = self.trades.pop_front() else { break };
+            self.volume -= trade.qty;
+            self.turnover -= trade.price * trade.qty;
+            if trade.side == Side::Buy {
+                self.buy_volume -= trade.qty;
+            }
+        }
+        FlowStats {
+            window_start,
+            window_end: now,
+            trades: self.trades.len() as u64,
+            volume: self.volume,
+            turnover: self.turnover,
+            buy_volume: self.buy_volume,
+            sell_volume: self.volume - self.buy_volume,
+        }
+    }
+}
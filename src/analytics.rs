@@ -0,0 +1,84 @@
+use std::{ops::RangeInclusive, time::Duration};
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+use crate::{
+    market::{FundingHistoryQuery, FundingRate, OpenInterest, OpenInterestInterval, OpenInterestQuery},
+    Category, Client,
+};
+
+// One bucket of an aligned series. Both values are the last known as of the end of the bucket, carried forward
+// through buckets without a new observation and None before the first one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OiFundingPoint {
+    pub start: DateTime<Utc>,
+    pub open_interest: Option<Decimal>,
+    // against the previous bucket, None until both have a value
+    pub open_interest_change: Option<Decimal>,
+    // the last settled rate
+    pub funding_rate: Option<Decimal>,
+    // a funding settlement fell within the bucket
+    pub settled: bool,
+}
+
+// Joins open interest and funding rates, in any order, onto buckets of interval aligned to the unix epoch like
+// klines, from the one holding the start of range to the one holding its end. Observations from before the range
+// only seed the carried values
+pub fn align(open_interest: &[OpenInterest], funding: &[FundingRate], interval: Duration, range: RangeInclusive<DateTime<Utc>>) -> Vec<OiFundingPoint> {
+    let step = (interval.as_millis() as i64).max(1);
+    let bucket = |time: DateTime<Utc>| time.timestamp_millis() - time.timestamp_millis().rem_euclid(step);
+    let mut open_interest: Vec<(i64, Decimal)> = open_interest.iter().map(|oi| (oi.timestamp.timestamp_millis(), oi.open_interest)).collect();
+    let mut funding: Vec<(i64, Decimal)> = funding.iter().map(|rate| (rate.funding_rate_timestamp.timestamp_millis(), rate.funding_rate)).collect();
+    open_interest.sort_by_key(|(time, _)| *time);
+    funding.sort_by_key(|(time, _)| *time);
+
+    let (mut open_interest, mut funding) = (open_interest.into_iter().peekable(), funding.into_iter().peekable());
+    let (mut last_oi, mut last_rate) = (None, None);
+    let mut start = bucket(*range.start());
+    while let Some((_, oi)) = open_interest.next_if(|(time, _)| *time < start) {
+        last_oi = Some(oi);
+    }
+    while let Some((_, rate)) = funding.next_if(|(time, _)| *time < start) {
+        last_rate = Some(rate);
+    }
+    let mut points = Vec::new();
+    while start <= bucket(*range.end()) {
+        let end = start + step;
+        let previous = last_oi;
+        while let Some((_, oi)) = open_interest.next_if(|(time, _)| *time < end) {
+            last_oi = Some(oi);
+        }
+        let mut settled = false;
+        while let Some((time, rate)) = funding.next_if(|(time, _)| *time < end) {
+            last_rate = Some(rate);
+            settled |= time >= start;
+        }
+        points.push(OiFundingPoint {
+            start: DateTime::from_timestamp_millis(start).unwrap_or_default(),
+            open_interest: last_oi,
+            open_interest_change: previous.zip(last_oi).map(|(previous, oi)| oi - previous),
+            funding_rate: last_rate,
+            settled,
+        });
+        start = end;
+    }
+    points
+}
+
+impl Client {
+    // Open interest history at granularity and the funding rates over range, fetched concurrently and aligned onto
+    // buckets of resample. The funding history reaches a day further back so the rate in effect at the start is known
+    pub async fn open_interest_funding<F, R, E>(&self, category: Category, symbol: &str, granularity: OpenInterestInterval, resample: Duration, range: RangeInclusive<DateTime<Utc>>, send: F) -> crate::Result<Vec<OiFundingPoint>>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let (start, end) = (*range.start(), *range.end());
+        let oi_start = start - chrono::Duration::from_std(granularity.duration()).unwrap_or_default();
+        let open_interest = OpenInterestQuery::new(category, symbol, granularity).with_range(oi_start, end).with_limit(200);
+        let funding = FundingHistoryQuery::new(category, symbol).with_range(start - chrono::Duration::days(1), end);
+        let (open_interest, funding) = futures::try_join!(self.get_all_open_interest(&open_interest, &send), self.get_all_funding_history(&funding, &send))?;
+        Ok(align(&open_interest, &funding, resample, range))
+    }
+}
@@ -0,0 +1,905 @@
+use std::{collections::{BTreeMap, HashMap, HashSet}, time::Duration};
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::{query, AccountType, BybitRequest, Client, IntoGetRequest, IntoPostRequest};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BybitBalance {
+    pub coin: String,
+    #[serde(rename = "transferBalance")]
+    pub transfer_balance: String,
+    #[serde(rename = "walletBalance")]
+    pub wallet_balance: String,
+    pub bonus: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct FundingBalance {
+    #[serde(rename = "accountType")]
+    pub account_type: AccountType,
+    #[serde(rename = "memberId")]
+    pub member_id: String,
+    pub balance: Vec<BybitBalance>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl Client {
+    // member_id queries a sub-account's wallet from the master account
+    pub fn get_funding_balance(&self, coin: Option<String>, member_id: Option<String>, with_bonus: bool, recv_window: &Duration) -> crate::Result<BybitRequest<FundingBalance>> {
+        self.get_account_coins_balance(AccountType::FUND, coin, member_id, with_bonus, recv_window)
+    }
+
+    pub fn get_account_coins_balance(&self, account_type: AccountType, coin: Option<String>, member_id: Option<String>, with_bonus: bool, recv_window: &Duration) -> crate::Result<BybitRequest<FundingBalance>> {
+            #[derive(Serialize, Debug)]
+            struct FundingRequest {
+                #[serde(rename = "memberId")]
+                member_id: Option<String>,
+                #[serde(rename = "accountType")]
+                account_type: AccountType,
+                coin: Option<String>,
+                #[serde(rename = "withBonus")]
+                with_bonus: i32,
+            }
+
+            impl IntoGetRequest for FundingRequest {
+                const ENDPOINT: &'static str = "/v5/asset/transfer/query-account-coins-balance";
+                type Response = FundingBalance;
+            }
+
+            let request = FundingRequest {
+                        member_id,
+                        account_type,
+                        coin,
+                        with_bonus: with_bonus as i32,
+            };
+
+            request.as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+}
+
+// depositStatus codes, anything Bybit adds later lands in Other
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(from = "i32")]
+pub enum DepositStatus {
+    Unknown,
+    ToBeConfirmed,
+    Processing,
+    Success,
+    Failed,
+    PendingToFundingPool,
+    CreditedToFundingPool,
+    Other(i32)
+}
+
+impl From<i32> for DepositStatus {
+    fn from(code: i32) -> Self {
+        match code {
+            0 => Self::Unknown,
+            1 => Self::ToBeConfirmed,
+            2 => Self::Processing,
+            3 => Self::Success,
+            4 => Self::Failed,
+            10011 => Self::PendingToFundingPool,
+            10012 => Self::CreditedToFundingPool,
+            code => Self::Other(code),
+        }
+    }
+}
+
+impl DepositStatus {
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Success | Self::Failed | Self::CreditedToFundingPool)
+    }
+
+    pub fn is_success(&self) -> bool {
+        matches!(self, Self::Success | Self::CreditedToFundingPool)
+    }
+}
+
+// v5 reports withdrawal status as a name rather than a code
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+pub enum WithdrawalStatus {
+    SecurityCheck,
+    Pending,
+    #[serde(rename = "success")]
+    Success,
+    CancelByUser,
+    Reject,
+    Fail,
+    BlockchainConfirmed,
+    MoreInformationRequired,
+    Unknown,
+    #[serde(untagged)]
+    Other(String)
+}
+
+impl WithdrawalStatus {
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Success | Self::CancelByUser | Self::Reject | Self::Fail)
+    }
+
+    pub fn is_success(&self) -> bool {
+        matches!(self, Self::Success)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DepositChain {
+    #[serde(rename = "chainType")]
+    pub chain_type: String,
+    pub chain: String,
+    #[serde(rename = "addressDeposit")]
+    pub address_deposit: String,
+    #[serde(rename = "tagDeposit")]
+    pub tag_deposit: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DepositAddress {
+    pub coin: String,
+    pub chains: Vec<DepositChain>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DepositRecord {
+    // absent from sub member records on older accounts
+    #[serde(default)]
+    pub id: String,
+    pub coin: String,
+    pub chain: String,
+    pub amount: String,
+    #[serde(rename = "txID")]
+    pub tx_id: String,
+    pub status: DepositStatus,
+    #[serde(rename = "toAddress")]
+    pub to_address: String,
+    pub tag: String,
+    #[serde(rename = "depositFee")]
+    pub deposit_fee: String,
+    #[serde(rename = "successAt")]
+    pub success_at: String,
+    // confirmations seen so far, as a string
+    pub confirmations: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DepositPage {
+    pub rows: Vec<DepositRecord>,
+    #[serde(rename = "nextPageCursor")]
+    pub next_page_cursor: String,
+}
+
+impl DepositPage {
+    pub fn next_cursor(&self) -> Option<&str> {
+        Some(self.next_page_cursor.as_str()).filter(|cursor| !cursor.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DepositQuery {
+    #[serde(rename = "txID")]
+    pub tx_id: Option<String>,
+    pub coin: Option<String>,
+    // unix millis, Bybit defaults to the last 30 days
+    #[serde(rename = "startTime")]
+    pub start_time: Option<i64>,
+    #[serde(rename = "endTime")]
+    pub end_time: Option<i64>,
+    pub limit: Option<u32>,
+    pub cursor: Option<String>,
+}
+
+impl DepositQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_tx_id(mut self, tx_id: impl Into<String>) -> Self {
+        self.tx_id = Some(tx_id.into());
+        self
+    }
+
+    pub fn with_coin(mut self, coin: impl Into<String>) -> Self {
+        self.coin = Some(coin.into());
+        self
+    }
+
+    pub fn with_time_range(mut self, start_time: i64, end_time: i64) -> Self {
+        self.start_time = Some(start_time);
+        self.end_time = Some(end_time);
+        self
+    }
+
+    pub fn with_cursor(mut self, cursor: Option<&str>) -> Self {
+        self.cursor = cursor.map(query::decode);
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WalletShare {
+    pub member_id: String,
+    pub account_type: AccountType,
+    pub wallet_balance: Decimal,
+    pub transfer_balance: Decimal,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CoinTotal {
+    pub coin: String,
+    pub wallet_balance: Decimal,
+    pub transfer_balance: Decimal,
+    pub wallets: Vec<WalletShare>,
+}
+
+fn decimal(value: &str) -> crate::Result<Decimal> {
+    if value.is_empty() {
+        return Ok(Decimal::ZERO);
+    }
+    value.parse().map_err(|err| crate::Error::Unexpected(format!("{value:?} isn't a number: {err}")))
+}
+
+impl Client {
+    // queries every wallet type for the caller and each sub member concurrently and merges them per coin
+    pub async fn total_balances<F, R, E>(&self, account_types: &[AccountType], sub_member_ids: &[String], recv_window: &Duration, send: F) -> crate::Result<BTreeMap<String, CoinTotal>>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let members = std::iter::once(None).chain(sub_member_ids.iter().cloned().map(Some));
+        let requests = members.flat_map(|member_id| {
+            account_types.iter().map(move |account_type| (account_type.clone(), member_id.clone()))
+        });
+        let send = &send;
+        let balances = futures::future::try_join_all(requests.map(|(account_type, member_id)| async move {
+            self.get_account_coins_balance(account_type, None, member_id, false, recv_window)?.send(send).await
+        })).await?;
+
+        let mut totals: BTreeMap<String, CoinTotal> = BTreeMap::new();
+        for wallet in balances {
+            for balance in wallet.balance {
+                let wallet_balance = decimal(&balance.wallet_balance)?;
+                let transfer_balance = decimal(&balance.transfer_balance)?;
+                if wallet_balance.is_zero() && transfer_balance.is_zero() {
+                    continue;
+                }
+                let total = totals.entry(balance.coin.clone()).or_insert_with(|| CoinTotal { coin: balance.coin, ..Default::default() });
+                total.wallet_balance += wallet_balance;
+                total.transfer_balance += transfer_balance;
+                total.wallets.push(WalletShare {
+                    member_id: wallet.member_id.clone(),
+                    account_type: wallet.account_type.clone(),
+                    wallet_balance,
+                    transfer_balance,
+                });
+            }
+        }
+        Ok(totals)
+    }
+}
+
+// transferId has to be a UUID, resending a request with the same id is how a transfer is retried safely
+pub fn new_transfer_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum TransferStatus {
+    SUCCESS,
+    PENDING,
+    FAILED,
+    #[serde(rename = "STATUS_UNKNOWN")]
+    UNKNOWN
+}
+
+impl TransferStatus {
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::SUCCESS | Self::FAILED)
+    }
+}
+
+// between two wallets of the same (sub) account
+#[derive(Debug, Clone, Serialize)]
+pub struct InternalTransferRequest {
+    #[serde(rename = "transferId")]
+    pub transfer_id: String,
+    pub coin: String,
+    pub amount: Decimal,
+    #[serde(rename = "fromAccountType")]
+    pub from_account_type: AccountType,
+    #[serde(rename = "toAccountType")]
+    pub to_account_type: AccountType,
+}
+
+impl InternalTransferRequest {
+    pub fn new(coin: impl Into<String>, amount: Decimal, from_account_type: AccountType, to_account_type: AccountType) -> Self {
+        Self { transfer_id: new_transfer_id(), coin: coin.into(), amount, from_account_type, to_account_type }
+    }
+
+    pub fn with_transfer_id(mut self, transfer_id: impl Into<String>) -> Self {
+        self.transfer_id = transfer_id.into();
+        self
+    }
+}
+
+impl IntoPostRequest for InternalTransferRequest {
+    const ENDPOINT: &'static str = "/v5/asset/transfer/inter-transfer";
+    type Response = TransferResult;
+}
+
+// between the master and its sub accounts or between two subs, signed by the master key
+#[derive(Debug, Clone, Serialize)]
+pub struct UniversalTransferRequest {
+    #[serde(rename = "transferId")]
+    pub transfer_id: String,
+    pub coin: String,
+    pub amount: Decimal,
+    #[serde(rename = "fromMemberId")]
+    pub from_member_id: u64,
+    #[serde(rename = "toMemberId")]
+    pub to_member_id: u64,
+    #[serde(rename = "fromAccountType")]
+    pub from_account_type: AccountType,
+    #[serde(rename = "toAccountType")]
+    pub to_account_type: AccountType,
+}
+
+impl UniversalTransferRequest {
+    pub fn new(coin: impl Into<String>, amount: Decimal, from: (u64, AccountType), to: (u64, AccountType)) -> Self {
+        Self {
+            transfer_id: new_transfer_id(),
+            coin: coin.into(),
+            amount,
+            from_member_id: from.0,
+            to_member_id: to.0,
+            from_account_type: from.1,
+            to_account_type: to.1,
+        }
+    }
+
+    pub fn with_transfer_id(mut self, transfer_id: impl Into<String>) -> Self {
+        self.transfer_id = transfer_id.into();
+        self
+    }
+}
+
+impl IntoPostRequest for UniversalTransferRequest {
+    const ENDPOINT: &'static str = "/v5/asset/transfer/universal-transfer";
+    type Response = TransferResult;
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransferResult {
+    #[serde(rename = "transferId")]
+    pub transfer_id: String,
+    pub status: Option<TransferStatus>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+// member ids are only present in universal transfer records
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransferRecord {
+    #[serde(rename = "transferId")]
+    pub transfer_id: String,
+    pub coin: String,
+    pub amount: String,
+    #[serde(rename = "fromMemberId")]
+    pub from_member_id: Option<String>,
+    #[serde(rename = "toMemberId")]
+    pub to_member_id: Option<String>,
+    #[serde(rename = "fromAccountType")]
+    pub from_account_type: AccountType,
+    #[serde(rename = "toAccountType")]
+    pub to_account_type: AccountType,
+    pub timestamp: String,
+    pub status: TransferStatus,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransferPage {
+    pub list: Vec<TransferRecord>,
+    #[serde(rename = "nextPageCursor")]
+    pub next_page_cursor: String,
+}
+
+impl TransferPage {
+    pub fn next_cursor(&self) -> Option<&str> {
+        Some(self.next_page_cursor.as_str()).filter(|cursor| !cursor.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TransferQuery {
+    #[serde(rename = "transferId")]
+    pub transfer_id: Option<String>,
+    pub coin: Option<String>,
+    pub status: Option<TransferStatus>,
+    // unix millis, Bybit defaults to the last 7 days
+    #[serde(rename = "startTime")]
+    pub start_time: Option<i64>,
+    #[serde(rename = "endTime")]
+    pub end_time: Option<i64>,
+    pub limit: Option<u32>,
+    pub cursor: Option<String>,
+}
+
+impl TransferQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_transfer_id(mut self, transfer_id: impl Into<String>) -> Self {
+        self.transfer_id = Some(transfer_id.into());
+        self
+    }
+
+    pub fn with_coin(mut self, coin: impl Into<String>) -> Self {
+        self.coin = Some(coin.into());
+        self
+    }
+
+    pub fn with_cursor(mut self, cursor: Option<&str>) -> Self {
+        self.cursor = cursor.map(query::decode);
+        self
+    }
+}
+
+// one side of move_funds, member_id None is the account owning the api key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wallet {
+    pub member_id: Option<u64>,
+    pub account_type: AccountType,
+}
+
+impl Wallet {
+    pub fn own(account_type: AccountType) -> Self {
+        Self { member_id: None, account_type }
+    }
+
+    pub fn member(member_id: u64, account_type: AccountType) -> Self {
+        Self { member_id: Some(member_id), account_type }
+    }
+}
+
+pub const TRANSFER_POLL_INTERVAL: Duration = Duration::from_secs(1);
+pub const TRANSFER_MAX_POLLS: u32 = 30;
+
+#[derive(Debug, thiserror::Error)]
+pub enum TransferError {
+    #[error("only {available} {coin} is transferable, {requested} requested")]
+    Insufficient { coin: String, available: Decimal, requested: Decimal },
+    #[error("a universal transfer needs both member ids, name the api key's own uid with Wallet::member")]
+    MissingMemberId,
+    #[error("transfer {0} still pending after polling")]
+    StillPending(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AllowedAddress {
+    pub address: String,
+    pub tag: Option<String>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AddressBookError {
+    #[error("{address} on {coin}/{chain} is not in the address book")]
+    NotAllowed { coin: String, chain: String, address: String },
+}
+
+// User maintained withdrawal allowlist, checked locally before a withdrawal is signed. Coins and chains
+// are matched case-insensitively, addresses and tags exactly since several chains use case-sensitive encodings
+#[derive(Debug, Clone, Default)]
+pub struct AddressBook {
+    entries: HashMap<(String, String), HashSet<AllowedAddress>>,
+}
+
+impl AddressBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn allow(&mut self, coin: &str, chain: &str, address: impl Into<String>, tag: Option<String>) {
+        self.entries
+            .entry((coin.to_uppercase(), chain.to_uppercase()))
+            .or_default()
+            .insert(AllowedAddress { address: address.into(), tag });
+    }
+
+    pub fn with(mut self, coin: &str, chain: &str, address: impl Into<String>, tag: Option<String>) -> Self {
+        self.allow(coin, chain, address, tag);
+        self
+    }
+
+    pub fn remove(&mut self, coin: &str, chain: &str, address: &str) {
+        if let Some(addresses) = self.entries.get_mut(&(coin.to_uppercase(), chain.to_uppercase())) {
+            addresses.retain(|allowed| allowed.address != address);
+        }
+    }
+
+    pub fn is_allowed(&self, coin: &str, chain: &str, address: &str, tag: Option<&str>) -> bool {
+        self.entries
+            .get(&(coin.to_uppercase(), chain.to_uppercase()))
+            .is_some_and(|addresses| addresses.iter().any(|allowed| allowed.address == address && allowed.tag.as_deref() == tag))
+    }
+
+    pub fn check(&self, coin: &str, chain: &str, address: &str, tag: Option<&str>) -> Result<(), AddressBookError> {
+        if self.is_allowed(coin, chain, address, tag) {
+            return Ok(());
+        }
+        Err(AddressBookError::NotAllowed { coin: coin.to_string(), chain: chain.to_string(), address: address.to_string() })
+    }
+}
+
+// the wallet a withdrawal is paid from, FundThenUnified tops up from the unified wallet when funding runs short
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum WithdrawAccount {
+    #[serde(rename = "FUND")]
+    Fund,
+    #[serde(rename = "UTA")]
+    Unified,
+    #[serde(rename = "FUND,UTA")]
+    FundThenUnified,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WithdrawRequest {
+    pub coin: String,
+    pub chain: String,
+    pub address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    pub amount: Decimal,
+    // unix millis, Bybit rejects replays of the same timestamp. Client::withdraw stamps it from the client's clock
+    // when unset, set it to have a resent withdrawal rejected instead of made twice
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+    #[serde(rename = "accountType")]
+    pub account_type: WithdrawAccount,
+    // 1 to withdraw on chain even when the address belongs to a Bybit user
+    #[serde(rename = "forceChain", skip_serializing_if = "Option::is_none")]
+    pub force_chain: Option<u8>,
+    // 1 deducts the fee from amount instead of on top of it
+    #[serde(rename = "feeType", skip_serializing_if = "Option::is_none")]
+    pub fee_type: Option<u8>,
+    #[serde(rename = "requestId", skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl WithdrawRequest {
+    pub fn new(coin: impl Into<String>, chain: impl Into<String>, address: impl Into<String>, amount: Decimal, account_type: WithdrawAccount) -> Self {
+        Self {
+            coin: coin.into(),
+            chain: chain.into(),
+            address: address.into(),
+            tag: None,
+            amount: amount.normalize(),
+            timestamp: None,
+            account_type,
+            force_chain: None,
+            fee_type: None,
+            request_id: None,
+        }
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    pub fn with_timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.timestamp = Some(timestamp.timestamp_millis());
+        self
+    }
+
+    pub fn force_chain(mut self) -> Self {
+        self.force_chain = Some(1);
+        self
+    }
+
+    pub fn fee_from_amount(mut self) -> Self {
+        self.fee_type = Some(1);
+        self
+    }
+
+    pub fn with_request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = Some(request_id.into());
+        self
+    }
+}
+
+impl IntoPostRequest for WithdrawRequest {
+    const ENDPOINT: &'static str = "/v5/asset/withdraw/create";
+    type Response = WithdrawResult;
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WithdrawResult {
+    pub id: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CancelWithdrawalResult {
+    // 1 when the withdrawal was cancelled, 0 when it had already progressed too far
+    pub status: i32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WithdrawalRecord {
+    #[serde(rename = "withdrawId")]
+    pub withdraw_id: String,
+    #[serde(rename = "txID")]
+    pub tx_id: String,
+    // 0 on chain, 1 off chain to another Bybit user
+    #[serde(rename = "withdrawType")]
+    pub withdraw_type: i32,
+    pub coin: String,
+    pub chain: String,
+    pub amount: String,
+    #[serde(rename = "withdrawFee")]
+    pub withdraw_fee: String,
+    pub status: WithdrawalStatus,
+    #[serde(rename = "toAddress")]
+    pub to_address: String,
+    pub tag: String,
+    #[serde(rename = "createTime")]
+    pub create_time: String,
+    #[serde(rename = "updateTime")]
+    pub update_time: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WithdrawalPage {
+    pub rows: Vec<WithdrawalRecord>,
+    #[serde(rename = "nextPageCursor")]
+    pub next_page_cursor: String,
+}
+
+impl WithdrawalPage {
+    pub fn next_cursor(&self) -> Option<&str> {
+        Some(self.next_page_cursor.as_str()).filter(|cursor| !cursor.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WithdrawalQuery {
+    #[serde(rename = "withdrawID")]
+    pub withdraw_id: Option<String>,
+    #[serde(rename = "txID")]
+    pub tx_id: Option<String>,
+    pub coin: Option<String>,
+    // 0 on chain (Bybit's default), 1 off chain, 2 both
+    #[serde(rename = "withdrawType")]
+    pub withdraw_type: Option<i32>,
+    // unix millis, Bybit defaults to the last 30 days
+    #[serde(rename = "startTime")]
+    pub start_time: Option<i64>,
+    #[serde(rename = "endTime")]
+    pub end_time: Option<i64>,
+    pub limit: Option<u32>,
+    pub cursor: Option<String>,
+}
+
+impl WithdrawalQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_withdraw_id(mut self, withdraw_id: impl Into<String>) -> Self {
+        self.withdraw_id = Some(withdraw_id.into());
+        self
+    }
+
+    pub fn with_coin(mut self, coin: impl Into<String>) -> Self {
+        self.coin = Some(coin.into());
+        self
+    }
+
+    pub fn with_cursor(mut self, cursor: Option<&str>) -> Self {
+        self.cursor = cursor.map(query::decode);
+        self
+    }
+}
+
+impl Client {
+    pub fn create_internal_transfer(&self, request: &InternalTransferRequest, recv_window: &Duration) -> crate::Result<BybitRequest<TransferResult>> {
+        request.as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+    pub fn create_universal_transfer(&self, request: &UniversalTransferRequest, recv_window: &Duration) -> crate::Result<BybitRequest<TransferResult>> {
+        request.as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+    pub fn get_internal_transfers(&self, query: &TransferQuery, recv_window: &Duration) -> crate::Result<BybitRequest<TransferPage>> {
+        #[derive(Serialize, Debug)]
+        struct InternalTransfersRequest<'a>(&'a TransferQuery);
+
+        impl IntoGetRequest for InternalTransfersRequest<'_> {
+            const ENDPOINT: &'static str = "/v5/asset/transfer/query-inter-transfer-list";
+            type Response = TransferPage;
+        }
+
+        InternalTransfersRequest(query).as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+    pub fn get_universal_transfers(&self, query: &TransferQuery, recv_window: &Duration) -> crate::Result<BybitRequest<TransferPage>> {
+        #[derive(Serialize, Debug)]
+        struct UniversalTransfersRequest<'a>(&'a TransferQuery);
+
+        impl IntoGetRequest for UniversalTransfersRequest<'_> {
+            const ENDPOINT: &'static str = "/v5/asset/transfer/query-universal-transfer-list";
+            type Response = TransferPage;
+        }
+
+        UniversalTransfersRequest(query).as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+    pub fn withdraw(&self, request: &WithdrawRequest, recv_window: &Duration) -> crate::Result<BybitRequest<WithdrawResult>> {
+        let now = self.clock.now();
+        let mut request = request.clone();
+        request.timestamp.get_or_insert(now.timestamp_millis());
+        request.as_request_at(self.environment.base_url(), self.credentials()?, recv_window, now)
+    }
+
+    // withdraw, refusing before anything is signed when the destination isn't in the address book
+    pub fn withdraw_checked(&self, address_book: &AddressBook, request: &WithdrawRequest, recv_window: &Duration) -> crate::Result<BybitRequest<WithdrawResult>> {
+        address_book.check(&request.coin, &request.chain, &request.address, request.tag.as_deref()).map_err(|err| crate::Error::Invalid(err.into()))?;
+        self.withdraw(request, recv_window)
+    }
+
+    pub fn cancel_withdrawal(&self, id: &str, recv_window: &Duration) -> crate::Result<BybitRequest<CancelWithdrawalResult>> {
+        #[derive(Serialize, Debug)]
+        struct CancelWithdrawalRequest<'a> {
+            id: &'a str,
+        }
+
+        impl IntoPostRequest for CancelWithdrawalRequest<'_> {
+            const ENDPOINT: &'static str = "/v5/asset/withdraw/cancel";
+            type Response = CancelWithdrawalResult;
+        }
+
+        CancelWithdrawalRequest { id }.as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+    pub fn get_withdrawal_records(&self, query: &WithdrawalQuery, recv_window: &Duration) -> crate::Result<BybitRequest<WithdrawalPage>> {
+        #[derive(Serialize, Debug)]
+        struct WithdrawalRecordsRequest<'a>(&'a WithdrawalQuery);
+
+        impl IntoGetRequest for WithdrawalRecordsRequest<'_> {
+            const ENDPOINT: &'static str = "/v5/asset/withdraw/query-record";
+            type Response = WithdrawalPage;
+        }
+
+        WithdrawalRecordsRequest(query).as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+    // chain_type narrows the result to a single chain
+    pub fn get_deposit_address(&self, coin: &str, chain_type: Option<&str>, recv_window: &Duration) -> crate::Result<BybitRequest<DepositAddress>> {
+        #[derive(Serialize, Debug)]
+        struct DepositAddressRequest<'a> {
+            coin: &'a str,
+            #[serde(rename = "chainType")]
+            chain_type: Option<&'a str>,
+        }
+
+        impl IntoGetRequest for DepositAddressRequest<'_> {
+            const ENDPOINT: &'static str = "/v5/asset/deposit/query-address";
+            type Response = DepositAddress;
+        }
+
+        DepositAddressRequest { coin, chain_type }.as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+    // master key only, chain_type is required for sub member addresses
+    pub fn get_sub_deposit_address(&self, sub_member_id: &str, coin: &str, chain_type: &str, recv_window: &Duration) -> crate::Result<BybitRequest<DepositAddress>> {
+        #[derive(Serialize, Debug)]
+        struct SubDepositAddressRequest<'a> {
+            coin: &'a str,
+            #[serde(rename = "chainType")]
+            chain_type: &'a str,
+            #[serde(rename = "subMemberId")]
+            sub_member_id: &'a str,
+        }
+
+        impl IntoGetRequest for SubDepositAddressRequest<'_> {
+            const ENDPOINT: &'static str = "/v5/asset/deposit/query-sub-member-address";
+            type Response = DepositAddress;
+        }
+
+        SubDepositAddressRequest { coin, chain_type, sub_member_id }.as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+    pub fn get_deposit_records(&self, query: &DepositQuery, recv_window: &Duration) -> crate::Result<BybitRequest<DepositPage>> {
+        #[derive(Serialize, Debug)]
+        struct DepositRecordsRequest<'a>(&'a DepositQuery);
+
+        impl IntoGetRequest for DepositRecordsRequest<'_> {
+            const ENDPOINT: &'static str = "/v5/asset/deposit/query-record";
+            type Response = DepositPage;
+        }
+
+        DepositRecordsRequest(query).as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+    pub fn get_sub_deposit_records(&self, sub_member_id: &str, query: &DepositQuery, recv_window: &Duration) -> crate::Result<BybitRequest<DepositPage>> {
+        #[derive(Serialize, Debug)]
+        struct SubDepositRecordsRequest<'a> {
+            #[serde(rename = "subMemberId")]
+            sub_member_id: &'a str,
+            #[serde(flatten)]
+            query: &'a DepositQuery,
+        }
+
+        impl IntoGetRequest for SubDepositRecordsRequest<'_> {
+            const ENDPOINT: &'static str = "/v5/asset/deposit/query-sub-member-record";
+            type Response = DepositPage;
+        }
+
+        SubDepositRecordsRequest { sub_member_id, query }.as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+    // Moves amount of coin between two wallets: an internal transfer within one account, a universal transfer across
+    // members. Checks the transferable balance first and polls the transfer records until it settles
+    pub async fn move_funds<F, R, E>(&self, coin: &str, amount: Decimal, from: Wallet, to: Wallet, recv_window: &Duration, send: F) -> crate::Result<TransferRecord>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let balance = self.get_account_coins_balance(from.account_type.clone(), Some(coin.to_string()), from.member_id.map(|id| id.to_string()), false, recv_window)
+            ?.send(&send)
+            .await?;
+        let available = balance.balance.iter()
+            .filter(|balance| balance.coin == coin)
+            .map(|balance| decimal(&balance.transfer_balance))
+            .sum::<crate::Result<Decimal>>()?;
+        if available < amount {
+            return Err(TransferError::Insufficient { coin: coin.to_string(), available, requested: amount }.into());
+        }
+
+        // only the key's own account can use an internal transfer, anything naming a member goes through universal
+        let internal = from.member_id.is_none() && to.member_id.is_none();
+        let transfer_id = if internal {
+            let request = InternalTransferRequest::new(coin, amount, from.account_type, to.account_type);
+            self.create_internal_transfer(&request, recv_window)?.send(&send).await?.transfer_id
+        } else {
+            let (Some(from_member), Some(to_member)) = (from.member_id, to.member_id) else {
+                return Err(TransferError::MissingMemberId.into());
+            };
+            let request = UniversalTransferRequest::new(coin, amount, (from_member, from.account_type), (to_member, to.account_type));
+            self.create_universal_transfer(&request, recv_window)?.send(&send).await?.transfer_id
+        };
+
+        let query = TransferQuery::new().with_transfer_id(transfer_id.clone());
+        for _ in 0..TRANSFER_MAX_POLLS {
+            let page = if internal {
+                self.get_internal_transfers(&query, recv_window)?.send(&send).await?
+            } else {
+                self.get_universal_transfers(&query, recv_window)?.send(&send).await?
+            };
+            if let Some(record) = page.list.into_iter().find(|record| record.transfer_id == transfer_id)
+                && record.status.is_terminal()
+            {
+                return Ok(record);
+            }
+            futures_timer::Delay::new(TRANSFER_POLL_INTERVAL).await;
+        }
+        Err(TransferError::StillPending(transfer_id).into())
+    }
+}
@@ -0,0 +1,231 @@
+// Command line front end for common operations, e.g. for runbooks. Signed commands read their credentials the way
+// BybitConfig::from_env does, klines works without any. Tables go to stdout tab separated with a header line
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use bybit_rs::{
+    account::WalletBalances,
+    blocking,
+    export::CsvExporter,
+    position::PositionQuery,
+    trade::{CancelOrderRequest, OrderQuery, OrderRef, PlaceOrderRequest},
+    AccountType, BoxError, BybitConfig, Category, Client, Environment, Interval, Side,
+};
+use bytes::Bytes;
+use chrono::{NaiveDate, Utc};
+use clap::{ArgGroup, Parser, Subcommand};
+use rust_decimal::Decimal;
+
+#[derive(Debug, Parser)]
+#[command(name = "bybit", version, about = "Bybit v5 REST from the command line")]
+struct Cli {
+    #[arg(long, global = true, value_parser = parse_enum::<Environment>, help = "mainnet, testnet or demo, overrides BYBIT_ENVIRONMENT")]
+    environment: Option<Environment>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    #[command(about = "Unified wallet balance per coin")]
+    Balance {
+        #[arg(long, help = "only this coin, may be repeated")]
+        coin: Vec<String>,
+    },
+    #[command(about = "Open positions, linear and inverse need --symbol or --settle-coin")]
+    Positions {
+        #[arg(long, value_parser = parse_enum::<Category>)]
+        category: Category,
+        #[arg(long)]
+        symbol: Option<String>,
+        #[arg(long)]
+        settle_coin: Option<String>,
+    },
+    #[command(about = "Open orders")]
+    Orders {
+        #[arg(long, value_parser = parse_enum::<Category>)]
+        category: Category,
+        #[arg(long)]
+        symbol: Option<String>,
+        #[arg(long)]
+        settle_coin: Option<String>,
+    },
+    #[command(about = "Place a market order, or a limit order with --price")]
+    Place {
+        #[arg(long, value_parser = parse_enum::<Category>)]
+        category: Category,
+        #[arg(long)]
+        symbol: String,
+        #[arg(long, value_parser = parse_enum::<Side>, help = "Buy or Sell")]
+        side: Side,
+        #[arg(long)]
+        qty: Decimal,
+        #[arg(long)]
+        price: Option<Decimal>,
+        #[arg(long)]
+        order_link_id: Option<String>,
+        #[arg(long)]
+        reduce_only: bool,
+    },
+    #[command(about = "Cancel an order by id or link id", group(ArgGroup::new("order").required(true).args(["order_id", "order_link_id"])))]
+    Cancel {
+        #[arg(long, value_parser = parse_enum::<Category>)]
+        category: Category,
+        #[arg(long)]
+        symbol: String,
+        #[arg(long)]
+        order_id: Option<String>,
+        #[arg(long)]
+        order_link_id: Option<String>,
+    },
+    #[command(about = "Klines as CSV, newest first")]
+    Klines {
+        #[arg(long, value_parser = parse_enum::<Category>)]
+        category: Category,
+        #[arg(long)]
+        symbol: String,
+        #[arg(long, value_parser = parse_enum::<Interval>, help = "1, 3, 5, 15, 30, 60, 120, 240, 360, 720, D, W or M")]
+        interval: Interval,
+        #[arg(long, value_parser = parse_time, help = "unix millis, RFC 3339 or YYYY-MM-DD")]
+        start: i64,
+        #[arg(long, value_parser = parse_time, help = "unix millis, RFC 3339 or YYYY-MM-DD, defaults to now")]
+        end: Option<i64>,
+        #[arg(long, help = "file to write instead of stdout")]
+        out: Option<PathBuf>,
+    },
+}
+
+// the names the API uses, so the enums parse like they deserialize
+fn parse_enum<T: serde::de::DeserializeOwned>(value: &str) -> Result<T, String> {
+    serde_json::from_value(serde_json::Value::String(value.to_string()))
+        .or_else(|_| serde_json::from_value(serde_json::Value::String(value.to_lowercase())))
+        .map_err(|_| format!("unknown value {value:?}"))
+}
+
+fn parse_time(value: &str) -> Result<i64, String> {
+    if let Ok(millis) = value.parse() {
+        return Ok(millis);
+    }
+    if let Ok(time) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(time.timestamp_millis());
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| date.and_time(chrono::NaiveTime::MIN).and_utc().timestamp_millis())
+        .map_err(|_| format!("{value:?} is neither unix millis, RFC 3339 nor YYYY-MM-DD"))
+}
+
+fn transport(agent: &ureq::Agent) -> impl Fn(http::Request<String>) -> Result<Bytes, ureq::Error> + '_ {
+    move |request| {
+        let mut response = agent.run(request)?;
+        Ok(Bytes::from(response.body_mut().read_to_vec()?))
+    }
+}
+
+fn table(header: &[&str], rows: impl IntoIterator<Item = Vec<String>>) -> std::io::Result<()> {
+    let mut out = std::io::stdout().lock();
+    writeln!(out, "{}", header.join("\t"))?;
+    for row in rows {
+        writeln!(out, "{}", row.join("\t"))?;
+    }
+    Ok(())
+}
+
+fn signed(environment: Option<Environment>) -> bybit_rs::Result<Client> {
+    let config = BybitConfig::from_env()?;
+    Ok(Client::from_config(&config).with_environment(environment.unwrap_or(config.environment)))
+}
+
+fn run(cli: Cli) -> Result<(), BoxError> {
+    let agent = ureq::Agent::new_with_defaults();
+    let send = transport(&agent);
+    match cli.command {
+        Command::Balance { coin } => {
+            let client = blocking::Client::new(signed(cli.environment)?, &send);
+            let WalletBalances { list } = client.execute(client.get_wallet_balance(AccountType::UNIFIED, coin, client.recv_window())?)?;
+            let coins = list.iter().flat_map(|wallet| &wallet.coin).filter(|coin| coin.wallet_balance.parse::<Decimal>().is_ok_and(|balance| !balance.is_zero()));
+            table(&["coin", "walletBalance", "equity", "usdValue", "locked", "unrealisedPnl"], coins.map(|coin| {
+                vec![coin.coin.clone(), coin.wallet_balance.clone(), coin.equity.clone(), coin.usd_value.clone(), coin.locked.clone(), coin.unrealised_pnl.clone()]
+            }))?;
+        }
+        Command::Positions { category, symbol, settle_coin } => {
+            let client = blocking::Client::new(signed(cli.environment)?, &send);
+            let mut query = PositionQuery::new(category);
+            query.symbol = symbol;
+            query.settle_coin = settle_coin;
+            let mut positions = Vec::new();
+            loop {
+                let page = client.execute(client.get_positions(&query, client.recv_window())?)?;
+                query = query.with_cursor(page.next_cursor());
+                positions.extend(page.list.into_iter().filter(|position| !position.side.is_empty()));
+                if query.cursor.is_none() {
+                    break;
+                }
+            }
+            table(&["symbol", "side", "size", "avgPrice", "markPrice", "liqPrice", "unrealisedPnl"], positions.into_iter().map(|position| {
+                vec![position.symbol, position.side, position.size, position.avg_price, position.mark_price, position.liq_price, position.unrealised_pnl]
+            }))?;
+        }
+        Command::Orders { category, symbol, settle_coin } => {
+            let client = blocking::Client::new(signed(cli.environment)?, &send);
+            let mut query = OrderQuery::new(category);
+            query.symbol = symbol;
+            query.settle_coin = settle_coin;
+            let orders = client.block_on(client.get_all_open_orders(&query, client.recv_window(), client.transport()))?;
+            table(&["orderId", "orderLinkId", "symbol", "side", "orderType", "price", "qty", "leavesQty", "orderStatus"], orders.into_iter().map(|order| {
+                vec![order.order_id, order.order_link_id, order.symbol, format!("{:?}", order.side), format!("{:?}", order.order_type), order.price, order.qty, order.leaves_qty, format!("{:?}", order.order_status)]
+            }))?;
+        }
+        Command::Place { category, symbol, side, qty, price, order_link_id, reduce_only } => {
+            let client = blocking::Client::new(signed(cli.environment)?, &send);
+            let mut request = match price {
+                Some(price) => PlaceOrderRequest::limit(category, symbol, side, qty, price),
+                None => PlaceOrderRequest::market(category, symbol, side, qty),
+            };
+            if let Some(order_link_id) = order_link_id {
+                request = request.with_order_link_id(order_link_id);
+            }
+            if reduce_only {
+                request = request.reduce_only();
+            }
+            let placed = client.execute(client.place_order(&request, client.recv_window())?)?;
+            table(&["orderId", "orderLinkId"], [vec![placed.order_id, placed.order_link_id]])?;
+        }
+        Command::Cancel { category, symbol, order_id, order_link_id } => {
+            let client = blocking::Client::new(signed(cli.environment)?, &send);
+            let order = match (order_id, order_link_id) {
+                (Some(order_id), _) => OrderRef::OrderId(order_id),
+                (None, Some(order_link_id)) => OrderRef::OrderLinkId(order_link_id),
+                (None, None) => unreachable!("clap requires one of them"),
+            };
+            let cancelled = client.execute(client.cancel_order(&CancelOrderRequest::new(category, symbol, order), client.recv_window())?)?;
+            table(&["orderId", "orderLinkId"], [vec![cancelled.order_id, cancelled.order_link_id]])?;
+        }
+        Command::Klines { category, symbol, interval, start, end, out } => {
+            let client = blocking::Client::new(Client::public(cli.environment.unwrap_or_default()), &send);
+            let range = start..=end.unwrap_or_else(|| Utc::now().timestamp_millis());
+            let writer: Box<dyn Write> = match out {
+                Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+                None => Box::new(std::io::stdout().lock()),
+            };
+            let mut csv = CsvExporter::new(writer)?;
+            let written = client.block_on(client.export_klines(category, &symbol, interval, range, &mut csv, client.transport()))?;
+            csv.finish()?;
+            eprintln!("{written} klines");
+        }
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
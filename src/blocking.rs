@@ -0,0 +1,71 @@
+use std::future::Future;
+
+use bytes::Bytes;
+use futures::future::Ready;
+
+use crate::{BoxError, BybitRequest, Response, Result, Warning};
+
+// Synchronous facade for scripts and CLIs without an async runtime. It pairs a Client with a blocking transport, typed
+// requests go through execute and the async helpers run on a local executor via block_on, e.g.
+// client.block_on(client.snapshot(&scopes, &recv_window, client.transport()))
+#[derive(Debug, Clone)]
+pub struct Client<F> {
+    inner: crate::Client,
+    send: F,
+}
+
+impl<F, E> Client<F>
+where F: Fn(http::Request<String>) -> std::result::Result<Bytes, E>,
+    E: Into<BoxError>
+{
+    pub fn new(inner: crate::Client, send: F) -> Self {
+        Self { inner, send }
+    }
+
+    pub fn inner(&self) -> &crate::Client {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> crate::Client {
+        self.inner
+    }
+
+    pub fn execute<T, X>(&self, request: BybitRequest<T, X>) -> Result<T>
+    where T: for<'a> serde::Deserialize<'a>,
+        X: for<'a> serde::Deserialize<'a>
+    {
+        request.send_blocking(&self.send)
+    }
+
+    pub fn execute_with_warnings<T, X>(&self, request: BybitRequest<T, X>) -> Result<(T, Vec<Warning>)>
+    where T: for<'a> serde::Deserialize<'a>,
+        X: for<'a> serde::Deserialize<'a>
+    {
+        request.send_with_warnings_blocking(&self.send)
+    }
+
+    pub fn execute_response<T, X>(&self, request: BybitRequest<T, X>) -> Result<Response<T, X>>
+    where T: for<'a> serde::Deserialize<'a>,
+        X: for<'a> serde::Deserialize<'a>
+    {
+        request.send_response_blocking(&self.send)
+    }
+
+    // the blocking transport wrapped in the async shape the multi-request helpers expect, every call completes
+    // immediately so concurrent helpers run their requests one after another
+    pub fn transport(&self) -> impl Fn(http::Request<String>) -> Ready<std::result::Result<Bytes, E>> + '_ {
+        move |request| futures::future::ready((self.send)(request))
+    }
+
+    pub fn block_on<Fut: Future>(&self, future: Fut) -> Fut::Output {
+        futures::executor::block_on(future)
+    }
+}
+
+impl<F> std::ops::Deref for Client<F> {
+    type Target = crate::Client;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
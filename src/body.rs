@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+
+use bytes::{Bytes, BytesMut};
+use futures::{Stream, StreamExt};
+
+pub const DEFAULT_BODY_LIMIT: usize = 8 * 1024 * 1024;
+
+#[derive(Debug, thiserror::Error)]
+#[error("response body exceeds the {limit} byte limit")]
+pub struct BodyTooLarge {
+    pub limit: usize,
+}
+
+// Max response size per endpoint path prefix (e.g. "/v5/market" or "/v5/market/kline"), the longest matching prefix wins.
+// The crate doesn't own the transport, send closures look the limit up here and read the body through read_limited
+#[derive(Debug, Clone)]
+pub struct BodyLimits {
+    default: usize,
+    prefixes: HashMap<String, usize>,
+}
+
+impl BodyLimits {
+    pub fn new(default: usize) -> Self {
+        Self { default, prefixes: HashMap::new() }
+    }
+
+    pub fn set(&mut self, path_prefix: impl Into<String>, limit: usize) {
+        self.prefixes.insert(path_prefix.into(), limit);
+    }
+
+    pub fn with(mut self, path_prefix: impl Into<String>, limit: usize) -> Self {
+        self.set(path_prefix, limit);
+        self
+    }
+
+    pub fn limit(&self, path: &str) -> usize {
+        self.prefixes
+            .iter()
+            .filter(|(prefix, _)| path.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, limit)| *limit)
+            .unwrap_or(self.default)
+    }
+
+    pub fn for_request(&self, request: &http::Request<String>) -> usize {
+        self.limit(request.uri().path())
+    }
+}
+
+impl Default for BodyLimits {
+    fn default() -> Self {
+        Self::new(DEFAULT_BODY_LIMIT)
+    }
+}
+
+// collects a streamed body chunk by chunk, bailing out as soon as it grows past limit instead of buffering all of it
+pub async fn read_limited<S, E>(body: S, limit: usize) -> crate::Result<Bytes>
+where S: Stream<Item = Result<Bytes, E>>,
+    E: Into<crate::BoxError>
+{
+    let mut body = std::pin::pin!(body);
+    let mut buffer = BytesMut::new();
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(|err| crate::Error::Transport(err.into()))?;
+        if buffer.len() + chunk.len() > limit {
+            return Err(BodyTooLarge { limit }.into());
+        }
+        buffer.extend_from_slice(&chunk);
+    }
+    Ok(buffer.freeze())
+}
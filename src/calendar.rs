@@ -0,0 +1,108 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, NaiveDate, TimeDelta, Utc, Weekday};
+
+// dated futures and options expire at 08:00 UTC
+pub const EXPIRY_HOUR: u32 = 8;
+pub const DEFAULT_FUNDING_INTERVAL: Duration = Duration::from_secs(8 * 60 * 60);
+
+// Funding settles every interval counted from 00:00 UTC, 8h unless the instrument says otherwise (fundingInterval is
+// in minutes). Some symbols run 4h, 2h or 1h intervals
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FundingSchedule {
+    pub interval: Duration,
+}
+
+impl Default for FundingSchedule {
+    fn default() -> Self {
+        Self::new(DEFAULT_FUNDING_INTERVAL)
+    }
+}
+
+impl FundingSchedule {
+    pub fn new(interval: Duration) -> Self {
+        Self { interval: interval.max(Duration::from_secs(60)) }
+    }
+
+    pub fn from_minutes(minutes: u64) -> Self {
+        Self::new(Duration::from_secs(minutes * 60))
+    }
+
+    fn interval_ms(&self) -> i64 {
+        self.interval.as_millis() as i64
+    }
+
+    // the first funding strictly after time
+    pub fn next_after(&self, time: DateTime<Utc>) -> DateTime<Utc> {
+        let millis = time.timestamp_millis();
+        let next = millis - millis.rem_euclid(self.interval_ms()) + self.interval_ms();
+        DateTime::from_timestamp_millis(next).unwrap_or(time)
+    }
+
+    // the last funding at or before time
+    pub fn previous(&self, time: DateTime<Utc>) -> DateTime<Utc> {
+        let millis = time.timestamp_millis();
+        DateTime::from_timestamp_millis(millis - millis.rem_euclid(self.interval_ms())).unwrap_or(time)
+    }
+
+    pub fn time_to_next(&self, now: DateTime<Utc>) -> Duration {
+        (self.next_after(now) - now).to_std().unwrap_or_default()
+    }
+
+    // fundings in [from, to)
+    pub fn between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> impl Iterator<Item = DateTime<Utc>> + use<> {
+        let step = TimeDelta::milliseconds(self.interval_ms());
+        let first = if self.previous(from) == from { from } else { self.next_after(from) };
+        std::iter::successors(Some(first), move |time| Some(*time + step)).take_while(move |time| *time < to)
+    }
+}
+
+// Parses the expiry out of a dated symbol: BTC-26DEC25 or BTCUSDT-26DEC25 style futures, BTC-29JUL22-25000-C style
+// options and inverse quarterlies like BTCUSDH25, which expire on the last Friday of their month (H, M, U, Z for
+// March, June, September, December). None for perpetuals and spot
+pub fn expiry(symbol: &str) -> Option<DateTime<Utc>> {
+    let date = match symbol.split('-').nth(1) {
+        Some(date) => parse_date(date)?,
+        None => quarterly_date(symbol)?,
+    };
+    Some(date.and_hms_opt(EXPIRY_HOUR, 0, 0)?.and_utc())
+}
+
+pub fn time_to_expiry(symbol: &str, now: DateTime<Utc>) -> Option<Duration> {
+    (expiry(symbol)? - now).to_std().ok()
+}
+
+pub fn is_expired(symbol: &str, now: DateTime<Utc>) -> bool {
+    expiry(symbol).is_some_and(|expiry| expiry <= now)
+}
+
+const MONTHS: [&str; 12] = ["JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC"];
+
+// 5JUL24 or 29JUL22, days aren't zero padded
+fn parse_date(date: &str) -> Option<NaiveDate> {
+    let split = date.find(|c: char| c.is_ascii_alphabetic())?;
+    let (day, rest) = date.split_at(split);
+    if rest.len() != 5 {
+        return None;
+    }
+    let (month, year) = rest.split_at(3);
+    let month = MONTHS.iter().position(|name| *name == month)? as u32 + 1;
+    NaiveDate::from_ymd_opt(2000 + year.parse::<i32>().ok()?, month, day.parse().ok()?)
+}
+
+fn quarterly_date(symbol: &str) -> Option<NaiveDate> {
+    let (rest, year) = symbol.split_at_checked(symbol.len().checked_sub(2)?)?;
+    let year = 2000 + year.parse::<i32>().ok()?;
+    let month = match rest.chars().last()? {
+        'H' => 3,
+        'M' => 6,
+        'U' => 9,
+        'Z' => 12,
+        _ => return None,
+    };
+    // the month code follows the quote currency, BTCUSDH25 but not a plain pair ending in those letters
+    rest.strip_suffix(|_: char| true)?.strip_suffix("USD")?;
+    NaiveDate::from_weekday_of_month_opt(year, month, Weekday::Fri, 5)
+        .or_else(|| NaiveDate::from_weekday_of_month_opt(year, month, Weekday::Fri, 4))
+        .filter(|date| date.month() == month)
+}
@@ -0,0 +1,76 @@
+use std::{
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc,
+    },
+};
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+// Source of the timestamps requests are signed with. The system clock by default, tests can freeze it
+// to get reproducible signatures
+pub trait Clock: Debug + Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl FixedClock {
+    pub fn from_millis(millis: i64) -> Self {
+        Self(DateTime::from_timestamp_millis(millis).unwrap_or_default())
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+// Another clock shifted by an offset that can change while requests are being signed, clones share the offset.
+// TimeSync keeps it at the server's clock minus the local one
+#[derive(Debug, Clone)]
+pub struct OffsetClock {
+    inner: Arc<dyn Clock>,
+    offset_ms: Arc<AtomicI64>,
+}
+
+impl OffsetClock {
+    pub fn new(inner: Arc<dyn Clock>) -> Self {
+        Self { inner, offset_ms: Arc::new(AtomicI64::new(0)) }
+    }
+
+    // shares offset_ms with whoever else holds it
+    pub fn shared(inner: Arc<dyn Clock>, offset_ms: Arc<AtomicI64>) -> Self {
+        Self { inner, offset_ms }
+    }
+
+    pub fn offset_ms(&self) -> i64 {
+        self.offset_ms.load(Ordering::Relaxed)
+    }
+
+    pub fn set_offset_ms(&self, offset_ms: i64) {
+        self.offset_ms.store(offset_ms, Ordering::Relaxed);
+    }
+
+    pub fn adjust_ms(&self, delta_ms: i64) {
+        self.offset_ms.fetch_add(delta_ms, Ordering::Relaxed);
+    }
+}
+
+impl Clock for OffsetClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.inner.now() + TimeDelta::milliseconds(self.offset_ms())
+    }
+}
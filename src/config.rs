@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer};
+
+use crate::{retry::RetryPolicy, Client, Environment, Error};
+
+const DEFAULT_RECV_WINDOW: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Deserialize)]
+pub struct BybitConfig {
+    #[serde(default)]
+    pub environment: Environment,
+    pub api_key: String,
+    pub secret: String,
+    #[serde(rename = "recv_window_ms", default = "default_recv_window", deserialize_with = "millis")]
+    pub recv_window: Duration,
+    // 1 disables retries, see RetryPolicy
+    #[serde(default = "default_retry_attempts")]
+    pub retry_attempts: u32,
+}
+
+// the secret stays out of logs, like Credentials
+impl std::fmt::Debug for BybitConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("BybitConfig")
+            .field("environment", &self.environment)
+            .field("api_key", &self.api_key)
+            .field("recv_window", &self.recv_window)
+            .field("retry_attempts", &self.retry_attempts)
+            .finish_non_exhaustive()
+    }
+}
+
+fn default_recv_window() -> Duration {
+    DEFAULT_RECV_WINDOW
+}
+
+fn default_retry_attempts() -> u32 {
+    1
+}
+
+fn millis<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    Ok(Duration::from_millis(u64::deserialize(deserializer)?))
+}
+
+impl BybitConfig {
+    pub fn new(api_key: String, secret: String) -> Self {
+        Self { environment: Environment::Mainnet, api_key, secret, recv_window: DEFAULT_RECV_WINDOW, retry_attempts: default_retry_attempts() }
+    }
+
+    pub fn with_environment(mut self, environment: Environment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    pub fn with_recv_window(mut self, recv_window: Duration) -> Self {
+        self.recv_window = recv_window;
+        self
+    }
+
+    pub fn with_retry_attempts(mut self, retry_attempts: u32) -> Self {
+        self.retry_attempts = retry_attempts;
+        self
+    }
+
+    // BYBIT_API_KEY and BYBIT_API_SECRET are required, BYBIT_ENVIRONMENT (mainnet, testnet, demo), BYBIT_RECV_WINDOW_MS
+    // and BYBIT_RETRY_ATTEMPTS are optional
+    pub fn from_env() -> crate::Result<Self> {
+        let var = |name: &str| std::env::var(name).map_err(|_| Error::Config(format!("{name} is not set")));
+        let mut config = Self::new(var("BYBIT_API_KEY")?, var("BYBIT_API_SECRET")?);
+        if let Ok(environment) = std::env::var("BYBIT_ENVIRONMENT") {
+            config.environment = serde_json::from_value(serde_json::Value::String(environment.to_lowercase()))
+                .map_err(|_| Error::Config(format!("BYBIT_ENVIRONMENT {environment:?} isn't mainnet, testnet or demo")))?;
+        }
+        if let Ok(recv_window) = std::env::var("BYBIT_RECV_WINDOW_MS") {
+            config.recv_window = Duration::from_millis(recv_window.parse().map_err(|err| Error::Config(format!("BYBIT_RECV_WINDOW_MS {recv_window:?}: {err}")))?);
+        }
+        if let Ok(retry_attempts) = std::env::var("BYBIT_RETRY_ATTEMPTS") {
+            config.retry_attempts = retry_attempts.parse().map_err(|err| Error::Config(format!("BYBIT_RETRY_ATTEMPTS {retry_attempts:?}: {err}")))?;
+        }
+        Ok(config)
+    }
+
+    #[cfg(feature = "toml")]
+    pub fn from_toml(toml: &str) -> crate::Result<Self> {
+        toml::from_str(toml).map_err(|err| Error::Config(err.to_string()))
+    }
+}
+
+impl Client {
+    pub fn from_config(config: &BybitConfig) -> Self {
+        Self::new(config.api_key.clone(), config.secret.clone()).with_environment(config.environment)
+            .with_recv_window(config.recv_window)
+            .with_retry_policy(RetryPolicy::new(config.retry_attempts))
+    }
+}
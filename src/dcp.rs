@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use futures::channel::mpsc::UnboundedSender;
+use serde::Serialize;
+
+use crate::{BybitRequest, Client, Empty, IntoPostRequest};
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub enum DcpProduct {
+    OPTIONS,
+    DERIVATIVES,
+    SPOT
+}
+
+impl Client {
+    pub fn set_dcp_window(&self, product: Option<DcpProduct>, time_window: Duration, recv_window: &Duration) -> crate::Result<BybitRequest<Empty>> {
+        #[derive(Serialize, Debug)]
+        struct DcpRequest {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            product: Option<DcpProduct>,
+            #[serde(rename = "timeWindow")]
+            time_window: u64,
+        }
+
+        impl IntoPostRequest for DcpRequest {
+            const ENDPOINT: &'static str = "/v5/order/disconnected-cancel-all";
+            type Response = Empty;
+        }
+
+        let request = DcpRequest {
+            product,
+            time_window: time_window.as_secs(),
+        };
+
+        request.as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+}
+
+#[derive(Debug)]
+pub enum DcpAlert {
+    RefreshFailed { consecutive: u32, error: crate::Error },
+    GaveUp { consecutive: u32 },
+}
+
+// Re-arms the disconnect-cancel-all countdown, the refresh interval should stay well below the window
+// so a single slow or failed refresh doesn't trip it
+#[derive(Debug, Clone)]
+pub struct DcpKeepalive {
+    pub product: Option<DcpProduct>,
+    pub window: Duration,
+    pub interval: Duration,
+    pub max_failures: u32,
+    pub recv_window: Duration,
+}
+
+impl DcpKeepalive {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            product: None,
+            window,
+            interval: window / 3,
+            max_failures: 3,
+            recv_window: Duration::from_secs(5),
+        }
+    }
+
+    // runs until max_failures consecutive refreshes fail or the client is shut down, spawn it on whatever
+    // runtime drives the transport. The countdown is left armed on shutdown so it still fires if the process dies.
+    // The failure that made it give up is returned rather than alerted
+    pub async fn run<F, R, E>(&self, client: &Client, send: F, alerts: UnboundedSender<DcpAlert>) -> crate::Result<()>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let _guard = client.shutdown.guard();
+        let mut consecutive = 0;
+        loop {
+            if client.shutdown.is_triggered() {
+                return Ok(());
+            }
+            match client.set_dcp_window(self.product, self.window, &self.recv_window)?.send(&send).await {
+                Ok(_) => consecutive = 0,
+                Err(error) => {
+                    consecutive += 1;
+                    if consecutive >= self.max_failures {
+                        let _ = alerts.unbounded_send(DcpAlert::GaveUp { consecutive });
+                        return Err(error);
+                    }
+                    let _ = alerts.unbounded_send(DcpAlert::RefreshFailed { consecutive, error });
+                }
+            }
+            futures::future::select(futures_timer::Delay::new(self.interval), client.shutdown.signal()).await;
+        }
+    }
+}
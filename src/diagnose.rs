@@ -0,0 +1,45 @@
+use std::time::{Duration, Instant};
+
+use crate::Client;
+
+// Bybit rejects timestamps more than this far ahead of its clock regardless of recv_window
+pub const MAX_FUTURE_SKEW: Duration = Duration::from_millis(1000);
+
+#[derive(Debug, Clone)]
+pub struct Diagnostics {
+    // server clock minus local clock at the midpoint of the round trip, positive when the local clock is behind
+    pub offset_ms: i64,
+    pub round_trip: Duration,
+    pub recv_window: Duration,
+    // headroom left before a request is rejected as too old (behind) or from the future (ahead),
+    // negative when requests signed right now would already fail
+    pub margin_ms: i64,
+}
+
+impl Diagnostics {
+    pub fn is_safe(&self) -> bool {
+        self.margin_ms > 0
+    }
+}
+
+impl Client {
+    // measures clock offset and latency against /v5/market/time, meant to be logged once at startup
+    pub async fn diagnose<F, R, E>(&self, recv_window: &Duration, send: F) -> crate::Result<Diagnostics>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let sent_at = self.clock.now();
+        let started = Instant::now();
+        let server = self.get_server_time()?.send(send).await?;
+        let round_trip = started.elapsed();
+        let server = server.as_datetime().ok_or_else(|| crate::Error::Unexpected(format!("unparseable server time {server:?}")))?;
+
+        let half_trip = round_trip.as_millis() as i64 / 2;
+        let offset_ms = (server - sent_at).num_milliseconds() - half_trip;
+        // a request signed now reaches the server half a round trip later
+        let lag = offset_ms + half_trip;
+        let margin_ms = (recv_window.as_millis() as i64 - lag).min(MAX_FUTURE_SKEW.as_millis() as i64 + lag);
+        Ok(Diagnostics { offset_ms, round_trip, recv_window: *recv_window, margin_ms })
+    }
+}
@@ -0,0 +1,208 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    ops::RangeInclusive,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::{market::Kline, Category, Client, Interval};
+
+const KLINE_PAGE: u32 = 1000;
+
+// klines of one symbol and interval starting within range (unix millis)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KlineJob {
+    pub category: Category,
+    pub symbol: String,
+    pub interval: Interval,
+    pub range: RangeInclusive<i64>,
+}
+
+impl KlineJob {
+    pub fn new(category: Category, symbol: impl Into<String>, interval: Interval, range: RangeInclusive<i64>) -> Self {
+        Self { category, symbol: symbol.into(), interval, range }
+    }
+
+    // what the job is found under in a Checkpoint
+    pub fn key(&self) -> String {
+        format!("{}:{}:{}:{}-{}", self.category.as_str(), self.symbol, self.interval.as_str(), self.range.start(), self.range.end())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobProgress {
+    // klines starting after this were delivered, pages go backwards from the end of the range
+    Before(i64),
+    Done,
+}
+
+// How far each job got, by KlineJob::key. Persist the one of the last event handled (it serializes) and pass it to
+// with_checkpoint to pick an interrupted download up where it stopped
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint(pub BTreeMap<String, JobProgress>);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DownloadProgress {
+    pub jobs: usize,
+    // finished jobs, those a checkpoint already had as done included
+    pub finished: usize,
+    pub requests: u64,
+    pub klines: u64,
+}
+
+// one page of a job, checkpoint already counts it as delivered
+#[derive(Debug, Clone)]
+pub struct DownloadEvent {
+    pub job: KlineJob,
+    // newest first like the endpoint returns them
+    pub klines: Vec<Kline>,
+    pub finished: bool,
+    pub progress: DownloadProgress,
+    pub checkpoint: Checkpoint,
+}
+
+// requests allowed per sliding window, shared by every job of a download
+#[derive(Debug)]
+struct Budget {
+    max: usize,
+    per: Duration,
+    sent: Mutex<VecDeque<Instant>>,
+}
+
+impl Budget {
+    async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut sent = self.sent.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                let now = Instant::now();
+                while sent.front().is_some_and(|time| now.duration_since(*time) >= self.per) {
+                    sent.pop_front();
+                }
+                match sent.front() {
+                    Some(oldest) if sent.len() >= self.max => self.per - now.duration_since(*oldest),
+                    _ => {
+                        sent.push_back(now);
+                        return;
+                    }
+                }
+            };
+            futures_timer::Delay::new(wait).await;
+        }
+    }
+}
+
+// Fetches klines for many symbol and interval combinations, a few jobs at a time, while every request draws on one
+// budget. Bybit meters market data per IP, 600 requests per 5 seconds at the time of writing
+#[derive(Debug)]
+pub struct BulkDownload {
+    jobs: Vec<KlineJob>,
+    concurrency: usize,
+    budget: Option<(usize, Duration)>,
+    checkpoint: Checkpoint,
+}
+
+impl BulkDownload {
+    pub fn new(jobs: Vec<KlineJob>) -> Self {
+        Self { jobs, concurrency: 4, budget: None, checkpoint: Checkpoint::default() }
+    }
+
+    // jobs with a request in flight at any time
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    // at most max requests per sliding window across all jobs, on top of the client's RateLimiter if it has one
+    pub fn with_budget(mut self, max: usize, per: Duration) -> Self {
+        self.budget = Some((max.max(1), per));
+        self
+    }
+
+    // jobs the checkpoint has as done are skipped, the others continue from where it left them
+    pub fn with_checkpoint(mut self, checkpoint: Checkpoint) -> Self {
+        self.checkpoint = checkpoint;
+        self
+    }
+}
+
+struct Page {
+    job: usize,
+    klines: Vec<Kline>,
+    // end of the next page, None once the job is done
+    next: Option<i64>,
+}
+
+impl Client {
+    // A page per event, in whatever order jobs make progress. A failed request ends its job with an Err, the other
+    // jobs carry on and the last checkpoint still has the failed one where it stopped
+    pub fn download<'a, F, R, E>(&'a self, download: BulkDownload, send: &'a F) -> impl Stream<Item = crate::Result<DownloadEvent>> + 'a
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>> + 'a,
+        E: Into<crate::BoxError>
+    {
+        let BulkDownload { jobs, concurrency, budget, mut checkpoint } = download;
+        let budget = budget.map(|(max, per)| Arc::new(Budget { max, per, sent: Mutex::new(VecDeque::new()) }));
+        let mut progress = DownloadProgress { jobs: jobs.len(), ..DownloadProgress::default() };
+        let mut pending = Vec::new();
+        for (index, job) in jobs.iter().enumerate() {
+            match checkpoint.0.get(&job.key()) {
+                Some(JobProgress::Done) => progress.finished += 1,
+                Some(JobProgress::Before(end)) => pending.push((index, *end)),
+                None => pending.push((index, *job.range.end())),
+            }
+        }
+        let pages = {
+            let jobs = jobs.clone();
+            futures::stream::iter(pending)
+                .map(move |(index, end)| self.job_pages(index, jobs[index].clone(), end, budget.clone(), send).boxed_local())
+                .flatten_unordered(concurrency)
+        };
+        pages.map(move |page| {
+            let page = page?;
+            let job = jobs[page.job].clone();
+            progress.requests += 1;
+            progress.klines += page.klines.len() as u64;
+            let state = match page.next {
+                Some(end) => JobProgress::Before(end),
+                None => {
+                    progress.finished += 1;
+                    JobProgress::Done
+                }
+            };
+            checkpoint.0.insert(job.key(), state);
+            Ok(DownloadEvent { job, klines: page.klines, finished: page.next.is_none(), progress, checkpoint: checkpoint.clone() })
+        })
+    }
+
+    fn job_pages<'a, F, R, E>(&'a self, index: usize, job: KlineJob, end: i64, budget: Option<Arc<Budget>>, send: &'a F) -> impl Stream<Item = crate::Result<Page>> + 'a
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>> + 'a,
+        E: Into<crate::BoxError>
+    {
+        futures::stream::unfold(Some(end), move |end| {
+            let (job, budget) = (job.clone(), budget.clone());
+            async move {
+                let end = end?;
+                if let Some(budget) = &budget {
+                    budget.acquire().await;
+                }
+                if let Some(limiter) = &self.rate_limiter {
+                    limiter.acquire("/v5/market/kline").await;
+                }
+                let start = *job.range.start();
+                let page = async { self.get_klines(job.category, job.symbol.clone(), job.interval, Some(start), Some(end), Some(KLINE_PAGE))?.send(send).await }.await;
+                let klines = match page {
+                    Ok(page) => page.list,
+                    Err(err) => return Some((Err(err), None)),
+                };
+                let oldest = klines.iter().filter_map(|kline| kline.start.parse::<i64>().ok()).min();
+                let next = oldest.filter(|oldest| klines.len() >= KLINE_PAGE as usize && *oldest > start).map(|oldest| oldest - 1);
+                Some((Ok(Page { job: index, klines, next }), next))
+            }
+        })
+    }
+}
@@ -0,0 +1,265 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+
+use crate::{
+    market::SymbolRules,
+    trade::{envelope, AmendOrderRequest, CancelAllOrdersRequest, CancelOrderRequest, CancelScope, MarketUnit, OrderRef, OrderType, PlaceOrderRequest},
+    BybitRequest, Category, Client, IntoPostRequest, OrderStatus, Simulated,
+};
+
+// the codes Bybit answers the same mistakes with, so strategy error handling is exercised as it would be live
+const PARAM_ERROR: i32 = 10001;
+const ORDER_NOT_FOUND: i32 = 110001;
+const INSUFFICIENT_BALANCE: i32 = 110007;
+const DUPLICATE_LINK_ID: i32 = 110072;
+
+#[derive(Debug, Clone)]
+pub struct SimulatedOrder {
+    pub order_id: String,
+    // empty when the request had none, as Bybit acks it
+    pub order_link_id: String,
+    // as placed, with any amendments applied
+    pub request: PlaceOrderRequest,
+    // New until cancelled, a dry run never fills
+    pub status: OrderStatus,
+    pub created_time: DateTime<Utc>,
+}
+
+impl SimulatedOrder {
+    fn matches(&self, category: Category, symbol: &str, order: &OrderRef) -> bool {
+        let found = match order {
+            OrderRef::OrderId(id) => self.order_id == *id,
+            OrderRef::OrderLinkId(id) => !id.is_empty() && self.order_link_id == *id,
+        };
+        found && self.status == OrderStatus::New && self.request.category == category && self.request.symbol == symbol
+    }
+}
+
+#[derive(Debug, Default)]
+struct Book {
+    rules: HashMap<String, SymbolRules>,
+    prices: HashMap<String, Decimal>,
+    balance: Option<Decimal>,
+    orders: Vec<SimulatedOrder>,
+}
+
+// Stands in for Bybit on the order mutating endpoints of a Client with_dry_run: orders are checked against the
+// symbol rules and the balance given here and kept in a local book, every call is answered with the envelope Bybit
+// would have sent, errors included. Clones share the book, keep one to look at what the strategy did
+#[derive(Debug, Clone, Default)]
+pub struct DryRun(Arc<Mutex<Book>>);
+
+impl DryRun {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // orders for symbols without rules skip the filter checks
+    pub fn with_rules(self, rules: SymbolRules) -> Self {
+        self.book().rules.insert(rules.symbol.clone(), rules);
+        self
+    }
+
+    // Quote currency the open simulated orders may tie up, in notional. Margin and leverage aren't modelled,
+    // reduce only orders and orders without a known price aren't counted
+    pub fn with_balance(self, balance: Decimal) -> Self {
+        self.book().balance = Some(balance);
+        self
+    }
+
+    // price market orders for symbol are valued at, keep it current from a ticker stream
+    pub fn set_price(&self, symbol: impl Into<String>, price: Decimal) {
+        self.book().prices.insert(symbol.into(), price);
+    }
+
+    // every order placed so far, cancelled ones included, oldest first
+    pub fn orders(&self) -> Vec<SimulatedOrder> {
+        self.book().orders.clone()
+    }
+
+    pub fn open_orders(&self) -> Vec<SimulatedOrder> {
+        self.book().orders.iter().filter(|order| order.status == OrderStatus::New).cloned().collect()
+    }
+
+    fn book(&self) -> std::sync::MutexGuard<'_, Book> {
+        // the book stays consistent across a panicking caller, every change is a single push or field update
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn place(&self, request: &PlaceOrderRequest, now: DateTime<Utc>) -> Result<Value, (i32, String)> {
+        let mut book = self.book();
+        let order_link_id = request.order_link_id.clone().unwrap_or_default();
+        if !order_link_id.is_empty() && book.orders.iter().any(|order| order.order_link_id == order_link_id) {
+            return Err((DUPLICATE_LINK_ID, format!("orderLinkId {order_link_id} is duplicate")));
+        }
+        book.check(request, None)?;
+        let order = SimulatedOrder {
+            order_id: uuid::Uuid::new_v4().to_string(),
+            order_link_id,
+            request: request.clone(),
+            status: OrderStatus::New,
+            created_time: now,
+        };
+        let ack = json!({ "orderId": order.order_id, "orderLinkId": order.order_link_id });
+        book.orders.push(order);
+        Ok(ack)
+    }
+
+    fn amend(&self, request: &AmendOrderRequest) -> Result<Value, (i32, String)> {
+        let mut book = self.book();
+        let index = book.find(request.category, &request.symbol, &request.order)?;
+        let mut amended = book.orders[index].request.clone();
+        amended.qty = request.qty.unwrap_or(amended.qty);
+        amended.price = request.price.or(amended.price);
+        if let (Some(trigger), Some(price)) = (&mut amended.trigger, request.trigger_price) {
+            trigger.price = price;
+        }
+        amended.take_profit = request.take_profit.or(amended.take_profit);
+        amended.stop_loss = request.stop_loss.or(amended.stop_loss);
+        book.check(&amended, Some(index))?;
+        let order = &mut book.orders[index];
+        order.request = amended;
+        Ok(json!({ "orderId": order.order_id, "orderLinkId": order.order_link_id }))
+    }
+
+    fn cancel(&self, request: &CancelOrderRequest) -> Result<Value, (i32, String)> {
+        let mut book = self.book();
+        let index = book.find(request.category, &request.symbol, &request.order)?;
+        let order = &mut book.orders[index];
+        order.status = OrderStatus::Cancelled;
+        Ok(json!({ "orderId": order.order_id, "orderLinkId": order.order_link_id }))
+    }
+
+    // coin scopes are matched against the start (base) or end (settle) of the symbol, the book has no instrument info
+    fn cancel_all(&self, request: &CancelAllOrdersRequest) -> Result<Value, (i32, String)> {
+        let mut book = self.book();
+        let mut list = Vec::new();
+        for order in book.orders.iter_mut().filter(|order| order.status == OrderStatus::New && order.request.category == request.category) {
+            let symbol = &order.request.symbol;
+            let in_scope = match &request.scope {
+                None => true,
+                Some(CancelScope::Symbol(wanted)) => symbol == wanted,
+                Some(CancelScope::BaseCoin(coin)) => symbol.starts_with(coin.as_str()),
+                Some(CancelScope::SettleCoin(coin)) => symbol.ends_with(coin.as_str()),
+            };
+            if in_scope {
+                order.status = OrderStatus::Cancelled;
+                list.push(json!({ "orderId": order.order_id, "orderLinkId": order.order_link_id }));
+            }
+        }
+        Ok(json!({ "list": list, "success": "1" }))
+    }
+}
+
+impl Book {
+    fn find(&self, category: Category, symbol: &str, order: &OrderRef) -> Result<usize, (i32, String)> {
+        self.orders
+            .iter()
+            .position(|simulated| simulated.matches(category, symbol, order))
+            .ok_or_else(|| (ORDER_NOT_FOUND, "order not exists or too late to cancel".to_string()))
+    }
+
+    // skip is the order being amended, its current notional is replaced rather than added to
+    fn check(&self, request: &PlaceOrderRequest, skip: Option<usize>) -> Result<(), (i32, String)> {
+        let market = request.order_type == OrderType::Market;
+        let quote_qty = request.market_unit == Some(MarketUnit::QuoteCoin);
+        if let Some(rules) = self.rules.get(&request.symbol).filter(|_| !quote_qty) {
+            let price = if market { None } else { request.price };
+            rules.validate(request.qty, price, market).map_err(|violation| (PARAM_ERROR, violation.to_string()))?;
+        }
+        let (Some(balance), Some(notional)) = (self.balance, self.notional(request)) else {
+            return Ok(());
+        };
+        let committed: Decimal = self
+            .orders
+            .iter()
+            .enumerate()
+            .filter(|(index, order)| Some(*index) != skip && order.status == OrderStatus::New)
+            .filter_map(|(_, order)| self.notional(&order.request))
+            .sum();
+        if committed + notional > balance {
+            return Err((INSUFFICIENT_BALANCE, format!("order notional {notional} exceeds the {} left of the balance", balance - committed)));
+        }
+        Ok(())
+    }
+
+    fn notional(&self, request: &PlaceOrderRequest) -> Option<Decimal> {
+        if request.reduce_only == Some(true) {
+            return None;
+        }
+        if request.market_unit == Some(MarketUnit::QuoteCoin) {
+            return Some(request.qty);
+        }
+        let price = match request.order_type {
+            OrderType::Market => self.prices.get(&request.symbol).copied(),
+            _ => request.price,
+        };
+        price.map(|price| request.qty * price)
+    }
+}
+
+// an unsigned request carrying its simulated answer, it is never sent
+fn simulate<T: IntoPostRequest>(request: &T, base_url: &str, envelope: Value) -> crate::Result<BybitRequest<T::Response>> {
+    let body = serde_json::to_string(request).map_err(|err| crate::Error::Serialization(err.to_string()))?;
+    let mut request = http::Request::builder().method("POST").uri(request.uri(base_url)).body(body)?;
+    request.extensions_mut().insert(Simulated(envelope.to_string().into()));
+    Ok(BybitRequest::new(request))
+}
+
+impl Client {
+    // Nothing that places, amends or cancels orders goes to Bybit any more, dry_run answers instead. Reads and
+    // market data still go out, so a strategy runs against live data, and no credentials are needed to place
+    pub fn with_dry_run(mut self, dry_run: DryRun) -> Self {
+        self.dry_run = Some(dry_run);
+        self
+    }
+
+    pub fn dry_run(&self) -> Option<&DryRun> {
+        self.dry_run.as_ref()
+    }
+
+    pub(crate) fn simulate_place(&self, request: &PlaceOrderRequest) -> Option<crate::Result<BybitRequest<<PlaceOrderRequest as IntoPostRequest>::Response>>> {
+        let dry_run = self.dry_run.as_ref()?;
+        let now = self.clock.now();
+        Some(simulate(request, self.environment.base_url(), envelope(dry_run.place(request, now), json!({}), now.timestamp_millis())))
+    }
+
+    pub(crate) fn simulate_amend(&self, request: &AmendOrderRequest) -> Option<crate::Result<BybitRequest<<AmendOrderRequest as IntoPostRequest>::Response>>> {
+        let dry_run = self.dry_run.as_ref()?;
+        Some(simulate(request, self.environment.base_url(), envelope(dry_run.amend(request), json!({}), self.clock.now().timestamp_millis())))
+    }
+
+    pub(crate) fn simulate_cancel(&self, request: &CancelOrderRequest) -> Option<crate::Result<BybitRequest<<CancelOrderRequest as IntoPostRequest>::Response>>> {
+        let dry_run = self.dry_run.as_ref()?;
+        Some(simulate(request, self.environment.base_url(), envelope(dry_run.cancel(request), json!({}), self.clock.now().timestamp_millis())))
+    }
+
+    pub(crate) fn simulate_cancel_all(&self, request: &CancelAllOrdersRequest) -> Option<crate::Result<BybitRequest<<CancelAllOrdersRequest as IntoPostRequest>::Response>>> {
+        let dry_run = self.dry_run.as_ref()?;
+        Some(simulate(request, self.environment.base_url(), envelope(dry_run.cancel_all(request), json!({}), self.clock.now().timestamp_millis())))
+    }
+
+    // each order is placed on its own, a rejected one gets its code in retExtInfo and an empty entry in the list
+    pub(crate) fn simulate_batch<T: IntoPostRequest>(&self, batch: &T, orders: &[PlaceOrderRequest]) -> Option<crate::Result<BybitRequest<T::Response>>> {
+        let dry_run = self.dry_run.as_ref()?;
+        let now = self.clock.now();
+        let (list, statuses): (Vec<Value>, Vec<Value>) = orders
+            .iter()
+            .map(|order| {
+                let (ack, code, message) = match dry_run.place(order, now) {
+                    Ok(ack) => (ack, 0, "OK".to_string()),
+                    Err((code, message)) => (json!({ "orderId": "", "orderLinkId": order.order_link_id.clone().unwrap_or_default() }), code, message),
+                };
+                let item = json!({ "symbol": order.symbol, "orderId": ack["orderId"], "orderLinkId": ack["orderLinkId"] });
+                (item, json!({ "code": code, "msg": message }))
+            })
+            .unzip();
+        Some(simulate(batch, self.environment.base_url(), envelope(Ok(json!({ "list": list })), json!({ "list": statuses }), now.timestamp_millis())))
+    }
+}
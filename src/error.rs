@@ -0,0 +1,223 @@
+use crate::BybitError;
+
+// whatever the caller's transport fails with
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("failed to sign request: {0}")]
+    Signing(String),
+    #[error("failed to serialize request: {0}")]
+    Serialization(String),
+    #[error("failed to build request: {0}")]
+    Http(#[from] http::Error),
+    // refused locally before anything was sent, e.g. an amend with nothing to change
+    #[error("invalid request: {0}")]
+    Invalid(BoxError),
+    #[error("transport failed: {0}")]
+    Transport(BoxError),
+    #[error(transparent)]
+    Api(#[from] BybitError),
+    // retCode and retMsg are pulled out of the body on a best effort basis, usually schema drift in result
+    // Bybit answered without an error but not with what the call needs, e.g. no unified wallet
+    #[error("unexpected response: {0}")]
+    Unexpected(String),
+    // the websocket broke protocol or Bybit refused an op on it, e.g. auth, a subscription or a missed pong
+    #[error("websocket: {0}")]
+    WebSocket(String),
+    // a stream message under DecodePolicy::Terminate that didn't match its model
+    #[error("failed to decode {topic}: {source}")]
+    Decode { topic: String, source: serde_json::Error },
+    #[error("invalid config: {0}")]
+    Config(String),
+    #[error("i/o failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    BodyTooLarge(#[from] crate::body::BodyTooLarge),
+    // refused by the client's RiskPolicy before anything was signed
+    #[cfg(feature = "trade")]
+    #[error("risk limit: {0}")]
+    Risk(#[from] crate::risk::RiskViolation),
+    #[cfg(feature = "parquet")]
+    #[error("parquet: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+    #[cfg(feature = "storage")]
+    #[error("storage: {0}")]
+    Storage(#[from] rusqlite::Error),
+    #[cfg(feature = "asset")]
+    #[error(transparent)]
+    Transfer(#[from] crate::asset::TransferError),
+    #[cfg(feature = "user")]
+    #[error(transparent)]
+    Permission(#[from] crate::user::PermissionError),
+    // boxed, it carries what was already created
+    #[cfg(feature = "user")]
+    #[error(transparent)]
+    Provision(Box<crate::user::ProvisionError>),
+    // Bybit answered with retCode 0 but listed why it didn't switch
+    #[cfg(feature = "position")]
+    #[error("margin mode switch refused: {}", .0.iter().map(|reason| reason.reason_msg.as_str()).collect::<Vec<_>>().join(", "))]
+    MarginModeRefused(Vec<crate::position::MarginModeReason>),
+    // boxed, it carries the closing orders that were already placed
+    #[cfg(feature = "position")]
+    #[error(transparent)]
+    PartialClose(Box<crate::position::PartialCloseError>),
+    // TimeSync skips samples whose round trip is too long to tell the offset apart from latency
+    #[error("round trip of {0:?} too slow to sync the clock")]
+    SlowRoundTrip(std::time::Duration),
+    #[error("failed to deserialize response{}: {source}, body: {}", envelope(.ret_code, .ret_msg), preview(.raw_body))]
+    Deserialize { source: serde_json::Error, raw_body: bytes::Bytes, ret_code: Option<i32>, ret_msg: Option<String> },
+}
+
+#[cfg(feature = "user")]
+impl From<crate::user::ProvisionError> for Error {
+    fn from(err: crate::user::ProvisionError) -> Self {
+        Self::Provision(Box::new(err))
+    }
+}
+
+#[cfg(feature = "position")]
+impl From<crate::position::PartialCloseError> for Error {
+    fn from(err: crate::position::PartialCloseError) -> Self {
+        Self::PartialClose(Box::new(err))
+    }
+}
+
+// how much of an undecodable body goes into the error message, raw_body always holds all of it
+const PREVIEW_LEN: usize = 512;
+
+fn envelope(ret_code: &Option<i32>, ret_msg: &Option<String>) -> String {
+    match (ret_code, ret_msg) {
+        (Some(code), Some(message)) => format!(" (retCode {code}, retMsg {message:?})"),
+        (Some(code), None) => format!(" (retCode {code})"),
+        _ => String::new(),
+    }
+}
+
+fn preview(body: &bytes::Bytes) -> String {
+    let text = String::from_utf8_lossy(body);
+    match text.char_indices().nth(PREVIEW_LEN) {
+        Some((end, _)) => format!("{}... ({} bytes)", &text[..end], body.len()),
+        None => text.into_owned(),
+    }
+}
+
+impl Error {
+    pub(crate) fn deserialize(source: serde_json::Error, raw_body: bytes::Bytes) -> Self {
+        #[derive(serde::Deserialize)]
+        struct Envelope {
+            #[serde(rename = "retCode")]
+            ret_code: Option<i32>,
+            #[serde(rename = "retMsg")]
+            ret_msg: Option<String>,
+        }
+
+        let (ret_code, ret_msg) = serde_json::from_slice::<Envelope>(&raw_body)
+            .map(|envelope| (envelope.ret_code, envelope.ret_msg))
+            .unwrap_or_default();
+        Self::Deserialize { source, raw_body, ret_code, ret_msg }
+    }
+
+    // the body Bybit sent when it couldn't be deserialized
+    pub fn raw_body(&self) -> Option<&bytes::Bytes> {
+        match self {
+            Self::Deserialize { raw_body, .. } => Some(raw_body),
+            _ => None,
+        }
+    }
+
+    // the retCode when Bybit answered with an error
+    pub fn code(&self) -> Option<i32> {
+        match self {
+            Self::Api(err) => Some(err.code()),
+            _ => None,
+        }
+    }
+
+    pub fn known_code(&self) -> Option<KnownErrorCode> {
+        self.code().map(KnownErrorCode::from)
+    }
+
+    // retryable api errors plus transport failures, which may or may not have reached Bybit
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Transport(_) => true,
+            err => err.known_code().is_some_and(|code| code.is_retryable()),
+        }
+    }
+}
+
+// Documented retCodes worth branching on, anything else is Unknown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KnownErrorCode {
+    ServerTimeout,
+    ParamError,
+    // timestamp outside recv_window, usually clock drift
+    TimestampError,
+    InvalidApiKey,
+    SignatureError,
+    PermissionDenied,
+    RateLimited,
+    AuthenticationFailed,
+    IpNotWhitelisted,
+    InternalError,
+    IpRateLimited,
+    TradingBanned,
+    OrderNotFound,
+    PriceOutOfRange,
+    WalletInsufficient,
+    InsufficientBalance,
+    ReduceOnlyRejected,
+    PositionModeNotModified,
+    MarginModeNotModified,
+    LeverageNotModified,
+    SpotInsufficientBalance,
+    Unknown(i32),
+}
+
+impl From<i32> for KnownErrorCode {
+    fn from(code: i32) -> Self {
+        match code {
+            10000 => Self::ServerTimeout,
+            10001 => Self::ParamError,
+            10002 => Self::TimestampError,
+            10003 => Self::InvalidApiKey,
+            10004 => Self::SignatureError,
+            10005 => Self::PermissionDenied,
+            10006 => Self::RateLimited,
+            10007 => Self::AuthenticationFailed,
+            10010 => Self::IpNotWhitelisted,
+            10016 => Self::InternalError,
+            10018 => Self::IpRateLimited,
+            10027 => Self::TradingBanned,
+            110001 => Self::OrderNotFound,
+            110003 => Self::PriceOutOfRange,
+            110004 => Self::WalletInsufficient,
+            110007 => Self::InsufficientBalance,
+            110017 => Self::ReduceOnlyRejected,
+            110025 => Self::PositionModeNotModified,
+            110026 => Self::MarginModeNotModified,
+            110043 => Self::LeverageNotModified,
+            170131 => Self::SpotInsufficientBalance,
+            code => Self::Unknown(code),
+        }
+    }
+}
+
+impl KnownErrorCode {
+    // the same request can succeed later, a timestamp error only once it has been signed again
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::ServerTimeout | Self::TimestampError | Self::RateLimited | Self::InternalError | Self::IpRateLimited)
+    }
+
+    // the setting already had the requested value
+    pub fn is_not_modified(&self) -> bool {
+        matches!(self, Self::PositionModeNotModified | Self::MarginModeNotModified | Self::LeverageNotModified)
+    }
+
+    pub fn is_insufficient_balance(&self) -> bool {
+        matches!(self, Self::WalletInsufficient | Self::InsufficientBalance | Self::SpotInsufficientBalance)
+    }
+}
@@ -0,0 +1,23 @@
+#[cfg(all(feature = "ws", feature = "trade"))]
+mod driver;
+mod fees;
+mod fills;
+mod grid;
+mod iceberg;
+mod lifecycle;
+#[cfg(all(feature = "ws", feature = "trade"))]
+mod oco;
+mod twap;
+
+#[cfg(all(feature = "ws", feature = "trade"))]
+pub use driver::{Command, ExecutorHandle};
+#[cfg(all(feature = "ws", feature = "trade"))]
+pub(crate) use driver::{ChildChange, Children, Wake};
+pub use fees::*;
+pub use fills::*;
+pub use grid::*;
+pub use iceberg::*;
+pub use lifecycle::*;
+#[cfg(all(feature = "ws", feature = "trade"))]
+pub use oco::*;
+pub use twap::*;
@@ -0,0 +1,264 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use futures::{
+    channel::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    FutureExt, Stream, StreamExt,
+};
+use rust_decimal::Decimal;
+
+use crate::{
+    error::KnownErrorCode,
+    trade::{BatchItemError, CancelOrderRequest, OrderRef, PlaceOrderRequest},
+    ws::PrivateEvent,
+    BybitError, Category, Client, Error, OrderStatus,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Pause,
+    Resume,
+    Cancel,
+}
+
+// Steers an executor while its run is in progress, commands are picked up between stream events
+#[derive(Debug, Clone)]
+pub struct ExecutorHandle(UnboundedSender<Command>);
+
+impl ExecutorHandle {
+    // no new child orders go out until resumed, working ones stay on the book
+    pub fn pause(&self) {
+        let _ = self.0.unbounded_send(Command::Pause);
+    }
+
+    pub fn resume(&self) {
+        let _ = self.0.unbounded_send(Command::Resume);
+    }
+
+    // cancels the working child orders, run returns once the stream confirmed they ended
+    pub fn cancel(&self) {
+        let _ = self.0.unbounded_send(Command::Cancel);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ChildChange {
+    // newly filled quantity
+    Filled { order_link_id: String, qty: Decimal },
+    // the child reached a terminal status, unfilled never executed
+    Ended { order_link_id: String, status: OrderStatus, unfilled: Decimal },
+}
+
+pub(crate) enum Wake {
+    Command(Command),
+    Changes(Vec<ChildChange>),
+    Timer,
+}
+
+#[derive(Debug, Clone)]
+struct Child {
+    qty: Decimal,
+    // the higher of what order updates and executions reported, whichever arrives first counts
+    filled: Decimal,
+    executed: Decimal,
+    exec_ids: HashSet<String>,
+    ended: bool,
+}
+
+// Child order plumbing shared by the executors: orderLinkIds, placement, cancellation and turning the private
+// order and execution streams into fills and ends of the children placed here
+#[derive(Debug)]
+pub(crate) struct Children {
+    prefix: String,
+    next_id: u32,
+    orders: HashMap<String, Child>,
+    commands: UnboundedReceiver<Command>,
+    handle: UnboundedSender<Command>,
+}
+
+impl Children {
+    pub(crate) fn new() -> Self {
+        let (handle, commands) = mpsc::unbounded();
+        Self {
+            // short enough to leave room for the child number within Bybit's 36 characters
+            prefix: uuid::Uuid::new_v4().simple().to_string()[..16].to_string(),
+            next_id: 0,
+            orders: HashMap::new(),
+            commands,
+            handle,
+        }
+    }
+
+    pub(crate) fn set_prefix(&mut self, prefix: impl Into<String>) {
+        self.prefix = prefix.into();
+    }
+
+    pub(crate) fn handle(&self) -> ExecutorHandle {
+        ExecutorHandle(self.handle.clone())
+    }
+
+    pub(crate) fn working(&self) -> impl Iterator<Item = &str> {
+        self.orders.iter().filter(|(_, child)| !child.ended).map(|(order_link_id, _)| order_link_id.as_str())
+    }
+
+    pub(crate) fn is_idle(&self) -> bool {
+        self.working().next().is_none()
+    }
+
+    // places request under the next orderLinkId and returns it, a child is tracked from before it's sent so one
+    // placed by a run dropped mid request is still cancelled later
+    pub(crate) async fn place<F, R, E>(&mut self, client: &Client, mut request: PlaceOrderRequest, recv_window: &Duration, send: F) -> crate::Result<String>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let order_link_id = self.next_link_id();
+        request.order_link_id = Some(order_link_id.clone());
+        self.track(order_link_id.clone(), request.qty);
+        match async { client.place_order(&request, recv_window)?.send(&send).await }.await {
+            Ok(_) => Ok(order_link_id),
+            Err(err) => {
+                self.orders.remove(&order_link_id);
+                Err(err)
+            }
+        }
+    }
+
+    // places requests through the batch endpoint, chunked to its cap, each under its own orderLinkId. Results line up
+    // with requests
+    pub(crate) async fn place_batch<F, R, E>(&mut self, client: &Client, requests: Vec<PlaceOrderRequest>, pacing: Duration, recv_window: &Duration, send: F) -> Vec<crate::Result<String>>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let requests: Vec<PlaceOrderRequest> = requests
+            .into_iter()
+            .map(|mut request| {
+                let order_link_id = self.next_link_id();
+                self.track(order_link_id.clone(), request.qty);
+                request.order_link_id = Some(order_link_id);
+                request
+            })
+            .collect();
+        let results = client.place_orders(&requests, pacing, recv_window, send).await;
+        requests
+            .into_iter()
+            .zip(results)
+            .map(|(request, result)| {
+                let order_link_id = request.order_link_id.unwrap_or_default();
+                match result {
+                    Ok(_) => Ok(order_link_id),
+                    Err(err) => {
+                        self.orders.remove(&order_link_id);
+                        Err(match err {
+                            BatchItemError::Rejected { code, message } => Error::Api(BybitError::from_ack(code, Some(message), None)),
+                            BatchItemError::Request(message) => Error::Transport(message.into()),
+                        })
+                    }
+                }
+            })
+            .collect()
+    }
+
+    fn next_link_id(&mut self) -> String {
+        let order_link_id = format!("{}-{}", self.prefix, self.next_id);
+        self.next_id += 1;
+        order_link_id
+    }
+
+    fn track(&mut self, order_link_id: String, qty: Decimal) {
+        self.orders.insert(order_link_id, Child { qty, filled: Decimal::ZERO, executed: Decimal::ZERO, exec_ids: HashSet::new(), ended: false });
+    }
+
+    // an order that already ended can't be cancelled any more, its end still comes through the stream
+    pub(crate) async fn cancel<F, R, E>(&self, client: &Client, category: Category, symbol: &str, order_link_id: &str, recv_window: &Duration, send: F) -> crate::Result<()>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let request = CancelOrderRequest::new(category, symbol, OrderRef::OrderLinkId(order_link_id.to_string()));
+        match async { client.cancel_order(&request, recv_window)?.send(&send).await }.await {
+            Err(Error::Api(err)) if err.known_code() == KnownErrorCode::OrderNotFound => Ok(()),
+            result => result.map(|_| ()),
+        }
+    }
+
+    pub(crate) async fn cancel_working<F, R, E>(&self, client: &Client, category: Category, symbol: &str, recv_window: &Duration, send: F) -> crate::Result<()>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        for order_link_id in self.working() {
+            self.cancel(client, category, symbol, order_link_id, recv_window, &send).await?;
+        }
+        Ok(())
+    }
+
+    // next command or stream event, or Timer once wait elapsed. None waits on the other two only
+    pub(crate) async fn wake<St>(&mut self, events: &mut St, wait: Option<Duration>) -> crate::Result<Wake>
+    where St: Stream<Item = crate::Result<PrivateEvent>> + Unpin
+    {
+        let timer = match wait {
+            Some(wait) => futures_timer::Delay::new(wait).left_future(),
+            None => futures::future::pending().right_future(),
+        };
+        let event = futures::select_biased! {
+            command = self.commands.next() => return Ok(command.map_or(Wake::Timer, Wake::Command)),
+            event = events.next().fuse() => event,
+            _ = timer.fuse() => return Ok(Wake::Timer),
+        };
+        match event {
+            Some(event) => Ok(Wake::Changes(self.apply(&event?)?)),
+            None => Err(Error::WebSocket("private stream ended while child orders were working".into())),
+        }
+    }
+
+    pub(crate) fn apply(&mut self, event: &PrivateEvent) -> crate::Result<Vec<ChildChange>> {
+        let mut changes = Vec::new();
+        match event {
+            PrivateEvent::Order { data, .. } => {
+                for update in data {
+                    let Some(child) = self.orders.get_mut(&update.order_link_id).filter(|child| !child.ended) else {
+                        continue;
+                    };
+                    let filled = parse(&update.cum_exec_qty, "cumExecQty")?;
+                    if filled > child.filled {
+                        changes.push(ChildChange::Filled { order_link_id: update.order_link_id.clone(), qty: filled - child.filled });
+                        child.filled = filled;
+                    }
+                    if update.order_status.is_terminal() {
+                        child.ended = true;
+                        let unfilled = (child.qty - child.filled).max(Decimal::ZERO);
+                        changes.push(ChildChange::Ended { order_link_id: update.order_link_id.clone(), status: update.order_status, unfilled });
+                    }
+                }
+            }
+            PrivateEvent::Fill { data, .. } => {
+                for execution in data {
+                    let Some(child) = self.orders.get_mut(&execution.order_link_id).filter(|child| !child.ended) else {
+                        continue;
+                    };
+                    if !child.exec_ids.insert(execution.exec_id.clone()) {
+                        continue;
+                    }
+                    child.executed += parse(&execution.exec_qty, "execQty")?;
+                    if child.executed > child.filled {
+                        changes.push(ChildChange::Filled { order_link_id: execution.order_link_id.clone(), qty: child.executed - child.filled });
+                        child.filled = child.executed;
+                    }
+                }
+            }
+            PrivateEvent::Disconnect { .. } => {
+                return Err(Error::WebSocket("private stream disconnected while child orders were working".into()));
+            }
+            _ => {}
+        }
+        Ok(changes)
+    }
+}
+
+fn parse(value: &str, field: &str) -> crate::Result<Decimal> {
+    value.parse().map_err(|err| Error::Unexpected(format!("invalid {field} {value:?}: {err}")))
+}
@@ -0,0 +1,124 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+use super::Fill;
+
+// What an execution's fee was charged in. Spot fees name their coin, which can be the base, the quote or a third
+// token such as a fee deduction coupon, derivatives fees come out of the settle coin and carry no feeCurrency
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FeeCurrency {
+    Coin(String),
+    Settle,
+}
+
+impl FeeCurrency {
+    pub fn from_fee_currency(fee_currency: &str) -> Self {
+        match fee_currency {
+            "" => Self::Settle,
+            coin => Self::Coin(coin.to_string()),
+        }
+    }
+
+    pub fn coin(&self) -> Option<&str> {
+        match self {
+            Self::Coin(coin) => Some(coin),
+            Self::Settle => None,
+        }
+    }
+
+    // base and quote of the traded symbol, e.g. BTC and USDT for BTCUSDT
+    pub fn side(&self, base: &str, quote: &str) -> FeeSide {
+        match self.coin() {
+            Some(coin) if coin == base => FeeSide::Base,
+            Some(coin) if coin == quote => FeeSide::Quote,
+            Some(_) => FeeSide::Other,
+            None => FeeSide::Settle,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FeeSide {
+    // reduces the received base on buys
+    Base,
+    // reduces the received quote on sells
+    Quote,
+    // charged in a third token, doesn't touch the traded pair
+    Other,
+    Settle,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeeTotal {
+    pub fills: usize,
+    pub paid: Decimal,
+    // maker rebates come through as negative fees
+    pub rebates: Decimal,
+}
+
+impl FeeTotal {
+    pub fn net(&self) -> Decimal {
+        self.paid - self.rebates
+    }
+}
+
+// Nets fees per currency over an optional period, executions seen twice are only counted once
+#[derive(Debug, Clone, Default)]
+pub struct FeeLedger {
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    totals: HashMap<FeeCurrency, FeeTotal>,
+    seen: HashSet<String>,
+}
+
+impl FeeLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // executions outside [from, to) are ignored
+    pub fn with_period(mut self, from: DateTime<Utc>, to: DateTime<Utc>) -> Self {
+        self.from = Some(from);
+        self.to = Some(to);
+        self
+    }
+
+    pub fn add<F: Fill>(&mut self, fill: &F) -> bool {
+        let exec_time = fill.exec_time();
+        if self.from.is_some_and(|from| exec_time < from) || self.to.is_some_and(|to| exec_time >= to) {
+            return false;
+        }
+        if !self.seen.insert(fill.exec_id().to_string()) {
+            return false;
+        }
+        let total = self.totals.entry(FeeCurrency::from_fee_currency(fill.fee_currency())).or_default();
+        total.fills += 1;
+        let fee = fill.fee();
+        if fee.is_sign_negative() {
+            total.rebates -= fee;
+        } else {
+            total.paid += fee;
+        }
+        true
+    }
+
+    pub fn extend<'a, F: Fill + 'a>(&mut self, fills: impl IntoIterator<Item = &'a F>) {
+        for fill in fills {
+            self.add(fill);
+        }
+    }
+
+    pub fn get(&self, currency: &FeeCurrency) -> Option<&FeeTotal> {
+        self.totals.get(currency)
+    }
+
+    pub fn totals(&self) -> impl Iterator<Item = (&FeeCurrency, &FeeTotal)> {
+        self.totals.iter()
+    }
+
+    pub fn net(&self) -> HashMap<FeeCurrency, Decimal> {
+        self.totals.iter().map(|(currency, total)| (currency.clone(), total.net())).collect()
+    }
+}
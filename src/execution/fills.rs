@@ -0,0 +1,97 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+use super::FeeCurrency;
+
+// Anything that describes a single execution, implemented by the execution models
+// so stream messages and REST history can be aggregated together
+pub trait Fill {
+    fn exec_id(&self) -> &str;
+    fn order_id(&self) -> &str;
+    fn price(&self) -> Decimal;
+    fn qty(&self) -> Decimal;
+    fn fee(&self) -> Decimal;
+    fn fee_currency(&self) -> &str;
+    fn is_maker(&self) -> bool;
+    fn exec_time(&self) -> DateTime<Utc>;
+
+    fn fee_coin(&self) -> FeeCurrency {
+        FeeCurrency::from_fee_currency(self.fee_currency())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionSummary {
+    pub order_id: String,
+    pub fills: usize,
+    pub qty: Decimal,
+    pub notional: Decimal,
+    pub maker_qty: Decimal,
+    pub taker_qty: Decimal,
+    pub fees: HashMap<String, Decimal>,
+}
+
+impl ExecutionSummary {
+    pub fn avg_price(&self) -> Option<Decimal> {
+        if self.qty.is_zero() {
+            return None;
+        }
+        Some(self.notional / self.qty)
+    }
+
+    pub fn add<F: Fill>(&mut self, fill: &F) {
+        self.fills += 1;
+        self.qty += fill.qty();
+        self.notional += fill.price() * fill.qty();
+        if fill.is_maker() {
+            self.maker_qty += fill.qty();
+        } else {
+            self.taker_qty += fill.qty();
+        }
+        *self.fees.entry(fill.fee_currency().to_string()).or_default() += fill.fee();
+    }
+}
+
+// Per order summaries, executions seen twice (stream and REST backfill overlapping) are only counted once
+#[derive(Debug, Clone, Default)]
+pub struct FillAggregator {
+    summaries: HashMap<String, ExecutionSummary>,
+    seen: HashSet<String>,
+}
+
+impl FillAggregator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add<F: Fill>(&mut self, fill: &F) -> bool {
+        if !self.seen.insert(fill.exec_id().to_string()) {
+            return false;
+        }
+        self.summaries
+            .entry(fill.order_id().to_string())
+            .or_insert_with(|| ExecutionSummary { order_id: fill.order_id().to_string(), ..Default::default() })
+            .add(fill);
+        true
+    }
+
+    pub fn extend<'a, F: Fill + 'a>(&mut self, fills: impl IntoIterator<Item = &'a F>) {
+        for fill in fills {
+            self.add(fill);
+        }
+    }
+
+    pub fn get(&self, order_id: &str) -> Option<&ExecutionSummary> {
+        self.summaries.get(order_id)
+    }
+
+    pub fn summaries(&self) -> impl Iterator<Item = &ExecutionSummary> {
+        self.summaries.values()
+    }
+
+    pub fn into_summaries(self) -> HashMap<String, ExecutionSummary> {
+        self.summaries
+    }
+}
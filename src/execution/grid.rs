@@ -0,0 +1,252 @@
+#[cfg(all(feature = "ws", feature = "trade"))]
+use std::{collections::HashMap, time::Duration};
+
+use rust_decimal::{Decimal, MathematicalOps};
+
+#[cfg(all(feature = "ws", feature = "trade"))]
+use super::{ChildChange, Children, Command, ExecutorHandle, Wake};
+use crate::{number::Precision, Side};
+#[cfg(all(feature = "ws", feature = "trade"))]
+use crate::{trade::PlaceOrderRequest, ws::PrivateEvent, Category, Client, OrderStatus};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GridSpacing {
+    Arithmetic,
+    Geometric,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GridOrder {
+    pub level: usize,
+    pub side: Side,
+    pub price: Decimal,
+    pub qty: Decimal,
+}
+
+// Generates limit levels between two bounds and re-arms the neighbouring level on the opposite side
+// whenever one fills, the returned orders are meant to be sent in batches by the caller
+#[derive(Debug, Clone)]
+pub struct Grid {
+    prices: Vec<Decimal>,
+    qty: Decimal,
+    armed: Vec<Option<Side>>,
+}
+
+impl Grid {
+    pub fn new(lower: Decimal, upper: Decimal, levels: usize, qty: Decimal, spacing: GridSpacing) -> crate::Result<Self> {
+        let invalid = |message: String| Err(crate::Error::Invalid(message.into()));
+        if lower <= Decimal::ZERO {
+            return invalid(format!("grid lower bound {lower} isn't positive"));
+        }
+        if lower >= upper {
+            return invalid(format!("grid lower bound {lower} isn't below the upper bound {upper}"));
+        }
+        if levels < 2 {
+            return invalid(format!("a grid needs at least 2 levels, got {levels}"));
+        }
+        let steps = Decimal::from(levels - 1);
+        let prices = (0..levels)
+            .map(|i| {
+                let i = Decimal::from(i);
+                match spacing {
+                    GridSpacing::Arithmetic => lower + (upper - lower) * i / steps,
+                    GridSpacing::Geometric => {
+                        let ratio = (upper / lower).powd(Decimal::ONE / steps);
+                        lower * ratio.powd(i)
+                    }
+                }
+            })
+            .collect();
+        Ok(Self { prices, qty, armed: vec![None; levels] })
+    }
+
+    pub fn with_tick_size(mut self, tick: Decimal) -> Self {
+        let precision = Precision::new(tick, Decimal::ZERO);
+        for price in &mut self.prices {
+            *price = precision.price(*price);
+        }
+        self
+    }
+
+    pub fn prices(&self) -> &[Decimal] {
+        &self.prices
+    }
+
+    pub fn armed(&self, level: usize) -> Option<Side> {
+        self.armed.get(level).copied().flatten()
+    }
+
+    // buys below the reference price and sells above it, the level closest to it is left empty
+    pub fn initial_orders(&mut self, reference: Decimal) -> Vec<GridOrder> {
+        let skip = self
+            .prices
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, price)| (**price - reference).abs())
+            .map(|(i, _)| i);
+        let mut orders = Vec::new();
+        for level in 0..self.prices.len() {
+            if Some(level) == skip {
+                continue;
+            }
+            let side = if self.prices[level] < reference { Side::Buy } else { Side::Sell };
+            orders.push(self.arm(level, side));
+        }
+        orders
+    }
+
+    pub fn on_fill(&mut self, level: usize) -> Option<GridOrder> {
+        let side = self.armed.get_mut(level)?.take()?;
+        let (next, side) = match side {
+            Side::Buy => (level + 1, Side::Sell),
+            Side::Sell => (level.checked_sub(1)?, Side::Buy),
+        };
+        if next >= self.prices.len() || self.armed[next].is_some() {
+            return None;
+        }
+        Some(self.arm(next, side))
+    }
+
+    pub fn on_cancel(&mut self, level: usize) {
+        if let Some(armed) = self.armed.get_mut(level) {
+            *armed = None;
+        }
+    }
+
+    fn arm(&mut self, level: usize, side: Side) -> GridOrder {
+        self.armed[level] = Some(side);
+        GridOrder { level, side, price: self.prices[level], qty: self.qty }
+    }
+}
+
+// Keeps a Grid on the book: the initial levels and every re-armed level go out through the batch endpoint, chunked
+// to its cap, and a level re-arms its neighbour once the order stream reports it completely filled
+#[cfg(all(feature = "ws", feature = "trade"))]
+#[derive(Debug)]
+pub struct GridExecutor {
+    grid: Grid,
+    category: Category,
+    symbol: String,
+    reference: Decimal,
+    pacing: Duration,
+    children: Children,
+    levels: HashMap<String, usize>,
+    // armed in the grid but not on the book yet, paused or the placement failed
+    unplaced: Vec<GridOrder>,
+    started: bool,
+    cancelled: bool,
+    paused: bool,
+}
+
+#[cfg(all(feature = "ws", feature = "trade"))]
+impl GridExecutor {
+    // levels below reference start as buys and the ones above as sells, see Grid::initial_orders
+    pub fn new(grid: Grid, category: Category, symbol: impl Into<String>, reference: Decimal) -> Self {
+        Self {
+            grid,
+            category,
+            symbol: symbol.into(),
+            reference,
+            pacing: Duration::ZERO,
+            children: Children::new(),
+            levels: HashMap::new(),
+            unplaced: Vec::new(),
+            started: false,
+            cancelled: false,
+            paused: false,
+        }
+    }
+
+    // gap between two batch calls when the client has no rate limiter, see Client::place_orders
+    pub fn with_pacing(mut self, pacing: Duration) -> Self {
+        self.pacing = pacing;
+        self
+    }
+
+    // level orderLinkIds are prefix-<n>, a random prefix by default
+    pub fn with_link_id_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.children.set_prefix(prefix);
+        self
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    pub fn handle(&self) -> ExecutorHandle {
+        self.children.handle()
+    }
+
+    // A grid never finishes by itself, run returns once it was cancelled and every level order ended. Levels that
+    // failed to place are kept and sent again by the next run, the first failure is returned
+    pub async fn run<St, F, R, E>(&mut self, client: &Client, events: &mut St, recv_window: &Duration, send: F) -> crate::Result<()>
+    where St: futures::Stream<Item = crate::Result<PrivateEvent>> + Unpin,
+        F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        if !self.started {
+            self.unplaced = self.grid.initial_orders(self.reference);
+            self.started = true;
+        }
+        loop {
+            if !self.unplaced.is_empty() && !self.paused && !self.cancelled {
+                self.place(client, recv_window, &send).await?;
+            }
+            if self.cancelled && self.children.is_idle() {
+                return Ok(());
+            }
+            match self.children.wake(events, None).await? {
+                Wake::Command(Command::Pause) => self.paused = true,
+                Wake::Command(Command::Resume) => self.paused = false,
+                Wake::Command(Command::Cancel) => {
+                    self.cancelled = true;
+                    self.unplaced.clear();
+                    self.children.cancel_working(client, self.category, &self.symbol, recv_window, &send).await?;
+                }
+                Wake::Changes(changes) => {
+                    for change in changes {
+                        let ChildChange::Ended { order_link_id, status, .. } = change else {
+                            continue;
+                        };
+                        let Some(level) = self.levels.remove(&order_link_id) else {
+                            continue;
+                        };
+                        if status != OrderStatus::Filled || self.cancelled {
+                            self.grid.on_cancel(level);
+                        } else if let Some(order) = self.grid.on_fill(level) {
+                            self.unplaced.push(order);
+                        }
+                    }
+                }
+                Wake::Timer => {}
+            }
+        }
+    }
+
+    async fn place<F, R, E>(&mut self, client: &Client, recv_window: &Duration, send: F) -> crate::Result<()>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let orders = std::mem::take(&mut self.unplaced);
+        let requests = orders
+            .iter()
+            .map(|order| PlaceOrderRequest::limit(self.category, self.symbol.clone(), order.side, order.qty, order.price))
+            .collect();
+        let results = self.children.place_batch(client, requests, self.pacing, recv_window, send).await;
+        let mut failure = None;
+        for (order, result) in orders.into_iter().zip(results) {
+            match result {
+                Ok(order_link_id) => {
+                    self.levels.insert(order_link_id, order.level);
+                }
+                Err(err) => {
+                    failure.get_or_insert(err);
+                    self.unplaced.push(order);
+                }
+            }
+        }
+        failure.map_or(Ok(()), Err)
+    }
+}
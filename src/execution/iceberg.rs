@@ -0,0 +1,188 @@
+#[cfg(all(feature = "ws", feature = "trade"))]
+use std::time::Duration;
+
+use rust_decimal::Decimal;
+
+#[cfg(all(feature = "ws", feature = "trade"))]
+use super::{ChildChange, Children, Command, ExecutorHandle, Wake};
+use crate::number::Precision;
+#[cfg(all(feature = "ws", feature = "trade"))]
+use crate::{trade::PlaceOrderRequest, ws::PrivateEvent, Client};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IcebergState {
+    Active,
+    Cancelled,
+    Completed,
+}
+
+// Only ever keeps one child of at most `display` size on the book, the caller places whatever
+// `replenish`/`on_fill` hands back and feeds fills from the execution stream
+#[derive(Debug, Clone)]
+pub struct Iceberg {
+    total: Decimal,
+    display: Decimal,
+    precision: Precision,
+    filled: Decimal,
+    visible: Decimal,
+    state: IcebergState,
+}
+
+impl Iceberg {
+    // child quantities are rounded down to precision's qty step, a remainder below one step can't be shown and the
+    // iceberg completes without it, see remaining
+    pub fn new(total: Decimal, display: Decimal, precision: Precision) -> crate::Result<Self> {
+        let invalid = |message: String| Err(crate::Error::Invalid(message.into()));
+        if total <= Decimal::ZERO {
+            return invalid(format!("iceberg total {total} isn't positive"));
+        }
+        if display <= Decimal::ZERO {
+            return invalid(format!("iceberg display qty {display} isn't positive"));
+        }
+        if precision.qty(display).is_zero() {
+            return invalid(format!("iceberg display qty {display} is below one qty step"));
+        }
+        Ok(Self {
+            total,
+            display: precision.qty(display),
+            precision,
+            filled: Decimal::ZERO,
+            visible: Decimal::ZERO,
+            state: IcebergState::Active,
+        })
+    }
+
+    pub fn state(&self) -> IcebergState {
+        self.state
+    }
+
+    pub fn filled(&self) -> Decimal {
+        self.filled
+    }
+
+    pub fn visible(&self) -> Decimal {
+        self.visible
+    }
+
+    pub fn hidden(&self) -> Decimal {
+        self.total - self.filled - self.visible
+    }
+
+    pub fn remaining(&self) -> Decimal {
+        self.total - self.filled
+    }
+
+    // quantity of the next child order, None while a child is still working or nothing is left
+    pub fn replenish(&mut self) -> Option<Decimal> {
+        if self.state != IcebergState::Active || !self.visible.is_zero() {
+            return None;
+        }
+        let qty = self.precision.qty(self.display.min(self.hidden()));
+        if qty <= Decimal::ZERO {
+            self.state = IcebergState::Completed;
+            return None;
+        }
+        self.visible = qty;
+        Some(qty)
+    }
+
+    // fills still arriving after a cancel are booked but leave the iceberg Cancelled
+    pub fn on_fill(&mut self, qty: Decimal) -> Option<Decimal> {
+        self.filled += qty;
+        self.visible = (self.visible - qty).max(Decimal::ZERO);
+        if self.filled >= self.total && self.state == IcebergState::Active {
+            self.state = IcebergState::Completed;
+        }
+        self.replenish()
+    }
+
+    // visible child ended without filling (cancelled by the exchange, expired), hand its size back to the hidden pool
+    pub fn on_release(&mut self) -> Option<Decimal> {
+        self.visible = Decimal::ZERO;
+        self.replenish()
+    }
+
+    pub fn cancel(&mut self) {
+        if self.state == IcebergState::Active {
+            self.state = IcebergState::Cancelled;
+        }
+    }
+}
+
+// Runs an Iceberg against the exchange: each visible child goes out as a copy of template with the child's qty,
+// its fills and end come from the private order and execution streams
+#[cfg(all(feature = "ws", feature = "trade"))]
+#[derive(Debug)]
+pub struct IcebergExecutor {
+    iceberg: Iceberg,
+    template: PlaceOrderRequest,
+    children: Children,
+    // handed out by the iceberg but not placed yet, paused or the placement failed
+    unplaced: Option<Decimal>,
+    paused: bool,
+}
+
+#[cfg(all(feature = "ws", feature = "trade"))]
+impl IcebergExecutor {
+    pub fn new(iceberg: Iceberg, template: PlaceOrderRequest) -> Self {
+        Self { iceberg, template, children: Children::new(), unplaced: None, paused: false }
+    }
+
+    // child orderLinkIds are prefix-<n>, a random prefix by default
+    pub fn with_link_id_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.children.set_prefix(prefix);
+        self
+    }
+
+    pub fn iceberg(&self) -> &Iceberg {
+        &self.iceberg
+    }
+
+    pub fn handle(&self) -> ExecutorHandle {
+        self.children.handle()
+    }
+
+    // Keeps one child on the book until the iceberg completed, or was cancelled and its child ended. An error
+    // leaves the executor as it was, run it again (after a resync when the stream dropped) to carry on
+    pub async fn run<St, F, R, E>(&mut self, client: &Client, events: &mut St, recv_window: &Duration, send: F) -> crate::Result<()>
+    where St: futures::Stream<Item = crate::Result<PrivateEvent>> + Unpin,
+        F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        loop {
+            if self.unplaced.is_none() {
+                self.unplaced = self.iceberg.replenish();
+            }
+            if let Some(qty) = self.unplaced.filter(|_| !self.paused) {
+                let request = PlaceOrderRequest { qty, ..self.template.clone() };
+                self.children.place(client, request, recv_window, &send).await?;
+                self.unplaced = None;
+            }
+            if self.iceberg.state() != IcebergState::Active && self.children.is_idle() {
+                return Ok(());
+            }
+            match self.children.wake(events, None).await? {
+                Wake::Command(Command::Pause) => self.paused = true,
+                Wake::Command(Command::Resume) => self.paused = false,
+                Wake::Command(Command::Cancel) => {
+                    self.iceberg.cancel();
+                    self.unplaced = None;
+                    self.children.cancel_working(client, self.template.category, &self.template.symbol, recv_window, &send).await?;
+                }
+                Wake::Changes(changes) => {
+                    for change in changes {
+                        let next = match change {
+                            ChildChange::Filled { qty, .. } => self.iceberg.on_fill(qty),
+                            // a child that filled completely was already replaced by its last fill
+                            ChildChange::Ended { unfilled, .. } if unfilled > Decimal::ZERO => self.iceberg.on_release(),
+                            ChildChange::Ended { .. } => None,
+                        };
+                        self.unplaced = self.unplaced.or(next);
+                    }
+                }
+                Wake::Timer => {}
+            }
+        }
+    }
+}
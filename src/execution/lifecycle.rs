@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use rust_decimal::Decimal;
+
+use crate::OrderStatus;
+
+#[derive(Debug, Clone)]
+pub struct TrackedOrder {
+    pub order_link_id: String,
+    pub order_id: Option<String>,
+    pub qty: Decimal,
+    pub filled: Decimal,
+    // None until the exchange acknowledged the order
+    pub status: Option<OrderStatus>,
+    pub amendments: u32,
+}
+
+impl TrackedOrder {
+    pub fn is_terminal(&self) -> bool {
+        self.status.is_some_and(|status| status.is_terminal())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Inconsistency {
+    #[error("update for unknown order {0}")]
+    UnknownOrder(String),
+    #[error("order {0} submitted twice")]
+    DuplicateSubmit(String),
+    #[error("order {id} moved from terminal {from:?} to {to:?}")]
+    AfterTerminal { id: String, from: OrderStatus, to: OrderStatus },
+    #[error("fill for order {0} after it reached a terminal state")]
+    FillAfterTerminal(String),
+    #[error("order {id} filled {filled} of {qty}")]
+    Overfill { id: String, filled: Decimal, qty: Decimal },
+    #[error("order {id} acknowledged as {got} but already known as {known}")]
+    OrderIdMismatch { id: String, known: String, got: String },
+}
+
+// Follows orders from submission to a terminal state, orders are keyed by orderLinkId
+// but every update can also be addressed by the exchange orderId once it has been acknowledged
+#[derive(Debug, Clone, Default)]
+pub struct OrderTracker {
+    orders: HashMap<String, TrackedOrder>,
+    order_ids: HashMap<String, String>,
+}
+
+impl OrderTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&TrackedOrder> {
+        self.orders.get(self.resolve(id)?)
+    }
+
+    pub fn open(&self) -> impl Iterator<Item = &TrackedOrder> {
+        self.orders.values().filter(|order| !order.is_terminal())
+    }
+
+    pub fn submit(&mut self, order_link_id: String, qty: Decimal) -> Result<(), Inconsistency> {
+        if self.orders.contains_key(&order_link_id) {
+            return Err(Inconsistency::DuplicateSubmit(order_link_id));
+        }
+        self.orders.insert(order_link_id.clone(), TrackedOrder {
+            order_link_id,
+            order_id: None,
+            qty,
+            filled: Decimal::ZERO,
+            status: None,
+            amendments: 0,
+        });
+        Ok(())
+    }
+
+    pub fn ack(&mut self, order_link_id: &str, order_id: String) -> Result<&TrackedOrder, Inconsistency> {
+        let order = self.orders.get_mut(order_link_id).ok_or_else(|| Inconsistency::UnknownOrder(order_link_id.to_string()))?;
+        match &order.order_id {
+            Some(known) if *known != order_id => {
+                return Err(Inconsistency::OrderIdMismatch { id: order_link_id.to_string(), known: known.clone(), got: order_id });
+            }
+            _ => {}
+        }
+        order.order_id = Some(order_id.clone());
+        order.status.get_or_insert(OrderStatus::New);
+        self.order_ids.insert(order_id, order_link_id.to_string());
+        Ok(order)
+    }
+
+    pub fn on_status(&mut self, id: &str, status: OrderStatus) -> Result<&TrackedOrder, Inconsistency> {
+        let order = self.find_mut(id)?;
+        if let Some(current) = order.status
+            && current.is_terminal()
+            && current != status
+        {
+            return Err(Inconsistency::AfterTerminal { id: id.to_string(), from: current, to: status });
+        }
+        order.status = Some(status);
+        Ok(order)
+    }
+
+    pub fn on_fill(&mut self, id: &str, qty: Decimal) -> Result<&TrackedOrder, Inconsistency> {
+        let order = self.find_mut(id)?;
+        if order.is_terminal() && order.filled >= order.qty {
+            return Err(Inconsistency::FillAfterTerminal(id.to_string()));
+        }
+        // checked before applying, a rejected fill leaves the order as it was
+        let filled = order.filled + qty;
+        if filled > order.qty {
+            return Err(Inconsistency::Overfill { id: id.to_string(), filled, qty: order.qty });
+        }
+        order.filled = filled;
+        if !order.is_terminal() {
+            order.status = Some(if order.filled == order.qty { OrderStatus::Filled } else { OrderStatus::PartiallyFilled });
+        }
+        Ok(order)
+    }
+
+    pub fn on_amend(&mut self, id: &str, qty: Option<Decimal>) -> Result<&TrackedOrder, Inconsistency> {
+        let order = self.find_mut(id)?;
+        if let Some(status) = order.status.filter(OrderStatus::is_terminal) {
+            return Err(Inconsistency::AfterTerminal { id: id.to_string(), from: status, to: status });
+        }
+        if let Some(qty) = qty {
+            order.qty = qty;
+        }
+        order.amendments += 1;
+        Ok(order)
+    }
+
+    // drop terminal orders so long running trackers don't grow without bound
+    pub fn prune(&mut self) {
+        self.orders.retain(|_, order| !order.is_terminal());
+        let orders = &self.orders;
+        self.order_ids.retain(|_, link_id| orders.contains_key(link_id));
+    }
+
+    fn resolve<'a>(&'a self, id: &'a str) -> Option<&'a str> {
+        if self.orders.contains_key(id) {
+            return Some(id);
+        }
+        self.order_ids.get(id).map(String::as_str)
+    }
+
+    fn find_mut(&mut self, id: &str) -> Result<&mut TrackedOrder, Inconsistency> {
+        let link_id = self.resolve(id).map(str::to_string);
+        link_id
+            .and_then(|link_id| self.orders.get_mut(&link_id))
+            .ok_or_else(|| Inconsistency::UnknownOrder(id.to_string()))
+    }
+}
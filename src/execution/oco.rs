@@ -0,0 +1,216 @@
+use std::time::Duration;
+
+use rust_decimal::Decimal;
+
+use super::{ChildChange, Children, Command, ExecutorHandle, Wake};
+use crate::{
+    trade::{OrderType, PlaceOrderRequest},
+    ws::{OrderUpdate, PrivateEvent},
+    Client,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OcoLeg {
+    TakeProfit,
+    Stop,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OcoState {
+    Armed,
+    Filled(OcoLeg),
+    Cancelled,
+}
+
+// Links a take profit limit and a stop order by their orderLinkId, feed it updates from the private
+// order stream and cancel whatever sibling it hands back
+#[derive(Debug, Clone)]
+pub struct Oco {
+    take_profit: String,
+    stop: String,
+    state: OcoState,
+}
+
+impl Oco {
+    pub fn new(take_profit_link_id: String, stop_link_id: String) -> Self {
+        Self {
+            take_profit: take_profit_link_id,
+            stop: stop_link_id,
+            state: OcoState::Armed,
+        }
+    }
+
+    pub fn state(&self) -> OcoState {
+        self.state
+    }
+
+    pub fn link_id(&self, leg: OcoLeg) -> &str {
+        match leg {
+            OcoLeg::TakeProfit => &self.take_profit,
+            OcoLeg::Stop => &self.stop,
+        }
+    }
+
+    pub fn leg(&self, link_id: &str) -> Option<OcoLeg> {
+        if link_id == self.take_profit {
+            Some(OcoLeg::TakeProfit)
+        } else if link_id == self.stop {
+            Some(OcoLeg::Stop)
+        } else {
+            None
+        }
+    }
+
+    // The orderLinkId of the sibling to cancel: on the first (partial) fill of either leg, or when one leg ended
+    // without filling (user cancel, rejection) since the pair is dead then. Updates for other orders are ignored
+    pub fn on_update(&mut self, update: &OrderUpdate) -> Option<&str> {
+        let filled = update.cum_exec_qty.parse::<Decimal>().is_ok_and(|filled| filled > Decimal::ZERO);
+        if filled {
+            self.on_fill(&update.order_link_id)
+        } else if update.order_status.is_terminal() {
+            self.on_cancel(&update.order_link_id)
+        } else {
+            None
+        }
+    }
+
+    // gives up on an armed pair, both legs are left to the caller to cancel
+    pub fn cancel(&mut self) {
+        if self.state == OcoState::Armed {
+            self.state = OcoState::Cancelled;
+        }
+    }
+
+    fn on_fill(&mut self, link_id: &str) -> Option<&str> {
+        let leg = self.leg(link_id)?;
+        if self.state != OcoState::Armed {
+            return None;
+        }
+        self.state = OcoState::Filled(leg);
+        Some(self.link_id(Self::sibling(leg)))
+    }
+
+    fn on_cancel(&mut self, link_id: &str) -> Option<&str> {
+        let leg = self.leg(link_id)?;
+        if self.state != OcoState::Armed {
+            return None;
+        }
+        self.state = OcoState::Cancelled;
+        Some(self.link_id(Self::sibling(leg)))
+    }
+
+    fn sibling(leg: OcoLeg) -> OcoLeg {
+        match leg {
+            OcoLeg::TakeProfit => OcoLeg::Stop,
+            OcoLeg::Stop => OcoLeg::TakeProfit,
+        }
+    }
+}
+
+// Places a take profit limit and a stop order for the same symbol and side, then follows the private order stream
+// and cancels the survivor as soon as one leg fills or goes away
+#[derive(Debug)]
+pub struct OcoExecutor {
+    take_profit: PlaceOrderRequest,
+    stop: PlaceOrderRequest,
+    children: Children,
+    placed: [Option<String>; 2],
+    oco: Option<Oco>,
+    cancelled: bool,
+    paused: bool,
+}
+
+impl OcoExecutor {
+    pub fn new(take_profit: PlaceOrderRequest, stop: PlaceOrderRequest) -> crate::Result<Self> {
+        let invalid = |message: &str| Err(crate::Error::Invalid(message.into()));
+        if take_profit.order_type != OrderType::Limit || take_profit.trigger.is_some() {
+            return invalid("the OCO take profit must be a plain limit order");
+        }
+        if stop.trigger.is_none() {
+            return invalid("the OCO stop needs a trigger");
+        }
+        if (take_profit.category, &take_profit.symbol, take_profit.side) != (stop.category, &stop.symbol, stop.side) {
+            return invalid("both OCO legs must be for the same category, symbol and side");
+        }
+        Ok(Self { take_profit, stop, children: Children::new(), placed: [None, None], oco: None, cancelled: false, paused: false })
+    }
+
+    // leg orderLinkIds are prefix-<n>, a random prefix by default
+    pub fn with_link_id_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.children.set_prefix(prefix);
+        self
+    }
+
+    // None until both legs are on the book
+    pub fn oco(&self) -> Option<&Oco> {
+        self.oco.as_ref()
+    }
+
+    pub fn handle(&self) -> ExecutorHandle {
+        self.children.handle()
+    }
+
+    // Places whichever leg isn't placed yet, then runs until one leg filled or the pair was cancelled and both legs
+    // ended. A failed placement leaves the other leg working, run again to retry it or cancel through the handle
+    pub async fn run<St, F, R, E>(&mut self, client: &Client, events: &mut St, recv_window: &Duration, send: F) -> crate::Result<()>
+    where St: futures::Stream<Item = crate::Result<PrivateEvent>> + Unpin,
+        F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        loop {
+            if !self.cancelled && !self.paused {
+                for (placed, request) in self.placed.iter_mut().zip([&self.take_profit, &self.stop]) {
+                    if placed.is_none() {
+                        *placed = Some(self.children.place(client, request.clone(), recv_window, &send).await?);
+                    }
+                }
+                if let ([Some(take_profit), Some(stop)], None) = (&self.placed, &self.oco) {
+                    self.oco = Some(Oco::new(take_profit.clone(), stop.clone()));
+                }
+            }
+            if self.finished() {
+                return Ok(());
+            }
+            match self.children.wake(events, None).await? {
+                Wake::Command(Command::Pause) => self.paused = true,
+                Wake::Command(Command::Resume) => self.paused = false,
+                Wake::Command(Command::Cancel) => {
+                    self.cancelled = true;
+                    if let Some(oco) = &mut self.oco {
+                        oco.cancel();
+                    }
+                    self.children.cancel_working(client, self.stop.category, &self.stop.symbol, recv_window, &send).await?;
+                }
+                Wake::Changes(changes) => {
+                    for change in changes {
+                        let Some(oco) = &mut self.oco else {
+                            continue;
+                        };
+                        let sibling = match &change {
+                            ChildChange::Filled { order_link_id, .. } => oco.on_fill(order_link_id),
+                            ChildChange::Ended { order_link_id, .. } => oco.on_cancel(order_link_id),
+                        };
+                        if let Some(sibling) = sibling.map(str::to_string) {
+                            self.children.cancel(client, self.stop.category, &self.stop.symbol, &sibling, recv_window, &send).await?;
+                        }
+                    }
+                }
+                Wake::Timer => {}
+            }
+        }
+    }
+
+    // a filled leg may still be working on the rest of its qty, only the sibling has to be gone
+    fn finished(&self) -> bool {
+        match self.oco.as_ref().map(|oco| (oco, oco.state())) {
+            Some((oco, OcoState::Filled(leg))) => {
+                let sibling = oco.link_id(Oco::sibling(leg));
+                self.children.working().all(|order_link_id| order_link_id != sibling)
+            }
+            Some((_, OcoState::Cancelled)) => self.children.is_idle(),
+            Some((_, OcoState::Armed)) => false,
+            None => self.cancelled && self.children.is_idle(),
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+#[cfg(all(feature = "ws", feature = "trade"))]
+use super::{ChildChange, Children, Command, ExecutorHandle, Wake};
+use crate::number::Precision;
+#[cfg(all(feature = "ws", feature = "trade"))]
+use crate::{trade::PlaceOrderRequest, ws::PrivateEvent, Client};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TwapState {
+    Running,
+    Paused,
+    Cancelled,
+    Completed,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChildSlice {
+    pub index: u32,
+    pub qty: Decimal,
+    pub due: DateTime<Utc>,
+}
+
+// Transport agnostic TWAP scheduler, the caller places the child orders it hands out and reports
+// fills back from the execution stream, quantity that was never sent or got released is carried into later slices
+#[derive(Debug, Clone)]
+pub struct Twap {
+    total: Decimal,
+    slices: u32,
+    interval: Duration,
+    start: DateTime<Utc>,
+    precision: Precision,
+    min_gap: Duration,
+    next_slice: u32,
+    last_sent: Option<DateTime<Utc>>,
+    filled: Decimal,
+    working: Decimal,
+    state: TwapState,
+}
+
+impl Twap {
+    pub fn new(total: Decimal, duration: Duration, slices: u32, start: DateTime<Utc>) -> Self {
+        let slices = slices.max(1);
+        Self {
+            total,
+            slices,
+            interval: duration / slices,
+            start,
+            precision: Precision::default(),
+            min_gap: Duration::ZERO,
+            next_slice: 0,
+            last_sent: None,
+            filled: Decimal::ZERO,
+            working: Decimal::ZERO,
+            state: TwapState::Running,
+        }
+    }
+
+    // a remainder below one step can't be sent, the TWAP completes without it and remaining() reports it
+    pub fn with_qty_step(mut self, step: Decimal) -> Self {
+        self.precision = Precision::new(Decimal::ZERO, step);
+        self
+    }
+
+    // lower bound between two child orders so catching up after a pause doesn't burst through the rate limit
+    pub fn with_min_gap(mut self, gap: Duration) -> Self {
+        self.min_gap = gap;
+        self
+    }
+
+    pub fn state(&self) -> TwapState {
+        self.state
+    }
+
+    pub fn filled(&self) -> Decimal {
+        self.filled
+    }
+
+    pub fn working(&self) -> Decimal {
+        self.working
+    }
+
+    pub fn remaining(&self) -> Decimal {
+        self.total - self.filled
+    }
+
+    pub fn next_due(&self) -> Option<DateTime<Utc>> {
+        if self.state != TwapState::Running {
+            return None;
+        }
+        let scheduled = if self.next_slice < self.slices {
+            self.start + self.interval * self.next_slice
+        } else if self.round(self.unsent()) > Decimal::ZERO {
+            // released after the last slice, sent again as soon as the gap allows
+            self.start + self.interval * (self.slices - 1)
+        } else {
+            return None;
+        };
+        match self.last_sent {
+            Some(last) => Some(scheduled.max(last + self.min_gap)),
+            None => Some(scheduled),
+        }
+    }
+
+    pub fn poll(&mut self, now: DateTime<Utc>) -> Option<ChildSlice> {
+        loop {
+            self.settle();
+            let due = self.next_due()?;
+            if due > now {
+                return None;
+            }
+            let index = self.next_slice;
+            self.next_slice += 1;
+            let target = if self.next_slice >= self.slices {
+                self.total
+            } else {
+                self.total * Decimal::from(self.next_slice) / Decimal::from(self.slices)
+            };
+            let qty = self.round(target - self.filled - self.working);
+            if qty <= Decimal::ZERO {
+                continue;
+            }
+            self.working += qty;
+            self.last_sent = Some(now);
+            return Some(ChildSlice { index, qty, due });
+        }
+    }
+
+    // fills still arriving after a cancel are booked but leave the TWAP Cancelled
+    pub fn on_fill(&mut self, qty: Decimal) {
+        self.filled += qty;
+        self.working = (self.working - qty).max(Decimal::ZERO);
+        if self.filled >= self.total && matches!(self.state, TwapState::Running | TwapState::Paused) {
+            self.state = TwapState::Completed;
+        }
+        self.settle();
+    }
+
+    // child order ended without filling this quantity (cancelled, expired, rejected)
+    pub fn on_release(&mut self, qty: Decimal) {
+        self.working = (self.working - qty).max(Decimal::ZERO);
+        self.settle();
+    }
+
+    pub fn pause(&mut self) {
+        if self.state == TwapState::Running {
+            self.state = TwapState::Paused;
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if self.state == TwapState::Paused {
+            self.state = TwapState::Running;
+        }
+    }
+
+    pub fn cancel(&mut self) {
+        if self.state != TwapState::Completed {
+            self.state = TwapState::Cancelled;
+        }
+    }
+
+    fn unsent(&self) -> Decimal {
+        self.total - self.filled - self.working
+    }
+
+    // done once every slice went out, nothing is working and what's left is less than one qty step
+    fn settle(&mut self) {
+        if self.state == TwapState::Running
+            && self.next_slice >= self.slices
+            && self.working.is_zero()
+            && self.round(self.unsent()) <= Decimal::ZERO
+        {
+            self.state = TwapState::Completed;
+        }
+    }
+
+    fn round(&self, qty: Decimal) -> Decimal {
+        self.precision.qty(qty)
+    }
+}
+
+// Runs a Twap against the exchange: every slice goes out as a copy of template with the slice's qty, fills and ends
+// of the child orders come from the private order and execution streams
+#[cfg(all(feature = "ws", feature = "trade"))]
+#[derive(Debug)]
+pub struct TwapExecutor {
+    twap: Twap,
+    template: PlaceOrderRequest,
+    children: Children,
+}
+
+#[cfg(all(feature = "ws", feature = "trade"))]
+impl TwapExecutor {
+    pub fn new(twap: Twap, template: PlaceOrderRequest) -> Self {
+        Self { twap, template, children: Children::new() }
+    }
+
+    // child orderLinkIds are prefix-<n>, a random prefix by default
+    pub fn with_link_id_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.children.set_prefix(prefix);
+        self
+    }
+
+    pub fn twap(&self) -> &Twap {
+        &self.twap
+    }
+
+    pub fn handle(&self) -> ExecutorHandle {
+        self.children.handle()
+    }
+
+    // Places slices as they come due until the TWAP completed, or was cancelled and its working children ended.
+    // An error leaves the executor as it was, run it again (after a resync when the stream dropped) to carry on
+    pub async fn run<St, F, R, E>(&mut self, client: &Client, events: &mut St, recv_window: &Duration, send: F) -> crate::Result<()>
+    where St: futures::Stream<Item = crate::Result<PrivateEvent>> + Unpin,
+        F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        loop {
+            while let Some(slice) = self.twap.poll(client.now()) {
+                let request = PlaceOrderRequest { qty: slice.qty, ..self.template.clone() };
+                if let Err(err) = self.children.place(client, request, recv_window, &send).await {
+                    self.twap.on_release(slice.qty);
+                    return Err(err);
+                }
+            }
+            if matches!(self.twap.state(), TwapState::Completed | TwapState::Cancelled) && self.children.is_idle() {
+                return Ok(());
+            }
+            let wait = self.twap.next_due().map(|due| (due - client.now()).to_std().unwrap_or_default());
+            match self.children.wake(events, wait).await? {
+                Wake::Command(Command::Pause) => self.twap.pause(),
+                Wake::Command(Command::Resume) => self.twap.resume(),
+                Wake::Command(Command::Cancel) => {
+                    self.twap.cancel();
+                    self.children.cancel_working(client, self.template.category, &self.template.symbol, recv_window, &send).await?;
+                }
+                Wake::Changes(changes) => {
+                    for change in changes {
+                        match change {
+                            ChildChange::Filled { qty, .. } => self.twap.on_fill(qty),
+                            ChildChange::Ended { unfilled, .. } => self.twap.on_release(unfilled),
+                        }
+                    }
+                }
+                Wake::Timer => {}
+            }
+        }
+    }
+}
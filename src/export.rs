@@ -0,0 +1,301 @@
+use std::{
+    borrow::Cow,
+    fs::File,
+    io::{BufWriter, Write},
+    marker::PhantomData,
+    path::Path,
+};
+#[cfg(any(feature = "trade", feature = "account"))]
+use std::time::Duration;
+#[cfg(feature = "market")]
+use std::ops::RangeInclusive;
+
+#[cfg(feature = "account")]
+use crate::account::{TransactionLogEntry, TransactionLogQuery};
+#[cfg(feature = "market")]
+use crate::{market::Kline, Category, Interval};
+#[cfg(feature = "position")]
+use crate::position::{ClosedPnl, ClosedPnlQuery};
+#[cfg(feature = "trade")]
+use crate::trade::{Execution, ExecutionQuery};
+#[cfg(any(feature = "trade", feature = "account", feature = "market"))]
+use crate::Client;
+#[cfg(any(feature = "trade", feature = "position"))]
+use crate::Side;
+
+// A history row as export columns, Bybit's own strings as it sent them so nothing is lost to parsing. Columns are
+// named after Bybit's fields, times are unix millis. Rows computed here rather than fetched format their own values
+pub trait ExportRow {
+    const COLUMNS: &'static [&'static str];
+
+    fn values(&self) -> Vec<Cow<'_, str>>;
+}
+
+#[cfg(any(feature = "trade", feature = "account", feature = "market"))]
+fn borrowed(values: Vec<&str>) -> Vec<Cow<'_, str>> {
+    values.into_iter().map(Cow::Borrowed).collect()
+}
+
+// Where the export_ calls put rows, a page at a time as they arrive
+pub trait ExportSink<T: ExportRow> {
+    fn write(&mut self, rows: &[T]) -> crate::Result<()>;
+}
+
+// RFC 4180 CSV with a header line, values are quoted only when they need it
+pub struct CsvExporter<T, W: Write = BufWriter<File>> {
+    writer: W,
+    rows: PhantomData<fn(&T)>,
+}
+
+impl<T: ExportRow> CsvExporter<T> {
+    pub fn create(path: impl AsRef<Path>) -> crate::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<T: ExportRow, W: Write> CsvExporter<T, W> {
+    // the header is written right away, an export without rows still has one
+    pub fn new(mut writer: W) -> crate::Result<Self> {
+        write_line(&mut writer, T::COLUMNS)?;
+        Ok(Self { writer, rows: PhantomData })
+    }
+
+    pub fn finish(mut self) -> crate::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl<T: ExportRow, W: Write> ExportSink<T> for CsvExporter<T, W> {
+    fn write(&mut self, rows: &[T]) -> crate::Result<()> {
+        for row in rows {
+            write_line(&mut self.writer, &row.values())?;
+        }
+        Ok(())
+    }
+}
+
+fn write_line(writer: &mut impl Write, values: &[impl AsRef<str>]) -> std::io::Result<()> {
+    for (index, value) in values.iter().enumerate() {
+        let value = value.as_ref();
+        if index > 0 {
+            writer.write_all(b",")?;
+        }
+        if value.contains([',', '"', '\n', '\r']) {
+            write!(writer, "\"{}\"", value.replace('"', "\"\""))?;
+        } else {
+            writer.write_all(value.as_bytes())?;
+        }
+    }
+    writer.write_all(b"\n")
+}
+
+#[cfg(feature = "parquet")]
+pub use parquet_exporter::*;
+
+#[cfg(feature = "parquet")]
+mod parquet_exporter {
+    use std::{fs::File, marker::PhantomData, path::Path, sync::Arc};
+
+    use arrow_array::{builder::StringBuilder, ArrayRef, RecordBatch};
+    use arrow_schema::{DataType, Field, Schema, SchemaRef};
+    use parquet::arrow::ArrowWriter;
+
+    use super::{ExportRow, ExportSink};
+
+    // Every column as a string, cast in the query (DuckDB, pandas) where a type is needed. The file is only readable
+    // once finished
+    pub struct ParquetExporter<T> {
+        writer: ArrowWriter<File>,
+        schema: SchemaRef,
+        rows: PhantomData<fn(&T)>,
+    }
+
+    impl<T: ExportRow> ParquetExporter<T> {
+        pub fn create(path: impl AsRef<Path>) -> crate::Result<Self> {
+            let schema = Arc::new(Schema::new(T::COLUMNS.iter().map(|column| Field::new(*column, DataType::Utf8, false)).collect::<Vec<_>>()));
+            let writer = ArrowWriter::try_new(File::create(path)?, schema.clone(), None)?;
+            Ok(Self { writer, schema, rows: PhantomData })
+        }
+
+        pub fn finish(self) -> crate::Result<()> {
+            self.writer.close()?;
+            Ok(())
+        }
+    }
+
+    impl<T: ExportRow> ExportSink<T> for ParquetExporter<T> {
+        fn write(&mut self, rows: &[T]) -> crate::Result<()> {
+            let mut columns: Vec<StringBuilder> = T::COLUMNS.iter().map(|_| StringBuilder::new()).collect();
+            for row in rows {
+                for (column, value) in columns.iter_mut().zip(row.values()) {
+                    column.append_value(value);
+                }
+            }
+            let columns: Vec<ArrayRef> = columns.iter_mut().map(|column| Arc::new(column.finish()) as ArrayRef).collect();
+            let batch = RecordBatch::try_new(self.schema.clone(), columns).map_err(parquet::errors::ParquetError::from)?;
+            Ok(self.writer.write(&batch)?)
+        }
+    }
+}
+
+#[cfg(any(feature = "trade", feature = "position"))]
+fn side(side: Side) -> &'static str {
+    match side {
+        Side::Buy => "Buy",
+        Side::Sell => "Sell",
+    }
+}
+
+#[cfg(feature = "trade")]
+impl ExportRow for Execution {
+    const COLUMNS: &'static [&'static str] = &[
+        "symbol", "orderId", "orderLinkId", "side", "orderPrice", "orderQty", "leavesQty", "orderType", "execId", "execPrice",
+        "execQty", "execValue", "execFee", "execType", "execTime", "feeRate", "feeCurrency", "isMaker", "markPrice", "closedSize",
+    ];
+
+    fn values(&self) -> Vec<Cow<'_, str>> {
+        borrowed(vec![
+            &self.symbol, &self.order_id, &self.order_link_id, side(self.side), &self.order_price, &self.order_qty, &self.leaves_qty,
+            &self.order_type, &self.exec_id, &self.exec_price, &self.exec_qty, &self.exec_value, &self.exec_fee, &self.exec_type,
+            &self.exec_time, &self.fee_rate, self.fee_currency.as_deref().unwrap_or_default(), if self.is_maker { "true" } else { "false" },
+            &self.mark_price, self.closed_size.as_deref().unwrap_or_default(),
+        ])
+    }
+}
+
+#[cfg(feature = "position")]
+impl ExportRow for ClosedPnl {
+    const COLUMNS: &'static [&'static str] = &[
+        "symbol", "orderId", "side", "qty", "orderPrice", "orderType", "execType", "closedSize", "cumEntryValue", "avgEntryPrice",
+        "cumExitValue", "avgExitPrice", "closedPnl", "fillCount", "leverage", "createdTime", "updatedTime",
+    ];
+
+    fn values(&self) -> Vec<Cow<'_, str>> {
+        borrowed(vec![
+            &self.symbol, &self.order_id, side(self.side), &self.qty, &self.order_price, &self.order_type, &self.exec_type, &self.closed_size,
+            &self.cum_entry_value, &self.avg_entry_price, &self.cum_exit_value, &self.avg_exit_price, &self.closed_pnl, &self.fill_count,
+            &self.leverage, &self.created_time, &self.updated_time,
+        ])
+    }
+}
+
+#[cfg(feature = "account")]
+impl ExportRow for TransactionLogEntry {
+    const COLUMNS: &'static [&'static str] = &[
+        "id", "symbol", "category", "side", "transactionTime", "type", "qty", "size", "currency", "tradePrice", "funding", "fee",
+        "cashFlow", "change", "cashBalance", "feeRate", "tradeId", "orderId", "orderLinkId",
+    ];
+
+    fn values(&self) -> Vec<Cow<'_, str>> {
+        borrowed(vec![
+            &self.id, &self.symbol, &self.category, &self.side, &self.transaction_time, &self.kind, &self.qty, &self.size, &self.currency,
+            &self.trade_price, &self.funding, &self.fee, &self.cash_flow, &self.change, &self.cash_balance, &self.fee_rate, &self.trade_id,
+            &self.order_id, &self.order_link_id,
+        ])
+    }
+}
+
+#[cfg(feature = "market")]
+impl ExportRow for Kline {
+    const COLUMNS: &'static [&'static str] = &["start", "open", "high", "low", "close", "volume", "turnover"];
+
+    fn values(&self) -> Vec<Cow<'_, str>> {
+        borrowed(vec![&self.start, &self.open, &self.high, &self.low, &self.close, &self.volume, &self.turnover])
+    }
+}
+
+#[cfg(feature = "market")]
+const KLINE_PAGE: u32 = 1000;
+
+#[cfg(feature = "trade")]
+impl Client {
+    // every page of query into sink as it arrives, returns the rows written
+    pub async fn export_executions<S, F, R, E>(&self, query: &ExecutionQuery, recv_window: &Duration, sink: &mut S, send: F) -> crate::Result<usize>
+    where S: ExportSink<Execution>,
+        F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let mut query = query.clone();
+        let mut written = 0;
+        loop {
+            let page = self.get_executions(&query, recv_window)?.send(&send).await?;
+            sink.write(&page.list)?;
+            written += page.list.len();
+            query = query.with_cursor(page.next_cursor());
+            if query.cursor.is_none() {
+                return Ok(written);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "position")]
+impl Client {
+    pub async fn export_closed_pnl<S, F, R, E>(&self, query: &ClosedPnlQuery, recv_window: &Duration, sink: &mut S, send: F) -> crate::Result<usize>
+    where S: ExportSink<ClosedPnl>,
+        F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let mut query = query.clone();
+        let mut written = 0;
+        loop {
+            let page = self.get_closed_pnl(&query, recv_window)?.send(&send).await?;
+            sink.write(&page.list)?;
+            written += page.list.len();
+            query = query.with_cursor(page.next_cursor());
+            if query.cursor.is_none() {
+                return Ok(written);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "account")]
+impl Client {
+    pub async fn export_transaction_log<S, F, R, E>(&self, query: &TransactionLogQuery, recv_window: &Duration, sink: &mut S, send: F) -> crate::Result<usize>
+    where S: ExportSink<TransactionLogEntry>,
+        F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let mut query = query.clone();
+        let mut written = 0;
+        loop {
+            let page = self.get_transaction_log(&query, recv_window)?.send(&send).await?;
+            sink.write(&page.list)?;
+            written += page.list.len();
+            query = query.with_cursor(page.next_cursor());
+            if query.cursor.is_none() {
+                return Ok(written);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "market")]
+impl Client {
+    // Klines starting within range (unix millis). The endpoint has no cursor, pages go backwards from the end of range
+    // so rows come newest first like a single page
+    pub async fn export_klines<S, F, R, E>(&self, category: Category, symbol: &str, interval: Interval, range: RangeInclusive<i64>, sink: &mut S, send: F) -> crate::Result<usize>
+    where S: ExportSink<Kline>,
+        F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let (start, mut end) = range.into_inner();
+        let mut written = 0;
+        loop {
+            let page = self.get_klines(category, symbol.to_string(), interval, Some(start), Some(end), Some(KLINE_PAGE))?.send(&send).await?.list;
+            sink.write(&page)?;
+            written += page.len();
+            let oldest = page.iter().filter_map(|kline| kline.start.parse::<i64>().ok()).min();
+            match oldest {
+                Some(oldest) if page.len() >= KLINE_PAGE as usize && oldest > start => end = oldest - 1,
+                _ => return Ok(written),
+            }
+        }
+    }
+}
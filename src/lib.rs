@@ -1,10 +1,97 @@
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
 use chrono::{DateTime, Utc};
 use serde::{de::Unexpected, Deserialize, Serialize};
 
+#[cfg(feature = "account")]
+pub mod account;
+pub mod aggregate;
+#[cfg(feature = "market")]
+pub mod analytics;
+#[cfg(feature = "asset")]
+pub mod asset;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod body;
+pub mod calendar;
+pub mod clock;
+pub mod config;
+#[cfg(feature = "trade")]
+pub mod dcp;
+#[cfg(feature = "market")]
+pub mod diagnose;
+#[cfg(feature = "market")]
+pub mod download;
+#[cfg(all(feature = "trade", feature = "market"))]
+pub mod dryrun;
+pub mod error;
+pub mod execution;
+pub mod export;
+pub mod number;
+#[cfg(feature = "market")]
+pub mod market;
+pub mod metrics;
+#[cfg(all(feature = "ws", feature = "trade"))]
+pub mod paper;
+#[cfg(feature = "position")]
+pub mod position;
+pub mod query;
+pub mod ratelimit;
+pub mod raw;
+#[cfg(all(feature = "trade", feature = "account"))]
+pub mod reconcile;
+pub mod record;
+pub mod retry;
+#[cfg(feature = "trade")]
+pub mod risk;
+pub mod scheduler;
+pub mod shutdown;
+pub mod sign;
+pub mod sizing;
+#[cfg(feature = "storage")]
+pub mod storage;
+#[cfg(all(feature = "ws", feature = "position"))]
+pub mod state;
+#[cfg(feature = "market")]
+pub mod timesync;
+#[cfg(feature = "trade")]
+pub mod trade;
+#[cfg(feature = "user")]
+pub mod user;
+#[cfg(feature = "ws")]
+pub mod ws;
+
+#[cfg(feature = "asset")]
+pub use asset::{BybitBalance, FundingBalance};
+pub use config::BybitConfig;
+pub use error::{BoxError, Error, KnownErrorCode, Result};
+pub use sign::{sign, Credentials};
+
 pub const MAINNET: &str = "https://api.bybit.com";
+pub const TESTNET: &str = "https://api-testnet.bybit.com";
+pub const DEMO: &str = "https://api-demo.bybit.com";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Environment {
+    #[default]
+    Mainnet,
+    Testnet,
+    Demo
+}
 
+impl Environment {
+    pub fn base_url(&self) -> &'static str {
+        match self {
+            Self::Mainnet => MAINNET,
+            Self::Testnet => TESTNET,
+            Self::Demo => DEMO,
+        }
+    }
+}
+
+// Query strings keep the struct's field declaration order (see query::to_string), Bybit signs the query exactly as it appears in the URI
+// so request builders serialize once and sign that same string rather than re-serializing
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(untagged)]
 pub enum Params<T> {
@@ -13,25 +100,16 @@ pub enum Params<T> {
 }
 
 impl<T:Serialize> Params<T> {
-    pub fn to_string(&self) -> anyhow::Result<String> {
+    pub fn to_string(&self) -> Result<String> {
         match self {
-            Params::Get(query) => Ok(serde_qs::to_string(query)?),
-            Params::Post(body) => Ok(serde_json::to_string(body)?),
+            Params::Get(query) => query::to_string(query),
+            Params::Post(body) => serde_json::to_string(body).map_err(|err| Error::Serialization(err.to_string())),
         }
     }
 }
 
-pub fn sign<T: Serialize>(secret: &str, timestamp: &DateTime<Utc>, api_key: &str, recv_window: &Duration, params: &Params<T>) -> anyhow::Result<String> {
-    let timestamp = timestamp.timestamp_millis().to_string();
-    let recv_window = recv_window.as_millis().to_string();
-    let params = params.to_string()?;
-    let signature = format!("{timestamp}{api_key}{recv_window}{params}");
-    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, secret.as_bytes());
-    Ok(hex::encode(ring::hmac::sign(&key, signature.as_bytes())))
-}
-
 #[derive(Debug, Clone, Deserialize)]
-pub struct Response<T> 
+pub struct Response<T, X = serde_json::Value>
 {
     #[serde(rename = "retCode")]
     pub return_code: i32,
@@ -39,30 +117,227 @@ pub struct Response<T>
     pub return_message: String,
     pub result: T,
     #[serde(rename = "retExtInfo")]
-    pub return_extended_info: Option<serde_json::Value>,
+    pub return_extended_info: Option<X>,
     pub time: u64,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone)]
+pub struct HttpResponse<T, X = serde_json::Value> {
+    pub correlation_id: CorrelationId,
+    pub status: http::StatusCode,
+    pub headers: http::HeaderMap,
+    pub response: Response<T, X>,
+}
+
+impl<T, X> HttpResponse<T, X> {
+    pub fn trace_id(&self) -> Option<&str> {
+        trace_id(&self.headers)
+    }
+}
+
+// Bybit's id for a call, the one to quote in support tickets
+pub const TRACE_ID_HEADER: &str = "traceid";
+
+pub fn trace_id(headers: &http::HeaderMap) -> Option<&str> {
+    headers.get(TRACE_ID_HEADER)?.to_str().ok()
+}
+
+// Generated for every request the crate builds and kept in its extensions, so transports can put it on their log
+// lines and spans. Retries of a request keep its id
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CorrelationId(pub uuid::Uuid);
+
+impl CorrelationId {
+    pub fn new() -> Self {
+        Self(uuid::Uuid::new_v4())
+    }
+
+    pub fn of<B>(request: &http::Request<B>) -> Option<Self> {
+        request.extensions().get().copied()
+    }
+}
+
+impl Default for CorrelationId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for CorrelationId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+// anything a successful response carries beyond its result, send drops these so use send_with_warnings or
+// send_response when they matter
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    // retMsg other than the usual OK/success
+    Message(String),
+    // a non-empty retExtInfo, e.g. per-item outcomes of batch endpoints
+    ExtInfo(serde_json::Value),
+}
+
+impl<T> Response<T, serde_json::Value> {
+    pub fn warnings(&self) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+        if !matches!(self.return_message.to_ascii_lowercase().as_str(), "" | "ok" | "success") {
+            warnings.push(Warning::Message(self.return_message.clone()));
+        }
+        match &self.return_extended_info {
+            None | Some(serde_json::Value::Null) => {}
+            Some(serde_json::Value::Object(info)) if info.is_empty() => {}
+            Some(info) => warnings.push(Warning::ExtInfo(info.clone())),
+        }
+        warnings
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    Spot,
+    Linear,
+    Inverse,
+    Option
+}
+
+impl Category {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Spot => "spot",
+            Self::Linear => "linear",
+            Self::Inverse => "inverse",
+            Self::Option => "option",
+        }
+    }
+}
+
+// option symbols look like BTC-29JUL22-25000-C (or ...-C-USDT for USDT settled ones), dated futures lack the C/P leg
+pub fn is_option_symbol(symbol: &str) -> bool {
+    symbol.split('-').count() >= 4 && symbol.split('-').any(|part| part == "C" || part == "P")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum Interval {
+    #[serde(rename = "1")]
+    Minute1,
+    #[serde(rename = "3")]
+    Minute3,
+    #[serde(rename = "5")]
+    Minute5,
+    #[serde(rename = "15")]
+    Minute15,
+    #[serde(rename = "30")]
+    Minute30,
+    #[serde(rename = "60")]
+    Hour1,
+    #[serde(rename = "120")]
+    Hour2,
+    #[serde(rename = "240")]
+    Hour4,
+    #[serde(rename = "360")]
+    Hour6,
+    #[serde(rename = "720")]
+    Hour12,
+    #[serde(rename = "D")]
+    Day,
+    #[serde(rename = "W")]
+    Week,
+    #[serde(rename = "M")]
+    Month
+}
+
+impl Interval {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Minute1 => "1",
+            Self::Minute3 => "3",
+            Self::Minute5 => "5",
+            Self::Minute15 => "15",
+            Self::Minute30 => "30",
+            Self::Hour1 => "60",
+            Self::Hour2 => "120",
+            Self::Hour4 => "240",
+            Self::Hour6 => "360",
+            Self::Hour12 => "720",
+            Self::Day => "D",
+            Self::Week => "W",
+            Self::Month => "M",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum Side {
+    Buy,
+    Sell
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum OrderStatus {
+    Created,
+    New,
+    Rejected,
+    PartiallyFilled,
+    PartiallyFilledCanceled,
+    Filled,
+    Cancelled,
+    Untriggered,
+    Triggered,
+    Deactivated,
+    Active
+}
+
+impl OrderStatus {
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Rejected | Self::PartiallyFilledCanceled | Self::Filled | Self::Cancelled | Self::Deactivated)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum MarginMode {
+    #[serde(rename = "REGULAR_MARGIN")]
+    Cross,
+    #[serde(rename = "ISOLATED_MARGIN")]
+    Isolated,
+    #[serde(rename = "PORTFOLIO_MARGIN")]
+    Portfolio
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum AccountType {
     UNIFIED,
     FUND,
     CONTRACT,
-    SPOT
+    SPOT,
+    OPTION,
+    INVESTMENT,
+    // wallet types Bybit adds later deserialize here instead of failing the whole response
+    #[serde(untagged)]
+    Other(String)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(from = "(String, String)")]
+pub struct PriceLevel {
+    pub price: String,
+    pub size: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct BybitBalance {
-    coin: String,
-    #[serde(rename = "transferBalance")]
-    transfer_balance: String,
-    #[serde(rename = "walletBalance")]
-    wallet_balance: String,
-    bonus: String,
+impl From<(String, String)> for PriceLevel {
+    fn from((price, size): (String, String)) -> Self {
+        Self { price, size }
+    }
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct Empty {}
 
-pub struct BybitRequest<T: for<'a> serde::Deserialize<'a>>(http::Request<String>,std::marker::PhantomData<T>);
+
+// X is the type of retExtInfo, endpoints that return structured data there switch it with with_ext_info
+pub struct BybitRequest<T: for<'a> serde::Deserialize<'a>, X: for<'a> serde::Deserialize<'a> = serde_json::Value>(http::Request<String>,std::marker::PhantomData<(T, X)>);
 
 #[derive(Debug, Deserialize)]
 pub struct BybitError {
@@ -71,7 +346,13 @@ pub struct BybitError {
     code: BybitErrorCode,
 
     #[serde(rename = "retMsg")]
-    message: Option<String>
+    message: Option<String>,
+
+    // filled in by the send methods, the trace id only when the transport hands back headers (send_http)
+    #[serde(skip)]
+    correlation_id: Option<CorrelationId>,
+    #[serde(skip)]
+    trace_id: Option<String>,
 }
 impl std::error::Error for BybitError {}
 impl std::fmt::Display for BybitError {
@@ -80,29 +361,158 @@ impl std::fmt::Display for BybitError {
             Some(data) => {data},
             None => "N/A"
         };
-        write!(f, "BybitError: {} ({})", message, self.code.0)
+        write!(f, "BybitError: {} ({})", message, self.code.0)?;
+        if let Some(trace_id) = &self.trace_id {
+            write!(f, " traceId {trace_id}")?;
+        }
+        Ok(())
     }
 }
 
-impl<T: for<'a> serde::Deserialize<'a>> BybitRequest<T> {
-    fn new(req: http::Request<String>) -> Self {
+impl BybitError {
+    pub fn code(&self) -> i32 {
+        self.code.0
+    }
+
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    pub fn known_code(&self) -> error::KnownErrorCode {
+        self.code.0.into()
+    }
+
+    #[cfg(all(feature = "ws", feature = "trade"))]
+    pub(crate) fn from_ack(code: i32, message: Option<String>, trace_id: Option<&str>) -> Self {
+        Self { code: BybitErrorCode(code), message, correlation_id: None, trace_id: trace_id.map(str::to_string) }
+    }
+
+    pub fn correlation_id(&self) -> Option<CorrelationId> {
+        self.correlation_id
+    }
+
+    pub fn trace_id(&self) -> Option<&str> {
+        self.trace_id.as_deref()
+    }
+}
+
+// the envelope a dry run answered with, the send methods return it without calling the transport
+#[derive(Debug, Clone)]
+pub(crate) struct Simulated(pub(crate) bytes::Bytes);
+
+impl<T: for<'a> serde::Deserialize<'a>, X: for<'a> serde::Deserialize<'a>> BybitRequest<T, X> {
+    fn new(mut req: http::Request<String>) -> Self {
+        req.extensions_mut().insert(CorrelationId::new());
         Self(req,std::marker::PhantomData)
     }
-    pub async fn send<F, R, E>(self, func: F) -> anyhow::Result<T>
+
+    // true when the request was answered by a dry run and never goes out, see Client::with_dry_run
+    pub fn is_simulated(&self) -> bool {
+        self.0.extensions().get::<Simulated>().is_some()
+    }
+
+    fn simulated(&self) -> Option<bytes::Bytes> {
+        self.0.extensions().get::<Simulated>().map(|simulated| simulated.0.clone())
+    }
+
+    pub fn correlation_id(&self) -> Option<CorrelationId> {
+        CorrelationId::of(&self.0)
+    }
+
+    pub fn with_ext_info<Y: for<'a> serde::Deserialize<'a>>(self) -> BybitRequest<T, Y> {
+        BybitRequest(self.0, std::marker::PhantomData)
+    }
+
+    pub async fn send<F, R, E>(self, func: F) -> Result<T>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        Ok(self.send_response(func).await?.result)
+    }
+
+    pub async fn send_with_warnings<F, R, E>(self, func: F) -> Result<(T, Vec<Warning>)>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let response = self.with_ext_info::<serde_json::Value>().send_response(func).await?;
+        let warnings = response.warnings();
+        Ok((response.result, warnings))
+    }
+
+    pub async fn send_response<F, R, E>(self, func: F) -> Result<Response<T, X>>
     where F: Fn(http::Request<String>) -> R,
         R: std::future::Future<Output = Result<bytes::Bytes, E>>,
-        anyhow::Error: From<E>
+        E: Into<crate::BoxError>
+    {
+        let correlation_id = self.correlation_id();
+        let body = match self.simulated() {
+            Some(body) => body,
+            None => func(self.0).await.map_err(|err| Error::Transport(err.into()))?,
+        };
+        Self::decode(body, correlation_id, None)
+    }
+
+    // for transports that hand back the whole http response, keeps the status and headers (rate limit state,
+    // traceId) next to the decoded envelope
+    pub async fn send_http<F, R, E>(self, func: F) -> Result<HttpResponse<T, X>>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<http::Response<bytes::Bytes>, E>>,
+        E: Into<crate::BoxError>
+    {
+        let correlation_id = self.correlation_id().unwrap_or_default();
+        let (parts, body) = match self.simulated() {
+            Some(body) => http::Response::new(body).into_parts(),
+            None => func(self.0).await.map_err(|err| Error::Transport(err.into()))?.into_parts(),
+        };
+        let response = Self::decode(body, Some(correlation_id), trace_id(&parts.headers))?;
+        Ok(HttpResponse { correlation_id, status: parts.status, headers: parts.headers, response })
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn send_blocking<F, E>(self, func: F) -> Result<T>
+    where F: Fn(http::Request<String>) -> Result<bytes::Bytes, E>,
+        E: Into<crate::BoxError>
+    {
+        Ok(self.send_response_blocking(func)?.result)
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn send_with_warnings_blocking<F, E>(self, func: F) -> Result<(T, Vec<Warning>)>
+    where F: Fn(http::Request<String>) -> Result<bytes::Bytes, E>,
+        E: Into<crate::BoxError>
+    {
+        let response = self.with_ext_info::<serde_json::Value>().send_response_blocking(func)?;
+        let warnings = response.warnings();
+        Ok((response.result, warnings))
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn send_response_blocking<F, E>(self, func: F) -> Result<Response<T, X>>
+    where F: Fn(http::Request<String>) -> Result<bytes::Bytes, E>,
+        E: Into<crate::BoxError>
     {
+        let correlation_id = self.correlation_id();
+        let body = match self.simulated() {
+            Some(body) => body,
+            None => func(self.0).map_err(|err| Error::Transport(err.into()))?,
+        };
+        Self::decode(body, correlation_id, None)
+    }
+
+    fn decode(body: bytes::Bytes, correlation_id: Option<CorrelationId>, trace_id: Option<&str>) -> Result<Response<T, X>> {
+        // the error variant goes first, results that deserialize from an empty object would otherwise swallow errors
         #[derive(serde::Deserialize)]
         #[serde(untagged)]
-        enum _Response<T> {
-            Ok(Response<T>),
-            Err(BybitError)
+        enum _Response<T, X> {
+            Err(BybitError),
+            Ok(Response<T, X>)
         }
-        let response: _Response<T> = serde_json::from_slice(&func(self.0).await?)?;
+        let response: _Response<T, X> = serde_json::from_slice(&body).map_err(|source| Error::deserialize(source, body.clone()))?;
         match response {
-            _Response::Ok(data) => Ok(data.result),
-            _Response::Err(err) => Err(err.into())
+            _Response::Ok(data) => Ok(data),
+            _Response::Err(err) => Err(Error::Api(BybitError { correlation_id, trace_id: trace_id.map(str::to_string), ..err }))
         }
     }
 }
@@ -124,101 +534,179 @@ impl<'de> Deserialize<'de> for BybitErrorCode {
     }
 }
 
+// auth headers shared by every signed request, payload is the query string or body exactly as it goes on the wire
+pub(crate) fn signed_builder(method: &str, credentials: &sign::Credentials, recv_window: &Duration, timestamp: &DateTime<Utc>, payload: &str) -> Result<http::request::Builder> {
+    Ok(http::request::Builder::new()
+        .method(method)
+        .header("X-BAPI-API-KEY", credentials.key())
+        .header("X-BAPI-SIGN", credentials.sign(timestamp, recv_window, payload)?)
+        .header("X-BAPI-TIMESTAMP", timestamp.timestamp_millis().to_string())
+        .header("X-BAPI-RECV-WINDOW", recv_window.as_millis().to_string()))
+}
+
 pub trait IntoPostRequest: serde::Serialize {
-    const DOMAIN: &'static str;
     const ENDPOINT: &'static str;
     type Response: for<'a> serde::Deserialize<'a>;
-    fn uri(&self) -> String {
-        format!("{}{}", Self::DOMAIN, Self::ENDPOINT)
+    fn uri(&self, base_url: &str) -> String {
+        format!("{}{}", base_url, Self::ENDPOINT)
     }
+    // signed as of the system time, a Client signs through its own clock with as_request_at
     fn as_request(
         &self,
+        base_url: &str,
         key: &str,
         secret: &str,
         recv_window: &Duration
-    ) -> anyhow::Result<BybitRequest<Self::Response>> {
-        let timestamp = Utc::now();
-        let params = Params::Post(self);
-        Ok(BybitRequest::new(http::request::Builder::new()
-            .method("POST")
-            .header("X-BAPI-API-KEY", key)
-            .header("X-BAPI-SIGN", sign(secret,&timestamp, key,recv_window,&params)?)
-            .header("X-BAPI-TIMESTAMP", timestamp.timestamp_millis().to_string())
-            .header("X-BAPI-RECV-WINDOW", recv_window.as_millis().to_string())
-            .uri(self.uri())
-            .body(serde_json::to_string(self)?)?))
+    ) -> Result<BybitRequest<Self::Response>> {
+        self.as_request_at(base_url, &sign::Credentials::hmac(key, secret), recv_window, Utc::now())
+    }
+    // signed with any credentials as of timestamp rather than now, see clock::Clock
+    fn as_request_at(
+        &self,
+        base_url: &str,
+        credentials: &sign::Credentials,
+        recv_window: &Duration,
+        timestamp: DateTime<Utc>
+    ) -> Result<BybitRequest<Self::Response>> {
+        let body = Params::Post(self).to_string()?;
+        Ok(BybitRequest::new(signed_builder("POST", credentials, recv_window, &timestamp, &body)?
+            .uri(self.uri(base_url))
+            .body(body)?))
     }
 }
 
 pub trait IntoGetRequest: serde::Serialize {
-    const DOMAIN: &'static str;
     const ENDPOINT: &'static str;
     type Response: for<'a> serde::Deserialize<'a>;
-    fn uri(&self) -> String {
-        format!("{}{}", Self::DOMAIN, Self::ENDPOINT)
+    fn uri(&self, base_url: &str) -> String {
+        format!("{}{}", base_url, Self::ENDPOINT)
     }
+    // signed as of the system time, a Client signs through its own clock with as_request_at
     fn as_request(
         &self,
+        base_url: &str,
         key: &str,
         secret: &str,
         recv_window: &Duration
-    ) -> anyhow::Result<BybitRequest<Self::Response>> {
-        let timestamp = Utc::now();
-        let params = Params::Get(self);
+    ) -> Result<BybitRequest<Self::Response>> {
+        self.as_request_at(base_url, &sign::Credentials::hmac(key, secret), recv_window, Utc::now())
+    }
+    // signed with any credentials as of timestamp rather than now, see clock::Clock
+    fn as_request_at(
+        &self,
+        base_url: &str,
+        credentials: &sign::Credentials,
+        recv_window: &Duration,
+        timestamp: DateTime<Utc>
+    ) -> Result<BybitRequest<Self::Response>> {
+        let query = Params::Get(self).to_string()?;
+        Ok(BybitRequest::new(signed_builder("GET", credentials, recv_window, &timestamp, &query)?
+            .uri(if query.is_empty() { self.uri(base_url) } else { format!("{}?{}", self.uri(base_url), query) })
+            .body(String::new())?))
+    }
+}
+
+// Public market endpoints, sent without any auth headers
+pub trait IntoPublicRequest: serde::Serialize {
+    const ENDPOINT: &'static str;
+    type Response: for<'a> serde::Deserialize<'a>;
+    fn uri(&self, base_url: &str) -> String {
+        format!("{}{}", base_url, Self::ENDPOINT)
+    }
+    fn as_request(&self, base_url: &str) -> Result<BybitRequest<Self::Response>> {
+        let query = Params::Get(self).to_string()?;
         Ok(BybitRequest::new(http::request::Builder::new()
             .method("GET")
-            .header("X-BAPI-API-KEY", key)
-            .header("X-BAPI-SIGN", sign(secret,&timestamp, key,recv_window,&params)?)
-            .header("X-BAPI-TIMESTAMP", timestamp.timestamp_millis().to_string())
-            .header("X-BAPI-RECV-WINDOW", recv_window.as_millis().to_string())
-            .uri(format!("{}?{}",self.uri(), params.to_string()?))
+            .uri(if query.is_empty() { self.uri(base_url) } else { format!("{}?{}", self.uri(base_url), query) })
             .body(String::new())?))
     }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(not(any(feature = "trade", feature = "position", feature = "account", feature = "asset", feature = "user", feature = "ws")), allow(dead_code))]
 pub struct Client {
-    api_key: String,
-    secret: String,
-}
-
-#[derive(Deserialize, Debug, Clone)]
-pub struct FundingBalance {
-    #[serde(rename = "accountType")]
-    pub account_type: AccountType,
-    #[serde(rename = "memberId")]
-    pub member_id: String,
-    pub balance: Vec<BybitBalance>,
+    // None for a public client, which refuses to build signed requests
+    credentials: Option<sign::Credentials>,
+    environment: Environment,
+    shutdown: shutdown::Shutdown,
+    clock: Arc<dyn clock::Clock>,
+    retry: retry::RetryPolicy,
+    rate_limiter: Option<ratelimit::RateLimiter>,
+    recv_window: Duration,
+    #[cfg(all(feature = "trade", feature = "market"))]
+    dry_run: Option<dryrun::DryRun>,
+    #[cfg(feature = "trade")]
+    risk: Option<risk::RiskPolicy>,
 }
 
 impl Client {
     pub fn new(api_key: String, secret: String) -> Self {
-        Self { api_key, secret }
+        Self::with_credentials(sign::Credentials::hmac(api_key, secret))
     }
 
-    pub fn get_funding_balance(&mut self, coin: Option<String>, recv_window: &Duration) -> BybitRequest<FundingBalance>{
-            #[derive(Serialize, Debug)]
-            struct FundingRequest {
-                #[serde(rename = "accountType")]
-                account_type: AccountType,
-                coin: Option<String>,
-                #[serde(rename = "withBonus")]
-                with_bonus: i32,
-            }
+    pub fn with_credentials(credentials: sign::Credentials) -> Self {
+        Self { credentials: Some(credentials), ..Self::public(Environment::Mainnet) }
+    }
 
-            impl IntoGetRequest for FundingRequest {
-                const DOMAIN: &'static str = MAINNET;
-                const ENDPOINT: &'static str = "/v5/asset/transfer/query-account-coins-balance";
-                type Response = FundingBalance;
-            }
+    // market data only, signed endpoints fail with Error::Signing before anything is sent
+    pub fn public(environment: Environment) -> Self {
+        Self {
+            credentials: None,
+            environment,
+            shutdown: shutdown::Shutdown::new(),
+            clock: Arc::new(clock::SystemClock),
+            retry: retry::RetryPolicy::none(),
+            rate_limiter: None,
+            recv_window: Duration::from_secs(5),
+            #[cfg(all(feature = "trade", feature = "market"))]
+            dry_run: None,
+            #[cfg(feature = "trade")]
+            risk: None,
+        }
+    }
+
+    pub fn is_authenticated(&self) -> bool {
+        self.credentials.is_some()
+    }
+
+    pub(crate) fn credentials(&self) -> Result<&sign::Credentials> {
+        self.credentials.as_ref().ok_or_else(|| Error::Signing("public client has no credentials".into()))
+    }
+
+    pub fn with_environment(mut self, environment: Environment) -> Self {
+        self.environment = environment;
+        self
+    }
 
-            let request = FundingRequest {
-                        account_type: AccountType::FUND,
-                        coin,
-                        with_bonus: 0,
-            };
+    pub fn environment(&self) -> Environment {
+        self.environment
+    }
+
+    // the recv_window configured for this client, endpoints still take theirs per call so pass this one along
+    pub fn with_recv_window(mut self, recv_window: Duration) -> Self {
+        self.recv_window = recv_window;
+        self
+    }
+
+    pub fn recv_window(&self) -> &Duration {
+        &self.recv_window
+    }
 
-            request.as_request(&self.api_key,&self.secret, recv_window).unwrap() 
+    pub fn with_clock(mut self, clock: impl clock::Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
     }
 
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    pub fn shutdown_handle(&self) -> &shutdown::Shutdown {
+        &self.shutdown
+    }
+
+    // stops every background component started from this client (and its clones) and waits for them to wind down
+    pub async fn shutdown(&self) {
+        self.shutdown.shutdown().await
+    }
 }
@@ -0,0 +1,867 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    calendar::FundingSchedule, number::Precision, query, sizing::Contract, BybitRequest, Category, Client, Interval, IntoPublicRequest,
+    PriceLevel,
+};
+
+// kline rows come as [startTime, open, high, low, close, volume, turnover]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(from = "(String, String, String, String, String, String, String)")]
+pub struct Kline {
+    pub start: String,
+    pub open: String,
+    pub high: String,
+    pub low: String,
+    pub close: String,
+    pub volume: String,
+    pub turnover: String,
+}
+
+impl From<(String, String, String, String, String, String, String)> for Kline {
+    fn from((start, open, high, low, close, volume, turnover): (String, String, String, String, String, String, String)) -> Self {
+        Self { start, open, high, low, close, volume, turnover }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Klines {
+    pub category: Category,
+    pub symbol: String,
+    // newest first
+    pub list: Vec<Kline>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+// spot, linear and inverse tickers, fields only one of them has are Options
+#[derive(Debug, Clone, Deserialize)]
+pub struct Ticker {
+    pub symbol: String,
+    #[serde(rename = "lastPrice")]
+    pub last_price: String,
+    #[serde(rename = "bid1Price")]
+    pub bid1_price: String,
+    #[serde(rename = "bid1Size")]
+    pub bid1_size: String,
+    #[serde(rename = "ask1Price")]
+    pub ask1_price: String,
+    #[serde(rename = "ask1Size")]
+    pub ask1_size: String,
+    #[serde(rename = "prevPrice24h")]
+    pub prev_price_24h: String,
+    #[serde(rename = "price24hPcnt")]
+    pub price_24h_pcnt: String,
+    #[serde(rename = "highPrice24h")]
+    pub high_price_24h: String,
+    #[serde(rename = "lowPrice24h")]
+    pub low_price_24h: String,
+    #[serde(rename = "turnover24h")]
+    pub turnover_24h: String,
+    #[serde(rename = "volume24h")]
+    pub volume_24h: String,
+    #[serde(rename = "indexPrice")]
+    pub index_price: Option<String>,
+    #[serde(rename = "markPrice")]
+    pub mark_price: Option<String>,
+    #[serde(rename = "openInterest")]
+    pub open_interest: Option<String>,
+    #[serde(rename = "openInterestValue")]
+    pub open_interest_value: Option<String>,
+    #[serde(rename = "fundingRate")]
+    pub funding_rate: Option<String>,
+    #[serde(rename = "nextFundingTime")]
+    pub next_funding_time: Option<String>,
+    #[serde(rename = "usdIndexPrice")]
+    pub usd_index_price: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl Ticker {
+    // Bybit's own schedule, takes precedence over calendar::FundingSchedule when a ticker is at hand
+    pub fn next_funding(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp_millis(self.next_funding_time.as_deref()?.parse().ok()?)
+    }
+
+    pub fn time_to_funding(&self, now: DateTime<Utc>) -> Option<std::time::Duration> {
+        (self.next_funding()? - now).to_std().ok()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Tickers<T> {
+    pub category: Category,
+    pub list: Vec<T>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Orderbook {
+    #[serde(rename = "s")]
+    pub symbol: String,
+    #[serde(rename = "b")]
+    pub bids: Vec<PriceLevel>,
+    #[serde(rename = "a")]
+    pub asks: Vec<PriceLevel>,
+    pub ts: u64,
+    #[serde(rename = "u")]
+    pub update_id: u64,
+    pub seq: Option<u64>,
+    pub cts: Option<u64>,
+}
+
+// REST counterpart of the option websocket ticker, note the differing field names (bid1Price, markIv)
+#[derive(Debug, Clone, Deserialize)]
+pub struct OptionTicker {
+    pub symbol: String,
+    #[serde(rename = "bid1Price")]
+    pub bid1_price: String,
+    #[serde(rename = "bid1Size")]
+    pub bid1_size: String,
+    #[serde(rename = "bid1Iv")]
+    pub bid1_iv: String,
+    #[serde(rename = "ask1Price")]
+    pub ask1_price: String,
+    #[serde(rename = "ask1Size")]
+    pub ask1_size: String,
+    #[serde(rename = "ask1Iv")]
+    pub ask1_iv: String,
+    #[serde(rename = "lastPrice")]
+    pub last_price: String,
+    #[serde(rename = "highPrice24h")]
+    pub high_price_24h: String,
+    #[serde(rename = "lowPrice24h")]
+    pub low_price_24h: String,
+    #[serde(rename = "markPrice")]
+    pub mark_price: String,
+    #[serde(rename = "indexPrice")]
+    pub index_price: String,
+    #[serde(rename = "markIv")]
+    pub mark_iv: String,
+    #[serde(rename = "underlyingPrice")]
+    pub underlying_price: String,
+    #[serde(rename = "openInterest")]
+    pub open_interest: String,
+    #[serde(rename = "turnover24h")]
+    pub turnover_24h: String,
+    #[serde(rename = "volume24h")]
+    pub volume_24h: String,
+    #[serde(rename = "totalVolume")]
+    pub total_volume: String,
+    #[serde(rename = "totalTurnover")]
+    pub total_turnover: String,
+    pub delta: String,
+    pub gamma: String,
+    pub vega: String,
+    pub theta: String,
+    #[serde(rename = "predictedDeliveryPrice")]
+    pub predicted_delivery_price: String,
+    #[serde(rename = "change24h")]
+    pub change_24h: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ServerTime {
+    #[serde(rename = "timeSecond")]
+    pub time_second: String,
+    #[serde(rename = "timeNano")]
+    pub time_nano: String,
+}
+
+impl ServerTime {
+    pub fn as_datetime(&self) -> Option<DateTime<Utc>> {
+        let nanos: i64 = self.time_nano.parse().ok()?;
+        Some(DateTime::from_timestamp_nanos(nanos))
+    }
+}
+
+impl Client {
+    pub fn get_server_time(&self) -> crate::Result<BybitRequest<ServerTime>> {
+        #[derive(Serialize, Debug)]
+        struct ServerTimeRequest {}
+
+        impl IntoPublicRequest for ServerTimeRequest {
+            const ENDPOINT: &'static str = "/v5/market/time";
+            type Response = ServerTime;
+        }
+
+        ServerTimeRequest {}.as_request(self.environment.base_url())
+    }
+
+    // start and end are unix millis
+    pub fn get_klines(&self, category: Category, symbol: String, interval: Interval, start: Option<i64>, end: Option<i64>, limit: Option<u32>) -> crate::Result<BybitRequest<Klines>> {
+        #[derive(Serialize, Debug)]
+        struct KlineRequest {
+            category: Category,
+            symbol: String,
+            interval: Interval,
+            start: Option<i64>,
+            end: Option<i64>,
+            limit: Option<u32>,
+        }
+
+        impl IntoPublicRequest for KlineRequest {
+            const ENDPOINT: &'static str = "/v5/market/kline";
+            type Response = Klines;
+        }
+
+        let request = KlineRequest {
+            category,
+            symbol,
+            interval,
+            start,
+            end,
+            limit,
+        };
+
+        request.as_request(self.environment.base_url())
+    }
+
+    // spot, linear and inverse, option tickers have their own shape (get_option_tickers)
+    pub fn get_tickers(&self, category: Category, symbol: Option<String>) -> crate::Result<BybitRequest<Tickers<Ticker>>> {
+        #[derive(Serialize, Debug)]
+        struct TickersRequest {
+            category: Category,
+            symbol: Option<String>,
+        }
+
+        impl IntoPublicRequest for TickersRequest {
+            const ENDPOINT: &'static str = "/v5/market/tickers";
+            type Response = Tickers<Ticker>;
+        }
+
+        TickersRequest { category, symbol }.as_request(self.environment.base_url())
+    }
+
+    // one of base_coin or symbol is required, exp_date looks like 25DEC22
+    pub fn get_option_tickers(&self, base_coin: Option<String>, symbol: Option<String>, exp_date: Option<String>) -> crate::Result<BybitRequest<Tickers<OptionTicker>>> {
+        #[derive(Serialize, Debug)]
+        struct OptionTickersRequest {
+            category: Category,
+            symbol: Option<String>,
+            #[serde(rename = "baseCoin")]
+            base_coin: Option<String>,
+            #[serde(rename = "expDate")]
+            exp_date: Option<String>,
+        }
+
+        impl IntoPublicRequest for OptionTickersRequest {
+            const ENDPOINT: &'static str = "/v5/market/tickers";
+            type Response = Tickers<OptionTicker>;
+        }
+
+        let request = OptionTickersRequest {
+            category: Category::Option,
+            symbol,
+            base_coin,
+            exp_date,
+        };
+
+        request.as_request(self.environment.base_url())
+    }
+
+    pub fn get_orderbook(&self, category: Category, symbol: String, limit: Option<u32>) -> crate::Result<BybitRequest<Orderbook>> {
+        #[derive(Serialize, Debug)]
+        struct OrderbookRequest {
+            category: Category,
+            symbol: String,
+            limit: Option<u32>,
+        }
+
+        impl IntoPublicRequest for OrderbookRequest {
+            const ENDPOINT: &'static str = "/v5/market/orderbook";
+            type Response = Orderbook;
+        }
+
+        OrderbookRequest { category, symbol, limit }.as_request(self.environment.base_url())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub enum InstrumentStatus {
+    PreLaunch,
+    Trading,
+    Delivering,
+    Closed,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LeverageFilter {
+    #[serde(rename = "minLeverage")]
+    pub min_leverage: String,
+    #[serde(rename = "maxLeverage")]
+    pub max_leverage: String,
+    #[serde(rename = "leverageStep")]
+    pub leverage_step: String,
+}
+
+// spot only sends tickSize
+#[derive(Debug, Clone, Deserialize)]
+pub struct PriceFilter {
+    #[serde(rename = "tickSize")]
+    pub tick_size: String,
+    #[serde(rename = "minPrice")]
+    pub min_price: Option<String>,
+    #[serde(rename = "maxPrice")]
+    pub max_price: Option<String>,
+}
+
+// Spot describes its lots by basePrecision (the qty step) and order amounts in quote, derivatives by qtyStep and
+// minNotionalValue
+#[derive(Debug, Clone, Deserialize)]
+pub struct LotSizeFilter {
+    #[serde(rename = "minOrderQty")]
+    pub min_order_qty: String,
+    #[serde(rename = "maxOrderQty")]
+    pub max_order_qty: String,
+    #[serde(rename = "qtyStep")]
+    pub qty_step: Option<String>,
+    #[serde(rename = "basePrecision")]
+    pub base_precision: Option<String>,
+    #[serde(rename = "maxMktOrderQty")]
+    pub max_mkt_order_qty: Option<String>,
+    #[serde(rename = "postOnlyMaxOrderQty")]
+    pub post_only_max_order_qty: Option<String>,
+    #[serde(rename = "minNotionalValue")]
+    pub min_notional_value: Option<String>,
+    #[serde(rename = "minOrderAmt")]
+    pub min_order_amt: Option<String>,
+    #[serde(rename = "maxOrderAmt")]
+    pub max_order_amt: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct InstrumentInfo {
+    pub symbol: String,
+    pub status: InstrumentStatus,
+    #[serde(rename = "baseCoin")]
+    pub base_coin: String,
+    #[serde(rename = "quoteCoin")]
+    pub quote_coin: String,
+    #[serde(rename = "settleCoin")]
+    pub settle_coin: Option<String>,
+    // LinearPerpetual, LinearFutures, InversePerpetual or InverseFutures, None for spot and option
+    #[serde(rename = "contractType")]
+    pub contract_type: Option<String>,
+    // Call or Put
+    #[serde(rename = "optionsType")]
+    pub options_type: Option<String>,
+    #[serde(rename = "launchTime")]
+    pub launch_time: Option<String>,
+    // unix millis, "0" for perpetuals
+    #[serde(rename = "deliveryTime")]
+    pub delivery_time: Option<String>,
+    #[serde(rename = "priceScale")]
+    pub price_scale: Option<String>,
+    #[serde(rename = "leverageFilter")]
+    pub leverage_filter: Option<LeverageFilter>,
+    #[serde(rename = "priceFilter")]
+    pub price_filter: PriceFilter,
+    #[serde(rename = "lotSizeFilter")]
+    pub lot_size_filter: LotSizeFilter,
+    // minutes between fundings, perpetuals only
+    #[serde(rename = "fundingInterval")]
+    pub funding_interval: Option<u64>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl InstrumentInfo {
+    pub fn is_trading(&self) -> bool {
+        self.status == InstrumentStatus::Trading
+    }
+
+    pub fn rules(&self) -> SymbolRules {
+        SymbolRules::from(self)
+    }
+
+    // inverse contracts are worth 1 USD each
+    pub fn contract(&self, category: Category) -> Contract {
+        Contract::new(category).with_qty_step(self.rules().qty_step)
+    }
+
+    pub fn funding_schedule(&self) -> Option<FundingSchedule> {
+        Some(FundingSchedule::from_minutes(self.funding_interval.filter(|minutes| *minutes > 0)?))
+    }
+
+    pub fn expiry(&self) -> Option<DateTime<Utc>> {
+        let millis: i64 = self.delivery_time.as_deref()?.parse().ok()?;
+        (millis > 0).then(|| DateTime::from_timestamp_millis(millis)).flatten()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct InstrumentsInfo {
+    pub category: Category,
+    pub list: Vec<InstrumentInfo>,
+    // spot isn't paged and leaves this out
+    #[serde(rename = "nextPageCursor", default)]
+    pub next_page_cursor: String,
+}
+
+impl InstrumentsInfo {
+    pub fn next_cursor(&self) -> Option<&str> {
+        Some(self.next_page_cursor.as_str()).filter(|cursor| !cursor.is_empty())
+    }
+}
+
+// without a status Bybit only returns Trading instruments (and PreLaunch ones for linear)
+#[derive(Debug, Clone, Serialize)]
+pub struct InstrumentsQuery {
+    pub category: Category,
+    pub symbol: Option<String>,
+    pub status: Option<String>,
+    #[serde(rename = "baseCoin")]
+    pub base_coin: Option<String>,
+    pub limit: Option<u32>,
+    pub cursor: Option<String>,
+}
+
+impl InstrumentsQuery {
+    pub fn new(category: Category) -> Self {
+        Self { category, symbol: None, status: None, base_coin: None, limit: None, cursor: None }
+    }
+
+    pub fn with_symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbol = Some(symbol.into());
+        self
+    }
+
+    pub fn with_status(mut self, status: impl Into<String>) -> Self {
+        self.status = Some(status.into());
+        self
+    }
+
+    pub fn with_base_coin(mut self, base_coin: impl Into<String>) -> Self {
+        self.base_coin = Some(base_coin.into());
+        self
+    }
+
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    // takes nextPageCursor as returned by Bybit
+    pub fn with_cursor(mut self, cursor: Option<&str>) -> Self {
+        self.cursor = cursor.map(query::decode);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RuleViolation {
+    #[error("qty {qty} is below the minimum of {min}")]
+    QtyTooSmall { qty: Decimal, min: Decimal },
+    #[error("qty {qty} is above the maximum of {max}")]
+    QtyTooLarge { qty: Decimal, max: Decimal },
+    #[error("qty {qty} is not a multiple of {step}")]
+    QtyStep { qty: Decimal, step: Decimal },
+    #[error("price {price} is not a multiple of {tick}")]
+    PriceTick { price: Decimal, tick: Decimal },
+    #[error("price {price} is outside {min}..={max}")]
+    PriceOutOfRange { price: Decimal, min: Decimal, max: Decimal },
+    #[error("notional {notional} is below the minimum of {min}")]
+    NotionalTooSmall { notional: Decimal, min: Decimal },
+}
+
+// The order limits of one symbol, to round and check orders before Bybit rejects them. Zero means no limit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolRules {
+    pub symbol: String,
+    pub tick_size: Decimal,
+    pub qty_step: Decimal,
+    pub min_qty: Decimal,
+    pub max_qty: Decimal,
+    pub max_market_qty: Decimal,
+    pub min_price: Decimal,
+    pub max_price: Decimal,
+    // in quote, minNotionalValue for derivatives and minOrderAmt for spot
+    pub min_notional: Decimal,
+}
+
+impl From<&InstrumentInfo> for SymbolRules {
+    fn from(info: &InstrumentInfo) -> Self {
+        let decimal = |value: Option<&String>| value.and_then(|value| value.parse().ok()).unwrap_or_default();
+        let lots = &info.lot_size_filter;
+        Self {
+            symbol: info.symbol.clone(),
+            tick_size: decimal(Some(&info.price_filter.tick_size)),
+            qty_step: decimal(lots.qty_step.as_ref().or(lots.base_precision.as_ref())),
+            min_qty: decimal(Some(&lots.min_order_qty)),
+            max_qty: decimal(Some(&lots.max_order_qty)),
+            max_market_qty: decimal(lots.max_mkt_order_qty.as_ref()),
+            min_price: decimal(info.price_filter.min_price.as_ref()),
+            max_price: decimal(info.price_filter.max_price.as_ref()),
+            min_notional: decimal(lots.min_notional_value.as_ref().or(lots.min_order_amt.as_ref())),
+        }
+    }
+}
+
+impl SymbolRules {
+    pub fn precision(&self) -> Precision {
+        Precision::new(self.tick_size, self.qty_step)
+    }
+
+    // rounded down to the qty step, so rounding never adds exposure
+    pub fn round_qty(&self, qty: Decimal) -> Decimal {
+        self.precision().qty(qty)
+    }
+
+    // rounded to the nearest tick
+    pub fn round_price(&self, price: Decimal) -> Decimal {
+        self.precision().price(price)
+    }
+
+    // checks an order as is, price None for market orders, whose notional can't be checked here
+    pub fn validate(&self, qty: Decimal, price: Option<Decimal>, market: bool) -> Result<(), RuleViolation> {
+        if !self.min_qty.is_zero() && qty < self.min_qty {
+            return Err(RuleViolation::QtyTooSmall { qty, min: self.min_qty });
+        }
+        let max = if market && !self.max_market_qty.is_zero() { self.max_market_qty } else { self.max_qty };
+        if !max.is_zero() && qty > max {
+            return Err(RuleViolation::QtyTooLarge { qty, max });
+        }
+        if !self.qty_step.is_zero() && !(qty % self.qty_step).is_zero() {
+            return Err(RuleViolation::QtyStep { qty, step: self.qty_step });
+        }
+        let Some(price) = price else {
+            return Ok(());
+        };
+        if !self.tick_size.is_zero() && !(price % self.tick_size).is_zero() {
+            return Err(RuleViolation::PriceTick { price, tick: self.tick_size });
+        }
+        if (!self.min_price.is_zero() && price < self.min_price) || (!self.max_price.is_zero() && price > self.max_price) {
+            return Err(RuleViolation::PriceOutOfRange { price, min: self.min_price, max: self.max_price });
+        }
+        if !self.min_notional.is_zero() && qty * price < self.min_notional {
+            return Err(RuleViolation::NotionalTooSmall { notional: qty * price, min: self.min_notional });
+        }
+        Ok(())
+    }
+}
+
+impl Client {
+    pub fn get_instruments_info(&self, query: &InstrumentsQuery) -> crate::Result<BybitRequest<InstrumentsInfo>> {
+        #[derive(Serialize, Debug)]
+        struct InstrumentsInfoRequest<'a>(&'a InstrumentsQuery);
+
+        impl IntoPublicRequest for InstrumentsInfoRequest<'_> {
+            const ENDPOINT: &'static str = "/v5/market/instruments-info";
+            type Response = InstrumentsInfo;
+        }
+
+        InstrumentsInfoRequest(query).as_request(self.environment.base_url())
+    }
+
+    // follows nextPageCursor from query's cursor until the last page
+    pub async fn get_all_instruments<F, R, E>(&self, query: &InstrumentsQuery, send: F) -> crate::Result<Vec<InstrumentInfo>>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let mut query = query.clone();
+        let mut instruments = Vec::new();
+        loop {
+            let page = self.get_instruments_info(&query)?.send(&send).await?;
+            query = query.with_cursor(page.next_cursor());
+            instruments.extend(page.list);
+            if query.cursor.is_none() {
+                return Ok(instruments);
+            }
+        }
+    }
+}
+
+// Bybit sends timestamps as strings of unix millis
+fn millis_string<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+    let millis: i64 = String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)?;
+    DateTime::from_timestamp_millis(millis).ok_or_else(|| serde::de::Error::custom(format!("timestamp {millis} out of range")))
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FundingRate {
+    pub symbol: String,
+    #[serde(rename = "fundingRate")]
+    pub funding_rate: Decimal,
+    #[serde(rename = "fundingRateTimestamp", deserialize_with = "millis_string")]
+    pub funding_rate_timestamp: DateTime<Utc>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FundingHistory {
+    pub category: Category,
+    // newest first
+    pub list: Vec<FundingRate>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+// linear and inverse perpetuals. Without a range Bybit returns the latest rates, a start needs an end to go with it
+#[derive(Debug, Clone, Serialize)]
+pub struct FundingHistoryQuery {
+    pub category: Category,
+    pub symbol: String,
+    #[serde(rename = "startTime")]
+    pub start_time: Option<i64>,
+    #[serde(rename = "endTime")]
+    pub end_time: Option<i64>,
+    // at most 200
+    pub limit: Option<u32>,
+}
+
+impl FundingHistoryQuery {
+    pub fn new(category: Category, symbol: impl Into<String>) -> Self {
+        Self { category, symbol: symbol.into(), start_time: None, end_time: None, limit: None }
+    }
+
+    pub fn with_range(mut self, start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        self.start_time = Some(start.timestamp_millis());
+        self.end_time = Some(end.timestamp_millis());
+        self
+    }
+
+    // the rates up to end, newest first
+    pub fn with_end(mut self, end: DateTime<Utc>) -> Self {
+        self.end_time = Some(end.timestamp_millis());
+        self
+    }
+
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+impl Client {
+    pub fn get_funding_history(&self, query: &FundingHistoryQuery) -> crate::Result<BybitRequest<FundingHistory>> {
+        #[derive(Serialize, Debug)]
+        struct FundingHistoryRequest<'a>(&'a FundingHistoryQuery);
+
+        impl IntoPublicRequest for FundingHistoryRequest<'_> {
+            const ENDPOINT: &'static str = "/v5/market/funding/history";
+            type Response = FundingHistory;
+        }
+
+        FundingHistoryRequest(query).as_request(self.environment.base_url())
+    }
+
+    // the endpoint has no cursor, pages backwards by moving the end before the oldest rate seen until a short page
+    // or the start of the range. Newest first like a single page
+    pub async fn get_all_funding_history<F, R, E>(&self, query: &FundingHistoryQuery, send: F) -> crate::Result<Vec<FundingRate>>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let mut query = query.clone();
+        let limit = query.limit.unwrap_or(200) as usize;
+        let mut rates: Vec<FundingRate> = Vec::new();
+        loop {
+            let page = self.get_funding_history(&query)?.send(&send).await?.list;
+            let full = page.len() >= limit;
+            rates.extend(page);
+            let Some(oldest) = rates.last().map(|rate| rate.funding_rate_timestamp.timestamp_millis()) else {
+                return Ok(rates);
+            };
+            if !full || query.start_time.is_some_and(|start| oldest <= start) {
+                return Ok(rates);
+            }
+            query.end_time = Some(oldest - 1);
+        }
+    }
+}
+
+// granularity of the open interest history, Bybit's intervalTime values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum OpenInterestInterval {
+    #[serde(rename = "5min")]
+    Minute5,
+    #[serde(rename = "15min")]
+    Minute15,
+    #[serde(rename = "30min")]
+    Minute30,
+    #[serde(rename = "1h")]
+    Hour1,
+    #[serde(rename = "4h")]
+    Hour4,
+    #[serde(rename = "1d")]
+    Day,
+}
+
+impl OpenInterestInterval {
+    pub fn duration(&self) -> std::time::Duration {
+        let minutes = match self {
+            Self::Minute5 => 5,
+            Self::Minute15 => 15,
+            Self::Minute30 => 30,
+            Self::Hour1 => 60,
+            Self::Hour4 => 240,
+            Self::Day => 1440,
+        };
+        std::time::Duration::from_secs(minutes * 60)
+    }
+}
+
+// contracts open at timestamp, in the base coin for linear and in USD for inverse
+#[derive(Debug, Clone, Deserialize)]
+pub struct OpenInterest {
+    #[serde(rename = "openInterest")]
+    pub open_interest: Decimal,
+    #[serde(deserialize_with = "millis_string")]
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OpenInterestPage {
+    pub category: Category,
+    pub symbol: String,
+    // newest first
+    pub list: Vec<OpenInterest>,
+    #[serde(rename = "nextPageCursor")]
+    pub next_page_cursor: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl OpenInterestPage {
+    pub fn next_cursor(&self) -> Option<&str> {
+        Some(self.next_page_cursor.as_str()).filter(|cursor| !cursor.is_empty())
+    }
+}
+
+// linear and inverse contracts
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenInterestQuery {
+    pub category: Category,
+    pub symbol: String,
+    #[serde(rename = "intervalTime")]
+    pub interval: OpenInterestInterval,
+    #[serde(rename = "startTime")]
+    pub start_time: Option<i64>,
+    #[serde(rename = "endTime")]
+    pub end_time: Option<i64>,
+    // at most 200
+    pub limit: Option<u32>,
+    pub cursor: Option<String>,
+}
+
+impl OpenInterestQuery {
+    pub fn new(category: Category, symbol: impl Into<String>, interval: OpenInterestInterval) -> Self {
+        Self { category, symbol: symbol.into(), interval, start_time: None, end_time: None, limit: None, cursor: None }
+    }
+
+    pub fn with_range(mut self, start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        self.start_time = Some(start.timestamp_millis());
+        self.end_time = Some(end.timestamp_millis());
+        self
+    }
+
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    // takes nextPageCursor as returned by Bybit
+    pub fn with_cursor(mut self, cursor: Option<&str>) -> Self {
+        self.cursor = cursor.map(query::decode);
+        self
+    }
+}
+
+impl Client {
+    pub fn get_open_interest(&self, query: &OpenInterestQuery) -> crate::Result<BybitRequest<OpenInterestPage>> {
+        #[derive(Serialize, Debug)]
+        struct OpenInterestRequest<'a>(&'a OpenInterestQuery);
+
+        impl IntoPublicRequest for OpenInterestRequest<'_> {
+            const ENDPOINT: &'static str = "/v5/market/open-interest";
+            type Response = OpenInterestPage;
+        }
+
+        OpenInterestRequest(query).as_request(self.environment.base_url())
+    }
+
+    // follows nextPageCursor from query's cursor until the last page, newest first like a single page
+    pub async fn get_all_open_interest<F, R, E>(&self, query: &OpenInterestQuery, send: F) -> crate::Result<Vec<OpenInterest>>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let mut query = query.clone();
+        let mut open_interest = Vec::new();
+        loop {
+            let page = self.get_open_interest(&query)?.send(&send).await?;
+            query = query.with_cursor(page.next_cursor());
+            open_interest.extend(page.list);
+            if query.cursor.is_none() {
+                return Ok(open_interest);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SymbolSnapshot {
+    pub ticker: Ticker,
+    // top of the book, None when that side is empty
+    pub bid: Option<PriceLevel>,
+    pub ask: Option<PriceLevel>,
+    pub rules: SymbolRules,
+}
+
+impl Client {
+    // Ticker, top of book and order rules for each symbol, for warming up strategies. Tickers and instruments come
+    // from category wide calls, the orderbooks are fetched concurrently. Spot, linear and inverse only
+    pub async fn market_snapshot<F, R, E>(&self, category: Category, symbols: &[String], send: F) -> crate::Result<HashMap<String, SymbolSnapshot>>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        if category == Category::Option {
+            return Err(crate::Error::Invalid("option tickers have their own shape, use get_option_tickers".into()));
+        }
+        let send = &send;
+        let tickers = async move { self.get_tickers(category, None)?.send(send).await };
+        let instruments_query = InstrumentsQuery::new(category).with_limit(1000);
+        let instruments = self.get_all_instruments(&instruments_query, send);
+        let orderbooks = futures::future::try_join_all(symbols.iter().map(|symbol| async move {
+            self.get_orderbook(category, symbol.clone(), Some(1))?.send(send).await
+        }));
+        let (tickers, orderbooks, instruments) = futures::future::try_join3(tickers, orderbooks, instruments).await?;
+
+        let mut tickers: HashMap<String, Ticker> = tickers.list.into_iter()
+            .filter(|ticker| symbols.contains(&ticker.symbol))
+            .map(|ticker| (ticker.symbol.clone(), ticker))
+            .collect();
+        let mut rules: HashMap<String, SymbolRules> = instruments.iter()
+            .filter(|info| symbols.contains(&info.symbol))
+            .map(|info| (info.symbol.clone(), info.rules()))
+            .collect();
+        let mut snapshots = HashMap::with_capacity(symbols.len());
+        for orderbook in orderbooks {
+            let ticker = tickers.remove(&orderbook.symbol).ok_or_else(|| crate::Error::Invalid(format!("no {} ticker for {}", category.as_str(), orderbook.symbol).into()))?;
+            let rules = rules.remove(&orderbook.symbol).ok_or_else(|| crate::Error::Invalid(format!("no {} instrument info for {}", category.as_str(), orderbook.symbol).into()))?;
+            snapshots.insert(orderbook.symbol, SymbolSnapshot {
+                ticker,
+                rules,
+                bid: orderbook.bids.into_iter().next(),
+                ask: orderbook.asks.into_iter().next(),
+            });
+        }
+        Ok(snapshots)
+    }
+}
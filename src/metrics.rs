@@ -0,0 +1,123 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use futures::{channel::mpsc::UnboundedSender, future::BoxFuture};
+
+use crate::CorrelationId;
+
+// what a single request came back with, status, ret_code and trace_id are None when the transport itself failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestOutcome {
+    pub path: String,
+    pub correlation_id: Option<CorrelationId>,
+    pub trace_id: Option<String>,
+    pub latency: Duration,
+    pub status: Option<http::StatusCode>,
+    pub ret_code: Option<i32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EndpointSummary {
+    pub requests: u64,
+    pub transport_errors: u64,
+    pub total_latency: Duration,
+    pub max_latency: Duration,
+    pub statuses: HashMap<u16, u64>,
+    pub ret_codes: HashMap<i32, u64>,
+}
+
+impl EndpointSummary {
+    pub fn mean_latency(&self) -> Option<Duration> {
+        let requests = u32::try_from(self.requests).ok().filter(|requests| *requests > 0)?;
+        Some(self.total_latency / requests)
+    }
+
+    fn record(&mut self, outcome: &RequestOutcome) {
+        self.requests += 1;
+        self.total_latency += outcome.latency;
+        self.max_latency = self.max_latency.max(outcome.latency);
+        match outcome.status {
+            Some(status) => *self.statuses.entry(status.as_u16()).or_default() += 1,
+            None => self.transport_errors += 1,
+        }
+        if let Some(code) = outcome.ret_code {
+            *self.ret_codes.entry(code).or_default() += 1;
+        }
+    }
+}
+
+// Latency, HTTP status and retCode per endpoint path for every request sent through wrap, with_hook also forwards
+// each outcome as it happens, e.g. to an exporter. Clones share state
+#[derive(Debug, Clone, Default)]
+pub struct RequestMetrics {
+    summaries: Arc<Mutex<HashMap<String, EndpointSummary>>>,
+    hook: Option<UnboundedSender<RequestOutcome>>,
+}
+
+impl RequestMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_hook(mut self, hook: UnboundedSender<RequestOutcome>) -> Self {
+        self.hook = Some(hook);
+        self
+    }
+
+    pub fn summary(&self, path: &str) -> Option<EndpointSummary> {
+        self.summaries.lock().unwrap().get(path).cloned()
+    }
+
+    pub fn summaries(&self) -> HashMap<String, EndpointSummary> {
+        self.summaries.lock().unwrap().clone()
+    }
+
+    pub fn reset(&self) {
+        self.summaries.lock().unwrap().clear();
+    }
+
+    pub fn record(&self, outcome: RequestOutcome) {
+        self.summaries.lock().unwrap().entry(outcome.path.clone()).or_default().record(&outcome);
+        if let Some(hook) = &self.hook {
+            // a dropped receiver only stops the forwarding, the summary keeps counting
+            let _ = hook.unbounded_send(outcome);
+        }
+    }
+
+    // Times a transport returning the whole http response and passes the response on untouched, so it works with
+    // BybitRequest::send_http as well as underneath Client::transport, where every retry is recorded on its own
+    pub fn wrap<'a, F, R, E>(&'a self, send: &'a F) -> impl Fn(http::Request<String>) -> BoxFuture<'a, Result<http::Response<Bytes>, E>> + 'a
+    where F: Fn(http::Request<String>) -> R + Sync,
+        R: Future<Output = Result<http::Response<Bytes>, E>> + Send + 'a,
+        E: 'a
+    {
+        move |request| Box::pin(async move {
+            let path = request.uri().path().to_string();
+            let correlation_id = CorrelationId::of(&request);
+            let started = Instant::now();
+            let response = send(request).await;
+            let latency = started.elapsed();
+            let (status, ret_code, trace_id) = match &response {
+                Ok(response) => (Some(response.status()), ret_code(response.body()), crate::trace_id(response.headers()).map(str::to_string)),
+                Err(_) => (None, None, None),
+            };
+            self.record(RequestOutcome { path, correlation_id, trace_id, latency, status, ret_code });
+            response
+        })
+    }
+}
+
+fn ret_code(body: &Bytes) -> Option<i32> {
+    #[derive(serde::Deserialize)]
+    struct Envelope {
+        #[serde(rename = "retCode")]
+        ret_code: i32,
+    }
+
+    serde_json::from_slice::<Envelope>(body).ok().map(|envelope| envelope.ret_code)
+}
@@ -0,0 +1,90 @@
+use std::str::FromStr;
+
+use rust_decimal::{Decimal, RoundingStrategy};
+
+// Numbers as users tend to have them. f64 goes through its shortest round-trip representation so 0.1 stays 0.1
+// instead of picking up binary noise, strings are parsed as written (scientific notation included)
+pub trait ToDecimal {
+    fn to_decimal(&self) -> crate::Result<Decimal>;
+}
+
+impl ToDecimal for Decimal {
+    fn to_decimal(&self) -> crate::Result<Decimal> {
+        Ok(*self)
+    }
+}
+
+impl ToDecimal for f64 {
+    fn to_decimal(&self) -> crate::Result<Decimal> {
+        if !self.is_finite() {
+            return Err(crate::Error::Invalid(format!("{self} isn't a valid order number").into()));
+        }
+        parse(&self.to_string())
+    }
+}
+
+impl ToDecimal for &str {
+    fn to_decimal(&self) -> crate::Result<Decimal> {
+        parse(self)
+    }
+}
+
+impl ToDecimal for String {
+    fn to_decimal(&self) -> crate::Result<Decimal> {
+        parse(self)
+    }
+}
+
+impl ToDecimal for i64 {
+    fn to_decimal(&self) -> crate::Result<Decimal> {
+        Ok(Decimal::from(*self))
+    }
+}
+
+impl ToDecimal for u64 {
+    fn to_decimal(&self) -> crate::Result<Decimal> {
+        Ok(Decimal::from(*self))
+    }
+}
+
+fn parse(value: &str) -> crate::Result<Decimal> {
+    let value = value.trim();
+    let parsed = if value.contains(['e', 'E']) { Decimal::from_scientific(value) } else { Decimal::from_str(value) };
+    parsed.map_err(|err| crate::Error::Invalid(format!("{value:?} isn't a valid order number: {err}").into()))
+}
+
+// plain digits with trailing zeros trimmed, the form Bybit accepts for every numeric string field
+pub fn format(value: Decimal) -> String {
+    value.normalize().to_string()
+}
+
+// Instrument precision, prices round to the nearest tick and quantities down to the step so rounding never
+// increases the size of an order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Precision {
+    pub tick_size: Option<Decimal>,
+    pub qty_step: Option<Decimal>,
+}
+
+impl Precision {
+    pub fn new(tick_size: Decimal, qty_step: Decimal) -> Self {
+        Self {
+            tick_size: (!tick_size.is_zero()).then_some(tick_size),
+            qty_step: (!qty_step.is_zero()).then_some(qty_step),
+        }
+    }
+
+    pub fn price(&self, price: Decimal) -> Decimal {
+        match self.tick_size {
+            Some(tick) => ((price / tick).round() * tick).normalize(),
+            None => price.normalize(),
+        }
+    }
+
+    pub fn qty(&self, qty: Decimal) -> Decimal {
+        match self.qty_step {
+            Some(step) => ((qty / step).round_dp_with_strategy(0, RoundingStrategy::ToZero) * step).normalize(),
+            None => qty.normalize(),
+        }
+    }
+}
@@ -0,0 +1,501 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use bytes::Bytes;
+use futures::channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{
+    trade::{envelope, OrderType, TimeInForce},
+    ws::{ExecutionUpdate, OrderUpdate, PrivateEvent, PublicEvent, UpdateKind},
+    Category, Error, OrderStatus, PriceLevel, Side,
+};
+
+const PARAM_ERROR: i32 = 10001;
+const ORDER_NOT_FOUND: i32 = 110001;
+
+// a create body, batch items carry their category on the batch instead
+#[derive(Debug, Deserialize)]
+struct Placement {
+    category: Option<Category>,
+    symbol: String,
+    side: Side,
+    #[serde(rename = "orderType")]
+    order_type: OrderType,
+    qty: Decimal,
+    price: Option<Decimal>,
+    #[serde(rename = "timeInForce")]
+    time_in_force: Option<TimeInForce>,
+    #[serde(rename = "orderLinkId")]
+    order_link_id: Option<String>,
+    #[serde(rename = "reduceOnly")]
+    reduce_only: Option<bool>,
+    #[serde(rename = "positionIdx")]
+    position_idx: Option<i32>,
+}
+
+// an amend, cancel or cancel-all body
+#[derive(Debug, Deserialize)]
+struct Target {
+    category: Category,
+    symbol: Option<String>,
+    #[serde(rename = "orderId")]
+    order_id: Option<String>,
+    #[serde(rename = "orderLinkId")]
+    order_link_id: Option<String>,
+    qty: Option<Decimal>,
+    price: Option<Decimal>,
+}
+
+#[derive(Debug, Clone)]
+struct PaperOrder {
+    order_id: String,
+    order_link_id: String,
+    category: Category,
+    symbol: String,
+    side: Side,
+    order_type: OrderType,
+    time_in_force: TimeInForce,
+    price: Option<Decimal>,
+    qty: Decimal,
+    filled: Decimal,
+    value: Decimal,
+    fee: Decimal,
+    status: OrderStatus,
+    reject_reason: &'static str,
+    reduce_only: bool,
+    position_idx: i32,
+    created_time: u64,
+    updated_time: u64,
+}
+
+impl PaperOrder {
+    fn working(&self) -> bool {
+        !self.status.is_terminal()
+    }
+
+    fn remaining(&self) -> Decimal {
+        self.qty - self.filled
+    }
+
+    // whether a level at price is good enough to trade with, market orders take any
+    fn accepts(&self, price: Decimal) -> bool {
+        match (self.side, self.price.filter(|_| self.order_type == OrderType::Limit)) {
+            (_, None) => true,
+            (Side::Buy, Some(limit)) => price <= limit,
+            (Side::Sell, Some(limit)) => price >= limit,
+        }
+    }
+
+    // inverse contracts are quoted in USD and valued in coin
+    fn value_of(&self, price: Decimal, qty: Decimal) -> Decimal {
+        match self.category {
+            Category::Inverse => qty / price,
+            _ => qty * price,
+        }
+    }
+
+    fn update(&self) -> OrderUpdate {
+        let avg_price = match self.category {
+            _ if self.value.is_zero() => Decimal::ZERO,
+            Category::Inverse => self.filled / self.value,
+            _ => self.value / self.filled,
+        };
+        let leaves_qty = if self.working() { self.remaining() } else { Decimal::ZERO };
+        OrderUpdate {
+            category: self.category,
+            order_id: self.order_id.clone(),
+            order_link_id: self.order_link_id.clone(),
+            symbol: self.symbol.clone(),
+            side: self.side,
+            order_type: format!("{:?}", self.order_type),
+            price: self.price.unwrap_or_default().to_string(),
+            qty: self.qty.to_string(),
+            order_status: self.status,
+            time_in_force: format!("{:?}", self.time_in_force),
+            position_idx: self.position_idx,
+            avg_price: avg_price.normalize().to_string(),
+            leaves_qty: leaves_qty.to_string(),
+            cum_exec_qty: self.filled.to_string(),
+            cum_exec_value: self.value.normalize().to_string(),
+            cum_exec_fee: self.fee.normalize().to_string(),
+            reject_reason: self.reject_reason.to_string(),
+            cancel_type: if self.status == OrderStatus::Cancelled { "CancelByUser" } else { "UNKNOWN" }.to_string(),
+            reduce_only: self.reduce_only,
+            smp_type: None,
+            smp_group: None,
+            smp_order_id: None,
+            created_time: self.created_time.to_string(),
+            updated_time: self.updated_time.to_string(),
+            extra: HashMap::new(),
+        }
+    }
+}
+
+// every level of one symbol's orderbook, or as many as the subscribed depth carries
+#[derive(Debug, Default)]
+struct Depth {
+    bids: BTreeMap<Decimal, Decimal>,
+    asks: BTreeMap<Decimal, Decimal>,
+}
+
+impl Depth {
+    fn apply(side: &mut BTreeMap<Decimal, Decimal>, levels: &[PriceLevel], kind: UpdateKind) {
+        if kind == UpdateKind::Snapshot {
+            side.clear();
+        }
+        for level in levels {
+            let (Ok(price), Ok(size)) = (level.price.parse::<Decimal>(), level.size.parse::<Decimal>()) else {
+                continue;
+            };
+            if size.is_zero() {
+                side.remove(&price);
+            } else {
+                side.insert(price, size);
+            }
+        }
+    }
+
+    // the levels an order on side trades against, best first
+    fn opposite(&mut self, side: Side) -> Box<dyn Iterator<Item = (&Decimal, &mut Decimal)> + '_> {
+        match side {
+            Side::Buy => Box::new(self.asks.iter_mut()),
+            Side::Sell => Box::new(self.bids.iter_mut().rev()),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    maker_fee: Decimal,
+    taker_fee: Decimal,
+    books: HashMap<String, Depth>,
+    orders: Vec<PaperOrder>,
+    subscribers: Vec<UnboundedSender<crate::Result<PrivateEvent>>>,
+    // exchange time, the ts of the last public event
+    now: u64,
+    next_id: u64,
+}
+
+// A matching engine for paper trading against live market data. Feed it the public orderbook and trade streams
+// through on_public and use send as the transport for the order endpoints: an order takes what the local book
+// offers when it arrives and rests otherwise, resting orders fill from public trades printing at or through their
+// price, capped by the trade's size. Fills, fees and order changes come back through events as the PrivateEvents
+// the private stream would carry, so executors and trackers run unchanged. Clones share the exchange
+#[derive(Debug, Clone, Default)]
+pub struct PaperExchange(Arc<Mutex<State>>);
+
+impl PaperExchange {
+    // fee rates as fractions of the traded value, 0.00055 for 0.055%, a negative maker fee is a rebate
+    pub fn new(maker_fee: Decimal, taker_fee: Decimal) -> Self {
+        Self(Arc::new(Mutex::new(State { maker_fee, taker_fee, ..State::default() })))
+    }
+
+    // a new subscriber to order and execution events, each gets every event from here on
+    pub fn events(&self) -> UnboundedReceiver<crate::Result<PrivateEvent>> {
+        let (sender, receiver) = mpsc::unbounded();
+        self.state().subscribers.push(sender);
+        receiver
+    }
+
+    pub fn on_public(&self, event: &PublicEvent) {
+        let mut state = self.state();
+        match event {
+            PublicEvent::Orderbook { kind, ts, data, .. } => {
+                state.now = state.now.max(*ts);
+                let depth = state.books.entry(data.symbol.clone()).or_default();
+                Depth::apply(&mut depth.bids, &data.bids, *kind);
+                Depth::apply(&mut depth.asks, &data.asks, *kind);
+            }
+            PublicEvent::Trades { ts, data, .. } => {
+                state.now = state.now.max(*ts);
+                for trade in data {
+                    if let (Ok(price), Ok(size)) = (trade.price.parse(), trade.size.parse()) {
+                        state.on_trade(&trade.symbol, trade.side, price, size);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // The transport for a Client's order endpoints: create, create-batch, amend, cancel and cancel-all are answered
+    // here as Bybit would, any other request fails
+    pub fn send(&self, request: http::Request<String>) -> std::future::Ready<Result<Bytes, Error>> {
+        std::future::ready(self.answer(request.uri().path(), request.body()))
+    }
+
+    fn answer(&self, path: &str, body: &str) -> Result<Bytes, Error> {
+        let body: Value = serde_json::from_str(body).map_err(|err| Error::Serialization(err.to_string()))?;
+        let mut state = self.state();
+        let (result, ext_info) = match path {
+            "/v5/order/create" => (parse(body).and_then(|placement| state.place(None, placement)), json!({})),
+            "/v5/order/create-batch" => {
+                let category = body["category"].as_str().and_then(|category| serde_json::from_value(json!(category)).ok());
+                let items = body["request"].as_array().cloned().unwrap_or_default();
+                let (list, statuses): (Vec<Value>, Vec<Value>) = items
+                    .into_iter()
+                    .map(|item| match parse(item).and_then(|placement| state.place(category, placement)) {
+                        Ok(ack) => (ack, json!({ "code": 0, "msg": "OK" })),
+                        Err((code, message)) => (json!({ "orderId": "", "orderLinkId": "" }), json!({ "code": code, "msg": message })),
+                    })
+                    .unzip();
+                (Ok(json!({ "list": list })), json!({ "list": statuses }))
+            }
+            "/v5/order/amend" => (parse(body).and_then(|target| state.amend(target)), json!({})),
+            "/v5/order/cancel" => (parse(body).and_then(|target| state.cancel(target)), json!({})),
+            "/v5/order/cancel-all" => (parse(body).map(|target| state.cancel_all(target)), json!({})),
+            path => return Err(Error::Invalid(format!("the paper exchange doesn't serve {path}").into())),
+        };
+        Ok(envelope(result, ext_info, state.now as i64).to_string().into())
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn parse<T: for<'de> Deserialize<'de>>(body: Value) -> Result<T, (i32, String)> {
+    serde_json::from_value(body).map_err(|err| (PARAM_ERROR, err.to_string()))
+}
+
+fn ack(order: &PaperOrder) -> Value {
+    json!({ "orderId": order.order_id, "orderLinkId": order.order_link_id })
+}
+
+impl State {
+    fn place(&mut self, category: Option<Category>, placement: Placement) -> Result<Value, (i32, String)> {
+        let category = placement.category.or(category).ok_or((PARAM_ERROR, "category is missing".to_string()))?;
+        if placement.qty <= Decimal::ZERO {
+            return Err((PARAM_ERROR, format!("qty {} isn't positive", placement.qty)));
+        }
+        if placement.order_type == OrderType::Limit && placement.price.is_none_or(|price| price <= Decimal::ZERO) {
+            return Err((PARAM_ERROR, "a limit order needs a positive price".to_string()));
+        }
+        let order_link_id = placement.order_link_id.unwrap_or_default();
+        if !order_link_id.is_empty() && self.orders.iter().any(|order| order.order_link_id == order_link_id) {
+            return Err((110072, format!("orderLinkId {order_link_id} is duplicate")));
+        }
+        self.next_id += 1;
+        let market = placement.order_type == OrderType::Market;
+        let order = PaperOrder {
+            order_id: format!("paper-{}", self.next_id),
+            order_link_id,
+            category,
+            symbol: placement.symbol,
+            side: placement.side,
+            order_type: placement.order_type,
+            time_in_force: if market { TimeInForce::IOC } else { placement.time_in_force.unwrap_or(TimeInForce::GTC) },
+            price: placement.price.filter(|_| !market),
+            qty: placement.qty,
+            filled: Decimal::ZERO,
+            value: Decimal::ZERO,
+            fee: Decimal::ZERO,
+            status: OrderStatus::New,
+            reject_reason: "EC_NoError",
+            reduce_only: placement.reduce_only.unwrap_or(false),
+            position_idx: placement.position_idx.unwrap_or(0),
+            created_time: self.now,
+            updated_time: self.now,
+        };
+        let response = ack(&order);
+        self.orders.push(order);
+        self.arrive(self.orders.len() - 1);
+        Ok(response)
+    }
+
+    // an order arriving at the book, new or amended: takes what it can as allowed by its time in force
+    fn arrive(&mut self, index: usize) {
+        let order = &self.orders[index];
+        let depth = self.books.entry(order.symbol.clone()).or_default();
+        let available: Decimal = depth.opposite(order.side).take_while(|(price, _)| order.accepts(**price)).map(|(_, size)| *size).sum();
+        let rejected = match order.time_in_force {
+            TimeInForce::PostOnly if !available.is_zero() => Some("EC_PostOnlyWillTakeLiquidity"),
+            TimeInForce::FOK if available < order.remaining() => Some("EC_FOKOrderNotFullyFilled"),
+            _ => None,
+        };
+        if let Some(reason) = rejected {
+            self.end(index, OrderStatus::Cancelled, reason);
+            return;
+        }
+        if order.time_in_force != TimeInForce::PostOnly {
+            self.take(index);
+        }
+        let order = &self.orders[index];
+        if order.working() && order.time_in_force == TimeInForce::IOC {
+            let status = if order.filled.is_zero() { OrderStatus::Cancelled } else { OrderStatus::PartiallyFilledCanceled };
+            self.end(index, status, "EC_NoImmediateQtyToFill");
+        } else {
+            self.publish_order(index);
+        }
+    }
+
+    // walks the opposite side of the book as a taker, the liquidity taken is gone until the next snapshot
+    fn take(&mut self, index: usize) {
+        let order = &self.orders[index];
+        let mut remaining = order.remaining();
+        let mut fills = Vec::new();
+        let depth = self.books.entry(order.symbol.clone()).or_default();
+        for (price, size) in depth.opposite(order.side) {
+            if remaining.is_zero() || !order.accepts(*price) {
+                break;
+            }
+            let qty = remaining.min(*size);
+            *size -= qty;
+            remaining -= qty;
+            fills.push((*price, qty));
+        }
+        for side in [&mut depth.bids, &mut depth.asks] {
+            side.retain(|_, size| !size.is_zero());
+        }
+        for (price, qty) in fills {
+            self.fill(index, price, qty, false);
+        }
+    }
+
+    // resting orders on the side the trade's taker hit, best price first and then oldest
+    fn on_trade(&mut self, symbol: &str, taker_side: Side, price: Decimal, size: Decimal) {
+        let mut resting: Vec<usize> = (0..self.orders.len())
+            .filter(|index| {
+                let order = &self.orders[*index];
+                order.working() && order.symbol == symbol && order.side != taker_side && order.order_type == OrderType::Limit && order.accepts(price)
+            })
+            .collect();
+        resting.sort_by(|a, b| {
+            let (a, b) = (self.orders[*a].price, self.orders[*b].price);
+            match taker_side {
+                Side::Sell => b.cmp(&a),
+                Side::Buy => a.cmp(&b),
+            }
+        });
+        let mut left = size;
+        for index in resting {
+            if left.is_zero() {
+                break;
+            }
+            let order = &self.orders[index];
+            let qty = left.min(order.remaining());
+            let limit = order.price.unwrap_or(price);
+            left -= qty;
+            self.fill(index, limit, qty, true);
+            self.publish_order(index);
+        }
+    }
+
+    fn fill(&mut self, index: usize, price: Decimal, qty: Decimal, maker: bool) {
+        let rate = if maker { self.maker_fee } else { self.taker_fee };
+        self.next_id += 1;
+        let exec_id = format!("paper-exec-{}", self.next_id);
+        let now = self.now;
+        let order = &mut self.orders[index];
+        let value = order.value_of(price, qty);
+        let fee = value * rate;
+        order.filled += qty;
+        order.value += value;
+        order.fee += fee;
+        order.status = if order.remaining().is_zero() { OrderStatus::Filled } else { OrderStatus::PartiallyFilled };
+        order.updated_time = now;
+        let execution = ExecutionUpdate {
+            category: order.category,
+            symbol: order.symbol.clone(),
+            order_id: order.order_id.clone(),
+            order_link_id: order.order_link_id.clone(),
+            side: order.side,
+            order_price: order.price.unwrap_or_default().to_string(),
+            order_qty: order.qty.to_string(),
+            leaves_qty: order.remaining().to_string(),
+            order_type: format!("{:?}", order.order_type),
+            exec_id,
+            exec_price: price.to_string(),
+            exec_qty: qty.to_string(),
+            exec_value: value.normalize().to_string(),
+            exec_fee: fee.normalize().to_string(),
+            exec_type: "Trade".to_string(),
+            exec_time: now.to_string(),
+            fee_rate: rate.to_string(),
+            fee_currency: None,
+            is_maker: maker,
+            mark_price: price.to_string(),
+            closed_size: None,
+            seq: None,
+            extra: HashMap::new(),
+        };
+        self.publish(PrivateEvent::Fill { topic: "execution".to_string(), creation_time: now, data: vec![execution] });
+    }
+
+    fn amend(&mut self, target: Target) -> Result<Value, (i32, String)> {
+        let index = self.find(&target)?;
+        let order = &mut self.orders[index];
+        if let Some(qty) = target.qty {
+            if qty <= order.filled {
+                return Err((PARAM_ERROR, format!("qty {qty} isn't above the {} already filled", order.filled)));
+            }
+            order.qty = qty;
+        }
+        if let Some(price) = target.price.filter(|_| order.order_type == OrderType::Limit) {
+            order.price = Some(price);
+        }
+        order.updated_time = self.now;
+        let response = ack(order);
+        self.arrive(index);
+        Ok(response)
+    }
+
+    fn cancel(&mut self, target: Target) -> Result<Value, (i32, String)> {
+        let index = self.find(&target)?;
+        self.end(index, OrderStatus::Cancelled, "EC_PerCancelRequest");
+        Ok(ack(&self.orders[index]))
+    }
+
+    fn cancel_all(&mut self, target: Target) -> Value {
+        let cancelled: Vec<usize> = (0..self.orders.len())
+            .filter(|index| {
+                let order = &self.orders[*index];
+                order.working() && order.category == target.category && target.symbol.as_ref().is_none_or(|symbol| *symbol == order.symbol)
+            })
+            .collect();
+        let list: Vec<Value> = cancelled
+            .into_iter()
+            .map(|index| {
+                self.end(index, OrderStatus::Cancelled, "EC_PerCancelRequest");
+                ack(&self.orders[index])
+            })
+            .collect();
+        json!({ "list": list, "success": "1" })
+    }
+
+    fn find(&self, target: &Target) -> Result<usize, (i32, String)> {
+        self.orders
+            .iter()
+            .position(|order| {
+                let found = match (&target.order_id, &target.order_link_id) {
+                    (Some(order_id), _) => order.order_id == *order_id,
+                    (None, Some(order_link_id)) => !order_link_id.is_empty() && order.order_link_id == *order_link_id,
+                    (None, None) => false,
+                };
+                found && order.working() && order.category == target.category && target.symbol.as_ref().is_none_or(|symbol| *symbol == order.symbol)
+            })
+            .ok_or_else(|| (ORDER_NOT_FOUND, "order not exists or too late to cancel".to_string()))
+    }
+
+    fn end(&mut self, index: usize, status: OrderStatus, reason: &'static str) {
+        let order = &mut self.orders[index];
+        order.status = status;
+        order.reject_reason = reason;
+        order.updated_time = self.now;
+        self.publish_order(index);
+    }
+
+    fn publish_order(&mut self, index: usize) {
+        let update = self.orders[index].update();
+        self.publish(PrivateEvent::Order { topic: "order".to_string(), creation_time: self.now, data: vec![update] });
+    }
+
+    fn publish(&mut self, event: PrivateEvent) {
+        self.subscribers.retain(|subscriber| subscriber.unbounded_send(Ok(event.clone())).is_ok());
+    }
+}
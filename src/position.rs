@@ -0,0 +1,885 @@
+use std::{collections::{BTreeSet, HashMap}, time::Duration};
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    market::InstrumentsQuery,
+    number::Precision,
+    query,
+    trade::{CancelAllOrdersRequest, CancelScope, OrderType, PlaceOrderRequest, PlaceOrderResponse, TpslMode, TriggerBy},
+    BybitRequest, Category, Client, Empty, IntoGetRequest, IntoPostRequest, MarginMode, OrderStatus, Side,
+};
+#[cfg(feature = "ws")]
+use crate::ws::PrivateEvent;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Position {
+    pub symbol: String,
+    // empty string for a flat position in hedge mode, hence not Side
+    pub side: String,
+    pub size: String,
+    #[serde(rename = "positionIdx")]
+    pub position_idx: i32,
+    // entry price
+    #[serde(rename = "avgPrice")]
+    pub avg_price: String,
+    #[serde(rename = "positionValue")]
+    pub position_value: String,
+    #[serde(rename = "markPrice")]
+    pub mark_price: String,
+    pub leverage: String,
+    // empty when the position can't be liquidated
+    #[serde(rename = "liqPrice")]
+    pub liq_price: String,
+    #[serde(rename = "positionIM")]
+    pub position_im: String,
+    #[serde(rename = "positionMM")]
+    pub position_mm: String,
+    #[serde(rename = "takeProfit")]
+    pub take_profit: String,
+    #[serde(rename = "stopLoss")]
+    pub stop_loss: String,
+    #[serde(rename = "trailingStop")]
+    pub trailing_stop: String,
+    #[serde(rename = "unrealisedPnl")]
+    pub unrealised_pnl: String,
+    #[serde(rename = "curRealisedPnl")]
+    pub cur_realised_pnl: String,
+    #[serde(rename = "cumRealisedPnl")]
+    pub cum_realised_pnl: String,
+    #[serde(rename = "positionStatus")]
+    pub position_status: String,
+    #[serde(rename = "createdTime")]
+    pub created_time: String,
+    #[serde(rename = "updatedTime")]
+    pub updated_time: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PositionPage {
+    pub category: Category,
+    pub list: Vec<Position>,
+    #[serde(rename = "nextPageCursor")]
+    pub next_page_cursor: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl PositionPage {
+    pub fn next_cursor(&self) -> Option<&str> {
+        Some(self.next_page_cursor.as_str()).filter(|cursor| !cursor.is_empty())
+    }
+}
+
+// linear and inverse need a symbol or settle coin
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionQuery {
+    pub category: Category,
+    pub symbol: Option<String>,
+    #[serde(rename = "baseCoin")]
+    pub base_coin: Option<String>,
+    #[serde(rename = "settleCoin")]
+    pub settle_coin: Option<String>,
+    pub limit: Option<u32>,
+    pub cursor: Option<String>,
+}
+
+impl PositionQuery {
+    pub fn new(category: Category) -> Self {
+        Self { category, symbol: None, base_coin: None, settle_coin: None, limit: None, cursor: None }
+    }
+
+    pub fn with_symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbol = Some(symbol.into());
+        self
+    }
+
+    pub fn with_settle_coin(mut self, settle_coin: impl Into<String>) -> Self {
+        self.settle_coin = Some(settle_coin.into());
+        self
+    }
+
+    pub fn with_cursor(mut self, cursor: Option<&str>) -> Self {
+        self.cursor = cursor.map(query::decode);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MarginModeReason {
+    #[serde(rename = "reasonCode")]
+    pub reason_code: String,
+    #[serde(rename = "reasonMsg")]
+    pub reason_msg: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetMarginModeResponse {
+    // why the switch was refused, empty on success
+    pub reasons: Vec<MarginModeReason>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+// hedge mode (BothSides) keeps separate long and short positions per symbol, positionIdx 1 and 2
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PositionMode {
+    MergedSingle = 0,
+    BothSides = 3
+}
+
+impl Serialize for PositionMode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i32(*self as i32)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TradingStopRequest {
+    pub category: Category,
+    pub symbol: String,
+    #[serde(rename = "tpslMode")]
+    pub tpsl_mode: TpslMode,
+    #[serde(rename = "positionIdx")]
+    pub position_idx: i32,
+    // "0" cancels an existing take profit / stop loss / trailing stop, hence strings
+    #[serde(rename = "takeProfit", skip_serializing_if = "Option::is_none")]
+    pub take_profit: Option<String>,
+    #[serde(rename = "stopLoss", skip_serializing_if = "Option::is_none")]
+    pub stop_loss: Option<String>,
+    #[serde(rename = "trailingStop", skip_serializing_if = "Option::is_none")]
+    pub trailing_stop: Option<String>,
+    #[serde(rename = "tpTriggerBy", skip_serializing_if = "Option::is_none")]
+    pub tp_trigger_by: Option<TriggerBy>,
+    #[serde(rename = "slTriggerBy", skip_serializing_if = "Option::is_none")]
+    pub sl_trigger_by: Option<TriggerBy>,
+    #[serde(rename = "activePrice", skip_serializing_if = "Option::is_none")]
+    pub active_price: Option<Decimal>,
+    #[serde(rename = "tpSize", skip_serializing_if = "Option::is_none")]
+    pub tp_size: Option<Decimal>,
+    #[serde(rename = "slSize", skip_serializing_if = "Option::is_none")]
+    pub sl_size: Option<Decimal>,
+    #[serde(rename = "tpLimitPrice", skip_serializing_if = "Option::is_none")]
+    pub tp_limit_price: Option<Decimal>,
+    #[serde(rename = "slLimitPrice", skip_serializing_if = "Option::is_none")]
+    pub sl_limit_price: Option<Decimal>,
+    #[serde(rename = "tpOrderType", skip_serializing_if = "Option::is_none")]
+    pub tp_order_type: Option<OrderType>,
+    #[serde(rename = "slOrderType", skip_serializing_if = "Option::is_none")]
+    pub sl_order_type: Option<OrderType>,
+}
+
+impl TradingStopRequest {
+    // position_idx is 0 in one-way mode, 1 (buy side) or 2 (sell side) in hedge mode
+    pub fn new(category: Category, symbol: impl Into<String>, position_idx: i32) -> Self {
+        Self {
+            category,
+            symbol: symbol.into(),
+            tpsl_mode: TpslMode::Full,
+            position_idx,
+            take_profit: None,
+            stop_loss: None,
+            trailing_stop: None,
+            tp_trigger_by: None,
+            sl_trigger_by: None,
+            active_price: None,
+            tp_size: None,
+            sl_size: None,
+            tp_limit_price: None,
+            sl_limit_price: None,
+            tp_order_type: None,
+            sl_order_type: None,
+        }
+    }
+
+    pub fn with_take_profit(mut self, price: Decimal, trigger_by: Option<TriggerBy>) -> Self {
+        self.take_profit = Some(price.to_string());
+        self.tp_trigger_by = trigger_by;
+        self
+    }
+
+    pub fn with_stop_loss(mut self, price: Decimal, trigger_by: Option<TriggerBy>) -> Self {
+        self.stop_loss = Some(price.to_string());
+        self.sl_trigger_by = trigger_by;
+        self
+    }
+
+    pub fn with_trailing_stop(mut self, distance: Decimal, active_price: Option<Decimal>) -> Self {
+        self.trailing_stop = Some(distance.to_string());
+        self.active_price = active_price;
+        self
+    }
+
+    pub fn cancel_take_profit(mut self) -> Self {
+        self.take_profit = Some("0".to_string());
+        self
+    }
+
+    pub fn cancel_stop_loss(mut self) -> Self {
+        self.stop_loss = Some("0".to_string());
+        self
+    }
+}
+
+impl IntoPostRequest for TradingStopRequest {
+    const ENDPOINT: &'static str = "/v5/position/trading-stop";
+    type Response = Empty;
+}
+
+impl Client {
+    pub fn get_positions(&self, query: &PositionQuery, recv_window: &Duration) -> crate::Result<BybitRequest<PositionPage>> {
+        #[derive(Serialize, Debug)]
+        struct PositionsRequest<'a>(&'a PositionQuery);
+
+        impl IntoGetRequest for PositionsRequest<'_> {
+            const ENDPOINT: &'static str = "/v5/position/list";
+            type Response = PositionPage;
+        }
+
+        PositionsRequest(query).as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+    // one-way mode positions need buy and sell leverage to be equal
+    pub fn set_leverage(&self, category: Category, symbol: String, buy_leverage: Decimal, sell_leverage: Decimal, recv_window: &Duration) -> crate::Result<BybitRequest<Empty>> {
+        #[derive(Serialize, Debug)]
+        struct LeverageRequest {
+            category: Category,
+            symbol: String,
+            #[serde(rename = "buyLeverage")]
+            buy_leverage: Decimal,
+            #[serde(rename = "sellLeverage")]
+            sell_leverage: Decimal,
+        }
+
+        impl IntoPostRequest for LeverageRequest {
+            const ENDPOINT: &'static str = "/v5/position/set-leverage";
+            type Response = Empty;
+        }
+
+        let request = LeverageRequest {
+            category,
+            symbol,
+            buy_leverage,
+            sell_leverage,
+        };
+
+        request.as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+    // unified accounts switch margin mode for the whole account rather than per symbol
+    pub fn switch_margin_mode(&self, margin_mode: MarginMode, recv_window: &Duration) -> crate::Result<BybitRequest<SetMarginModeResponse>> {
+        #[derive(Serialize, Debug)]
+        struct MarginModeRequest {
+            #[serde(rename = "setMarginMode")]
+            set_margin_mode: MarginMode,
+        }
+
+        impl IntoPostRequest for MarginModeRequest {
+            const ENDPOINT: &'static str = "/v5/account/set-margin-mode";
+            type Response = SetMarginModeResponse;
+        }
+
+        let request = MarginModeRequest {
+            set_margin_mode: margin_mode,
+        };
+
+        request.as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+    pub fn set_trading_stop(&self, request: &TradingStopRequest, recv_window: &Duration) -> crate::Result<BybitRequest<Empty>> {
+        request.as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+    // linear and inverse only, fails while the symbol has open positions or orders
+    pub fn switch_position_mode(&self, category: Category, symbol: String, mode: PositionMode, recv_window: &Duration) -> crate::Result<BybitRequest<Empty>> {
+        #[derive(Serialize, Debug)]
+        struct PositionModeRequest {
+            category: Category,
+            symbol: String,
+            mode: PositionMode,
+        }
+
+        impl IntoPostRequest for PositionModeRequest {
+            const ENDPOINT: &'static str = "/v5/position/switch-mode";
+            type Response = Empty;
+        }
+
+        PositionModeRequest { category, symbol, mode }.as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+}
+
+fn is_not_modified(err: &crate::Error) -> bool {
+    err.known_code().is_some_and(|code| code.is_not_modified())
+}
+
+#[derive(Debug, Clone)]
+pub struct SymbolSetup {
+    pub category: Category,
+    pub symbol: String,
+    pub position_mode: Option<PositionMode>,
+    // applied to both sides
+    pub leverage: Option<Decimal>,
+}
+
+impl SymbolSetup {
+    pub fn new(category: Category, symbol: impl Into<String>) -> Self {
+        Self { category, symbol: symbol.into(), position_mode: None, leverage: None }
+    }
+
+    pub fn with_position_mode(mut self, mode: PositionMode) -> Self {
+        self.position_mode = Some(mode);
+        self
+    }
+
+    pub fn with_leverage(mut self, leverage: Decimal) -> Self {
+        self.leverage = Some(leverage);
+        self
+    }
+}
+
+// desired state for ensure_account_setup, None leaves a setting as it is
+#[derive(Debug, Clone, Default)]
+pub struct AccountSetup {
+    pub margin_mode: Option<MarginMode>,
+    pub symbols: Vec<SymbolSetup>,
+}
+
+impl AccountSetup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_margin_mode(mut self, margin_mode: MarginMode) -> Self {
+        self.margin_mode = Some(margin_mode);
+        self
+    }
+
+    pub fn with_symbol(mut self, symbol: SymbolSetup) -> Self {
+        self.symbols.push(symbol);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SetupChange {
+    MarginMode(MarginMode),
+    PositionMode { symbol: String, mode: PositionMode },
+    Leverage { symbol: String, leverage: Decimal },
+}
+
+impl Client {
+    // Reads the current margin mode, position mode and leverage and only switches what differs, so it can run on
+    // every startup. Returns the switches that were sent
+    pub async fn ensure_account_setup<F, R, E>(&self, desired: &AccountSetup, recv_window: &Duration, send: F) -> crate::Result<Vec<SetupChange>>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let mut changes = Vec::new();
+        if let Some(margin_mode) = desired.margin_mode {
+            let current = self.get_account_info(recv_window)?.send(&send).await?;
+            if current.margin_mode != margin_mode {
+                let response = self.switch_margin_mode(margin_mode, recv_window)?.send(&send).await;
+                match response {
+                    Ok(response) if !response.reasons.is_empty() => return Err(crate::Error::MarginModeRefused(response.reasons)),
+                    Ok(_) => changes.push(SetupChange::MarginMode(margin_mode)),
+                    Err(err) if is_not_modified(&err) => {}
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+
+        for setup in &desired.symbols {
+            let query = PositionQuery::new(setup.category).with_symbol(setup.symbol.clone());
+            let positions = self.get_positions(&query, recv_window)?.send(&send).await?.list;
+            let current_mode = if positions.iter().any(|position| position.position_idx != 0) { PositionMode::BothSides } else { PositionMode::MergedSingle };
+
+            if let Some(mode) = setup.position_mode.filter(|mode| *mode != current_mode) {
+                match self.switch_position_mode(setup.category, setup.symbol.clone(), mode, recv_window)?.send(&send).await {
+                    Ok(_) => changes.push(SetupChange::PositionMode { symbol: setup.symbol.clone(), mode }),
+                    Err(err) if is_not_modified(&err) => {}
+                    Err(err) => return Err(err),
+                }
+            }
+
+            let Some(leverage) = setup.leverage else {
+                continue;
+            };
+            let matches = !positions.is_empty() && positions.iter().all(|position| position.leverage.parse::<Decimal>().is_ok_and(|current| current == leverage));
+            if matches {
+                continue;
+            }
+            match self.set_leverage(setup.category, setup.symbol.clone(), leverage, leverage, recv_window)?.send(&send).await {
+                Ok(_) => changes.push(SetupChange::Leverage { symbol: setup.symbol.clone(), leverage }),
+                Err(err) if is_not_modified(&err) => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(changes)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlattenStep {
+    DiscoverSettleCoins { category: Category },
+    CancelOrders { category: Category, settle_coin: Option<String> },
+    ListPositions { category: Category, settle_coin: Option<String> },
+    ClosePosition { category: Category, symbol: String, side: Side, qty: Decimal, position_idx: i32 },
+}
+
+#[derive(Debug)]
+pub enum FlattenOutcome {
+    // dry run, the step would have been sent
+    Skipped,
+    Discovered(Vec<String>),
+    Cancelled(usize),
+    Listed(usize),
+    Closed(PlaceOrderResponse),
+    Failed(crate::Error),
+}
+
+#[derive(Debug, Default)]
+pub struct FlattenReport {
+    pub actions: Vec<(FlattenStep, FlattenOutcome)>,
+}
+
+impl FlattenReport {
+    pub fn failures(&self) -> impl Iterator<Item = &(FlattenStep, FlattenOutcome)> {
+        self.actions.iter().filter(|(_, outcome)| matches!(outcome, FlattenOutcome::Failed(_)))
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.failures().next().is_none()
+    }
+}
+
+// What flatten_all covers. Linear and inverse can only be cancelled and listed per settle coin, so each scope is a
+// category plus an optional settle coin, a linear or inverse scope without one covers every settle coin instruments
+// info lists for the category. Spot has no positions, spot scopes only cancel orders
+#[derive(Debug, Clone)]
+pub struct FlattenAll {
+    pub scopes: Vec<(Category, Option<String>)>,
+    pub dry_run: bool,
+}
+
+impl Default for FlattenAll {
+    fn default() -> Self {
+        Self {
+            scopes: [Category::Linear, Category::Inverse, Category::Spot, Category::Option].map(|category| (category, None)).to_vec(),
+            dry_run: false,
+        }
+    }
+}
+
+impl FlattenAll {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn only(mut self, categories: &[Category]) -> Self {
+        self.scopes.retain(|(category, _)| categories.contains(category));
+        self
+    }
+
+    pub fn with_scope(mut self, category: Category, settle_coin: Option<String>) -> Self {
+        self.scopes.push((category, settle_coin));
+        self
+    }
+
+    pub fn dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+}
+
+impl Client {
+    // Emergency kill switch: cancels every open order, then closes every position with a reduce-only market order.
+    // Failures are recorded in the report and the remaining steps still run. A dry run still lists positions
+    // (read only) so the report shows what would be closed
+    pub async fn flatten_all<F, R, E>(&self, options: &FlattenAll, recv_window: &Duration, send: F) -> FlattenReport
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let mut report = FlattenReport::default();
+        let mut scopes = Vec::new();
+        for (category, settle_coin) in &options.scopes {
+            if settle_coin.is_some() || !matches!(category, Category::Linear | Category::Inverse) {
+                scopes.push((*category, settle_coin.clone()));
+                continue;
+            }
+            // read only, a dry run discovers them too
+            let step = FlattenStep::DiscoverSettleCoins { category: *category };
+            match self.get_all_instruments(&InstrumentsQuery::new(*category), &send).await {
+                Ok(instruments) => {
+                    let settle_coins: BTreeSet<String> = instruments.into_iter().filter_map(|instrument| instrument.settle_coin).collect();
+                    scopes.extend(settle_coins.iter().map(|settle_coin| (*category, Some(settle_coin.clone()))));
+                    report.actions.push((step, FlattenOutcome::Discovered(settle_coins.into_iter().collect())));
+                }
+                Err(err) => report.actions.push((step, FlattenOutcome::Failed(err))),
+            }
+        }
+
+        for (category, settle_coin) in &scopes {
+            let step = FlattenStep::CancelOrders { category: *category, settle_coin: settle_coin.clone() };
+            let outcome = if options.dry_run {
+                FlattenOutcome::Skipped
+            } else {
+                let mut request = CancelAllOrdersRequest::new(*category);
+                if let Some(settle_coin) = settle_coin {
+                    request = request.with_scope(CancelScope::SettleCoin(settle_coin.clone()));
+                }
+                match async { self.cancel_all_orders(&request, recv_window)?.send(&send).await }.await {
+                    Ok(cancelled) => FlattenOutcome::Cancelled(cancelled.list.len()),
+                    Err(err) => FlattenOutcome::Failed(err),
+                }
+            };
+            report.actions.push((step, outcome));
+        }
+
+        for (category, settle_coin) in &scopes {
+            if *category == Category::Spot {
+                continue;
+            }
+            let step = FlattenStep::ListPositions { category: *category, settle_coin: settle_coin.clone() };
+            let positions = match self.get_all_positions(*category, settle_coin.clone(), recv_window, &send).await {
+                Ok(positions) => positions,
+                Err(err) => {
+                    report.actions.push((step, FlattenOutcome::Failed(err)));
+                    continue;
+                }
+            };
+            report.actions.push((step, FlattenOutcome::Listed(positions.len())));
+
+            for position in positions {
+                let qty: Decimal = position.size.parse().unwrap_or_default();
+                let side = match position.side.as_str() {
+                    "Buy" => Side::Sell,
+                    "Sell" => Side::Buy,
+                    _ => continue,
+                };
+                if qty.is_zero() {
+                    continue;
+                }
+                let step = FlattenStep::ClosePosition {
+                    category: *category,
+                    symbol: position.symbol.clone(),
+                    side,
+                    qty,
+                    position_idx: position.position_idx,
+                };
+                let outcome = if options.dry_run {
+                    FlattenOutcome::Skipped
+                } else {
+                    let request = PlaceOrderRequest::market(*category, position.symbol, side, qty)
+                        .with_position_idx(position.position_idx)
+                        .reduce_only();
+                    match async { self.place_order(&request, recv_window)?.send(&send).await }.await {
+                        Ok(placed) => FlattenOutcome::Closed(placed),
+                        Err(err) => FlattenOutcome::Failed(err),
+                    }
+                };
+                report.actions.push((step, outcome));
+            }
+        }
+        report
+    }
+
+    pub(crate) async fn get_all_positions<F, R, E>(&self, category: Category, settle_coin: Option<String>, recv_window: &Duration, send: F) -> crate::Result<Vec<Position>>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let mut query = PositionQuery::new(category);
+        query.settle_coin = settle_coin;
+        let mut positions = Vec::new();
+        loop {
+            let page = self.get_positions(&query, recv_window)?.send(&send).await?;
+            query = query.with_cursor(page.next_cursor());
+            positions.extend(page.list);
+            if query.cursor.is_none() {
+                return Ok(positions);
+            }
+        }
+    }
+}
+
+// one closed (or partially closed) position, side is that of the closing order
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ClosedPnl {
+    pub symbol: String,
+    #[serde(rename = "orderId")]
+    pub order_id: String,
+    pub side: Side,
+    pub qty: String,
+    #[serde(rename = "orderPrice")]
+    pub order_price: String,
+    #[serde(rename = "orderType")]
+    pub order_type: String,
+    #[serde(rename = "execType")]
+    pub exec_type: String,
+    #[serde(rename = "closedSize")]
+    pub closed_size: String,
+    #[serde(rename = "cumEntryValue")]
+    pub cum_entry_value: String,
+    #[serde(rename = "avgEntryPrice")]
+    pub avg_entry_price: String,
+    #[serde(rename = "cumExitValue")]
+    pub cum_exit_value: String,
+    #[serde(rename = "avgExitPrice")]
+    pub avg_exit_price: String,
+    // after fees, funding isn't part of it
+    #[serde(rename = "closedPnl")]
+    pub closed_pnl: String,
+    #[serde(rename = "fillCount")]
+    pub fill_count: String,
+    pub leverage: String,
+    #[serde(rename = "createdTime")]
+    pub created_time: String,
+    #[serde(rename = "updatedTime")]
+    pub updated_time: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClosedPnlPage {
+    pub category: Category,
+    pub list: Vec<ClosedPnl>,
+    #[serde(rename = "nextPageCursor")]
+    pub next_page_cursor: String,
+}
+
+impl ClosedPnlPage {
+    pub fn next_cursor(&self) -> Option<&str> {
+        Some(self.next_page_cursor.as_str()).filter(|cursor| !cursor.is_empty())
+    }
+}
+
+// linear and inverse only, without a time range Bybit returns the last 7 days and a range can't span more
+#[derive(Debug, Clone, Serialize)]
+pub struct ClosedPnlQuery {
+    pub category: Category,
+    pub symbol: Option<String>,
+    // unix millis
+    #[serde(rename = "startTime")]
+    pub start_time: Option<i64>,
+    #[serde(rename = "endTime")]
+    pub end_time: Option<i64>,
+    pub limit: Option<u32>,
+    pub cursor: Option<String>,
+}
+
+impl ClosedPnlQuery {
+    pub fn new(category: Category) -> Self {
+        Self { category, symbol: None, start_time: None, end_time: None, limit: None, cursor: None }
+    }
+
+    pub fn with_symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbol = Some(symbol.into());
+        self
+    }
+
+    pub fn with_time_range(mut self, start_time: i64, end_time: i64) -> Self {
+        self.start_time = Some(start_time);
+        self.end_time = Some(end_time);
+        self
+    }
+
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn with_cursor(mut self, cursor: Option<&str>) -> Self {
+        self.cursor = cursor.map(query::decode);
+        self
+    }
+}
+
+impl Client {
+    pub fn get_closed_pnl(&self, query: &ClosedPnlQuery, recv_window: &Duration) -> crate::Result<BybitRequest<ClosedPnlPage>> {
+        #[derive(Serialize, Debug)]
+        struct ClosedPnlRequest<'a>(&'a ClosedPnlQuery);
+
+        impl IntoGetRequest for ClosedPnlRequest<'_> {
+            const ENDPOINT: &'static str = "/v5/position/closed-pnl";
+            type Response = ClosedPnlPage;
+        }
+
+        ClosedPnlRequest(query).as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+    pub async fn get_all_closed_pnl<F, R, E>(&self, query: &ClosedPnlQuery, recv_window: &Duration, send: F) -> crate::Result<Vec<ClosedPnl>>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let mut query = query.clone();
+        let mut closed = Vec::new();
+        loop {
+            let page = self.get_closed_pnl(&query, recv_window)?.send(&send).await?;
+            query = query.with_cursor(page.next_cursor());
+            closed.extend(page.list);
+            if query.cursor.is_none() {
+                return Ok(closed);
+            }
+        }
+    }
+}
+
+// Partial close of one symbol. pct is in percent of the current size, 100 closes everything. In hedge mode both
+// sides are closed unless position_idx picks one (1 the long side, 2 the short side)
+#[derive(Debug, Clone)]
+pub struct ClosePosition {
+    pub category: Category,
+    pub symbol: String,
+    pub pct: Decimal,
+    pub position_idx: Option<i32>,
+    // without it partial closes aren't rounded and can be rejected for precision
+    pub qty_step: Option<Decimal>,
+}
+
+impl ClosePosition {
+    pub fn new(category: Category, symbol: impl Into<String>, pct: Decimal) -> Self {
+        Self { category, symbol: symbol.into(), pct, position_idx: None, qty_step: None }
+    }
+
+    pub fn with_position_idx(mut self, position_idx: i32) -> Self {
+        self.position_idx = Some(position_idx);
+        self
+    }
+
+    pub fn with_qty_step(mut self, qty_step: Decimal) -> Self {
+        self.qty_step = Some(qty_step);
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ClosingOrder {
+    pub symbol: String,
+    pub position_idx: i32,
+    pub side: Side,
+    pub position_size: Decimal,
+    pub qty: Decimal,
+    pub order: PlaceOrderResponse,
+    // kept up to date by confirm_close
+    pub filled: Decimal,
+    pub status: Option<OrderStatus>,
+}
+
+impl ClosingOrder {
+    pub fn remaining(&self) -> Decimal {
+        self.qty - self.filled
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.status.is_some_and(|status| status.is_terminal())
+    }
+}
+
+// In hedge mode a later side can fail after an earlier one was placed, those orders are working and still have to be
+// confirmed or cancelled
+#[derive(Debug, thiserror::Error)]
+#[error("{} closing order(s) were placed before the next one failed: {source}", .placed.len())]
+pub struct PartialCloseError {
+    pub placed: Vec<ClosingOrder>,
+    pub source: crate::Error,
+}
+
+impl Client {
+    // Sends a reduce-only market order for pct of each matching position. The orders are only placed here, pass
+    // them to confirm_close with a private stream subscribed to order updates to learn how much actually closed.
+    // A failure after some orders went out comes back as Error::PartialClose carrying them
+    pub async fn close_position<F, R, E>(&self, options: &ClosePosition, recv_window: &Duration, send: F) -> crate::Result<Vec<ClosingOrder>>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        if options.pct <= Decimal::ZERO || options.pct > Decimal::ONE_HUNDRED {
+            return Err(crate::Error::Invalid(format!("close percentage {} is outside (0, 100]", options.pct).into()));
+        }
+        let query = PositionQuery::new(options.category).with_symbol(options.symbol.clone());
+        let positions = self.get_positions(&query, recv_window)?.send(&send).await?.list;
+
+        let mut closing = Vec::new();
+        for position in positions {
+            if options.position_idx.is_some_and(|idx| idx != position.position_idx) {
+                continue;
+            }
+            let side = match position.side.as_str() {
+                "Buy" => Side::Sell,
+                "Sell" => Side::Buy,
+                _ => continue,
+            };
+            let size: Decimal = match position.size.parse() {
+                Ok(size) => size,
+                Err(err) => return Err(partial(closing, crate::Error::Unexpected(format!("invalid position size {:?}: {err}", position.size)))),
+            };
+            let qty = if options.pct == Decimal::ONE_HUNDRED { size } else { size * options.pct / Decimal::ONE_HUNDRED };
+            let qty = Precision::new(Decimal::ZERO, options.qty_step.unwrap_or_default()).qty(qty);
+            if qty.is_zero() {
+                continue;
+            }
+            let request = PlaceOrderRequest::market(options.category, position.symbol.clone(), side, qty)
+                .with_position_idx(position.position_idx)
+                .reduce_only();
+            let placed = async { self.place_order(&request, recv_window)?.send(&send).await }.await;
+            let order = match placed {
+                Ok(order) => order,
+                Err(err) => return Err(partial(closing, err)),
+            };
+            closing.push(ClosingOrder {
+                symbol: position.symbol,
+                position_idx: position.position_idx,
+                side,
+                position_size: size,
+                qty: request.qty,
+                order,
+                filled: Decimal::ZERO,
+                status: None,
+            });
+        }
+        if closing.is_empty() {
+            return Err(crate::Error::Invalid(format!("no open {} position to close", options.symbol).into()));
+        }
+        Ok(closing)
+    }
+}
+
+fn partial(placed: Vec<ClosingOrder>, source: crate::Error) -> crate::Error {
+    if placed.is_empty() {
+        return source;
+    }
+    PartialCloseError { placed, source }.into()
+}
+
+// Follows the order stream until every closing order is terminal, a market order can still end partially filled
+// (e.g. reduce-only capped by a concurrent fill) so check remaining() afterwards
+#[cfg(feature = "ws")]
+pub async fn confirm_close<St>(orders: &mut [ClosingOrder], events: &mut St) -> crate::Result<()>
+where St: futures::Stream<Item = crate::Result<PrivateEvent>> + Unpin
+{
+    use futures::StreamExt;
+
+    while !orders.iter().all(ClosingOrder::is_done) {
+        let Some(event) = events.next().await else {
+            return Err(crate::Error::WebSocket("stream ended before the close was confirmed".into()));
+        };
+        let PrivateEvent::Order { data, .. } = event? else {
+            continue;
+        };
+        for update in data {
+            if let Some(order) = orders.iter_mut().find(|order| order.order.order_id == update.order_id) {
+                order.filled = update.cum_exec_qty.parse()
+                    .map_err(|err| crate::Error::Unexpected(format!("invalid cumExecQty {:?}: {err}", update.cum_exec_qty)))?;
+                order.status = Some(update.order_status);
+            }
+        }
+    }
+    Ok(())
+}
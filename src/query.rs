@@ -0,0 +1,67 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{Error, Result};
+
+// Query string serializer matching what Bybit expects: fields in declaration order, None skipped,
+// sequences comma joined (`symbol=BTCUSDT,ETHUSDT`) instead of serde_qs' `symbol[0]=` style
+pub fn to_string<T: Serialize + ?Sized>(params: &T) -> Result<String> {
+    let fields = match serde_json::to_value(params).map_err(|err| Error::Serialization(err.to_string()))? {
+        Value::Object(fields) => fields,
+        Value::Null => return Ok(String::new()),
+        other => return Err(Error::Serialization(format!("query parameters must serialize to a struct or map, got {other}"))),
+    };
+    let mut pairs = Vec::with_capacity(fields.len());
+    for (key, value) in fields {
+        let value = match value {
+            Value::Null => continue,
+            Value::Array(items) if items.is_empty() => continue,
+            Value::Array(items) => items.iter().map(|item| scalar(&key, item)).collect::<Result<Vec<_>>>()?.join(","),
+            value => scalar(&key, &value)?,
+        };
+        pairs.push(format!("{}={value}", encode(&key)));
+    }
+    Ok(pairs.join("&"))
+}
+
+fn scalar(key: &str, value: &Value) -> Result<String> {
+    match value {
+        Value::Bool(value) => Ok(value.to_string()),
+        Value::Number(value) => Ok(value.to_string()),
+        Value::String(value) => Ok(encode(value)),
+        _ => Err(Error::Serialization(format!("query parameter {key} must be a scalar or a sequence of scalars"))),
+    }
+}
+
+fn encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+// Bybit hands out pagination cursors already percent-encoded (`nextPageCursor: "123%3A1"`), decoding them
+// before they go back into a request struct keeps to_string from encoding them twice
+pub fn decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|hex| std::str::from_utf8(hex).ok()).and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,119 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+
+use crate::clock::{Clock, SystemClock};
+
+// Bybit's per endpoint budget as reported on the last response, see X-Bapi-Limit*
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    pub limit: u32,
+    pub remaining: u32,
+    pub reset_at: DateTime<Utc>,
+}
+
+impl RateLimitStatus {
+    pub fn from_headers(headers: &http::HeaderMap) -> Option<Self> {
+        let header = |name: &str| headers.get(name)?.to_str().ok()?.trim().parse::<i64>().ok();
+        Some(Self {
+            limit: header("X-Bapi-Limit")?.try_into().ok()?,
+            remaining: header("X-Bapi-Limit-Status")?.try_into().ok()?,
+            reset_at: DateTime::from_timestamp_millis(header("X-Bapi-Limit-Reset-Timestamp")?)?,
+        })
+    }
+}
+
+// Throttles requests per endpoint group using the limit headers of earlier responses. Groups default to the request
+// path, which is how Bybit meters most endpoints, with_group puts several paths under one budget. Clones share state
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    groups: Arc<Mutex<HashMap<String, String>>>,
+    status: Arc<Mutex<HashMap<String, RateLimitStatus>>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self {
+            groups: Arc::new(Mutex::new(HashMap::new())),
+            status: Arc::new(Mutex::new(HashMap::new())),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    pub fn with_group(self, path: impl Into<String>, group: impl Into<String>) -> Self {
+        self.groups.lock().unwrap().insert(path.into(), group.into());
+        self
+    }
+
+    pub fn group(&self, path: &str) -> String {
+        self.groups.lock().unwrap().get(path).cloned().unwrap_or_else(|| path.to_string())
+    }
+
+    pub fn status(&self, path: &str) -> Option<RateLimitStatus> {
+        self.status.lock().unwrap().get(&self.group(path)).copied()
+    }
+
+    pub fn observe(&self, path: &str, headers: &http::HeaderMap) {
+        if let Some(status) = RateLimitStatus::from_headers(headers) {
+            self.status.lock().unwrap().insert(self.group(path), status);
+        }
+    }
+
+    // how long a request to path has to wait, the budget is taken right away when it doesn't
+    pub fn try_acquire(&self, path: &str) -> Option<Duration> {
+        let now = self.clock.now();
+        let mut status = self.status.lock().unwrap();
+        let group = self.group(path);
+        let current = status.get_mut(&group)?;
+        if now >= current.reset_at {
+            status.remove(&group);
+            return None;
+        }
+        if current.remaining > 0 {
+            current.remaining -= 1;
+            return None;
+        }
+        Some((current.reset_at - now).to_std().unwrap_or_default())
+    }
+
+    pub async fn acquire(&self, path: &str) {
+        while let Some(wait) = self.try_acquire(path) {
+            futures_timer::Delay::new(wait).await;
+        }
+    }
+
+    // Turns a transport returning the whole http response into the body only transport the client helpers take,
+    // waiting for budget before each request and recording the limit headers of each response
+    pub fn wrap<'a, F, R, E>(&'a self, send: &'a F) -> impl Fn(http::Request<String>) -> BoxFuture<'a, Result<Bytes, E>> + 'a
+    where F: Fn(http::Request<String>) -> R + Sync,
+        R: Future<Output = Result<http::Response<Bytes>, E>> + Send + 'a,
+        E: 'a
+    {
+        move |request| Box::pin(async move {
+            let path = request.uri().path().to_string();
+            self.acquire(&path).await;
+            let response = send(request).await?;
+            self.observe(&path, response.headers());
+            Ok(response.into_body())
+        })
+    }
+}
@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{signed_builder, BybitRequest, Client, Params, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawMethod {
+    Get,
+    Post,
+}
+
+// Any v5 endpoint the crate doesn't wrap yet. GET params go into the query string and POST params into the JSON body,
+// signed exactly like the typed endpoints
+#[derive(Debug, Clone)]
+pub struct RawEndpoint<P> {
+    pub method: RawMethod,
+    // e.g. /v5/spot-margin-trade/state
+    pub path: String,
+    pub params: P,
+    // public endpoints go out without auth headers
+    pub signed: bool,
+}
+
+impl<P: Serialize> RawEndpoint<P> {
+    pub fn get(path: impl Into<String>, params: P) -> Self {
+        Self { method: RawMethod::Get, path: path.into(), params, signed: true }
+    }
+
+    pub fn post(path: impl Into<String>, params: P) -> Self {
+        Self { method: RawMethod::Post, path: path.into(), params, signed: true }
+    }
+
+    pub fn unsigned(mut self) -> Self {
+        self.signed = false;
+        self
+    }
+}
+
+impl Client {
+    // T is whatever result deserializes to, serde_json::Value when the shape isn't worth modelling
+    pub fn raw<P: Serialize, T: DeserializeOwned>(&self, endpoint: &RawEndpoint<P>, recv_window: &Duration) -> Result<BybitRequest<T>> {
+        let uri = format!("{}{}", self.environment.base_url(), endpoint.path);
+        let (method, payload) = match endpoint.method {
+            RawMethod::Get => ("GET", Params::Get(&endpoint.params).to_string()?),
+            RawMethod::Post => ("POST", Params::Post(&endpoint.params).to_string()?),
+        };
+        let builder = if endpoint.signed {
+            signed_builder(method, self.credentials()?, recv_window, &self.clock.now(), &payload)?
+        } else {
+            http::request::Builder::new().method(method)
+        };
+        let request = match endpoint.method {
+            RawMethod::Get if payload.is_empty() => builder.uri(uri).body(String::new())?,
+            RawMethod::Get => builder.uri(format!("{uri}?{payload}")).body(String::new())?,
+            RawMethod::Post => builder.uri(uri).body(payload)?,
+        };
+        Ok(BybitRequest::new(request))
+    }
+}
@@ -0,0 +1,138 @@
+use std::{borrow::Cow, collections::BTreeMap, ops::RangeInclusive, time::Duration};
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+use crate::{
+    account::{TransactionLogEntry, TransactionLogQuery},
+    export::ExportRow,
+    trade::{Execution, ExecutionQuery},
+    Category, Client,
+};
+
+// both endpoints take at most 7 days per query
+const WINDOW: i64 = 7 * 24 * 60 * 60 * 1000;
+
+// What one symbol cost and made over the report's range. Amounts are in the settle coin, for spot the fees are in
+// whichever coin each fill charged them in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReconciliationRow {
+    pub symbol: String,
+    // settle coin from the transaction log, empty when the symbol only shows up in executions
+    pub currency: String,
+    // fills other than funding
+    pub executions: usize,
+    // the sum of execFee over those fills
+    pub execution_fees: Decimal,
+    // the sum of fee over the transaction log, should match execution_fees
+    pub logged_fees: Decimal,
+    // funding settled, both as positive amounts
+    pub funding_paid: Decimal,
+    pub funding_received: Decimal,
+    // cashFlow of the transaction log, closed position PnL and settlements before fees and funding
+    pub realized_pnl: Decimal,
+    // what the wallet moved by, realized_pnl - logged_fees + funding_received - funding_paid as Bybit logged it
+    pub net: Decimal,
+}
+
+impl ReconciliationRow {
+    fn new(symbol: &str) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            currency: String::new(),
+            executions: 0,
+            execution_fees: Decimal::ZERO,
+            logged_fees: Decimal::ZERO,
+            funding_paid: Decimal::ZERO,
+            funding_received: Decimal::ZERO,
+            realized_pnl: Decimal::ZERO,
+            net: Decimal::ZERO,
+        }
+    }
+
+    // fees charged on fills that never reached the transaction log, or the other way round
+    pub fn fee_difference(&self) -> Decimal {
+        self.execution_fees - self.logged_fees
+    }
+
+    pub fn funding(&self) -> Decimal {
+        self.funding_received - self.funding_paid
+    }
+}
+
+impl ExportRow for ReconciliationRow {
+    const COLUMNS: &'static [&'static str] = &[
+        "symbol", "currency", "executions", "executionFees", "loggedFees", "feeDifference", "fundingPaid", "fundingReceived",
+        "realizedPnl", "net",
+    ];
+
+    fn values(&self) -> Vec<Cow<'_, str>> {
+        vec![
+            Cow::Borrowed(self.symbol.as_str()),
+            Cow::Borrowed(self.currency.as_str()),
+            Cow::Owned(self.executions.to_string()),
+            Cow::Owned(self.execution_fees.normalize().to_string()),
+            Cow::Owned(self.logged_fees.normalize().to_string()),
+            Cow::Owned(self.fee_difference().normalize().to_string()),
+            Cow::Owned(self.funding_paid.normalize().to_string()),
+            Cow::Owned(self.funding_received.normalize().to_string()),
+            Cow::Owned(self.realized_pnl.normalize().to_string()),
+            Cow::Owned(self.net.normalize().to_string()),
+        ]
+    }
+}
+
+fn amount(value: &str) -> Decimal {
+    value.parse().unwrap_or_default()
+}
+
+// One row per symbol, sorted by symbol. Funding comes from the transaction log where a negative funding is paid,
+// funding fills among the executions are left out so nothing counts twice. Entries without a symbol (transfers,
+// interest, bonuses) aren't any symbol's and are skipped
+pub fn reconcile(entries: &[TransactionLogEntry], executions: &[Execution]) -> Vec<ReconciliationRow> {
+    let mut rows: BTreeMap<&str, ReconciliationRow> = BTreeMap::new();
+    for entry in entries.iter().filter(|entry| !entry.symbol.is_empty()) {
+        let row = rows.entry(&entry.symbol).or_insert_with(|| ReconciliationRow::new(&entry.symbol));
+        if row.currency.is_empty() {
+            row.currency = entry.currency.clone();
+        }
+        let funding = amount(&entry.funding);
+        if funding.is_sign_negative() {
+            row.funding_paid -= funding;
+        } else {
+            row.funding_received += funding;
+        }
+        row.logged_fees += amount(&entry.fee);
+        row.realized_pnl += amount(&entry.cash_flow);
+        row.net += amount(&entry.change);
+    }
+    for execution in executions.iter().filter(|execution| execution.exec_type != "Funding") {
+        let row = rows.entry(&execution.symbol).or_insert_with(|| ReconciliationRow::new(&execution.symbol));
+        row.executions += 1;
+        row.execution_fees += amount(&execution.exec_fee);
+    }
+    rows.into_values().collect()
+}
+
+impl Client {
+    // The transaction log and executions of category over range, fetched a 7 day window at a time, reconciled per
+    // symbol. Rows export as they are through ExportSink::write
+    pub async fn reconciliation_report<F, R, E>(&self, category: Category, range: RangeInclusive<DateTime<Utc>>, recv_window: &Duration, send: F) -> crate::Result<Vec<ReconciliationRow>>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let (mut from, end) = (range.start().timestamp_millis(), range.end().timestamp_millis());
+        let (mut entries, mut executions) = (Vec::new(), Vec::new());
+        while from <= end {
+            let to = (from + WINDOW - 1).min(end);
+            let log = TransactionLogQuery::new().with_category(category).with_time_range(from, to).with_limit(50);
+            let fills = ExecutionQuery::new(category).with_time_range(from, to).with_limit(100);
+            let (log, fills) = futures::try_join!(self.get_all_transaction_log(&log, recv_window, &send), self.get_all_executions(&fills, recv_window, &send))?;
+            entries.extend(log);
+            executions.extend(fills);
+            from = to + 1;
+        }
+        Ok(reconcile(&entries, &executions))
+    }
+}
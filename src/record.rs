@@ -0,0 +1,649 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, Lines, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// One line of a recording, recv_ts is local unix millis at receipt and ts Bybit's own timestamp
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Record {
+    Message {
+        recv_ts: i64,
+        topic: String,
+        kind: Option<String>,
+        ts: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cts: Option<u64>,
+        data: serde_json::Value,
+    },
+    // messages may be missing between the records either side, for every topic when topic is None
+    Gap { recv_ts: i64, topic: Option<String>, reason: String },
+}
+
+impl Record {
+    pub fn recv_ts(&self) -> i64 {
+        match self {
+            Self::Message { recv_ts, .. } | Self::Gap { recv_ts, .. } => *recv_ts,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    Hourly,
+    Daily,
+    // bytes per file
+    Size(u64),
+}
+
+// flushes after this many records, a crash loses at most these
+const FLUSH_EVERY: usize = 100;
+
+// Where a Recorder puts its records. The calls block on file i/o, the Recorder makes them from its own writer thread
+pub trait RecordSink: Send + 'static {
+    fn write(&mut self, record: &Record) -> crate::Result<()>;
+
+    fn flush(&mut self) -> crate::Result<()>;
+}
+
+// hour or day number of recv_ts, a file holds a single one
+fn period(rotation: Rotation, recv_ts: i64) -> i64 {
+    match rotation {
+        Rotation::Hourly => recv_ts.div_euclid(60 * 60 * 1000),
+        Rotation::Daily => recv_ts.div_euclid(24 * 60 * 60 * 1000),
+        Rotation::Size(_) => 0,
+    }
+}
+
+fn file_path(dir: &Path, prefix: &str, recv_ts: i64, extension: &str) -> crate::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let started = DateTime::<Utc>::from_timestamp_millis(recv_ts).unwrap_or_default();
+    Ok(dir.join(format!("{prefix}-{}.{extension}", started.format("%Y%m%dT%H%M%S%3f"))))
+}
+
+// Appends records as NDJSON to files named <prefix>-<UTC start time>.ndjson in dir, starting a new file whenever the
+// rotation says so. Time based rotation follows recv_ts, so files line up with UTC hours or days
+pub struct RotatingWriter {
+    dir: PathBuf,
+    prefix: String,
+    rotation: Rotation,
+    file: Option<BufWriter<File>>,
+    // hour or day number of the open file, or its size so far
+    period: i64,
+    written: u64,
+    unflushed: usize,
+}
+
+impl RotatingWriter {
+    pub fn new(dir: impl Into<PathBuf>, prefix: impl Into<String>, rotation: Rotation) -> Self {
+        Self { dir: dir.into(), prefix: prefix.into(), rotation, file: None, period: 0, written: 0, unflushed: 0 }
+    }
+
+    pub fn write(&mut self, record: &Record) -> crate::Result<()> {
+        let mut line = serde_json::to_vec(record).map_err(|err| crate::Error::Serialization(err.to_string()))?;
+        line.push(b'\n');
+        let period = period(self.rotation, record.recv_ts());
+        let full = matches!(self.rotation, Rotation::Size(max) if self.written > 0 && self.written + line.len() as u64 > max);
+        if self.file.is_none() || period != self.period || full {
+            self.open(record.recv_ts(), period)?;
+        }
+        if let Some(file) = &mut self.file {
+            file.write_all(&line)?;
+        }
+        self.written += line.len() as u64;
+        self.unflushed += 1;
+        if self.unflushed >= FLUSH_EVERY {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> crate::Result<()> {
+        if let Some(file) = &mut self.file {
+            file.flush()?;
+        }
+        self.unflushed = 0;
+        Ok(())
+    }
+
+    fn open(&mut self, recv_ts: i64, period: i64) -> crate::Result<()> {
+        self.flush()?;
+        let path = file_path(&self.dir, &self.prefix, recv_ts, "ndjson")?;
+        self.file = Some(BufWriter::new(OpenOptions::new().create(true).append(true).open(path)?));
+        self.period = period;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl RecordSink for RotatingWriter {
+    fn write(&mut self, record: &Record) -> crate::Result<()> {
+        RotatingWriter::write(self, record)
+    }
+
+    fn flush(&mut self) -> crate::Result<()> {
+        RotatingWriter::flush(self)
+    }
+}
+
+impl Drop for RotatingWriter {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+// Reads back what a RotatingWriter wrote under dir with the same prefix, oldest file first
+pub struct RecordReader {
+    files: std::vec::IntoIter<PathBuf>,
+    lines: Option<Lines<BufReader<File>>>,
+}
+
+impl RecordReader {
+    pub fn open(dir: impl AsRef<Path>, prefix: &str) -> crate::Result<Self> {
+        let start = format!("{prefix}-");
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+            // the start time in the name sorts chronologically
+            if name.strip_prefix(&start).is_some_and(|rest| rest.ends_with(".ndjson")) {
+                files.push(path);
+            }
+        }
+        files.sort();
+        Ok(Self::from_files(files))
+    }
+
+    pub fn from_files(files: Vec<PathBuf>) -> Self {
+        Self { files: files.into_iter(), lines: None }
+    }
+}
+
+impl Iterator for RecordReader {
+    type Item = crate::Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(lines) = &mut self.lines {
+                match lines.next() {
+                    Some(Ok(line)) if line.trim().is_empty() => continue,
+                    Some(Ok(line)) => return Some(serde_json::from_str(&line).map_err(|err| crate::Error::deserialize(err, line.into()))),
+                    Some(Err(err)) => return Some(Err(err.into())),
+                    None => self.lines = None,
+                }
+            }
+            let path = self.files.next()?;
+            match File::open(&path) {
+                Ok(file) => self.lines = Some(BufReader::new(file).lines()),
+                Err(err) => return Some(Err(std::io::Error::new(err.kind(), format!("failed to open {}: {err}", path.display())).into())),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "parquet")]
+pub use parquet_writer::*;
+#[cfg(feature = "ws")]
+pub use recorder::*;
+#[cfg(feature = "ws")]
+pub use replay::*;
+
+#[cfg(feature = "parquet")]
+mod parquet_writer {
+    use std::{fs::File, path::PathBuf, sync::Arc};
+
+    use arrow_array::{
+        builder::{Int64Builder, StringBuilder, UInt64Builder},
+        ArrayRef, RecordBatch,
+    };
+    use arrow_schema::{DataType, Field, Schema, SchemaRef};
+    use parquet::arrow::ArrowWriter;
+
+    use super::{file_path, period, Record, RecordSink, Rotation};
+
+    // rows buffered before they are handed to the parquet writer
+    const BATCH_ROWS: usize = 1024;
+
+    // The parquet twin of RotatingWriter: one row per record, data as its JSON text. A parquet file is only readable
+    // once closed, so flush closes the open file and the next record starts a new one. Size rotation counts the
+    // encoded row groups, buffered rows aren't known until written
+    pub struct ParquetWriter {
+        dir: PathBuf,
+        prefix: String,
+        rotation: Rotation,
+        schema: SchemaRef,
+        file: Option<ArrowWriter<File>>,
+        period: i64,
+        rows: Rows,
+    }
+
+    #[derive(Default)]
+    struct Rows {
+        record_type: StringBuilder,
+        recv_ts: Int64Builder,
+        topic: StringBuilder,
+        kind: StringBuilder,
+        ts: UInt64Builder,
+        cts: UInt64Builder,
+        data: StringBuilder,
+        reason: StringBuilder,
+        len: usize,
+    }
+
+    impl Rows {
+        fn push(&mut self, record: &Record) {
+            match record {
+                Record::Message { recv_ts, topic, kind, ts, cts, data } => {
+                    self.record_type.append_value("message");
+                    self.recv_ts.append_value(*recv_ts);
+                    self.topic.append_value(topic);
+                    self.kind.append_option(kind.as_deref());
+                    self.ts.append_option(*ts);
+                    self.cts.append_option(*cts);
+                    self.data.append_value(data.to_string());
+                    self.reason.append_null();
+                }
+                Record::Gap { recv_ts, topic, reason } => {
+                    self.record_type.append_value("gap");
+                    self.recv_ts.append_value(*recv_ts);
+                    self.topic.append_option(topic.as_deref());
+                    self.kind.append_null();
+                    self.ts.append_null();
+                    self.cts.append_null();
+                    self.data.append_null();
+                    self.reason.append_value(reason);
+                }
+            }
+            self.len += 1;
+        }
+
+        fn finish(&mut self, schema: &SchemaRef) -> crate::Result<RecordBatch> {
+            self.len = 0;
+            let columns: Vec<ArrayRef> = vec![
+                Arc::new(self.record_type.finish()),
+                Arc::new(self.recv_ts.finish()),
+                Arc::new(self.topic.finish()),
+                Arc::new(self.kind.finish()),
+                Arc::new(self.ts.finish()),
+                Arc::new(self.cts.finish()),
+                Arc::new(self.data.finish()),
+                Arc::new(self.reason.finish()),
+            ];
+            Ok(RecordBatch::try_new(schema.clone(), columns).map_err(parquet::errors::ParquetError::from)?)
+        }
+    }
+
+    impl ParquetWriter {
+        pub fn new(dir: impl Into<PathBuf>, prefix: impl Into<String>, rotation: Rotation) -> Self {
+            let schema = Schema::new(vec![
+                Field::new("type", DataType::Utf8, false),
+                Field::new("recv_ts", DataType::Int64, false),
+                Field::new("topic", DataType::Utf8, true),
+                Field::new("kind", DataType::Utf8, true),
+                Field::new("ts", DataType::UInt64, true),
+                Field::new("cts", DataType::UInt64, true),
+                Field::new("data", DataType::Utf8, true),
+                Field::new("reason", DataType::Utf8, true),
+            ]);
+            Self {
+                dir: dir.into(),
+                prefix: prefix.into(),
+                rotation,
+                schema: Arc::new(schema),
+                file: None,
+                period: 0,
+                rows: Rows::default(),
+            }
+        }
+
+        pub fn write(&mut self, record: &Record) -> crate::Result<()> {
+            let period = period(self.rotation, record.recv_ts());
+            let full = match (&self.file, self.rotation) {
+                (Some(file), Rotation::Size(max)) => (file.bytes_written() + file.in_progress_size()) as u64 >= max,
+                _ => false,
+            };
+            if self.file.is_some() && (period != self.period || full) {
+                self.flush()?;
+            }
+            if self.file.is_none() {
+                let path = file_path(&self.dir, &self.prefix, record.recv_ts(), "parquet")?;
+                self.file = Some(ArrowWriter::try_new(File::create(path)?, self.schema.clone(), None)?);
+                self.period = period;
+            }
+            self.rows.push(record);
+            if self.rows.len >= BATCH_ROWS {
+                self.write_rows()?;
+            }
+            Ok(())
+        }
+
+        // writes the buffered rows and closes the open file
+        pub fn flush(&mut self) -> crate::Result<()> {
+            self.write_rows()?;
+            if let Some(file) = self.file.take() {
+                file.close()?;
+            }
+            Ok(())
+        }
+
+        fn write_rows(&mut self) -> crate::Result<()> {
+            let Some(file) = &mut self.file else {
+                return Ok(());
+            };
+            if self.rows.len > 0 {
+                file.write(&self.rows.finish(&self.schema)?)?;
+            }
+            Ok(())
+        }
+    }
+
+    impl RecordSink for ParquetWriter {
+        fn write(&mut self, record: &Record) -> crate::Result<()> {
+            ParquetWriter::write(self, record)
+        }
+
+        fn flush(&mut self) -> crate::Result<()> {
+            ParquetWriter::flush(self)
+        }
+    }
+
+    impl Drop for ParquetWriter {
+        fn drop(&mut self) {
+            let _ = self.flush();
+        }
+    }
+}
+
+#[cfg(feature = "ws")]
+mod recorder {
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            mpsc, Arc,
+        },
+    };
+
+    use futures::channel::oneshot;
+
+    use super::{Record, RecordSink};
+    use crate::{
+        ws::{DataMessage, Frame, Socket, Subscriptions, Topic, WsConnection},
+        Client,
+    };
+
+    // spot's cap on topics per subscribe request
+    const TOPICS_PER_REQUEST: usize = 10;
+
+    enum Job {
+        Write(Record),
+        Flush(oneshot::Sender<crate::Result<()>>),
+    }
+
+    // Owns the sink on a thread of its own so the recorder's async code never waits on the disk. The first failed
+    // write stops further writes and comes back from the next flush
+    struct WriterThread {
+        jobs: mpsc::Sender<Job>,
+        failed: Arc<AtomicBool>,
+    }
+
+    impl WriterThread {
+        fn spawn(mut sink: impl RecordSink) -> Self {
+            let (jobs, queue) = mpsc::channel();
+            let failed = Arc::new(AtomicBool::new(false));
+            let flag = failed.clone();
+            std::thread::spawn(move || {
+                let mut failure = None;
+                // ends once the recorder is dropped, the sink flushes what's left as it drops
+                for job in queue {
+                    match job {
+                        Job::Write(record) if failure.is_none() => {
+                            if let Err(err) = sink.write(&record) {
+                                failure = Some(err);
+                                flag.store(true, Ordering::Release);
+                            }
+                        }
+                        Job::Write(_) => {}
+                        Job::Flush(reply) => {
+                            let _ = reply.send(failure.take().map_or_else(|| sink.flush(), Err));
+                            flag.store(false, Ordering::Release);
+                        }
+                    }
+                }
+            });
+            Self { jobs, failed }
+        }
+
+        // a write is only queued, a failure shows up on a later write or flush
+        async fn write(&self, record: Record) -> crate::Result<()> {
+            if self.failed.load(Ordering::Acquire) {
+                return self.flush().await;
+            }
+            self.jobs.send(Job::Write(record)).map_err(|_| writer_gone())
+        }
+
+        async fn flush(&self) -> crate::Result<()> {
+            let (reply, done) = oneshot::channel();
+            self.jobs.send(Job::Flush(reply)).map_err(|_| writer_gone())?;
+            done.await.map_err(|_| writer_gone())?
+        }
+    }
+
+    fn writer_gone() -> crate::Error {
+        crate::Error::Io(std::io::Error::other("recorder writer thread is gone"))
+    }
+
+    // Tick recorder: subscribes a public connection to topics and writes every data message as received. Orderbook
+    // update ids are followed per topic and a skipped one is marked as a gap, as is the end of every session. The
+    // sink runs on a thread the recorder starts, so run is safe on any executor
+    pub struct Recorder {
+        writer: WriterThread,
+        topics: Vec<Topic>,
+        update_ids: HashMap<String, u64>,
+    }
+
+    impl Recorder {
+        // sink is a RotatingWriter for NDJSON, or a ParquetWriter with the parquet feature
+        pub fn new(sink: impl RecordSink, topics: Vec<Topic>) -> Self {
+            Self { writer: WriterThread::spawn(sink), topics, update_ids: HashMap::new() }
+        }
+
+        // Records until the connection drops or the client shuts down. Reconnecting is up to the caller, run again
+        // with a new connection and the recording carries on in the same files
+        pub async fn run<S: WsConnection>(&mut self, client: &Client, conn: S) -> crate::Result<()>
+        {
+            self.update_ids.clear();
+            let mut socket = Socket::new(conn);
+            socket.set_shutdown(&client.shutdown);
+            let result = self.record(client, &mut socket).await;
+            let reason = match &result {
+                Ok(()) => "session ended".to_string(),
+                Err(err) => format!("session failed: {err}"),
+            };
+            self.writer.write(Record::Gap { recv_ts: client.now().timestamp_millis(), topic: None, reason }).await?;
+            self.writer.flush().await?;
+            result
+        }
+
+        async fn record<S: WsConnection>(&mut self, client: &Client, socket: &mut Socket<S>) -> crate::Result<()>
+        {
+            for topics in self.topics.chunks(TOPICS_PER_REQUEST) {
+                socket.send("subscribe", Subscriptions::args(topics)).await?;
+            }
+            while let Some(frame) = socket.next().await {
+                match frame? {
+                    Frame::Data(message) => {
+                        let recv_ts = client.now().timestamp_millis();
+                        if let Some(reason) = self.skipped_update(&message) {
+                            self.writer.write(Record::Gap { recv_ts, topic: Some(message.topic.clone()), reason }).await?;
+                        }
+                        self.writer
+                            .write(Record::Message {
+                                recv_ts,
+                                topic: message.topic,
+                                kind: message.kind,
+                                ts: message.ts,
+                                cts: message.cts,
+                                data: message.data,
+                            })
+                            .await?;
+                    }
+                    Frame::Control(control) if control.op == "subscribe" && control.success == Some(false) => {
+                        return Err(crate::Error::WebSocket(format!("subscription rejected: {}", control.ret_msg.unwrap_or_default())));
+                    }
+                    Frame::Control(_) => {}
+                }
+            }
+            Ok(())
+        }
+
+        // orderbook deltas count u up by one, a snapshot starts over
+        fn skipped_update(&mut self, message: &DataMessage) -> Option<String> {
+            if !message.topic.starts_with("orderbook.") {
+                return None;
+            }
+            let update_id = message.data.get("u")?.as_u64()?;
+            let previous = self.update_ids.insert(message.topic.clone(), update_id);
+            match (message.kind.as_deref(), previous) {
+                (Some("delta"), Some(previous)) if update_id != previous + 1 => {
+                    Some(format!("orderbook update id jumped from {previous} to {update_id}"))
+                }
+                (Some("delta"), None) => Some("orderbook delta before any snapshot".to_string()),
+                _ => None,
+            }
+        }
+    }
+}
+
+#[cfg(feature = "ws")]
+mod replay {
+    use std::time::{Duration, Instant};
+
+    use futures::Stream;
+
+    use super::{Record, RecordReader};
+    use crate::ws::{decode_public, DataMessage, DecodePolicies, PublicEvent};
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Pacing {
+        AsFastAsPossible,
+        // waits out the recorded gaps between messages, divided by the speed. Build it with recorded, which checks speed
+        Recorded { speed: f64 },
+    }
+
+    impl Pacing {
+        pub fn realtime() -> Self {
+            Self::Recorded { speed: 1.0 }
+        }
+
+        // speed is a multiple of real time, 2.0 replays twice as fast
+        pub fn recorded(speed: f64) -> crate::Result<Self> {
+            if !speed.is_finite() || speed <= 0.0 {
+                return Err(crate::Error::Invalid(format!("replay speed has to be positive and finite, got {speed}").into()));
+            }
+            Ok(Self::Recorded { speed })
+        }
+    }
+
+    // Replays a recording as the PublicEvents a PublicWsClient would have produced, so strategy code written against
+    // the live stream runs unchanged. Gap markers aren't events, they are counted and the time of the last one kept
+    pub struct Replay {
+        reader: RecordReader,
+        pacing: Pacing,
+        policies: DecodePolicies,
+        decode_failures: u64,
+        gaps: u64,
+        last_gap: Option<i64>,
+        last_recv_ts: Option<i64>,
+    }
+
+    impl Replay {
+        pub fn new(reader: RecordReader) -> Self {
+            Self {
+                reader,
+                pacing: Pacing::AsFastAsPossible,
+                policies: DecodePolicies::default(),
+                decode_failures: 0,
+                gaps: 0,
+                last_gap: None,
+                last_recv_ts: None,
+            }
+        }
+
+        pub fn with_pacing(mut self, pacing: Pacing) -> Self {
+            self.pacing = pacing;
+            self
+        }
+
+        pub fn with_decode_policies(mut self, policies: DecodePolicies) -> Self {
+            self.policies = policies;
+            self
+        }
+
+        pub fn decode_failures(&self) -> u64 {
+            self.decode_failures
+        }
+
+        pub fn gaps(&self) -> u64 {
+            self.gaps
+        }
+
+        // recv_ts of the last gap marker passed, data before it may not line up with data after it
+        pub fn last_gap(&self) -> Option<i64> {
+            self.last_gap
+        }
+
+        // recv_ts of the last record read, the replay's current time
+        pub fn now(&self) -> Option<i64> {
+            self.last_recv_ts
+        }
+
+        pub async fn next(&mut self) -> Option<crate::Result<PublicEvent>> {
+            loop {
+                let record = match self.reader.next()? {
+                    Ok(record) => record,
+                    Err(err) => return Some(Err(err)),
+                };
+                self.pace(record.recv_ts()).await;
+                let Record::Message { topic, kind, ts, cts, data, .. } = record else {
+                    self.gaps += 1;
+                    self.last_gap = self.last_recv_ts;
+                    continue;
+                };
+                let message = DataMessage { topic, kind, ts, id: None, creation_time: None, cts, data };
+                if let Some(event) = decode_public(&self.policies, &mut self.decode_failures, message) {
+                    return Some(event);
+                }
+            }
+        }
+
+        pub fn into_stream(self) -> impl Stream<Item = crate::Result<PublicEvent>> {
+            futures::stream::unfold(self, |mut replay| async move {
+                let event = replay.next().await?;
+                Some((event, replay))
+            })
+        }
+
+        async fn pace(&mut self, recv_ts: i64) {
+            let previous = self.last_recv_ts.replace(recv_ts);
+            let (Pacing::Recorded { speed }, Some(previous)) = (self.pacing, previous) else {
+                return;
+            };
+            let elapsed = recv_ts.saturating_sub(previous);
+            if elapsed <= 0 {
+                return;
+            }
+            // a speed that slipped past Pacing::recorded, or a gap too long to wait out, replays the gap unpaced
+            let wait = Duration::try_from_secs_f64(elapsed as f64 / 1000.0 / speed)
+                .ok()
+                .filter(|wait| Instant::now().checked_add(*wait).is_some());
+            if let Some(wait) = wait {
+                futures_timer::Delay::new(wait).await;
+            }
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::{future::Future, time::Duration};
+
+use bytes::Bytes;
+use futures::future::BoxFuture;
+
+use crate::{error::KnownErrorCode, BoxError, Client};
+
+// Retries for transient failures: transport errors, HTTP 5xx and the retCodes KnownErrorCode::is_retryable lists.
+// Backoff doubles from base_delay up to max_delay, jitter takes a random share of up to that fraction off each delay
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32) -> Self {
+        Self { max_attempts, base_delay: Duration::from_millis(200), max_delay: Duration::from_secs(5), jitter: 0.5 }
+    }
+
+    pub fn none() -> Self {
+        Self::new(1)
+    }
+
+    pub fn with_backoff(mut self, base_delay: Duration, max_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self.max_delay = max_delay;
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    // delay before the given retry, the first retry is attempt 1
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let delay = self.base_delay.saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1))).min(self.max_delay);
+        // uuid already pulls in a random source, no need for another dependency
+        let random = (uuid::Uuid::new_v4().as_u64_pair().0 >> 11) as f64 / (1u64 << 53) as f64;
+        delay.mul_f64(1.0 - self.jitter * random)
+    }
+}
+
+// Only requests that can't take effect twice are resent: reads, and writes carrying the key Bybit dedupes them by.
+// A write that reached the exchange before the connection dropped would otherwise place a second order or move
+// funds again, with the key the resend is rejected as a duplicate instead
+pub fn is_idempotent(request: &http::Request<String>) -> bool {
+    if request.method() == http::Method::GET {
+        return true;
+    }
+    if request.method() != http::Method::POST {
+        return false;
+    }
+    let has_key = |body: &serde_json::Value, key: &str| body.get(key).and_then(|id| id.as_str()).is_some_and(|id| !id.is_empty());
+    let Ok(body) = serde_json::from_str::<serde_json::Value>(request.body()) else {
+        return false;
+    };
+    match request.uri().path() {
+        "/v5/order/create" => has_key(&body, "orderLinkId"),
+        "/v5/order/create-batch" => body.get("request")
+            .and_then(|orders| orders.as_array())
+            .is_some_and(|orders| !orders.is_empty() && orders.iter().all(|order| has_key(order, "orderLinkId"))),
+        "/v5/asset/withdraw/create" => has_key(&body, "requestId"),
+        "/v5/asset/transfer/inter-transfer" | "/v5/asset/transfer/universal-transfer" => has_key(&body, "transferId"),
+        _ => false,
+    }
+}
+
+fn retryable_code(body: &Bytes) -> bool {
+    #[derive(serde::Deserialize)]
+    struct Envelope {
+        #[serde(rename = "retCode")]
+        ret_code: i32,
+    }
+
+    serde_json::from_slice::<Envelope>(body).is_ok_and(|envelope| KnownErrorCode::from(envelope.ret_code).is_retryable())
+}
+
+fn duplicate(request: &http::Request<String>) -> http::Request<String> {
+    let mut duplicate = http::Request::new(request.body().clone());
+    *duplicate.method_mut() = request.method().clone();
+    *duplicate.uri_mut() = request.uri().clone();
+    *duplicate.version_mut() = request.version();
+    *duplicate.headers_mut() = request.headers().clone();
+    *duplicate.extensions_mut() = request.extensions().clone();
+    duplicate
+}
+
+impl Client {
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    pub fn with_rate_limiter(mut self, rate_limiter: crate::ratelimit::RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    pub fn rate_limiter(&self) -> Option<&crate::ratelimit::RateLimiter> {
+        self.rate_limiter.as_ref()
+    }
+
+    // Turns a transport returning the whole http response into the body only transport the helpers take, applying the
+    // retry policy and rate limiter configured on this client. Resent requests are signed again with a fresh timestamp
+    pub fn transport<'a, F, R, E>(&'a self, send: &'a F) -> impl Fn(http::Request<String>) -> BoxFuture<'a, Result<Bytes, BoxError>> + 'a
+    where F: Fn(http::Request<String>) -> R + Sync,
+        R: Future<Output = Result<http::Response<Bytes>, E>> + Send + 'a,
+        E: Into<BoxError> + 'a
+    {
+        move |mut request| Box::pin(async move {
+            let path = request.uri().path().to_string();
+            let max_attempts = if is_idempotent(&request) { self.retry.max_attempts.max(1) } else { 1 };
+            let mut attempt = 1;
+            loop {
+                if let Some(limiter) = &self.rate_limiter {
+                    limiter.acquire(&path).await;
+                }
+                let last = attempt >= max_attempts;
+                match send(duplicate(&request)).await {
+                    Err(err) if last => return Err(err.into()),
+                    Err(_) => {}
+                    Ok(response) => {
+                        if let Some(limiter) = &self.rate_limiter {
+                            limiter.observe(&path, response.headers());
+                        }
+                        let status = response.status();
+                        let body = response.into_body();
+                        if status.is_server_error() {
+                            if last {
+                                return Err(format!("HTTP {status} from {path}").into());
+                            }
+                        } else if last || !retryable_code(&body) {
+                            return Ok(body);
+                        }
+                    }
+                }
+                futures_timer::Delay::new(self.retry.delay(attempt)).await;
+                attempt += 1;
+                self.resign(&mut request);
+            }
+        })
+    }
+
+    fn resign(&self, request: &mut http::Request<String>) {
+        let Some(recv_window) = request.headers().get("X-BAPI-RECV-WINDOW")
+            .and_then(|value| value.to_str().ok()?.parse().ok())
+            .map(Duration::from_millis) else {
+            return;
+        };
+        let timestamp = self.clock.now();
+        let payload = match *request.method() {
+            http::Method::GET => request.uri().query().unwrap_or_default().to_string(),
+            _ => request.body().clone(),
+        };
+        let Ok(signature) = self.credentials().and_then(|credentials| credentials.sign(&timestamp, &recv_window, &payload)) else {
+            return;
+        };
+        let headers = request.headers_mut();
+        if let (Ok(signature), Ok(timestamp)) = (signature.parse(), timestamp.timestamp_millis().to_string().parse()) {
+            headers.insert("X-BAPI-SIGN", signature);
+            headers.insert("X-BAPI-TIMESTAMP", timestamp);
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+use crate::{
+    number::Precision,
+    trade::{AmendOrderRequest, OrderType, PlaceOrderRequest},
+    Client, Side,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RiskViolation {
+    #[error("{symbol}: order notional {notional} is above the {max} allowed")]
+    NotionalTooLarge { symbol: String, notional: Decimal, max: Decimal },
+    #[error("{symbol}: the order would take the position to {position}, beyond the {max} allowed")]
+    PositionTooLarge { symbol: String, position: Decimal, max: Decimal },
+    #[error("{symbol}: price {price} is more than {band} away from the mark price {mark}")]
+    OutsidePriceBand { symbol: String, price: Decimal, mark: Decimal, band: Decimal },
+    #[error("{symbol}: more than {max} orders within {per:?}")]
+    RateCapped { symbol: String, max: u32, per: Duration },
+    // the limits need a mark price that hasn't been given yet
+    #[error("{symbol}: no mark price to check the order against")]
+    NoMarkPrice { symbol: String },
+}
+
+// Limits for one symbol, every one of them off unless set
+#[derive(Debug, Clone, Default)]
+pub struct SymbolLimits {
+    max_notional: Option<Decimal>,
+    max_position: Option<Decimal>,
+    price_band: Option<Decimal>,
+    rate_cap: Option<(u32, Duration)>,
+    clamp: Option<Precision>,
+}
+
+impl SymbolLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // qty times price in quote, market orders are valued at the mark price
+    pub fn with_max_notional(mut self, max_notional: Decimal) -> Self {
+        self.max_notional = Some(max_notional);
+        self
+    }
+
+    // absolute net position in qty the order may leave behind, reduce only orders are never held back
+    pub fn with_max_position(mut self, max_position: Decimal) -> Self {
+        self.max_position = Some(max_position);
+        self
+    }
+
+    // largest distance of a limit price from the mark price, as a fraction: 0.05 for 5%
+    pub fn with_price_band(mut self, band: Decimal) -> Self {
+        self.price_band = Some(band);
+        self
+    }
+
+    // at most max orders placed or amended per sliding window
+    pub fn with_rate_cap(mut self, max: u32, per: Duration) -> Self {
+        self.rate_cap = Some((max, per));
+        self
+    }
+
+    // Shrinks orders breaking the notional or position limit down to what's allowed, rounded down to precision's
+    // qty step, and moves prices outside the band onto its edge instead of rejecting them. Rate caps always reject
+    pub fn clamping(mut self, precision: Precision) -> Self {
+        self.clamp = Some(precision);
+        self
+    }
+}
+
+#[derive(Debug, Default)]
+struct RiskState {
+    default: SymbolLimits,
+    symbols: HashMap<String, SymbolLimits>,
+    marks: HashMap<String, Decimal>,
+    // signed net size per symbol and positionIdx, sells negative
+    positions: HashMap<(String, i32), Decimal>,
+    sent: HashMap<String, VecDeque<DateTime<Utc>>>,
+}
+
+// Pre-send guardrails for a Client with_risk_policy: place_order, amend_order and the batch endpoints check every
+// order against the limits of its symbol before anything is signed, and fail with Error::Risk or clamp the order.
+// Mark prices and positions are whatever was last given here, clones share them
+#[derive(Debug, Clone, Default)]
+pub struct RiskPolicy(Arc<Mutex<RiskState>>);
+
+impl RiskPolicy {
+    // default applies to every symbol without limits of its own
+    pub fn new(default: SymbolLimits) -> Self {
+        Self(Arc::new(Mutex::new(RiskState { default, ..RiskState::default() })))
+    }
+
+    pub fn with_symbol(self, symbol: impl Into<String>, limits: SymbolLimits) -> Self {
+        self.state().symbols.insert(symbol.into(), limits);
+        self
+    }
+
+    pub fn set_mark_price(&self, symbol: impl Into<String>, mark: Decimal) {
+        self.state().marks.insert(symbol.into(), mark);
+    }
+
+    // size is signed, negative for a short, position_idx as Bybit numbers hedge mode sides
+    pub fn set_position(&self, symbol: impl Into<String>, position_idx: i32, size: Decimal) {
+        self.state().positions.insert((symbol.into(), position_idx), size);
+    }
+
+    // keeps mark prices current from linear and inverse ticker streams
+    #[cfg(feature = "ws")]
+    pub fn on_public(&self, event: &crate::ws::PublicEvent) {
+        if let crate::ws::PublicEvent::Ticker { data, .. } = event
+            && let Some(mark) = data.mark_price.as_deref().and_then(|mark| mark.parse().ok())
+        {
+            self.set_mark_price(data.symbol.clone(), mark);
+        }
+    }
+
+    // keeps positions current from the private position stream
+    #[cfg(feature = "ws")]
+    pub fn on_private(&self, event: &crate::ws::PrivateEvent) {
+        if let crate::ws::PrivateEvent::Position { data, .. } = event {
+            for position in data {
+                let size: Decimal = position.size.parse().unwrap_or_default();
+                let size = if position.side == "Sell" { -size } else { size };
+                self.set_position(position.symbol.clone(), position.position_idx, size);
+            }
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, RiskState> {
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    // the order as it may go out, clamped when its limits say so
+    pub(crate) fn check_order(&self, request: &PlaceOrderRequest, now: DateTime<Utc>) -> Result<PlaceOrderRequest, RiskViolation> {
+        let mut state = self.state();
+        let limits = state.symbols.get(&request.symbol).unwrap_or(&state.default).clone();
+        let symbol = request.symbol.clone();
+        let mark = state.marks.get(&symbol).copied();
+        let mut order = request.clone();
+
+        if let (Some(band), OrderType::Limit, Some(price)) = (limits.price_band, order.order_type, order.price) {
+            let mark = mark.ok_or_else(|| RiskViolation::NoMarkPrice { symbol: symbol.clone() })?;
+            let (low, high) = (mark * (Decimal::ONE - band), mark * (Decimal::ONE + band));
+            if price < low || price > high {
+                let Some(precision) = limits.clamp else {
+                    return Err(RiskViolation::OutsidePriceBand { symbol, price, mark, band });
+                };
+                order.price = Some(if price < low { to_tick(precision, low, true) } else { to_tick(precision, high, false) });
+            }
+        }
+
+        let reduce_only = order.reduce_only == Some(true);
+        if let (Some(max), false) = (limits.max_notional, reduce_only) {
+            let price = match order.order_type {
+                OrderType::Limit => order.price,
+                OrderType::Market => mark,
+            };
+            let price = price.ok_or_else(|| RiskViolation::NoMarkPrice { symbol: symbol.clone() })?;
+            let notional = order.qty * price;
+            if notional > max {
+                let qty = limits.clamp.map(|precision| precision.qty(max / price)).filter(|qty| !qty.is_zero());
+                order.qty = qty.ok_or(RiskViolation::NotionalTooLarge { symbol: symbol.clone(), notional, max })?;
+            }
+        }
+
+        if let (Some(max), false) = (limits.max_position, reduce_only) {
+            let net: Decimal = state.positions.iter().filter(|((position, _), _)| *position == symbol).map(|(_, size)| *size).sum();
+            let signed = match order.side {
+                Side::Buy => order.qty,
+                Side::Sell => -order.qty,
+            };
+            let position = net + signed;
+            if position.abs() > max {
+                let room = match order.side {
+                    Side::Buy => max - net,
+                    Side::Sell => max + net,
+                };
+                let qty = limits.clamp.map(|precision| precision.qty(room)).filter(|qty| *qty > Decimal::ZERO);
+                order.qty = qty.ok_or(RiskViolation::PositionTooLarge { symbol: symbol.clone(), position, max })?;
+            }
+        }
+
+        state.count(&symbol, limits.rate_cap, now)?;
+        Ok(order)
+    }
+
+    // amends only carry what changes, the price band and rate cap are checked and a new qty against max_notional
+    // when the price comes with it
+    pub(crate) fn check_amend(&self, request: &AmendOrderRequest, now: DateTime<Utc>) -> Result<(), RiskViolation> {
+        let mut state = self.state();
+        let limits = state.symbols.get(&request.symbol).unwrap_or(&state.default).clone();
+        let symbol = request.symbol.clone();
+        if let (Some(band), Some(price)) = (limits.price_band, request.price) {
+            let mark = state.marks.get(&symbol).copied().ok_or_else(|| RiskViolation::NoMarkPrice { symbol: symbol.clone() })?;
+            if (price - mark).abs() > mark * band {
+                return Err(RiskViolation::OutsidePriceBand { symbol, price, mark, band });
+            }
+        }
+        if let (Some(max), Some(qty), Some(price)) = (limits.max_notional, request.qty, request.price)
+            && qty * price > max
+        {
+            return Err(RiskViolation::NotionalTooLarge { symbol, notional: qty * price, max });
+        }
+        state.count(&symbol, limits.rate_cap, now)
+    }
+}
+
+// a band edge rounded inwards to the tick, so the clamped price stays inside the band
+fn to_tick(precision: Precision, price: Decimal, up: bool) -> Decimal {
+    match precision.tick_size {
+        Some(tick) if up => ((price / tick).ceil() * tick).normalize(),
+        Some(tick) => ((price / tick).floor() * tick).normalize(),
+        None => price.normalize(),
+    }
+}
+
+impl RiskState {
+    fn count(&mut self, symbol: &str, rate_cap: Option<(u32, Duration)>, now: DateTime<Utc>) -> Result<(), RiskViolation> {
+        let Some((max, per)) = rate_cap else {
+            return Ok(());
+        };
+        let sent = self.sent.entry(symbol.to_string()).or_default();
+        let window = chrono::Duration::from_std(per).unwrap_or(chrono::Duration::MAX);
+        while sent.front().is_some_and(|time| now.signed_duration_since(*time) >= window) {
+            sent.pop_front();
+        }
+        if sent.len() >= max as usize {
+            return Err(RiskViolation::RateCapped { symbol: symbol.to_string(), max, per });
+        }
+        sent.push_back(now);
+        Ok(())
+    }
+}
+
+impl Client {
+    pub fn with_risk_policy(mut self, policy: RiskPolicy) -> Self {
+        self.risk = Some(policy);
+        self
+    }
+
+    pub fn risk_policy(&self) -> Option<&RiskPolicy> {
+        self.risk.as_ref()
+    }
+
+    // request as it may be sent, a clamped copy when the policy changed it
+    pub(crate) fn check_risk<'a>(&self, request: &'a PlaceOrderRequest) -> crate::Result<std::borrow::Cow<'a, PlaceOrderRequest>> {
+        match &self.risk {
+            Some(policy) => Ok(std::borrow::Cow::Owned(policy.check_order(request, self.clock.now())?)),
+            None => Ok(std::borrow::Cow::Borrowed(request)),
+        }
+    }
+
+    pub(crate) fn check_amend_risk(&self, request: &AmendOrderRequest) -> crate::Result<()> {
+        match &self.risk {
+            Some(policy) => Ok(policy.check_amend(request, self.clock.now())?),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use futures::future::BoxFuture;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    // market data, history and account reads, waits whenever the budget runs low
+    Data,
+    // places, amends and cancels orders or changes their protection, may spend the reserve
+    Order,
+}
+
+impl Priority {
+    // by path, every order mutating endpoint and trading-stop are Order
+    pub fn of(request: &http::Request<String>) -> Self {
+        let order = request.method() == http::Method::POST && matches!(request.uri().path(),
+            "/v5/order/create" | "/v5/order/amend" | "/v5/order/cancel" | "/v5/order/cancel-all"
+            | "/v5/order/create-batch" | "/v5/order/amend-batch" | "/v5/order/cancel-batch" | "/v5/position/trading-stop");
+        if order { Self::Order } else { Self::Data }
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    sent: VecDeque<Instant>,
+    // Order requests waiting for budget, Data ones hold back while there are any
+    orders_waiting: usize,
+}
+
+// A request budget of max per sliding window shared by everything sent through wrap, where the last reserve requests
+// of each window are kept for Order calls. A backfill running through the same budget holds back once it gets low,
+// so a cancel of a live order never queues behind it. Clones share the budget
+#[derive(Debug, Clone)]
+pub struct RequestScheduler {
+    max: usize,
+    per: Duration,
+    reserve: usize,
+    overrides: HashMap<String, Priority>,
+    state: Arc<Mutex<State>>,
+}
+
+struct Queued<'a>(&'a RequestScheduler);
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        self.0.state().orders_waiting -= 1;
+    }
+}
+
+impl RequestScheduler {
+    pub fn new(max: usize, per: Duration) -> Self {
+        Self {
+            max: max.max(1),
+            per,
+            reserve: 1,
+            overrides: HashMap::new(),
+            state: Arc::new(Mutex::new(State { sent: VecDeque::new(), orders_waiting: 0 })),
+        }
+    }
+
+    // requests per window only Order calls may take, below max
+    pub fn with_reserve(mut self, reserve: usize) -> Self {
+        self.reserve = reserve.min(self.max - 1);
+        self
+    }
+
+    // priority of every request to path, instead of Priority::of
+    pub fn with_priority(mut self, path: impl Into<String>, priority: Priority) -> Self {
+        self.overrides.insert(path.into(), priority);
+        self
+    }
+
+    pub fn priority(&self, request: &http::Request<String>) -> Priority {
+        self.overrides.get(request.uri().path()).copied().unwrap_or_else(|| Priority::of(request))
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    // how long a request of priority has to wait, the budget is taken right away when it doesn't
+    fn try_acquire(&self, priority: Priority) -> Option<Duration> {
+        let mut state = self.state();
+        let now = Instant::now();
+        while state.sent.front().is_some_and(|time| now.duration_since(*time) >= self.per) {
+            state.sent.pop_front();
+        }
+        let free = self.max - state.sent.len().min(self.max);
+        let allowed = match priority {
+            Priority::Order => free > 0,
+            Priority::Data => free > self.reserve && state.orders_waiting == 0,
+        };
+        if allowed {
+            state.sent.push_back(now);
+            return None;
+        }
+        // an Order call blocked by a full window, or a Data call held back by the reserve, waits for the oldest request
+        // to leave it. Held back by a waiting Order call only, the window has room and a short poll will do
+        let wait = state.sent.front().map_or(Duration::ZERO, |oldest| self.per.saturating_sub(now.duration_since(*oldest)));
+        Some(wait.max(Duration::from_millis(1)))
+    }
+
+    pub async fn acquire(&self, priority: Priority) {
+        // counted as waiting until it's through or dropped, so no Data call slips in ahead of it
+        let mut queued = None;
+        while let Some(wait) = self.try_acquire(priority) {
+            if priority == Priority::Order && queued.is_none() {
+                self.state().orders_waiting += 1;
+                queued = Some(Queued(self));
+            }
+            futures_timer::Delay::new(wait).await;
+        }
+    }
+
+    // Puts a body only transport (the one the client helpers take, or Client::transport) behind the scheduler
+    pub fn wrap<'a, F, R, E>(&'a self, send: &'a F) -> impl Fn(http::Request<String>) -> BoxFuture<'a, Result<Bytes, E>> + 'a
+    where F: Fn(http::Request<String>) -> R + Sync,
+        R: Future<Output = Result<Bytes, E>> + Send + 'a,
+        E: 'a
+    {
+        move |request| Box::pin(async move {
+            self.acquire(self.priority(&request)).await;
+            send(request).await
+        })
+    }
+}
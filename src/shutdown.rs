@@ -0,0 +1,65 @@
+use std::sync::{Arc, Mutex};
+
+use futures::{
+    channel::{mpsc, oneshot},
+    future::{FutureExt, Shared},
+    StreamExt,
+};
+
+// Coordinated shutdown for background components, components hold a guard while running
+// and watch the signal, shutdown() fires the signal and waits until every guard is dropped
+#[derive(Debug, Clone)]
+pub struct Shutdown {
+    trigger: Arc<Mutex<Option<oneshot::Sender<()>>>>,
+    signal: Shared<oneshot::Receiver<()>>,
+    done_tx: Arc<Mutex<Option<mpsc::Sender<()>>>>,
+    done_rx: Arc<Mutex<Option<mpsc::Receiver<()>>>>,
+}
+
+#[derive(Debug)]
+pub struct ShutdownGuard {
+    _done: Option<mpsc::Sender<()>>,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        let (trigger, signal) = oneshot::channel();
+        let (done_tx, done_rx) = mpsc::channel(0);
+        Self {
+            trigger: Arc::new(Mutex::new(Some(trigger))),
+            signal: signal.shared(),
+            done_tx: Arc::new(Mutex::new(Some(done_tx))),
+            done_rx: Arc::new(Mutex::new(Some(done_rx))),
+        }
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        self.signal.peek().is_some()
+    }
+
+    pub fn guard(&self) -> ShutdownGuard {
+        ShutdownGuard { _done: self.done_tx.lock().unwrap().clone() }
+    }
+
+    // resolves once shutdown has been requested
+    pub fn signal(&self) -> impl std::future::Future<Output = ()> + Unpin + use<> {
+        self.signal.clone().map(|_| ())
+    }
+
+    pub async fn shutdown(&self) {
+        if let Some(trigger) = self.trigger.lock().unwrap().take() {
+            let _ = trigger.send(());
+        }
+        self.done_tx.lock().unwrap().take();
+        let done_rx = self.done_rx.lock().unwrap().take();
+        if let Some(mut done_rx) = done_rx {
+            while done_rx.next().await.is_some() {}
+        }
+    }
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,74 @@
+use rust_decimal::Decimal;
+
+use crate::{number::Precision, Category};
+
+// What one unit of order qty means for an instrument. Inverse contracts are quoted in USD (qty is the
+// notional, 1 contract = multiplier USD) while linear, spot and option qty is in the base coin,
+// converting through here lets position sizing treat every category the same way
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Contract {
+    pub category: Category,
+    pub multiplier: Decimal,
+    pub qty_step: Option<Decimal>,
+}
+
+impl Contract {
+    pub fn new(category: Category) -> Self {
+        Self { category, multiplier: Decimal::ONE, qty_step: None }
+    }
+
+    pub fn with_multiplier(mut self, multiplier: Decimal) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    pub fn with_qty_step(mut self, step: Decimal) -> Self {
+        self.qty_step = (!step.is_zero()).then_some(step);
+        self
+    }
+
+    pub fn is_inverse(&self) -> bool {
+        self.category == Category::Inverse
+    }
+
+    // base coin exposure of an order qty at price, None for inverse at a zero price
+    pub fn coin_qty(&self, qty: Decimal, price: Decimal) -> Option<Decimal> {
+        if !self.is_inverse() {
+            return Some(qty);
+        }
+        (qty * self.multiplier).checked_div(price)
+    }
+
+    // quote (USD for inverse) value of an order qty at price
+    pub fn notional(&self, qty: Decimal, price: Decimal) -> Decimal {
+        if self.is_inverse() {
+            qty * self.multiplier
+        } else {
+            qty * price
+        }
+    }
+
+    // order qty for a base coin amount, rounded down to the qty step
+    pub fn qty_for_coin(&self, coin: Decimal, price: Decimal) -> Option<Decimal> {
+        let qty = if self.is_inverse() { (coin * price).checked_div(self.multiplier)? } else { coin };
+        Some(self.round(qty))
+    }
+
+    // order qty for a quote value, rounded down to the qty step
+    pub fn qty_for_notional(&self, notional: Decimal, price: Decimal) -> Option<Decimal> {
+        let qty = if self.is_inverse() { notional.checked_div(self.multiplier)? } else { notional.checked_div(price)? };
+        Some(self.round(qty))
+    }
+
+    // unrealised pnl of a long (positive qty) or short position, in the settle coin for inverse and quote otherwise
+    pub fn pnl(&self, qty: Decimal, entry: Decimal, exit: Decimal) -> Option<Decimal> {
+        if !self.is_inverse() {
+            return Some(qty * (exit - entry));
+        }
+        Some(self.coin_qty(qty, entry)? - self.coin_qty(qty, exit)?)
+    }
+
+    fn round(&self, qty: Decimal) -> Decimal {
+        Precision { tick_size: None, qty_step: self.qty_step }.qty(qty)
+    }
+}
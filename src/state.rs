@@ -0,0 +1,312 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use futures::channel::mpsc::UnboundedSender;
+use rust_decimal::Decimal;
+
+use crate::{
+    account::{AccountSnapshot, CoinBalance, WalletBalance},
+    position::Position,
+    trade::Order,
+    ws::{OrderUpdate, PositionUpdate, PrivateEvent},
+    AccountType, Category, Client, OrderStatus, Side,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderState {
+    pub order_id: String,
+    pub order_link_id: String,
+    pub symbol: String,
+    pub side: Side,
+    pub status: OrderStatus,
+    pub qty: Decimal,
+    pub leaves_qty: Decimal,
+}
+
+impl From<&Order> for OrderState {
+    fn from(order: &Order) -> Self {
+        Self {
+            order_id: order.order_id.clone(),
+            order_link_id: order.order_link_id.clone(),
+            symbol: order.symbol.clone(),
+            side: order.side,
+            status: order.order_status,
+            qty: order.qty.parse().unwrap_or_default(),
+            leaves_qty: order.leaves_qty.parse().unwrap_or_default(),
+        }
+    }
+}
+
+impl From<&OrderUpdate> for OrderState {
+    fn from(order: &OrderUpdate) -> Self {
+        Self {
+            order_id: order.order_id.clone(),
+            order_link_id: order.order_link_id.clone(),
+            symbol: order.symbol.clone(),
+            side: order.side,
+            status: order.order_status,
+            qty: order.qty.parse().unwrap_or_default(),
+            leaves_qty: order.leaves_qty.parse().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PositionState {
+    pub symbol: String,
+    pub position_idx: i32,
+    pub side: String,
+    pub size: Decimal,
+    pub entry_price: Decimal,
+}
+
+impl From<&Position> for PositionState {
+    fn from(position: &Position) -> Self {
+        Self {
+            symbol: position.symbol.clone(),
+            position_idx: position.position_idx,
+            side: position.side.clone(),
+            size: position.size.parse().unwrap_or_default(),
+            entry_price: position.avg_price.parse().unwrap_or_default(),
+        }
+    }
+}
+
+impl From<&PositionUpdate> for PositionState {
+    fn from(position: &PositionUpdate) -> Self {
+        Self {
+            symbol: position.symbol.clone(),
+            position_idx: position.position_idx,
+            side: position.side.clone(),
+            size: position.size.parse().unwrap_or_default(),
+            entry_price: position.entry_price.parse().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateChange {
+    Order(OrderState),
+    // reached a terminal status and was dropped from the open orders
+    OrderClosed(OrderState),
+    Position(PositionState),
+    PositionClosed(PositionState),
+    Balance(AccountType),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Discrepancy {
+    // open on the exchange but unknown locally, an order update was missed
+    MissingOrder(OrderState),
+    // open locally but not on the exchange, its closing update was missed
+    StaleOrder(OrderState),
+    OrderMismatch { local: OrderState, remote: OrderState },
+    MissingPosition(PositionState),
+    StalePosition(PositionState),
+    PositionMismatch { local: PositionState, remote: PositionState },
+    BalanceMismatch { account_type: AccountType, coin: String, local: Decimal, remote: Decimal },
+}
+
+// In-memory view of balances, positions and open orders fed by PrivateEvents. Subscribe to the order, position and
+// wallet topics for the same categories the REST snapshots cover, anything outside them shows up as a discrepancy.
+// Positions are keyed by symbol and positionIdx, flat positions are dropped
+#[derive(Debug, Clone, Default)]
+pub struct AccountState {
+    orders: HashMap<String, OrderState>,
+    positions: HashMap<(String, i32), PositionState>,
+    balances: HashMap<AccountType, WalletBalance>,
+}
+
+impl AccountState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_snapshot(snapshot: &AccountSnapshot) -> Self {
+        let mut state = Self::new();
+        state.replace(snapshot);
+        state
+    }
+
+    pub fn open_orders(&self) -> impl Iterator<Item = &OrderState> {
+        self.orders.values()
+    }
+
+    pub fn order(&self, order_id: &str) -> Option<&OrderState> {
+        self.orders.get(order_id)
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = &PositionState> {
+        self.positions.values()
+    }
+
+    pub fn position(&self, symbol: &str, position_idx: i32) -> Option<&PositionState> {
+        self.positions.get(&(symbol.to_string(), position_idx))
+    }
+
+    pub fn balance(&self, account_type: &AccountType) -> Option<&WalletBalance> {
+        self.balances.get(account_type)
+    }
+
+    pub fn coin_balance(&self, account_type: &AccountType, coin: &str) -> Option<&CoinBalance> {
+        self.balance(account_type)?.coin.iter().find(|balance| balance.coin == coin)
+    }
+
+    // applies a stream event and returns what changed, fills and raw events don't touch the state
+    pub fn apply(&mut self, event: &PrivateEvent) -> Vec<StateChange> {
+        let mut changes = Vec::new();
+        match event {
+            PrivateEvent::Order { data, .. } => {
+                for update in data {
+                    let order = OrderState::from(update);
+                    if order.status.is_terminal() {
+                        self.orders.remove(&order.order_id);
+                        changes.push(StateChange::OrderClosed(order));
+                    } else {
+                        self.orders.insert(order.order_id.clone(), order.clone());
+                        changes.push(StateChange::Order(order));
+                    }
+                }
+            }
+            PrivateEvent::Position { data, .. } => {
+                for update in data {
+                    let position = PositionState::from(update);
+                    let key = (position.symbol.clone(), position.position_idx);
+                    if position.size.is_zero() {
+                        if self.positions.remove(&key).is_some() {
+                            changes.push(StateChange::PositionClosed(position));
+                        }
+                    } else {
+                        self.positions.insert(key, position.clone());
+                        changes.push(StateChange::Position(position));
+                    }
+                }
+            }
+            PrivateEvent::Balance { data, .. } => {
+                for wallet in data {
+                    self.balances.insert(wallet.account_type.clone(), wallet.clone());
+                    changes.push(StateChange::Balance(wallet.account_type.clone()));
+                }
+            }
+            _ => {}
+        }
+        changes
+    }
+
+    // compares the local view with a REST snapshot without changing it
+    pub fn diff(&self, snapshot: &AccountSnapshot) -> Vec<Discrepancy> {
+        let remote = Self::from_snapshot(snapshot);
+        let mut discrepancies = Vec::new();
+
+        for (order_id, remote_order) in &remote.orders {
+            match self.orders.get(order_id) {
+                None => discrepancies.push(Discrepancy::MissingOrder(remote_order.clone())),
+                Some(local) if local.status != remote_order.status || local.leaves_qty != remote_order.leaves_qty => {
+                    discrepancies.push(Discrepancy::OrderMismatch { local: local.clone(), remote: remote_order.clone() });
+                }
+                Some(_) => {}
+            }
+        }
+        discrepancies.extend(self.orders.iter()
+            .filter(|(order_id, _)| !remote.orders.contains_key(*order_id))
+            .map(|(_, order)| Discrepancy::StaleOrder(order.clone())));
+
+        for (key, remote_position) in &remote.positions {
+            match self.positions.get(key) {
+                None => discrepancies.push(Discrepancy::MissingPosition(remote_position.clone())),
+                Some(local) if local.side != remote_position.side || local.size != remote_position.size => {
+                    discrepancies.push(Discrepancy::PositionMismatch { local: local.clone(), remote: remote_position.clone() });
+                }
+                Some(_) => {}
+            }
+        }
+        discrepancies.extend(self.positions.iter()
+            .filter(|(key, _)| !remote.positions.contains_key(*key))
+            .map(|(_, position)| Discrepancy::StalePosition(position.clone())));
+
+        // only wallet balance is compared, equity and margin move with the mark price between updates
+        for (account_type, remote_wallet) in &remote.balances {
+            for remote_coin in &remote_wallet.coin {
+                let remote_balance: Decimal = remote_coin.wallet_balance.parse().unwrap_or_default();
+                let local_balance: Decimal = self.coin_balance(account_type, &remote_coin.coin)
+                    .and_then(|balance| balance.wallet_balance.parse().ok())
+                    .unwrap_or_default();
+                if local_balance != remote_balance {
+                    discrepancies.push(Discrepancy::BalanceMismatch {
+                        account_type: account_type.clone(),
+                        coin: remote_coin.coin.clone(),
+                        local: local_balance,
+                        remote: remote_balance,
+                    });
+                }
+            }
+        }
+        discrepancies
+    }
+
+    // replaces the local view with a REST snapshot, e.g. after a reconnect, and returns what had drifted
+    pub fn reconcile(&mut self, snapshot: &AccountSnapshot) -> Vec<Discrepancy> {
+        let discrepancies = self.diff(snapshot);
+        self.replace(snapshot);
+        discrepancies
+    }
+
+    fn replace(&mut self, snapshot: &AccountSnapshot) {
+        self.orders = snapshot.open_orders.iter()
+            .map(OrderState::from)
+            .filter(|order| !order.status.is_terminal())
+            .map(|order| (order.order_id.clone(), order))
+            .collect();
+        self.positions = snapshot.positions.iter()
+            .map(PositionState::from)
+            .filter(|position| !position.size.is_zero())
+            .map(|position| ((position.symbol.clone(), position.position_idx), position))
+            .collect();
+        self.balances = snapshot.wallets.iter()
+            .map(|wallet| (wallet.account_type.clone(), wallet.clone()))
+            .collect();
+    }
+}
+
+// Periodically compares an AccountState kept up to date by the caller against REST snapshots. A snapshot races the
+// stream, so a discrepancy is only reported once two audits in a row have seen it
+#[derive(Debug, Clone)]
+pub struct Auditor {
+    pub scopes: Vec<(Category, Option<String>)>,
+    pub interval: Duration,
+    pub recv_window: Duration,
+}
+
+impl Auditor {
+    pub fn new(scopes: Vec<(Category, Option<String>)>, interval: Duration) -> Self {
+        Self { scopes, interval, recv_window: Duration::from_secs(5) }
+    }
+
+    // Runs until the client is shut down, snapshot failures are skipped and retried on the next interval. A poisoned
+    // state lock ends the run with an error, whatever panicked while holding it may have left the state half applied
+    pub async fn run<F, R, E>(&self, client: &Client, state: &Mutex<AccountState>, send: F, discrepancies: UnboundedSender<Vec<Discrepancy>>) -> crate::Result<()>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let _guard = client.shutdown.guard();
+        let mut previous = Vec::new();
+        loop {
+            futures::future::select(futures_timer::Delay::new(self.interval), client.shutdown.signal()).await;
+            if client.shutdown.is_triggered() {
+                return Ok(());
+            }
+            let Ok(snapshot) = client.snapshot(&self.scopes, &self.recv_window, &send).await else {
+                continue;
+            };
+            let current = state
+                .lock()
+                .map_err(|_| crate::Error::Unexpected("account state lock poisoned by a panic while it was held".to_string()))?
+                .diff(&snapshot);
+            let confirmed: Vec<Discrepancy> = current.iter().filter(|discrepancy| previous.contains(*discrepancy)).cloned().collect();
+            if !confirmed.is_empty() {
+                let _ = discrepancies.unbounded_send(confirmed);
+            }
+            previous = current;
+        }
+    }
+}
@@ -0,0 +1,279 @@
+use std::{path::Path, sync::Mutex, time::Duration};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    account::{TransactionLogEntry, TransactionLogQuery},
+    market::Kline,
+    trade::{Execution, ExecutionQuery},
+    Category, Client, Interval,
+};
+
+// the widest range the execution and transaction log endpoints take in one query
+const WINDOW: i64 = 7 * 24 * 60 * 60 * 1000;
+const KLINE_PAGE: u32 = 1000;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS klines (
+    category TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    interval TEXT NOT NULL,
+    start INTEGER NOT NULL,
+    open TEXT NOT NULL,
+    high TEXT NOT NULL,
+    low TEXT NOT NULL,
+    close TEXT NOT NULL,
+    volume TEXT NOT NULL,
+    turnover TEXT NOT NULL,
+    PRIMARY KEY (category, symbol, interval, start)
+);
+CREATE TABLE IF NOT EXISTS executions (
+    exec_id TEXT PRIMARY KEY,
+    category TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    exec_time INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS executions_time ON executions (category, exec_time);
+CREATE TABLE IF NOT EXISTS transaction_log (
+    id TEXT PRIMARY KEY,
+    transaction_time INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS transaction_log_time ON transaction_log (transaction_time);
+CREATE TABLE IF NOT EXISTS synced (
+    scope TEXT PRIMARY KEY,
+    until INTEGER NOT NULL
+);
+";
+
+// Local SQLite copy of downloaded history, so a backfill only fetches what's new since the last one. Executions and
+// transaction log entries are kept as Bybit sent them, klines of a candle that was still forming are overwritten
+// by the next sync. Calls block on the database, they are short local writes
+#[derive(Debug)]
+pub struct Store(Mutex<Connection>);
+
+impl Store {
+    pub fn open(path: impl AsRef<Path>) -> crate::Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn in_memory() -> crate::Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(connection: Connection) -> crate::Result<Self> {
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self(Mutex::new(connection)))
+    }
+
+    fn connection(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    // klines starting within start..=end (unix millis), oldest first
+    pub fn klines(&self, category: Category, symbol: &str, interval: Interval, start: i64, end: i64) -> crate::Result<Vec<Kline>> {
+        let connection = self.connection();
+        let mut statement = connection.prepare(
+            "SELECT start, open, high, low, close, volume, turnover FROM klines
+             WHERE category = ?1 AND symbol = ?2 AND interval = ?3 AND start BETWEEN ?4 AND ?5 ORDER BY start",
+        )?;
+        let rows = statement.query_map(params![category.as_str(), symbol, interval.as_str(), start, end], |row| {
+            Ok(Kline {
+                start: row.get::<_, i64>(0)?.to_string(),
+                open: row.get(1)?,
+                high: row.get(2)?,
+                low: row.get(3)?,
+                close: row.get(4)?,
+                volume: row.get(5)?,
+                turnover: row.get(6)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    // executions within start..=end (unix millis), oldest first
+    pub fn executions(&self, category: Category, start: i64, end: i64) -> crate::Result<Vec<Execution>> {
+        let connection = self.connection();
+        let mut statement = connection.prepare(
+            "SELECT data FROM executions WHERE category = ?1 AND exec_time BETWEEN ?2 AND ?3 ORDER BY exec_time, exec_id",
+        )?;
+        let rows = statement.query_map(params![category.as_str(), start, end], |row| decode(row.get(0)?))?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    // transaction log entries within start..=end (unix millis), oldest first
+    pub fn transaction_log(&self, start: i64, end: i64) -> crate::Result<Vec<TransactionLogEntry>> {
+        let connection = self.connection();
+        let mut statement = connection.prepare(
+            "SELECT data FROM transaction_log WHERE transaction_time BETWEEN ?1 AND ?2 ORDER BY transaction_time, id",
+        )?;
+        let rows = statement.query_map(params![start, end], |row| decode(row.get(0)?))?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    // inserts or overwrites, returns how many klines were written
+    pub fn insert_klines(&self, category: Category, symbol: &str, interval: Interval, klines: &[Kline]) -> crate::Result<usize> {
+        let mut connection = self.connection();
+        let transaction = connection.transaction()?;
+        let mut written = 0;
+        {
+            let mut statement = transaction.prepare(
+                "INSERT OR REPLACE INTO klines (category, symbol, interval, start, open, high, low, close, volume, turnover)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            )?;
+            for kline in klines {
+                let start = millis(&kline.start, "kline start")?;
+                written += statement.execute(params![
+                    category.as_str(), symbol, interval.as_str(), start, kline.open, kline.high, kline.low, kline.close, kline.volume, kline.turnover
+                ])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(written)
+    }
+
+    // executions already stored are skipped, returns how many were new
+    pub fn insert_executions(&self, category: Category, executions: &[Execution]) -> crate::Result<usize> {
+        let rows = executions
+            .iter()
+            .map(|execution| Ok((execution.exec_id.as_str(), execution.symbol.as_str(), millis(&execution.exec_time, "execTime")?, encode(execution)?)))
+            .collect::<crate::Result<Vec<_>>>()?;
+        let mut connection = self.connection();
+        let transaction = connection.transaction()?;
+        let mut added = 0;
+        {
+            let mut statement = transaction.prepare(
+                "INSERT OR IGNORE INTO executions (exec_id, category, symbol, exec_time, data) VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for (exec_id, symbol, exec_time, data) in rows {
+                added += statement.execute(params![exec_id, category.as_str(), symbol, exec_time, data])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(added)
+    }
+
+    // entries already stored are skipped, returns how many were new
+    pub fn insert_transaction_log(&self, entries: &[TransactionLogEntry]) -> crate::Result<usize> {
+        let rows = entries
+            .iter()
+            .map(|entry| Ok((entry.id.as_str(), millis(&entry.transaction_time, "transactionTime")?, encode(entry)?)))
+            .collect::<crate::Result<Vec<_>>>()?;
+        let mut connection = self.connection();
+        let transaction = connection.transaction()?;
+        let mut added = 0;
+        {
+            let mut statement = transaction.prepare("INSERT OR IGNORE INTO transaction_log (id, transaction_time, data) VALUES (?1, ?2, ?3)")?;
+            for (id, transaction_time, data) in rows {
+                added += statement.execute(params![id, transaction_time, data])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(added)
+    }
+
+    // unix millis the last completed sync of scope reached
+    fn synced_until(&self, scope: &str) -> crate::Result<Option<i64>> {
+        Ok(self.connection().query_row("SELECT until FROM synced WHERE scope = ?1", params![scope], |row| row.get(0)).optional()?)
+    }
+
+    fn set_synced(&self, scope: &str, until: i64) -> crate::Result<()> {
+        self.connection().execute("INSERT OR REPLACE INTO synced (scope, until) VALUES (?1, ?2)", params![scope, until])?;
+        Ok(())
+    }
+}
+
+fn millis(value: &str, field: &str) -> crate::Result<i64> {
+    value.parse().map_err(|err| crate::Error::Unexpected(format!("invalid {field} {value:?}: {err}")))
+}
+
+fn encode<T: Serialize>(value: &T) -> crate::Result<String> {
+    serde_json::to_string(value).map_err(|err| crate::Error::Serialization(err.to_string()))
+}
+
+fn decode<T: DeserializeOwned>(data: String) -> rusqlite::Result<T> {
+    serde_json::from_str(&data).map_err(|err| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(err)))
+}
+
+impl Client {
+    // Stores the klines from since (unix millis), or from the newest one the last sync of this symbol and interval
+    // stored, up to now. Pages go backwards from now, an interrupted sync is redone from the same point. Returns how
+    // many klines were written
+    pub async fn sync_klines<F, R, E>(&self, store: &Store, category: Category, symbol: &str, interval: Interval, since: i64, send: F) -> crate::Result<usize>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let scope = format!("klines:{}:{symbol}:{}", category.as_str(), interval.as_str());
+        let from = store.synced_until(&scope)?.map_or(since, |until| until.max(since));
+        let mut end = self.clock.now().timestamp_millis();
+        let mut newest = None;
+        let mut written = 0;
+        loop {
+            let page = self.get_klines(category, symbol.to_string(), interval, Some(from), Some(end), Some(KLINE_PAGE))?.send(&send).await?.list;
+            written += store.insert_klines(category, symbol, interval, &page)?;
+            let starts = page.iter().map(|kline| millis(&kline.start, "kline start")).collect::<crate::Result<Vec<_>>>()?;
+            newest = newest.or(starts.iter().copied().max());
+            let oldest = starts.iter().copied().min();
+            if page.len() < KLINE_PAGE as usize || oldest.is_none_or(|oldest| oldest <= from) {
+                break;
+            }
+            end = oldest.unwrap_or(from) - 1;
+        }
+        // the newest kline may still have been forming, the next sync fetches it again
+        if let Some(newest) = newest {
+            store.set_synced(&scope, newest)?;
+        }
+        Ok(written)
+    }
+
+    // Stores the executions of category from since (unix millis), or from where the last sync stopped, up to now in
+    // 7 day windows. Progress is kept per window, so an interrupted sync picks up from the last complete one.
+    // Returns how many executions were new
+    pub async fn sync_executions<F, R, E>(&self, store: &Store, category: Category, since: i64, recv_window: &Duration, send: F) -> crate::Result<usize>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let scope = format!("executions:{}", category.as_str());
+        let mut added = 0;
+        for (from, to) in self.windows(store, &scope, since)? {
+            let query = ExecutionQuery::new(category).with_time_range(from, to).with_limit(100);
+            added += store.insert_executions(category, &self.get_all_executions(&query, recv_window, &send).await?)?;
+            store.set_synced(&scope, to)?;
+        }
+        Ok(added)
+    }
+
+    // Stores the unified account's transaction log like sync_executions does executions
+    pub async fn sync_transaction_log<F, R, E>(&self, store: &Store, since: i64, recv_window: &Duration, send: F) -> crate::Result<usize>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let scope = "transaction_log";
+        let mut added = 0;
+        for (from, to) in self.windows(store, scope, since)? {
+            let query = TransactionLogQuery::new().with_time_range(from, to).with_limit(50);
+            added += store.insert_transaction_log(&self.get_all_transaction_log(&query, recv_window, &send).await?)?;
+            store.set_synced(scope, to)?;
+        }
+        Ok(added)
+    }
+
+    // Query windows from where scope was last synced to now. They start at the last sync's end rather than after it,
+    // what came in within that millisecond is fetched again and skipped by the store
+    fn windows(&self, store: &Store, scope: &str, since: i64) -> crate::Result<Vec<(i64, i64)>> {
+        let now = self.clock.now().timestamp_millis();
+        let mut from = store.synced_until(scope)?.map_or(since, |until| until.max(since));
+        let mut windows = Vec::new();
+        while from <= now {
+            let to = (from + WINDOW - 1).min(now);
+            windows.push((from, to));
+            from = to + 1;
+        }
+        Ok(windows)
+    }
+}
@@ -0,0 +1,89 @@
+use std::{
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use crate::{clock::OffsetClock, diagnose::Diagnostics, Client};
+
+// Keeps the timestamps a client signs with at Bybit's clock so drift doesn't end in 10002 rejections.
+// Wire it in with Client::with_time_sync, then sync once at startup and/or keep run going in the background
+#[derive(Debug, Clone)]
+pub struct TimeSync {
+    offset_ms: Arc<AtomicI64>,
+    pub interval: Duration,
+    // samples with a slower round trip are too noisy to act on
+    pub max_round_trip: Duration,
+}
+
+impl Default for TimeSync {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimeSync {
+    pub fn new() -> Self {
+        Self { offset_ms: Arc::new(AtomicI64::new(0)), interval: Duration::from_secs(300), max_round_trip: Duration::from_secs(1) }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn with_max_round_trip(mut self, max_round_trip: Duration) -> Self {
+        self.max_round_trip = max_round_trip;
+        self
+    }
+
+    // server clock minus local clock as last measured
+    pub fn offset_ms(&self) -> i64 {
+        self.offset_ms.load(Ordering::Relaxed)
+    }
+
+    // Measures against /v5/market/time and folds the result into the offset. The client has to be wired to this
+    // TimeSync, its clock already carries the current offset so what diagnose sees is the remaining error
+    pub async fn sync<F, R, E>(&self, client: &Client, send: F) -> crate::Result<Diagnostics>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let diagnostics = client.diagnose(client.recv_window(), send).await?;
+        if diagnostics.round_trip > self.max_round_trip {
+            return Err(crate::Error::SlowRoundTrip(diagnostics.round_trip));
+        }
+        self.offset_ms.fetch_add(diagnostics.offset_ms, Ordering::Relaxed);
+        Ok(diagnostics)
+    }
+
+    // resyncs every interval until the client is shut down, failed samples keep the previous offset
+    pub async fn run<F, R, E>(&self, client: &Client, send: F)
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let _guard = client.shutdown.guard();
+        loop {
+            let _ = self.sync(client, &send).await;
+            futures::future::select(futures_timer::Delay::new(self.interval), client.shutdown.signal()).await;
+            if client.shutdown.is_triggered() {
+                return;
+            }
+        }
+    }
+
+    fn clock(&self, inner: Arc<dyn crate::clock::Clock>) -> OffsetClock {
+        OffsetClock::shared(inner, self.offset_ms.clone())
+    }
+}
+
+impl Client {
+    // signs with the local clock shifted by the TimeSync's offset from here on
+    pub fn with_time_sync(mut self, sync: &TimeSync) -> Self {
+        self.clock = Arc::new(sync.clock(self.clock.clone()));
+        self
+    }
+}
@@ -0,0 +1,1218 @@
+use std::{collections::{HashMap, HashSet}, time::Duration};
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::{execution::{Fill, FillAggregator, Inconsistency, OrderTracker}, number::{Precision, ToDecimal}, query, BybitRequest, Category, Client, IntoGetRequest, IntoPostRequest, OrderStatus, Side};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum OrderType {
+    Market,
+    Limit
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum TimeInForce {
+    GTC,
+    IOC,
+    FOK,
+    PostOnly,
+    RPI
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum TriggerBy {
+    LastPrice,
+    IndexPrice,
+    MarkPrice
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TriggerDirection {
+    // triggers when the price rises to the trigger price
+    Rise = 1,
+    // triggers when the price falls to the trigger price
+    Fall = 2
+}
+
+impl Serialize for TriggerDirection {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i32(*self as i32)
+    }
+}
+
+// spot only, selects whether the order is a plain, TP/SL or conditional order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum OrderFilter {
+    Order,
+    #[serde(rename = "tpslOrder")]
+    TpslOrder,
+    StopOrder
+}
+
+// spot market orders, whether qty is in the base or the quote coin
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum MarketUnit {
+    #[serde(rename = "baseCoin")]
+    BaseCoin,
+    #[serde(rename = "quoteCoin")]
+    QuoteCoin
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum SmpType {
+    None,
+    CancelMaker,
+    CancelTaker,
+    CancelBoth
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum TpslMode {
+    Full,
+    Partial
+}
+
+// The three trigger fields only make sense together, keeping them in one struct means a conditional order can't be half specified
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Trigger {
+    #[serde(rename = "triggerPrice")]
+    pub price: Decimal,
+    #[serde(rename = "triggerDirection")]
+    pub direction: TriggerDirection,
+    #[serde(rename = "triggerBy")]
+    pub by: TriggerBy,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlaceOrderRequest {
+    pub category: Category,
+    pub symbol: String,
+    #[serde(rename = "isLeverage", skip_serializing_if = "Option::is_none")]
+    pub is_leverage: Option<i32>,
+    pub side: Side,
+    #[serde(rename = "orderType")]
+    pub order_type: OrderType,
+    pub qty: Decimal,
+    #[serde(rename = "marketUnit", skip_serializing_if = "Option::is_none")]
+    pub market_unit: Option<MarketUnit>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<Decimal>,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub trigger: Option<Trigger>,
+    #[serde(rename = "orderFilter", skip_serializing_if = "Option::is_none")]
+    pub order_filter: Option<OrderFilter>,
+    #[serde(rename = "orderIv", skip_serializing_if = "Option::is_none")]
+    pub order_iv: Option<Decimal>,
+    #[serde(rename = "timeInForce", skip_serializing_if = "Option::is_none")]
+    pub time_in_force: Option<TimeInForce>,
+    #[serde(rename = "positionIdx", skip_serializing_if = "Option::is_none")]
+    pub position_idx: Option<i32>,
+    #[serde(rename = "orderLinkId", skip_serializing_if = "Option::is_none")]
+    pub order_link_id: Option<String>,
+    #[serde(rename = "takeProfit", skip_serializing_if = "Option::is_none")]
+    pub take_profit: Option<Decimal>,
+    #[serde(rename = "stopLoss", skip_serializing_if = "Option::is_none")]
+    pub stop_loss: Option<Decimal>,
+    #[serde(rename = "tpTriggerBy", skip_serializing_if = "Option::is_none")]
+    pub tp_trigger_by: Option<TriggerBy>,
+    #[serde(rename = "slTriggerBy", skip_serializing_if = "Option::is_none")]
+    pub sl_trigger_by: Option<TriggerBy>,
+    #[serde(rename = "tpslMode", skip_serializing_if = "Option::is_none")]
+    pub tpsl_mode: Option<TpslMode>,
+    #[serde(rename = "tpLimitPrice", skip_serializing_if = "Option::is_none")]
+    pub tp_limit_price: Option<Decimal>,
+    #[serde(rename = "slLimitPrice", skip_serializing_if = "Option::is_none")]
+    pub sl_limit_price: Option<Decimal>,
+    #[serde(rename = "tpOrderType", skip_serializing_if = "Option::is_none")]
+    pub tp_order_type: Option<OrderType>,
+    #[serde(rename = "slOrderType", skip_serializing_if = "Option::is_none")]
+    pub sl_order_type: Option<OrderType>,
+    #[serde(rename = "reduceOnly", skip_serializing_if = "Option::is_none")]
+    pub reduce_only: Option<bool>,
+    #[serde(rename = "closeOnTrigger", skip_serializing_if = "Option::is_none")]
+    pub close_on_trigger: Option<bool>,
+    #[serde(rename = "smpType", skip_serializing_if = "Option::is_none")]
+    pub smp_type: Option<SmpType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mmp: Option<bool>,
+    // order flags Bybit adds before they get a typed field here, merged into the body as is
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub extra_params: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+impl PlaceOrderRequest {
+    pub fn market(category: Category, symbol: impl Into<String>, side: Side, qty: Decimal) -> Self {
+        Self::new(category, symbol.into(), side, OrderType::Market, qty, None)
+    }
+
+    pub fn limit(category: Category, symbol: impl Into<String>, side: Side, qty: Decimal, price: Decimal) -> Self {
+        Self::new(category, symbol.into(), side, OrderType::Limit, qty, Some(price))
+    }
+
+    // like market but takes f64 or strings as well, e.g. straight from a config file
+    pub fn try_market(category: Category, symbol: impl Into<String>, side: Side, qty: impl ToDecimal) -> crate::Result<Self> {
+        Ok(Self::market(category, symbol, side, qty.to_decimal()?))
+    }
+
+    pub fn try_limit(category: Category, symbol: impl Into<String>, side: Side, qty: impl ToDecimal, price: impl ToDecimal) -> crate::Result<Self> {
+        Ok(Self::limit(category, symbol, side, qty.to_decimal()?, price.to_decimal()?))
+    }
+
+    fn new(category: Category, symbol: String, side: Side, order_type: OrderType, qty: Decimal, price: Option<Decimal>) -> Self {
+        Self {
+            category,
+            symbol,
+            is_leverage: None,
+            side,
+            order_type,
+            qty: qty.normalize(),
+            market_unit: None,
+            price: price.map(|price| price.normalize()),
+            trigger: None,
+            order_filter: None,
+            order_iv: None,
+            time_in_force: None,
+            position_idx: None,
+            order_link_id: None,
+            take_profit: None,
+            stop_loss: None,
+            tp_trigger_by: None,
+            sl_trigger_by: None,
+            tpsl_mode: None,
+            tp_limit_price: None,
+            sl_limit_price: None,
+            tp_order_type: None,
+            sl_order_type: None,
+            reduce_only: None,
+            close_on_trigger: None,
+            smp_type: None,
+            mmp: None,
+            extra_params: None,
+        }
+    }
+
+    pub fn with_order_link_id(mut self, order_link_id: impl Into<String>) -> Self {
+        self.order_link_id = Some(order_link_id.into());
+        self
+    }
+
+    pub fn with_time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.time_in_force = Some(time_in_force);
+        self
+    }
+
+    pub fn with_position_idx(mut self, position_idx: i32) -> Self {
+        self.position_idx = Some(position_idx);
+        self
+    }
+
+    pub fn reduce_only(mut self) -> Self {
+        self.reduce_only = Some(true);
+        self
+    }
+
+    // stop-market / stop-limit, for spot this also switches the order to a conditional StopOrder
+    pub fn with_trigger(mut self, price: Decimal, direction: TriggerDirection, by: TriggerBy) -> Self {
+        self.trigger = Some(Trigger { price, direction, by });
+        if self.category == Category::Spot {
+            self.order_filter = Some(OrderFilter::StopOrder);
+        }
+        self
+    }
+
+    pub fn with_take_profit(mut self, price: Decimal, trigger_by: Option<TriggerBy>) -> Self {
+        self.take_profit = Some(price);
+        self.tp_trigger_by = trigger_by;
+        self
+    }
+
+    pub fn with_stop_loss(mut self, price: Decimal, trigger_by: Option<TriggerBy>) -> Self {
+        self.stop_loss = Some(price);
+        self.sl_trigger_by = trigger_by;
+        self
+    }
+
+    // spot margin trading, borrows instead of requiring the full balance
+    pub fn with_spot_margin(mut self) -> Self {
+        self.is_leverage = Some(1);
+        self
+    }
+
+    pub fn with_market_unit(mut self, market_unit: MarketUnit) -> Self {
+        self.market_unit = Some(market_unit);
+        self
+    }
+
+    pub fn with_smp_type(mut self, smp_type: SmpType) -> Self {
+        self.smp_type = Some(smp_type);
+        self
+    }
+
+    pub fn with_extra_param(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.extra_params.get_or_insert_with(serde_json::Map::new).insert(key.into(), value.into());
+        self
+    }
+
+    // rounds every price to the tick size and qty down to the qty step, trimming trailing zeros
+    pub fn with_precision(mut self, precision: &Precision) -> Self {
+        self.qty = precision.qty(self.qty);
+        for price in [&mut self.price, &mut self.take_profit, &mut self.stop_loss, &mut self.tp_limit_price, &mut self.sl_limit_price] {
+            *price = price.map(|price| precision.price(price));
+        }
+        if let Some(trigger) = &mut self.trigger {
+            trigger.price = precision.price(trigger.price);
+        }
+        self
+    }
+}
+
+impl IntoPostRequest for PlaceOrderRequest {
+    const ENDPOINT: &'static str = "/v5/order/create";
+    type Response = PlaceOrderResponse;
+}
+
+// cancel and amend answer with the same pair of ids
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlaceOrderResponse {
+    #[serde(rename = "orderId")]
+    pub order_id: String,
+    #[serde(rename = "orderLinkId")]
+    pub order_link_id: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+// the envelope Bybit answers with, result or the error code and message. For endpoints answered locally
+#[cfg(any(feature = "market", feature = "ws"))]
+pub(crate) fn envelope(result: Result<serde_json::Value, (i32, String)>, ext_info: serde_json::Value, time: i64) -> serde_json::Value {
+    let (code, message, result) = match result {
+        Ok(result) => (0, "OK".to_string(), result),
+        Err((code, message)) => (code, message, serde_json::json!({})),
+    };
+    serde_json::json!({ "retCode": code, "retMsg": message, "result": result, "retExtInfo": ext_info, "time": time })
+}
+
+impl Client {
+    // checked against the client's RiskPolicy first, the order sent may be a clamped copy of request
+    pub fn place_order(&self, request: &PlaceOrderRequest, recv_window: &Duration) -> crate::Result<BybitRequest<PlaceOrderResponse>> {
+        let request = self.check_risk(request)?;
+        let request = request.as_ref();
+        #[cfg(feature = "market")]
+        if let Some(simulated) = self.simulate_place(request) {
+            return simulated;
+        }
+        request.as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+}
+
+// an order is addressed either by Bybit's id or by the caller's orderLinkId
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub enum OrderRef {
+    #[serde(rename = "orderId")]
+    OrderId(String),
+    #[serde(rename = "orderLinkId")]
+    OrderLinkId(String)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CancelOrderRequest {
+    pub category: Category,
+    pub symbol: String,
+    #[serde(flatten)]
+    pub order: OrderRef,
+    #[serde(rename = "orderFilter", skip_serializing_if = "Option::is_none")]
+    pub order_filter: Option<OrderFilter>,
+}
+
+impl CancelOrderRequest {
+    pub fn new(category: Category, symbol: impl Into<String>, order: OrderRef) -> Self {
+        Self { category, symbol: symbol.into(), order, order_filter: None }
+    }
+
+    pub fn with_order_filter(mut self, order_filter: OrderFilter) -> Self {
+        self.order_filter = Some(order_filter);
+        self
+    }
+}
+
+impl IntoPostRequest for CancelOrderRequest {
+    const ENDPOINT: &'static str = "/v5/order/cancel";
+    type Response = PlaceOrderResponse;
+}
+
+// linear and inverse need one of these, spot and option cancel the whole category without one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum CancelScope {
+    #[serde(rename = "symbol")]
+    Symbol(String),
+    #[serde(rename = "baseCoin")]
+    BaseCoin(String),
+    #[serde(rename = "settleCoin")]
+    SettleCoin(String)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CancelAllOrdersRequest {
+    pub category: Category,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<CancelScope>,
+    #[serde(rename = "orderFilter", skip_serializing_if = "Option::is_none")]
+    pub order_filter: Option<OrderFilter>,
+    #[serde(rename = "stopOrderType", skip_serializing_if = "Option::is_none")]
+    pub stop_order_type: Option<String>,
+}
+
+impl CancelAllOrdersRequest {
+    pub fn new(category: Category) -> Self {
+        Self { category, scope: None, order_filter: None, stop_order_type: None }
+    }
+
+    pub fn with_scope(mut self, scope: CancelScope) -> Self {
+        self.scope = Some(scope);
+        self
+    }
+
+    pub fn with_order_filter(mut self, order_filter: OrderFilter) -> Self {
+        self.order_filter = Some(order_filter);
+        self
+    }
+}
+
+impl IntoPostRequest for CancelAllOrdersRequest {
+    const ENDPOINT: &'static str = "/v5/order/cancel-all";
+    type Response = CancelAllOrdersResponse;
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CancelAllOrdersResponse {
+    pub list: Vec<PlaceOrderResponse>,
+    // "1" when every order was cancelled, only sent for some categories
+    pub success: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl Client {
+    pub fn cancel_order(&self, request: &CancelOrderRequest, recv_window: &Duration) -> crate::Result<BybitRequest<PlaceOrderResponse>> {
+        #[cfg(feature = "market")]
+        if let Some(simulated) = self.simulate_cancel(request) {
+            return simulated;
+        }
+        request.as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+    pub fn cancel_all_orders(&self, request: &CancelAllOrdersRequest, recv_window: &Duration) -> crate::Result<BybitRequest<CancelAllOrdersResponse>> {
+        #[cfg(feature = "market")]
+        if let Some(simulated) = self.simulate_cancel_all(request) {
+            return simulated;
+        }
+        request.as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AmendError {
+    #[error("amend request for {0:?} doesn't change any field")]
+    NothingToAmend(OrderRef),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AmendOrderRequest {
+    pub category: Category,
+    pub symbol: String,
+    #[serde(flatten)]
+    pub order: OrderRef,
+    #[serde(rename = "orderIv", skip_serializing_if = "Option::is_none")]
+    pub order_iv: Option<Decimal>,
+    #[serde(rename = "triggerPrice", skip_serializing_if = "Option::is_none")]
+    pub trigger_price: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub qty: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<Decimal>,
+    #[serde(rename = "tpslMode", skip_serializing_if = "Option::is_none")]
+    pub tpsl_mode: Option<TpslMode>,
+    #[serde(rename = "takeProfit", skip_serializing_if = "Option::is_none")]
+    pub take_profit: Option<Decimal>,
+    #[serde(rename = "stopLoss", skip_serializing_if = "Option::is_none")]
+    pub stop_loss: Option<Decimal>,
+    #[serde(rename = "tpTriggerBy", skip_serializing_if = "Option::is_none")]
+    pub tp_trigger_by: Option<TriggerBy>,
+    #[serde(rename = "slTriggerBy", skip_serializing_if = "Option::is_none")]
+    pub sl_trigger_by: Option<TriggerBy>,
+    #[serde(rename = "triggerBy", skip_serializing_if = "Option::is_none")]
+    pub trigger_by: Option<TriggerBy>,
+    #[serde(rename = "tpLimitPrice", skip_serializing_if = "Option::is_none")]
+    pub tp_limit_price: Option<Decimal>,
+    #[serde(rename = "slLimitPrice", skip_serializing_if = "Option::is_none")]
+    pub sl_limit_price: Option<Decimal>,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub extra_params: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+impl AmendOrderRequest {
+    pub fn new(category: Category, symbol: impl Into<String>, order: OrderRef) -> Self {
+        Self {
+            category,
+            symbol: symbol.into(),
+            order,
+            order_iv: None,
+            trigger_price: None,
+            qty: None,
+            price: None,
+            tpsl_mode: None,
+            take_profit: None,
+            stop_loss: None,
+            tp_trigger_by: None,
+            sl_trigger_by: None,
+            trigger_by: None,
+            tp_limit_price: None,
+            sl_limit_price: None,
+            extra_params: None,
+        }
+    }
+
+    pub fn with_qty(mut self, qty: Decimal) -> Self {
+        self.qty = Some(qty);
+        self
+    }
+
+    pub fn with_price(mut self, price: Decimal) -> Self {
+        self.price = Some(price);
+        self
+    }
+
+    pub fn with_trigger_price(mut self, trigger_price: Decimal) -> Self {
+        self.trigger_price = Some(trigger_price);
+        self
+    }
+
+    pub fn with_take_profit(mut self, price: Decimal, trigger_by: Option<TriggerBy>) -> Self {
+        self.take_profit = Some(price);
+        self.tp_trigger_by = trigger_by;
+        self
+    }
+
+    pub fn with_stop_loss(mut self, price: Decimal, trigger_by: Option<TriggerBy>) -> Self {
+        self.stop_loss = Some(price);
+        self.sl_trigger_by = trigger_by;
+        self
+    }
+
+    pub fn with_extra_param(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.extra_params.get_or_insert_with(serde_json::Map::new).insert(key.into(), value.into());
+        self
+    }
+
+    // trigger/tp/sl "by" fields and tpslMode only qualify the values they come with, on their own they change nothing
+    pub fn validate(&self) -> Result<(), AmendError> {
+        let changes = [self.order_iv, self.trigger_price, self.qty, self.price, self.take_profit, self.stop_loss, self.tp_limit_price, self.sl_limit_price];
+        if changes.iter().any(Option::is_some) || self.extra_params.as_ref().is_some_and(|params| !params.is_empty()) {
+            return Ok(());
+        }
+        Err(AmendError::NothingToAmend(self.order.clone()))
+    }
+}
+
+impl IntoPostRequest for AmendOrderRequest {
+    const ENDPOINT: &'static str = "/v5/order/amend";
+    type Response = PlaceOrderResponse;
+}
+
+impl Client {
+    // fails before signing when the request wouldn't change anything, Bybit rejects those anyway
+    pub fn amend_order(&self, request: &AmendOrderRequest, recv_window: &Duration) -> crate::Result<BybitRequest<PlaceOrderResponse>> {
+        request.validate().map_err(|err| crate::Error::Invalid(err.into()))?;
+        self.check_amend_risk(request)?;
+        #[cfg(feature = "market")]
+        if let Some(simulated) = self.simulate_amend(request) {
+            return simulated;
+        }
+        request.as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Order {
+    #[serde(rename = "orderId")]
+    pub order_id: String,
+    #[serde(rename = "orderLinkId")]
+    pub order_link_id: String,
+    pub symbol: String,
+    pub side: Side,
+    #[serde(rename = "orderType")]
+    pub order_type: OrderType,
+    pub price: String,
+    pub qty: String,
+    #[serde(rename = "orderStatus")]
+    pub order_status: OrderStatus,
+    #[serde(rename = "timeInForce")]
+    pub time_in_force: TimeInForce,
+    #[serde(rename = "positionIdx")]
+    pub position_idx: i32,
+    #[serde(rename = "avgPrice")]
+    pub avg_price: String,
+    #[serde(rename = "leavesQty")]
+    pub leaves_qty: String,
+    #[serde(rename = "cumExecQty")]
+    pub cum_exec_qty: String,
+    #[serde(rename = "cumExecValue")]
+    pub cum_exec_value: String,
+    #[serde(rename = "cumExecFee")]
+    pub cum_exec_fee: String,
+    #[serde(rename = "cancelType")]
+    pub cancel_type: String,
+    #[serde(rename = "rejectReason")]
+    pub reject_reason: String,
+    #[serde(rename = "stopOrderType")]
+    pub stop_order_type: String,
+    #[serde(rename = "triggerPrice")]
+    pub trigger_price: String,
+    #[serde(rename = "takeProfit")]
+    pub take_profit: String,
+    #[serde(rename = "stopLoss")]
+    pub stop_loss: String,
+    #[serde(rename = "reduceOnly")]
+    pub reduce_only: bool,
+    #[serde(rename = "smpType")]
+    pub smp_type: Option<SmpType>,
+    // 0 when the account isn't in an SMP group
+    #[serde(rename = "smpGroup")]
+    pub smp_group: Option<i64>,
+    // the counterparty order that triggered the self match
+    #[serde(rename = "smpOrderId")]
+    pub smp_order_id: Option<String>,
+    #[serde(rename = "createdTime")]
+    pub created_time: String,
+    #[serde(rename = "updatedTime")]
+    pub updated_time: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OrderPage {
+    pub category: Category,
+    pub list: Vec<Order>,
+    #[serde(rename = "nextPageCursor")]
+    pub next_page_cursor: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl OrderPage {
+    // None on the last page
+    pub fn next_cursor(&self) -> Option<&str> {
+        Some(self.next_page_cursor.as_str()).filter(|cursor| !cursor.is_empty())
+    }
+}
+
+// Filters shared by the open order and order history queries, open_only only applies to the former
+// and order_status/start_time/end_time to the latter
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderQuery {
+    pub category: Category,
+    pub symbol: Option<String>,
+    #[serde(rename = "baseCoin")]
+    pub base_coin: Option<String>,
+    #[serde(rename = "settleCoin")]
+    pub settle_coin: Option<String>,
+    #[serde(flatten)]
+    pub order: Option<OrderRef>,
+    #[serde(rename = "openOnly")]
+    pub open_only: Option<i32>,
+    #[serde(rename = "orderFilter")]
+    pub order_filter: Option<OrderFilter>,
+    #[serde(rename = "orderStatus")]
+    pub order_status: Option<OrderStatus>,
+    // unix millis
+    #[serde(rename = "startTime")]
+    pub start_time: Option<i64>,
+    #[serde(rename = "endTime")]
+    pub end_time: Option<i64>,
+    pub limit: Option<u32>,
+    pub cursor: Option<String>,
+}
+
+impl OrderQuery {
+    pub fn new(category: Category) -> Self {
+        Self {
+            category,
+            symbol: None,
+            base_coin: None,
+            settle_coin: None,
+            order: None,
+            open_only: None,
+            order_filter: None,
+            order_status: None,
+            start_time: None,
+            end_time: None,
+            limit: None,
+            cursor: None,
+        }
+    }
+
+    pub fn with_symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbol = Some(symbol.into());
+        self
+    }
+
+    pub fn with_order(mut self, order: OrderRef) -> Self {
+        self.order = Some(order);
+        self
+    }
+
+    // open order query only, returns the most recent closed orders instead of the open ones
+    pub fn closed(mut self) -> Self {
+        self.open_only = Some(1);
+        self
+    }
+
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    // takes nextPageCursor as returned by Bybit
+    pub fn with_cursor(mut self, cursor: Option<&str>) -> Self {
+        self.cursor = cursor.map(query::decode);
+        self
+    }
+}
+
+#[derive(Debug)]
+pub enum ReplaceOutcome {
+    // the original was cancelled and the successor placed as given, filled is what the original executed before the cancel
+    Replaced { original: Box<Order>, placed: PlaceOrderResponse, filled: Decimal },
+    // the original filled, or was otherwise closed, before the cancel went through, nothing was placed
+    Closed { original: Box<Order> },
+}
+
+impl Client {
+    pub fn get_open_orders(&self, query: &OrderQuery, recv_window: &Duration) -> crate::Result<BybitRequest<OrderPage>> {
+        #[derive(Serialize, Debug)]
+        struct OpenOrdersRequest<'a>(&'a OrderQuery);
+
+        impl IntoGetRequest for OpenOrdersRequest<'_> {
+            const ENDPOINT: &'static str = "/v5/order/realtime";
+            type Response = OrderPage;
+        }
+
+        OpenOrdersRequest(query).as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+    pub fn get_order_history(&self, query: &OrderQuery, recv_window: &Duration) -> crate::Result<BybitRequest<OrderPage>> {
+        #[derive(Serialize, Debug)]
+        struct OrderHistoryRequest<'a>(&'a OrderQuery);
+
+        impl IntoGetRequest for OrderHistoryRequest<'_> {
+            const ENDPOINT: &'static str = "/v5/order/history";
+            type Response = OrderPage;
+        }
+
+        OrderHistoryRequest(query).as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+    // follows nextPageCursor from query's cursor until the last page
+    pub async fn get_all_order_history<F, R, E>(&self, query: &OrderQuery, recv_window: &Duration, send: F) -> crate::Result<Vec<Order>>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let mut query = query.clone();
+        let mut orders = Vec::new();
+        loop {
+            let page = self.get_order_history(&query, recv_window)?.send(&send).await?;
+            query = query.with_cursor(page.next_cursor());
+            orders.extend(page.list);
+            if query.cursor.is_none() {
+                return Ok(orders);
+            }
+        }
+    }
+
+    pub async fn get_all_open_orders<F, R, E>(&self, query: &OrderQuery, recv_window: &Duration, send: F) -> crate::Result<Vec<Order>>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let mut query = query.clone();
+        let mut orders = Vec::new();
+        loop {
+            let page = self.get_open_orders(&query, recv_window)?.send(&send).await?;
+            query = query.with_cursor(page.next_cursor());
+            orders.extend(page.list);
+            if query.cursor.is_none() {
+                return Ok(orders);
+            }
+        }
+    }
+
+    // Cancels order and places successor in its place. A failed cancel usually means the original filled in the
+    // meantime, so its final state is looked up and reported instead of erroring. The successor is placed with its
+    // own qty, fills that landed on the original before the cancel are reported alongside so the caller can size
+    // follow ups
+    pub async fn replace_order<F, R, E>(&self, order: OrderRef, successor: &PlaceOrderRequest, recv_window: &Duration, send: F) -> crate::Result<ReplaceOutcome>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let cancel = CancelOrderRequest::new(successor.category, successor.symbol.clone(), order.clone());
+        let cancelled = self.cancel_order(&cancel, recv_window)?.send(&send).await;
+        let found = self.find_order(successor.category, order, recv_window, &send).await;
+        let original = match (cancelled, found) {
+            (Err(_), Ok(Some(original))) if original.order_status.is_terminal() => {
+                return Ok(ReplaceOutcome::Closed { original: Box::new(original) });
+            }
+            // the cancel error says more about what went wrong than anything the lookup after it could
+            (Err(err), _) => return Err(err),
+            (Ok(_), found) => found?.ok_or_else(|| crate::Error::Unexpected(format!("cancelled order {cancel:?} not found afterwards")))?,
+        };
+        let filled = original.cum_exec_qty.parse::<Decimal>().map_err(|err| {
+            crate::Error::Unexpected(format!("cumExecQty {:?} of order {}: {err}", original.cum_exec_qty, original.order_id))
+        })?;
+        let placed = self.place_order(successor, recv_window)?.send(&send).await?;
+        Ok(ReplaceOutcome::Replaced { original: Box::new(original), placed, filled })
+    }
+
+    // recently closed orders only show up in the open order query with openOnly=1, older ones in history
+    pub async fn find_order<F, R, E>(&self, category: Category, order: OrderRef, recv_window: &Duration, send: F) -> crate::Result<Option<Order>>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let query = OrderQuery::new(category).with_order(order);
+        for query in [query.clone(), query.clone().closed()] {
+            if let Some(order) = self.get_open_orders(&query, recv_window)?.send(&send).await?.list.pop() {
+                return Ok(Some(order));
+            }
+        }
+        Ok(self.get_order_history(&query, recv_window)?.send(&send).await?.list.pop())
+    }
+}
+
+// per request cap of /v5/order/create-batch
+pub fn batch_limit(category: Category) -> usize {
+    match category {
+        Category::Spot => 10,
+        _ => 20,
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchOrder {
+    pub symbol: String,
+    // empty when the item was rejected, see the matching BatchItemStatus
+    #[serde(rename = "orderId")]
+    pub order_id: String,
+    #[serde(rename = "orderLinkId")]
+    pub order_link_id: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchOrders {
+    pub list: Vec<BatchOrder>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchItemStatus {
+    pub code: i32,
+    pub msg: String,
+}
+
+// retExtInfo of batch endpoints, one status per item in request order
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchExtInfo {
+    pub list: Vec<BatchItemStatus>,
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum BatchItemError {
+    #[error("order rejected ({code}): {message}")]
+    Rejected { code: i32, message: String },
+    // the whole batch call carrying this item failed
+    #[error("batch request failed: {0}")]
+    Request(String),
+}
+
+impl Client {
+    // every order goes through the client's RiskPolicy, the first one it refuses fails the whole batch
+    pub fn create_batch_orders(&self, category: Category, orders: &[PlaceOrderRequest], recv_window: &Duration) -> crate::Result<BybitRequest<BatchOrders, BatchExtInfo>> {
+        let orders = orders.iter().map(|order| self.check_risk(order).map(std::borrow::Cow::into_owned)).collect::<crate::Result<Vec<_>>>()?;
+        let orders = orders.as_slice();
+        #[derive(Serialize, Debug)]
+        struct BatchRequest {
+            category: Category,
+            request: Vec<serde_json::Value>,
+        }
+
+        impl IntoPostRequest for BatchRequest {
+            const ENDPOINT: &'static str = "/v5/order/create-batch";
+            type Response = BatchOrders;
+        }
+
+        // category is only given once for the whole batch
+        let request = BatchRequest {
+            category,
+            request: orders.iter().map(|order| {
+                let mut order = serde_json::to_value(order).map_err(|err| crate::Error::Serialization(err.to_string()))?;
+                if let Some(fields) = order.as_object_mut() {
+                    fields.shift_remove("category");
+                }
+                Ok(order)
+            }).collect::<crate::Result<_>>()?,
+        };
+
+        #[cfg(feature = "market")]
+        if let Some(simulated) = self.simulate_batch(&request, orders) {
+            return Ok(simulated?.with_ext_info());
+        }
+        Ok(request.as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())?.with_ext_info())
+    }
+
+    // Places any number of orders through the batch endpoint: groups them by category, splits each group at the
+    // batch cap and waits for the client's rate limiter before each call, or pacing between calls when none is
+    // configured. Results line up with orders
+    pub async fn place_orders<F, R, E>(&self, orders: &[PlaceOrderRequest], pacing: Duration, recv_window: &Duration, send: F) -> Vec<Result<PlaceOrderResponse, BatchItemError>>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let mut results: Vec<Option<Result<PlaceOrderResponse, BatchItemError>>> = vec![None; orders.len()];
+        let mut groups: Vec<(Category, Vec<usize>)> = Vec::new();
+        for (index, order) in orders.iter().enumerate() {
+            match groups.iter_mut().find(|(category, _)| *category == order.category) {
+                Some((_, indices)) => indices.push(index),
+                None => groups.push((order.category, vec![index])),
+            }
+        }
+
+        let mut first = true;
+        for (category, indices) in groups {
+            for chunk in indices.chunks(batch_limit(category)) {
+                match &self.rate_limiter {
+                    // bybit meters batch placement per order against the single order budget
+                    Some(limiter) => {
+                        for _ in chunk {
+                            limiter.acquire("/v5/order/create").await;
+                        }
+                    }
+                    None if !first => futures_timer::Delay::new(pacing).await,
+                    None => {}
+                }
+                first = false;
+                let batch: Vec<PlaceOrderRequest> = chunk.iter().map(|index| orders[*index].clone()).collect();
+                match async { self.create_batch_orders(category, &batch, recv_window)?.send_response(&send).await }.await {
+                    Ok(response) => {
+                        let statuses = response.return_extended_info.map(|info| info.list).unwrap_or_default();
+                        let mut placed = response.result.list.into_iter();
+                        for (position, index) in chunk.iter().enumerate() {
+                            let order = placed.next();
+                            results[*index] = Some(match (statuses.get(position), order) {
+                                (Some(status), _) if status.code != 0 => Err(BatchItemError::Rejected { code: status.code, message: status.msg.clone() }),
+                                (_, Some(order)) => Ok(PlaceOrderResponse { order_id: order.order_id, order_link_id: order.order_link_id, extra: order.extra }),
+                                (_, None) => Err(BatchItemError::Request("missing from batch response".to_string())),
+                            });
+                        }
+                    }
+                    Err(err) => {
+                        for index in chunk {
+                            results[*index] = Some(Err(BatchItemError::Request(err.to_string())));
+                        }
+                    }
+                }
+            }
+        }
+        results.into_iter().map(|result| result.unwrap_or_else(|| Err(BatchItemError::Request("not sent".to_string())))).collect()
+    }
+}
+
+// fill level history, the REST counterpart of ExecutionUpdate
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Execution {
+    pub symbol: String,
+    #[serde(rename = "orderId")]
+    pub order_id: String,
+    #[serde(rename = "orderLinkId")]
+    pub order_link_id: String,
+    pub side: Side,
+    #[serde(rename = "orderPrice")]
+    pub order_price: String,
+    #[serde(rename = "orderQty")]
+    pub order_qty: String,
+    #[serde(rename = "leavesQty")]
+    pub leaves_qty: String,
+    #[serde(rename = "orderType")]
+    pub order_type: String,
+    #[serde(rename = "execId")]
+    pub exec_id: String,
+    #[serde(rename = "execPrice")]
+    pub exec_price: String,
+    #[serde(rename = "execQty")]
+    pub exec_qty: String,
+    #[serde(rename = "execValue")]
+    pub exec_value: String,
+    #[serde(rename = "execFee")]
+    pub exec_fee: String,
+    // Trade, Funding, AdlTrade, BustTrade, Delivery, Settle, BlockTrade, MovePosition
+    #[serde(rename = "execType")]
+    pub exec_type: String,
+    #[serde(rename = "execTime")]
+    pub exec_time: String,
+    #[serde(rename = "feeRate")]
+    pub fee_rate: String,
+    // only sent for spot, derivatives fees are charged in the settle coin
+    #[serde(rename = "feeCurrency")]
+    pub fee_currency: Option<String>,
+    #[serde(rename = "isMaker")]
+    pub is_maker: bool,
+    #[serde(rename = "markPrice")]
+    pub mark_price: String,
+    #[serde(rename = "closedSize")]
+    pub closed_size: Option<String>,
+    pub seq: Option<u64>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl Fill for Execution {
+    fn exec_id(&self) -> &str {
+        &self.exec_id
+    }
+
+    fn order_id(&self) -> &str {
+        &self.order_id
+    }
+
+    fn price(&self) -> Decimal {
+        self.exec_price.parse().unwrap_or_default()
+    }
+
+    fn qty(&self) -> Decimal {
+        self.exec_qty.parse().unwrap_or_default()
+    }
+
+    fn fee(&self) -> Decimal {
+        self.exec_fee.parse().unwrap_or_default()
+    }
+
+    fn fee_currency(&self) -> &str {
+        self.fee_currency.as_deref().unwrap_or_default()
+    }
+
+    fn is_maker(&self) -> bool {
+        self.is_maker
+    }
+
+    fn exec_time(&self) -> chrono::DateTime<chrono::Utc> {
+        self.exec_time.parse().ok().and_then(chrono::DateTime::from_timestamp_millis).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExecutionPage {
+    pub category: Category,
+    pub list: Vec<Execution>,
+    #[serde(rename = "nextPageCursor")]
+    pub next_page_cursor: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl ExecutionPage {
+    pub fn next_cursor(&self) -> Option<&str> {
+        Some(self.next_page_cursor.as_str()).filter(|cursor| !cursor.is_empty())
+    }
+}
+
+// without a time range Bybit returns the last 7 days, a range can't span more than 7 days either
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecutionQuery {
+    pub category: Category,
+    pub symbol: Option<String>,
+    #[serde(rename = "baseCoin")]
+    pub base_coin: Option<String>,
+    #[serde(flatten)]
+    pub order: Option<OrderRef>,
+    #[serde(rename = "execType")]
+    pub exec_type: Option<String>,
+    // unix millis
+    #[serde(rename = "startTime")]
+    pub start_time: Option<i64>,
+    #[serde(rename = "endTime")]
+    pub end_time: Option<i64>,
+    pub limit: Option<u32>,
+    pub cursor: Option<String>,
+}
+
+impl ExecutionQuery {
+    pub fn new(category: Category) -> Self {
+        Self {
+            category,
+            symbol: None,
+            base_coin: None,
+            order: None,
+            exec_type: None,
+            start_time: None,
+            end_time: None,
+            limit: None,
+            cursor: None,
+        }
+    }
+
+    pub fn with_symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbol = Some(symbol.into());
+        self
+    }
+
+    pub fn with_order(mut self, order: OrderRef) -> Self {
+        self.order = Some(order);
+        self
+    }
+
+    pub fn with_time_range(mut self, start_time: i64, end_time: i64) -> Self {
+        self.start_time = Some(start_time);
+        self.end_time = Some(end_time);
+        self
+    }
+
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn with_cursor(mut self, cursor: Option<&str>) -> Self {
+        self.cursor = cursor.map(query::decode);
+        self
+    }
+}
+
+// REST view of what a private stream may have missed, open orders now and every execution since a point in time
+#[derive(Debug, Clone)]
+pub struct Resync {
+    pub category: Category,
+    pub open_orders: Vec<Order>,
+    pub executions: Vec<Execution>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ResyncChange {
+    // an execution the aggregator had not counted yet, already applied to both sides
+    MissedFill(Execution),
+    // a tracked order the exchange reports in a different state
+    StatusChanged { order_link_id: String, from: Option<OrderStatus>, to: OrderStatus },
+    // open on the exchange but not in the tracker, placed by another session or before a restart
+    UnknownOrder(Order),
+    // open in the tracker but gone from the exchange, the final status isn't part of the open order list
+    NoLongerOpen(String),
+    // the tracker refused an update, its state for that order is left as it was
+    Inconsistent(Inconsistency),
+}
+
+impl Resync {
+    // Applies what the stream missed to local state and returns every difference found. The tracker is assumed to only
+    // hold orders of this category (and settle coin), anything else would be reported as no longer open
+    pub fn reconcile(&self, tracker: &mut OrderTracker, fills: &mut FillAggregator) -> Vec<ResyncChange> {
+        let mut changes = Vec::new();
+        for execution in &self.executions {
+            if !fills.add(execution) {
+                continue;
+            }
+            if let Some(id) = tracked_id(tracker, &execution.order_link_id, &execution.order_id)
+                && let Err(err) = tracker.on_fill(&id, execution.qty())
+            {
+                changes.push(ResyncChange::Inconsistent(err));
+            }
+            changes.push(ResyncChange::MissedFill(execution.clone()));
+        }
+
+        let mut open = HashSet::new();
+        for order in &self.open_orders {
+            let Some(id) = tracked_id(tracker, &order.order_link_id, &order.order_id) else {
+                changes.push(ResyncChange::UnknownOrder(order.clone()));
+                continue;
+            };
+            open.insert(id.clone());
+            if tracker.get(&id).is_some_and(|tracked| tracked.order_id.is_none())
+                && let Err(err) = tracker.ack(&id, order.order_id.clone())
+            {
+                changes.push(ResyncChange::Inconsistent(err));
+                continue;
+            }
+            let from = tracker.get(&id).and_then(|tracked| tracked.status);
+            if from == Some(order.order_status) {
+                continue;
+            }
+            match tracker.on_status(&id, order.order_status) {
+                Ok(_) => changes.push(ResyncChange::StatusChanged { order_link_id: id, from, to: order.order_status }),
+                Err(err) => changes.push(ResyncChange::Inconsistent(err)),
+            }
+        }
+
+        // orders that were never acknowledged may still be in flight, only acknowledged ones are known to be gone
+        let mut closed: Vec<_> = tracker
+            .open()
+            .filter(|tracked| tracked.order_id.is_some() && !open.contains(&tracked.order_link_id))
+            .map(|tracked| tracked.order_link_id.clone())
+            .collect();
+        closed.sort();
+        changes.extend(closed.into_iter().map(ResyncChange::NoLongerOpen));
+        changes
+    }
+}
+
+fn tracked_id(tracker: &OrderTracker, order_link_id: &str, order_id: &str) -> Option<String> {
+    tracker.get(order_link_id).or_else(|| tracker.get(order_id)).map(|tracked| tracked.order_link_id.clone())
+}
+
+impl Client {
+    pub fn get_executions(&self, query: &ExecutionQuery, recv_window: &Duration) -> crate::Result<BybitRequest<ExecutionPage>> {
+        #[derive(Serialize, Debug)]
+        struct ExecutionsRequest<'a>(&'a ExecutionQuery);
+
+        impl IntoGetRequest for ExecutionsRequest<'_> {
+            const ENDPOINT: &'static str = "/v5/execution/list";
+            type Response = ExecutionPage;
+        }
+
+        ExecutionsRequest(query).as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+    pub async fn get_all_executions<F, R, E>(&self, query: &ExecutionQuery, recv_window: &Duration, send: F) -> crate::Result<Vec<Execution>>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let mut query = query.clone();
+        let mut executions = Vec::new();
+        loop {
+            let page = self.get_executions(&query, recv_window)?.send(&send).await?;
+            query = query.with_cursor(page.next_cursor());
+            executions.extend(page.list);
+            if query.cursor.is_none() {
+                return Ok(executions);
+            }
+        }
+    }
+
+    // Call after a private stream reconnects or stalls with the time of the last update that was processed, then
+    // Resync::reconcile the result against the tracker and aggregator the stream feeds
+    pub async fn resync<F, R, E>(&self, category: Category, settle_coin: Option<String>, since: i64, recv_window: &Duration, send: F) -> crate::Result<Resync>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let send = &send;
+        let mut orders = OrderQuery::new(category);
+        orders.settle_coin = settle_coin;
+        let executions = ExecutionQuery::new(category).with_time_range(since, self.clock.now().timestamp_millis());
+        let (open_orders, executions) = futures::future::try_join(
+            self.get_all_open_orders(&orders, recv_window, send),
+            self.get_all_executions(&executions, recv_window, send),
+        ).await?;
+        Ok(Resync { category, open_orders, executions })
+    }
+}
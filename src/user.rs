@@ -0,0 +1,310 @@
+use std::{collections::HashMap, time::Duration};
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset::{new_transfer_id, TransferResult, UniversalTransferRequest},
+    AccountType, BybitRequest, Client, IntoGetRequest, IntoPostRequest,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum Permission {
+    ContractTrade,
+    Spot,
+    Wallet,
+    Options,
+    Derivatives,
+    CopyTrading,
+    BlockTrade,
+    Exchange,
+    NFT,
+    Affiliate
+}
+
+impl Permission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ContractTrade => "ContractTrade",
+            Self::Spot => "Spot",
+            Self::Wallet => "Wallet",
+            Self::Options => "Options",
+            Self::Derivatives => "Derivatives",
+            Self::CopyTrading => "CopyTrading",
+            Self::BlockTrade => "BlockTrade",
+            Self::Exchange => "Exchange",
+            Self::NFT => "NFT",
+            Self::Affiliate => "Affiliate",
+        }
+    }
+
+    pub fn is_trading(&self) -> bool {
+        matches!(self, Self::ContractTrade | Self::Spot | Self::Options | Self::Derivatives | Self::CopyTrading | Self::BlockTrade)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ApiKeyInfo {
+    pub id: String,
+    pub note: String,
+    #[serde(rename = "apiKey")]
+    pub api_key: String,
+    #[serde(rename = "readOnly")]
+    pub read_only: u8,
+    pub permissions: HashMap<String, Vec<String>>,
+    pub ips: Vec<String>,
+    #[serde(rename = "type")]
+    pub key_type: u8,
+    #[serde(rename = "deadlineDay")]
+    pub deadline_day: i64,
+    #[serde(rename = "expiredAt")]
+    pub expired_at: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    pub uta: u8,
+    #[serde(rename = "userID")]
+    pub user_id: u64,
+    #[serde(rename = "isMaster")]
+    pub is_master: bool,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl ApiKeyInfo {
+    pub fn has(&self, permission: Permission) -> bool {
+        if self.is_read_only() && permission.is_trading() {
+            return false;
+        }
+        self.permissions.get(permission.as_str()).is_some_and(|granted| !granted.is_empty())
+    }
+
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.expired_at).ok().map(|at| at.with_timezone(&Utc))
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at().is_some_and(|at| at <= now)
+    }
+
+    // bybit reports a key without an IP whitelist as ["*"]
+    pub fn is_ip_restricted(&self) -> bool {
+        !self.ips.is_empty() && !self.ips.iter().any(|ip| ip == "*")
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only == 1
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PermissionError {
+    #[error("api key expired at {0}")]
+    Expired(String),
+    #[error("api key is missing permissions: {}", .0.iter().map(Permission::as_str).collect::<Vec<_>>().join(", "))]
+    Missing(Vec<Permission>),
+    #[error("api key can trade but is not bound to any IP")]
+    Unrestricted,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SubMemberType {
+    Normal = 1,
+    // assets are managed by a third party custodian
+    Custodial = 6
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SubMember {
+    pub uid: String,
+    pub username: String,
+    #[serde(rename = "memberType")]
+    pub member_type: i32,
+    pub status: i32,
+    pub remark: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SubApiKey {
+    pub id: String,
+    pub note: String,
+    #[serde(rename = "apiKey")]
+    pub api_key: String,
+    #[serde(rename = "readOnly")]
+    pub read_only: u8,
+    // only ever returned here, Bybit can't show it again
+    pub secret: String,
+    pub permissions: HashMap<String, Vec<String>>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SubAccountSpec {
+    pub username: String,
+    pub member_type: SubMemberType,
+    pub note: Option<String>,
+    pub read_only: bool,
+    // None leaves the key unrestricted
+    pub ips: Option<Vec<String>>,
+    pub permissions: HashMap<Permission, Vec<String>>,
+    // funds moved from the master's wallet to the new member once its key exists
+    pub initial_transfer: Option<InitialTransfer>,
+}
+
+#[derive(Debug, Clone)]
+pub struct InitialTransfer {
+    pub coin: String,
+    pub amount: Decimal,
+    pub from_account_type: AccountType,
+    pub to_account_type: AccountType,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProvisionedSubAccount {
+    pub member: SubMember,
+    pub api_key: SubApiKey,
+    pub transfer: Option<TransferResult>,
+}
+
+// Which step of provision_subaccount failed, along with what was already created and has to be cleaned up by hand
+#[derive(Debug, thiserror::Error)]
+pub enum ProvisionError {
+    #[error("creating sub member {username} failed, nothing was created: {source}")]
+    Member { username: String, source: crate::Error },
+    #[error("sub member {} was created but its api key wasn't, retry create_sub_api_key for it or delete the member: {source}", .member.uid)]
+    ApiKey { member: SubMember, source: crate::Error },
+    // the transfer may still have gone through, check the universal transfer records for transfer_id before retrying with it
+    #[error("sub member {} and its api key were created but the initial transfer {transfer_id} failed: {source}", .member.uid)]
+    Transfer { member: SubMember, api_key: Box<SubApiKey>, transfer_id: String, source: crate::Error },
+}
+
+impl Client {
+    pub fn create_sub_member(&self, username: String, member_type: SubMemberType, quick_login: bool, note: Option<String>, recv_window: &Duration) -> crate::Result<BybitRequest<SubMember>> {
+        #[derive(Serialize, Debug)]
+        struct SubMemberRequest {
+            username: String,
+            #[serde(rename = "memberType")]
+            member_type: i32,
+            switch: i32,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            note: Option<String>,
+        }
+
+        impl IntoPostRequest for SubMemberRequest {
+            const ENDPOINT: &'static str = "/v5/user/create-sub-member";
+            type Response = SubMember;
+        }
+
+        let request = SubMemberRequest {
+            username,
+            member_type: member_type as i32,
+            switch: quick_login as i32,
+            note,
+        };
+
+        request.as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+    pub fn create_sub_api_key(&self, sub_uid: u64, note: Option<String>, read_only: bool, ips: Option<Vec<String>>, permissions: HashMap<Permission, Vec<String>>, recv_window: &Duration) -> crate::Result<BybitRequest<SubApiKey>> {
+        #[derive(Serialize, Debug)]
+        struct SubApiKeyRequest {
+            subuid: u64,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            note: Option<String>,
+            #[serde(rename = "readOnly")]
+            read_only: i32,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            ips: Option<String>,
+            permissions: HashMap<Permission, Vec<String>>,
+        }
+
+        impl IntoPostRequest for SubApiKeyRequest {
+            const ENDPOINT: &'static str = "/v5/user/create-sub-api";
+            type Response = SubApiKey;
+        }
+
+        let request = SubApiKeyRequest {
+            subuid: sub_uid,
+            note,
+            read_only: read_only as i32,
+            ips: ips.map(|ips| ips.join(",")),
+            permissions,
+        };
+
+        request.as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+    // creates the sub member, its api key and optionally funds it from the master account,
+    // a failure part way through reports what already exists as a ProvisionError
+    pub async fn provision_subaccount<F, R, E>(&self, spec: SubAccountSpec, recv_window: &Duration, send: F) -> crate::Result<ProvisionedSubAccount>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let member = self.create_sub_member(spec.username.clone(), spec.member_type, false, spec.note.clone(), recv_window)
+            ?.send(&send)
+            .await
+            .map_err(|source| ProvisionError::Member { username: spec.username.clone(), source })?;
+        let sub_uid = match member.uid.parse() {
+            Ok(uid) => uid,
+            Err(err) => {
+                let source = crate::Error::Unexpected(format!("sub member uid {} isn't numeric: {err}", member.uid));
+                return Err(ProvisionError::ApiKey { member, source }.into());
+            }
+        };
+        let api_key = match self.create_sub_api_key(sub_uid, spec.note, spec.read_only, spec.ips, spec.permissions, recv_window)?.send(&send).await {
+            Ok(api_key) => api_key,
+            Err(source) => return Err(ProvisionError::ApiKey { member, source }.into()),
+        };
+        let Some(initial) = spec.initial_transfer else {
+            return Ok(ProvisionedSubAccount { member, api_key, transfer: None });
+        };
+        let transfer_id = new_transfer_id();
+        let transfer = async {
+            let master = self.get_api_key_info(recv_window)?.send(&send).await?.user_id;
+            let request = UniversalTransferRequest::new(initial.coin, initial.amount, (master, initial.from_account_type), (sub_uid, initial.to_account_type))
+                .with_transfer_id(transfer_id.clone());
+            self.create_universal_transfer(&request, recv_window)?.send(&send).await
+        };
+        match transfer.await {
+            Ok(transfer) => Ok(ProvisionedSubAccount { member, api_key, transfer: Some(transfer) }),
+            Err(source) => Err(ProvisionError::Transfer { member, api_key: Box::new(api_key), transfer_id, source }.into()),
+        }
+    }
+
+    pub fn get_api_key_info(&self, recv_window: &Duration) -> crate::Result<BybitRequest<ApiKeyInfo>> {
+        #[derive(Serialize, Debug)]
+        struct ApiKeyRequest {}
+
+        impl IntoGetRequest for ApiKeyRequest {
+            const ENDPOINT: &'static str = "/v5/user/query-api";
+            type Response = ApiKeyInfo;
+        }
+
+        ApiKeyRequest {}.as_request_at(self.environment.base_url(), self.credentials()?, recv_window, self.clock.now())
+    }
+
+    // A key used from a non whitelisted IP fails the query itself (retCode 10010), so that case surfaces as the api error.
+    // A key that can trade from any IP is refused too, read only keys may stay unrestricted
+    pub async fn verify_permissions<F, R, E>(&self, required: &[Permission], recv_window: &Duration, send: F) -> crate::Result<ApiKeyInfo>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let info = self.get_api_key_info(recv_window)?.send(send).await?;
+        if info.is_expired(self.clock.now()) {
+            return Err(PermissionError::Expired(info.expired_at).into());
+        }
+        let missing: Vec<Permission> = required.iter().copied().filter(|permission| !info.has(*permission)).collect();
+        if !missing.is_empty() {
+            return Err(PermissionError::Missing(missing).into());
+        }
+        if !info.is_read_only() && !info.is_ip_restricted() {
+            return Err(PermissionError::Unrestricted.into());
+        }
+        Ok(info)
+    }
+}
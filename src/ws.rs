@@ -0,0 +1,372 @@
+use std::{collections::HashMap, time::{Duration, Instant}};
+
+use chrono::{DateTime, Utc};
+use futures::{FutureExt, Sink, SinkExt, Stream, StreamExt};
+use serde::Deserialize;
+
+use crate::{shutdown::{Shutdown, ShutdownGuard}, sign::Credentials, BoxError, Category, Environment, Error, Result};
+
+mod bbo;
+mod models;
+mod private;
+mod public;
+#[cfg(feature = "trade")]
+mod trade;
+
+pub use bbo::*;
+pub use models::*;
+pub use private::*;
+pub use public::*;
+#[cfg(feature = "trade")]
+pub use trade::*;
+
+pub const PING_INTERVAL: Duration = Duration::from_secs(20);
+
+impl Environment {
+    // demo trading only has private streams, its market data comes from mainnet
+    pub fn public_ws_url(&self, category: Category) -> String {
+        let host = match self {
+            Self::Testnet => "stream-testnet.bybit.com",
+            Self::Mainnet | Self::Demo => "stream.bybit.com",
+        };
+        format!("wss://{host}/v5/public/{}", category.as_str())
+    }
+
+    // demo trading has no websocket order entry
+    pub fn trade_ws_url(&self) -> Option<&'static str> {
+        match self {
+            Self::Mainnet => Some("wss://stream.bybit.com/v5/trade"),
+            Self::Testnet => Some("wss://stream-testnet.bybit.com/v5/trade"),
+            Self::Demo => None,
+        }
+    }
+
+    pub fn private_ws_url(&self) -> &'static str {
+        match self {
+            Self::Mainnet => "wss://stream.bybit.com/v5/private",
+            Self::Testnet => "wss://stream-testnet.bybit.com/v5/private",
+            Self::Demo => "wss://stream-demo.bybit.com/v5/private",
+        }
+    }
+}
+
+// Any connected websocket carrying text frames, e.g. a tokio-tungstenite stream mapped to and from Message::Text.
+// The crate stays runtime agnostic the same way BybitRequest::send takes the caller's transport, its errors surface
+// as Error::Transport
+pub trait WsConnection: Stream<Item = Result<String, <Self as Sink<String>>::Error>> + Sink<String, Error: Into<BoxError>> + Unpin {}
+
+impl<T> WsConnection for T
+where T: Stream<Item = Result<String, <T as Sink<String>>::Error>> + Sink<String, Error: Into<BoxError>> + Unpin
+{}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodePolicy {
+    // drop the message and count it in decode_failures
+    Skip,
+    // hand the message out untyped as a Raw event
+    Raw,
+    // end the stream with the decode error
+    Terminate,
+}
+
+// Decode policy per topic prefix (e.g. "tickers" or "tickers.BTCUSDT"), the longest matching prefix wins
+#[derive(Debug, Clone)]
+pub struct DecodePolicies {
+    default: DecodePolicy,
+    topics: HashMap<String, DecodePolicy>,
+}
+
+impl DecodePolicies {
+    pub fn new(default: DecodePolicy) -> Self {
+        Self { default, topics: HashMap::new() }
+    }
+
+    pub fn set(&mut self, topic_prefix: impl Into<String>, policy: DecodePolicy) {
+        self.topics.insert(topic_prefix.into(), policy);
+    }
+
+    pub fn policy(&self, topic: &str) -> DecodePolicy {
+        self.topics
+            .iter()
+            .filter(|(prefix, _)| topic.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, policy)| *policy)
+            .unwrap_or(self.default)
+    }
+}
+
+impl Default for DecodePolicies {
+    fn default() -> Self {
+        Self::new(DecodePolicy::Raw)
+    }
+}
+
+pub(crate) fn typed<T: serde::de::DeserializeOwned>(message: &DataMessage) -> Result<T, serde_json::Error> {
+    T::deserialize(&message.data)
+}
+
+// applies the topic's DecodePolicy to a modelled message that failed to decode, Some(Ok(message)) means hand it out as Raw
+pub(crate) fn decode_failed(
+    policies: &DecodePolicies,
+    failures: &mut u64,
+    message: DataMessage,
+    err: serde_json::Error,
+) -> Option<Result<DataMessage>> {
+    match policies.policy(&message.topic) {
+        DecodePolicy::Skip => {
+            *failures += 1;
+            None
+        }
+        DecodePolicy::Raw => {
+            *failures += 1;
+            Some(Ok(message))
+        }
+        DecodePolicy::Terminate => Some(Err(Error::Decode { topic: message.topic, source: err })),
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DataMessage {
+    pub topic: String,
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    pub ts: Option<u64>,
+    pub id: Option<String>,
+    #[serde(rename = "creationTime")]
+    pub creation_time: Option<u64>,
+    pub cts: Option<u64>,
+    pub data: serde_json::Value,
+}
+
+// the trade channel answers in camelCase with a retCode instead of success
+#[derive(Debug, Clone, Deserialize)]
+pub struct ControlMessage {
+    pub op: String,
+    pub success: Option<bool>,
+    #[serde(rename = "retCode")]
+    pub ret_code: Option<i32>,
+    #[serde(alias = "retMsg")]
+    pub ret_msg: Option<String>,
+    #[serde(alias = "connId")]
+    pub conn_id: Option<String>,
+    #[serde(alias = "reqId")]
+    pub req_id: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub data: Option<serde_json::Value>,
+    // trade acks echo rate limit state and the Traceid here
+    pub header: Option<serde_json::Value>,
+}
+
+impl ControlMessage {
+    fn is_pong(&self) -> bool {
+        self.op == "pong" || (self.op == "ping" && self.ret_msg.as_deref() == Some("pong"))
+    }
+
+    pub fn trace_id(&self) -> Option<&str> {
+        self.header.as_ref()?.get("Traceid")?.as_str()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum Frame {
+    Data(DataMessage),
+    Control(ControlMessage),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateKind {
+    Snapshot,
+    Delta,
+}
+
+impl UpdateKind {
+    fn parse(kind: Option<&str>) -> Self {
+        match kind {
+            Some("delta") => Self::Delta,
+            _ => Self::Snapshot,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubscriptionEvent<T> {
+    Subscribed { req_id: String, topics: Vec<T> },
+    Unsubscribed { req_id: String, topics: Vec<T> },
+    Rejected { req_id: String, op: String, topics: Vec<T>, message: String },
+}
+
+// Tracks subscribe/unsubscribe requests until Bybit acknowledges them
+#[derive(Debug, Clone)]
+pub(crate) struct Subscriptions<T> {
+    pending: HashMap<String, (String, Vec<T>)>,
+    active: Vec<T>,
+}
+
+impl<T: Clone + PartialEq + std::fmt::Display> Subscriptions<T> {
+    pub(crate) fn new() -> Self {
+        Self { pending: HashMap::new(), active: Vec::new() }
+    }
+
+    pub(crate) fn active(&self) -> &[T] {
+        &self.active
+    }
+
+    pub(crate) fn args(topics: &[T]) -> serde_json::Value {
+        topics.iter().map(ToString::to_string).collect()
+    }
+
+    pub(crate) fn request(&mut self, req_id: String, op: &str, topics: Vec<T>) {
+        self.pending.insert(req_id, (op.to_string(), topics));
+    }
+
+    pub(crate) fn ack(&mut self, control: ControlMessage) -> Option<SubscriptionEvent<T>> {
+        let req_id = control.req_id?;
+        let (op, topics) = self.pending.remove(&req_id)?;
+        if control.success != Some(true) {
+            let message = control.ret_msg.unwrap_or_default();
+            return Some(SubscriptionEvent::Rejected { req_id, op, topics, message });
+        }
+        if op == "unsubscribe" {
+            self.active.retain(|topic| !topics.contains(topic));
+            return Some(SubscriptionEvent::Unsubscribed { req_id, topics });
+        }
+        for topic in &topics {
+            if !self.active.contains(topic) {
+                self.active.push(topic.clone());
+            }
+        }
+        Some(SubscriptionEvent::Subscribed { req_id, topics })
+    }
+}
+
+// Connection plumbing shared by the public and private clients: request ids, ping/pong keepalive and shutdown
+pub(crate) struct Socket<S> {
+    conn: S,
+    ping_interval: Duration,
+    ping: futures_timer::Delay,
+    awaiting_pong: Option<Instant>,
+    next_req_id: u64,
+    shutdown: Option<(Shutdown, ShutdownGuard)>,
+    shut_down: bool,
+}
+
+enum Wake<T> {
+    Shutdown,
+    Ping,
+    Frame(T),
+}
+
+impl<S: WsConnection> Socket<S> {
+    pub(crate) fn new(conn: S) -> Self {
+        Self {
+            conn,
+            ping_interval: PING_INTERVAL,
+            ping: futures_timer::Delay::new(PING_INTERVAL),
+            awaiting_pong: None,
+            next_req_id: 1,
+            shutdown: None,
+            shut_down: false,
+        }
+    }
+
+    pub(crate) fn set_ping_interval(&mut self, interval: Duration) {
+        self.ping_interval = interval;
+        self.ping.reset(interval);
+    }
+
+    pub(crate) fn set_shutdown(&mut self, shutdown: &Shutdown) {
+        self.shutdown = Some((shutdown.clone(), shutdown.guard()));
+    }
+
+    // whether next ended because the client was shut down rather than the connection dropping
+    pub(crate) fn is_shut_down(&self) -> bool {
+        self.shut_down
+    }
+
+    pub(crate) fn next_req_id(&mut self) -> String {
+        let req_id = self.next_req_id.to_string();
+        self.next_req_id += 1;
+        req_id
+    }
+
+    pub(crate) async fn send(&mut self, op: &str, args: serde_json::Value) -> Result<String> {
+        let req_id = self.next_req_id();
+        let mut message = serde_json::json!({ "req_id": req_id, "op": op });
+        if !args.is_null() {
+            message["args"] = args;
+        }
+        self.send_message(message).await?;
+        Ok(req_id)
+    }
+
+    pub(crate) async fn send_message(&mut self, message: serde_json::Value) -> Result<()> {
+        self.conn.send(message.to_string()).await.map_err(|err| Error::Transport(err.into()))
+    }
+
+    // signs an auth request expiring AUTH_EXPIRY after now and waits for Bybit to accept it, frames read before
+    // the answer are dropped
+    pub(crate) async fn authenticate(&mut self, credentials: &Credentials, now: DateTime<Utc>) -> Result<()> {
+        let expires = (now + AUTH_EXPIRY).timestamp_millis();
+        let args = serde_json::json!([credentials.key(), expires, credentials.sign_ws_auth(expires)?]);
+        let req_id = self.send("auth", args).await?;
+        loop {
+            match self.next().await {
+                Some(Ok(Frame::Control(control))) if control.op == "auth" || control.req_id.as_ref() == Some(&req_id) => {
+                    if control.success == Some(true) || control.ret_code == Some(0) {
+                        return Ok(());
+                    }
+                    return Err(Error::WebSocket(format!("websocket auth rejected: {}", control.ret_msg.unwrap_or_default())));
+                }
+                Some(Ok(_)) => continue,
+                Some(Err(err)) => return Err(err),
+                None => return Err(Error::WebSocket("connection closed during auth".into())),
+            }
+        }
+    }
+
+    // next data or control frame, pongs are consumed here. None once the connection or the client shut down
+    pub(crate) async fn next(&mut self) -> Option<Result<Frame>> {
+        loop {
+            let wake = {
+                let shutdown = match &self.shutdown {
+                    Some((shutdown, _)) => shutdown.signal().left_future(),
+                    None => futures::future::pending().right_future(),
+                };
+                futures::select_biased! {
+                    _ = shutdown.fuse() => Wake::Shutdown,
+                    _ = (&mut self.ping).fuse() => Wake::Ping,
+                    frame = self.conn.next().fuse() => Wake::Frame(frame),
+                }
+            };
+            match wake {
+                Wake::Shutdown => {
+                    let _ = self.conn.close().await;
+                    self.shutdown = None;
+                    self.shut_down = true;
+                    return None;
+                }
+                Wake::Ping => {
+                    if let Some(sent) = self.awaiting_pong
+                        && sent.elapsed() >= self.ping_interval
+                    {
+                        return Some(Err(Error::WebSocket(format!("no pong within {:?}", self.ping_interval))));
+                    }
+                    self.ping.reset(self.ping_interval);
+                    if let Err(err) = self.send("ping", serde_json::Value::Null).await {
+                        return Some(Err(err));
+                    }
+                    self.awaiting_pong.get_or_insert_with(Instant::now);
+                }
+                Wake::Frame(None) => return None,
+                Wake::Frame(Some(Err(err))) => return Some(Err(Error::Transport(err.into()))),
+                Wake::Frame(Some(Ok(text))) => match serde_json::from_str::<Frame>(&text) {
+                    Ok(Frame::Control(control)) if control.is_pong() => self.awaiting_pong = None,
+                    Ok(frame) => return Some(Ok(frame)),
+                    Err(err) => return Some(Err(Error::WebSocket(format!("unrecognised websocket frame {text}: {err}")))),
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use futures::{Stream, StreamExt};
+use rust_decimal::Decimal;
+
+use super::{PublicEvent, UpdateKind};
+use crate::PriceLevel;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bbo {
+    pub symbol: String,
+    pub bid: Option<PriceLevel>,
+    pub ask: Option<PriceLevel>,
+    pub ts: u64,
+    pub update_id: u64,
+}
+
+// Top of book per symbol from orderbook.1 events, snapshots carry both sides and an empty one has no orders,
+// deltas only carry the side that changed and a zero size deletes that level
+#[derive(Debug, Clone, Default)]
+pub struct BboTracker {
+    books: HashMap<String, Bbo>,
+}
+
+impl BboTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&Bbo> {
+        self.books.get(symbol)
+    }
+
+    pub fn on_event(&mut self, event: &PublicEvent) -> Option<&Bbo> {
+        let PublicEvent::Orderbook { topic, kind, ts, data, .. } = event else {
+            return None;
+        };
+        if !topic.starts_with("orderbook.1.") {
+            return None;
+        }
+        let bbo = self.books.entry(data.symbol.clone()).or_insert_with(|| Bbo {
+            symbol: data.symbol.clone(),
+            bid: None,
+            ask: None,
+            ts: 0,
+            update_id: 0,
+        });
+        apply(&mut bbo.bid, &data.bids, *kind);
+        apply(&mut bbo.ask, &data.asks, *kind);
+        bbo.ts = *ts;
+        bbo.update_id = data.update_id;
+        Some(bbo)
+    }
+}
+
+// a snapshot replaces the side outright, a delta either brings a new top level or deletes the current one
+fn apply(side: &mut Option<PriceLevel>, levels: &[PriceLevel], kind: UpdateKind) {
+    if kind == UpdateKind::Snapshot {
+        *side = levels.iter().find(|level| !is_deleted(level)).cloned();
+        return;
+    }
+    if let Some(level) = levels.iter().find(|level| !is_deleted(level)) {
+        *side = Some(level.clone());
+    } else if levels.iter().any(|level| side.as_ref().is_some_and(|top| top.price == level.price)) {
+        *side = None;
+    }
+}
+
+fn is_deleted(level: &PriceLevel) -> bool {
+    level.size.parse::<Decimal>().is_ok_and(|size| size.is_zero())
+}
+
+// narrows a public event stream subscribed to orderbook.1 topics down to top of book updates
+pub fn bbo_stream(events: impl Stream<Item = crate::Result<PublicEvent>>) -> impl Stream<Item = crate::Result<Bbo>> {
+    let mut tracker = BboTracker::new();
+    events.filter_map(move |event| {
+        let bbo = match event {
+            Ok(event) => tracker.on_event(&event).cloned().map(Ok),
+            Err(err) => Some(Err(err)),
+        };
+        futures::future::ready(bbo)
+    })
+}
@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use crate::{execution::Fill, Category, OrderStatus, PriceLevel, Side};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OrderbookData {
+    #[serde(rename = "s")]
+    pub symbol: String,
+    #[serde(rename = "b")]
+    pub bids: Vec<PriceLevel>,
+    #[serde(rename = "a")]
+    pub asks: Vec<PriceLevel>,
+    #[serde(rename = "u")]
+    pub update_id: u64,
+    pub seq: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PublicTrade {
+    #[serde(rename = "T")]
+    pub time: u64,
+    #[serde(rename = "s")]
+    pub symbol: String,
+    #[serde(rename = "S")]
+    pub side: Side,
+    #[serde(rename = "v")]
+    pub size: String,
+    #[serde(rename = "p")]
+    pub price: String,
+    #[serde(rename = "L")]
+    pub tick_direction: Option<String>,
+    #[serde(rename = "i")]
+    pub trade_id: String,
+    #[serde(rename = "BT")]
+    pub block_trade: bool,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+// Linear/inverse tickers arrive as a snapshot followed by deltas carrying only the changed fields, hence the Options
+#[derive(Debug, Clone, Deserialize)]
+pub struct TickerData {
+    pub symbol: String,
+    #[serde(rename = "tickDirection")]
+    pub tick_direction: Option<String>,
+    #[serde(rename = "lastPrice")]
+    pub last_price: Option<String>,
+    #[serde(rename = "prevPrice24h")]
+    pub prev_price_24h: Option<String>,
+    #[serde(rename = "price24hPcnt")]
+    pub price_24h_pcnt: Option<String>,
+    #[serde(rename = "highPrice24h")]
+    pub high_price_24h: Option<String>,
+    #[serde(rename = "lowPrice24h")]
+    pub low_price_24h: Option<String>,
+    #[serde(rename = "prevPrice1h")]
+    pub prev_price_1h: Option<String>,
+    #[serde(rename = "markPrice")]
+    pub mark_price: Option<String>,
+    #[serde(rename = "indexPrice")]
+    pub index_price: Option<String>,
+    #[serde(rename = "openInterest")]
+    pub open_interest: Option<String>,
+    #[serde(rename = "openInterestValue")]
+    pub open_interest_value: Option<String>,
+    #[serde(rename = "turnover24h")]
+    pub turnover_24h: Option<String>,
+    #[serde(rename = "volume24h")]
+    pub volume_24h: Option<String>,
+    #[serde(rename = "fundingRate")]
+    pub funding_rate: Option<String>,
+    #[serde(rename = "nextFundingTime")]
+    pub next_funding_time: Option<String>,
+    #[serde(rename = "bid1Price")]
+    pub bid1_price: Option<String>,
+    #[serde(rename = "bid1Size")]
+    pub bid1_size: Option<String>,
+    #[serde(rename = "ask1Price")]
+    pub ask1_price: Option<String>,
+    #[serde(rename = "ask1Size")]
+    pub ask1_size: Option<String>,
+    #[serde(rename = "usdIndexPrice")]
+    pub usd_index_price: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct KlineData {
+    pub start: u64,
+    pub end: u64,
+    pub interval: String,
+    pub open: String,
+    pub close: String,
+    pub high: String,
+    pub low: String,
+    pub volume: String,
+    pub turnover: String,
+    pub confirm: bool,
+    pub timestamp: u64,
+}
+
+// Option tickers carry IV, greeks and the underlying instead of funding and open interest value fields
+#[derive(Debug, Clone, Deserialize)]
+pub struct OptionTickerData {
+    pub symbol: String,
+    #[serde(rename = "bidPrice")]
+    pub bid_price: String,
+    #[serde(rename = "bidSize")]
+    pub bid_size: String,
+    #[serde(rename = "bidIv")]
+    pub bid_iv: String,
+    #[serde(rename = "askPrice")]
+    pub ask_price: String,
+    #[serde(rename = "askSize")]
+    pub ask_size: String,
+    #[serde(rename = "askIv")]
+    pub ask_iv: String,
+    #[serde(rename = "lastPrice")]
+    pub last_price: String,
+    #[serde(rename = "highPrice24h")]
+    pub high_price_24h: String,
+    #[serde(rename = "lowPrice24h")]
+    pub low_price_24h: String,
+    #[serde(rename = "markPrice")]
+    pub mark_price: String,
+    #[serde(rename = "indexPrice")]
+    pub index_price: String,
+    #[serde(rename = "markPriceIv")]
+    pub mark_price_iv: String,
+    #[serde(rename = "underlyingPrice")]
+    pub underlying_price: String,
+    #[serde(rename = "openInterest")]
+    pub open_interest: String,
+    #[serde(rename = "turnover24h")]
+    pub turnover_24h: String,
+    #[serde(rename = "volume24h")]
+    pub volume_24h: String,
+    #[serde(rename = "totalVolume")]
+    pub total_volume: String,
+    #[serde(rename = "totalTurnover")]
+    pub total_turnover: String,
+    pub delta: String,
+    pub gamma: String,
+    pub vega: String,
+    pub theta: String,
+    #[serde(rename = "predictedDeliveryPrice")]
+    pub predicted_delivery_price: String,
+    #[serde(rename = "change24h")]
+    pub change_24h: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OrderUpdate {
+    pub category: Category,
+    #[serde(rename = "orderId")]
+    pub order_id: String,
+    #[serde(rename = "orderLinkId")]
+    pub order_link_id: String,
+    pub symbol: String,
+    pub side: Side,
+    #[serde(rename = "orderType")]
+    pub order_type: String,
+    pub price: String,
+    pub qty: String,
+    #[serde(rename = "orderStatus")]
+    pub order_status: OrderStatus,
+    #[serde(rename = "timeInForce")]
+    pub time_in_force: String,
+    #[serde(rename = "positionIdx")]
+    pub position_idx: i32,
+    #[serde(rename = "avgPrice")]
+    pub avg_price: String,
+    #[serde(rename = "leavesQty")]
+    pub leaves_qty: String,
+    #[serde(rename = "cumExecQty")]
+    pub cum_exec_qty: String,
+    #[serde(rename = "cumExecValue")]
+    pub cum_exec_value: String,
+    #[serde(rename = "cumExecFee")]
+    pub cum_exec_fee: String,
+    #[serde(rename = "rejectReason")]
+    pub reject_reason: String,
+    #[serde(rename = "cancelType")]
+    pub cancel_type: String,
+    #[serde(rename = "reduceOnly")]
+    pub reduce_only: bool,
+    #[serde(rename = "smpType")]
+    pub smp_type: Option<String>,
+    #[serde(rename = "smpGroup")]
+    pub smp_group: Option<i64>,
+    #[serde(rename = "smpOrderId")]
+    pub smp_order_id: Option<String>,
+    #[serde(rename = "createdTime")]
+    pub created_time: String,
+    #[serde(rename = "updatedTime")]
+    pub updated_time: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExecutionUpdate {
+    pub category: Category,
+    pub symbol: String,
+    #[serde(rename = "orderId")]
+    pub order_id: String,
+    #[serde(rename = "orderLinkId")]
+    pub order_link_id: String,
+    pub side: Side,
+    #[serde(rename = "orderPrice")]
+    pub order_price: String,
+    #[serde(rename = "orderQty")]
+    pub order_qty: String,
+    #[serde(rename = "leavesQty")]
+    pub leaves_qty: String,
+    #[serde(rename = "orderType")]
+    pub order_type: String,
+    #[serde(rename = "execId")]
+    pub exec_id: String,
+    #[serde(rename = "execPrice")]
+    pub exec_price: String,
+    #[serde(rename = "execQty")]
+    pub exec_qty: String,
+    #[serde(rename = "execValue")]
+    pub exec_value: String,
+    #[serde(rename = "execFee")]
+    pub exec_fee: String,
+    #[serde(rename = "execType")]
+    pub exec_type: String,
+    #[serde(rename = "execTime")]
+    pub exec_time: String,
+    #[serde(rename = "feeRate")]
+    pub fee_rate: String,
+    // only sent for spot, derivatives fees are charged in the settle coin
+    #[serde(rename = "feeCurrency")]
+    pub fee_currency: Option<String>,
+    #[serde(rename = "isMaker")]
+    pub is_maker: bool,
+    #[serde(rename = "markPrice")]
+    pub mark_price: String,
+    #[serde(rename = "closedSize")]
+    pub closed_size: Option<String>,
+    pub seq: Option<u64>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl Fill for ExecutionUpdate {
+    fn exec_id(&self) -> &str {
+        &self.exec_id
+    }
+
+    fn order_id(&self) -> &str {
+        &self.order_id
+    }
+
+    fn price(&self) -> Decimal {
+        self.exec_price.parse().unwrap_or_default()
+    }
+
+    fn qty(&self) -> Decimal {
+        self.exec_qty.parse().unwrap_or_default()
+    }
+
+    fn fee(&self) -> Decimal {
+        self.exec_fee.parse().unwrap_or_default()
+    }
+
+    fn fee_currency(&self) -> &str {
+        self.fee_currency.as_deref().unwrap_or_default()
+    }
+
+    fn is_maker(&self) -> bool {
+        self.is_maker
+    }
+
+    fn exec_time(&self) -> chrono::DateTime<chrono::Utc> {
+        self.exec_time.parse().ok().and_then(chrono::DateTime::from_timestamp_millis).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PositionUpdate {
+    pub category: Category,
+    pub symbol: String,
+    // empty string once the position is closed, hence not Side
+    pub side: String,
+    pub size: String,
+    #[serde(rename = "positionIdx")]
+    pub position_idx: i32,
+    #[serde(rename = "positionValue")]
+    pub position_value: String,
+    #[serde(rename = "entryPrice")]
+    pub entry_price: String,
+    #[serde(rename = "markPrice")]
+    pub mark_price: String,
+    pub leverage: String,
+    #[serde(rename = "liqPrice")]
+    pub liq_price: String,
+    #[serde(rename = "positionIM")]
+    pub position_im: String,
+    #[serde(rename = "positionMM")]
+    pub position_mm: String,
+    #[serde(rename = "takeProfit")]
+    pub take_profit: String,
+    #[serde(rename = "stopLoss")]
+    pub stop_loss: String,
+    #[serde(rename = "unrealisedPnl")]
+    pub unrealised_pnl: String,
+    #[serde(rename = "cumRealisedPnl")]
+    pub cum_realised_pnl: String,
+    #[serde(rename = "positionStatus")]
+    pub position_status: String,
+    #[serde(rename = "updatedTime")]
+    pub updated_time: String,
+    pub seq: Option<i64>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
@@ -0,0 +1,206 @@
+use std::{collections::VecDeque, fmt, time::Duration};
+
+use futures::Stream;
+
+use super::{
+    decode_failed, typed, DataMessage, DecodePolicies, ExecutionUpdate, Frame, OrderUpdate, PositionUpdate,
+    Socket, SubscriptionEvent, Subscriptions, WsConnection,
+};
+#[cfg(feature = "trade")]
+use crate::{
+    execution::{FillAggregator, OrderTracker},
+    trade::{Resync, ResyncChange},
+};
+use crate::{
+    account::WalletBalance,
+    clock::{Clock, SystemClock},
+    shutdown::Shutdown, sign::Credentials, Category, Client};
+
+// how far in the future the auth signature expires, it only has to outlive the handshake
+pub const AUTH_EXPIRY: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PrivateTopic {
+    // None subscribes across all categories
+    Order(Option<Category>),
+    Execution(Option<Category>),
+    Position(Option<Category>),
+    Wallet,
+    Custom(String),
+}
+
+impl fmt::Display for PrivateTopic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (name, category) = match self {
+            Self::Order(category) => ("order", category),
+            Self::Execution(category) => ("execution", category),
+            Self::Position(category) => ("position", category),
+            Self::Wallet => return f.write_str("wallet"),
+            Self::Custom(topic) => return f.write_str(topic),
+        };
+        match category {
+            Some(category) => write!(f, "{name}.{}", category.as_str()),
+            None => f.write_str(name),
+        }
+    }
+}
+
+// Every private topic multiplexed into one event type. Disconnect is the last event when the connection drops, shutting
+// the client down ends the stream without one
+#[derive(Debug, Clone)]
+pub enum PrivateEvent {
+    Order { topic: String, creation_time: u64, data: Vec<OrderUpdate> },
+    Fill { topic: String, creation_time: u64, data: Vec<ExecutionUpdate> },
+    Position { topic: String, creation_time: u64, data: Vec<PositionUpdate> },
+    Balance { topic: String, creation_time: u64, data: Vec<WalletBalance> },
+    Raw { topic: String, creation_time: Option<u64>, data: serde_json::Value },
+    Subscription(SubscriptionEvent<PrivateTopic>),
+    // last_update is the creationTime of the last data message, where a Client::resync after reconnecting starts from
+    Disconnect { last_update: Option<u64> },
+    // what a REST resync found the stream had missed, already applied to the tracker and aggregator it was reconciled into
+    #[cfg(feature = "trade")]
+    Resync { category: Category, changes: Vec<ResyncChange> },
+}
+
+// Account updates over a caller supplied connection to Environment::private_ws_url, authenticated before any subscription
+pub struct PrivateWsClient<S> {
+    socket: Socket<S>,
+    subscriptions: Subscriptions<PrivateTopic>,
+    policies: DecodePolicies,
+    decode_failures: u64,
+    pending: VecDeque<PrivateEvent>,
+    last_update: Option<u64>,
+    disconnected: bool,
+}
+
+impl<S: WsConnection> PrivateWsClient<S> {
+    // sends the auth request and waits for Bybit to accept it
+    pub async fn connect(conn: S, api_key: &str, secret: &str) -> crate::Result<Self> {
+        Self::connect_with(conn, &Credentials::hmac(api_key, secret)).await
+    }
+
+    pub async fn connect_with(conn: S, credentials: &Credentials) -> crate::Result<Self> {
+        Self::connect_at(conn, credentials, &SystemClock).await
+    }
+
+    // the auth signature expires relative to clock rather than the system time
+    pub async fn connect_at(conn: S, credentials: &Credentials, clock: &dyn Clock) -> crate::Result<Self> {
+        let mut client = Self {
+            socket: Socket::new(conn),
+            subscriptions: Subscriptions::new(),
+            policies: DecodePolicies::default(),
+            decode_failures: 0,
+            pending: VecDeque::new(),
+            last_update: None,
+            disconnected: false,
+        };
+        client.socket.authenticate(credentials, clock.now()).await?;
+        Ok(client)
+    }
+
+    pub fn with_decode_policies(mut self, policies: DecodePolicies) -> Self {
+        self.policies = policies;
+        self
+    }
+
+    pub fn with_ping_interval(mut self, interval: Duration) -> Self {
+        self.socket.set_ping_interval(interval);
+        self
+    }
+
+    pub fn with_shutdown(mut self, shutdown: &Shutdown) -> Self {
+        self.socket.set_shutdown(shutdown);
+        self
+    }
+
+    pub fn topics(&self) -> &[PrivateTopic] {
+        self.subscriptions.active()
+    }
+
+    pub fn decode_failures(&self) -> u64 {
+        self.decode_failures
+    }
+
+    pub async fn subscribe(&mut self, topics: Vec<PrivateTopic>) -> crate::Result<String> {
+        let req_id = self.socket.send("subscribe", Subscriptions::args(&topics)).await?;
+        self.subscriptions.request(req_id.clone(), "subscribe", topics);
+        Ok(req_id)
+    }
+
+    pub async fn unsubscribe(&mut self, topics: Vec<PrivateTopic>) -> crate::Result<String> {
+        let req_id = self.socket.send("unsubscribe", Subscriptions::args(&topics)).await?;
+        self.subscriptions.request(req_id.clone(), "unsubscribe", topics);
+        Ok(req_id)
+    }
+
+    // Reconciles a REST resync into tracker and fills and queues what changed as a Resync event ahead of the next frame.
+    // After a Disconnect, subscribe the replacement client first and resync from the Disconnect's last_update, so
+    // nothing falls between the REST view and the stream
+    #[cfg(feature = "trade")]
+    pub fn apply_resync(&mut self, resync: &Resync, tracker: &mut OrderTracker, fills: &mut FillAggregator) {
+        let changes = resync.reconcile(tracker, fills);
+        self.pending.push_back(PrivateEvent::Resync { category: resync.category, changes });
+    }
+
+    pub async fn next(&mut self) -> Option<crate::Result<PrivateEvent>> {
+        if let Some(event) = self.pending.pop_front() {
+            return Some(Ok(event));
+        }
+        if self.disconnected {
+            return None;
+        }
+        loop {
+            let frame = match self.socket.next().await {
+                Some(Ok(frame)) => frame,
+                Some(Err(err)) => return Some(Err(err)),
+                None if self.socket.is_shut_down() => return None,
+                None => {
+                    self.disconnected = true;
+                    return Some(Ok(PrivateEvent::Disconnect { last_update: self.last_update }));
+                }
+            };
+            let event = match frame {
+                Frame::Control(control) => self.subscriptions.ack(control).map(|event| Ok(PrivateEvent::Subscription(event))),
+                Frame::Data(message) => self.decode(message),
+            };
+            if event.is_some() {
+                return event;
+            }
+        }
+    }
+
+    pub fn into_stream(self) -> impl Stream<Item = crate::Result<PrivateEvent>> {
+        futures::stream::unfold(self, |mut client| async move {
+            let event = client.next().await?;
+            Some((event, client))
+        })
+    }
+
+    fn decode(&mut self, message: DataMessage) -> Option<crate::Result<PrivateEvent>> {
+        self.last_update = message.creation_time.or(self.last_update);
+        let creation_time = message.creation_time.unwrap_or_default();
+        let prefix = message.topic.split('.').next().unwrap_or_default();
+        let event = match prefix {
+            "order" => typed(&message).map(|data| PrivateEvent::Order { topic: message.topic.clone(), creation_time, data }),
+            "execution" => typed(&message).map(|data| PrivateEvent::Fill { topic: message.topic.clone(), creation_time, data }),
+            "position" => typed(&message).map(|data| PrivateEvent::Position { topic: message.topic.clone(), creation_time, data }),
+            "wallet" => typed(&message).map(|data| PrivateEvent::Balance { topic: message.topic.clone(), creation_time, data }),
+            _ => return Some(Ok(raw(message))),
+        };
+        match event {
+            Ok(event) => Some(Ok(event)),
+            Err(err) => decode_failed(&self.policies, &mut self.decode_failures, message, err).map(|message| message.map(raw)),
+        }
+    }
+}
+
+impl Client {
+    // authenticates with this client's credentials and ties the connection to the client's shutdown
+    pub async fn private_ws<S: WsConnection>(&self, conn: S) -> crate::Result<PrivateWsClient<S>> {
+        Ok(PrivateWsClient::connect_at(conn, self.credentials()?, self.clock.as_ref()).await?.with_shutdown(&self.shutdown))
+    }
+}
+
+fn raw(message: DataMessage) -> PrivateEvent {
+    PrivateEvent::Raw { topic: message.topic, creation_time: message.creation_time, data: message.data }
+}
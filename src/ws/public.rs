@@ -0,0 +1,149 @@
+use std::{fmt, time::Duration};
+
+use futures::Stream;
+
+use super::{
+    decode_failed, typed, DataMessage, DecodePolicies, Frame, KlineData, OptionTickerData, OrderbookData, PublicTrade,
+    Socket, SubscriptionEvent, Subscriptions, TickerData, UpdateKind, WsConnection,
+};
+use crate::{is_option_symbol, shutdown::Shutdown, Interval};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Topic {
+    Orderbook { depth: u32, symbol: String },
+    Trade { symbol: String },
+    Ticker { symbol: String },
+    Kline { interval: Interval, symbol: String },
+    // any topic the crate doesn't model yet, delivered as Raw events
+    Custom(String),
+}
+
+impl fmt::Display for Topic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Orderbook { depth, symbol } => write!(f, "orderbook.{depth}.{symbol}"),
+            Self::Trade { symbol } => write!(f, "publicTrade.{symbol}"),
+            Self::Ticker { symbol } => write!(f, "tickers.{symbol}"),
+            Self::Kline { interval, symbol } => write!(f, "kline.{}.{symbol}", interval.as_str()),
+            Self::Custom(topic) => f.write_str(topic),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PublicEvent {
+    Orderbook { topic: String, kind: UpdateKind, ts: u64, cts: Option<u64>, data: OrderbookData },
+    Trades { topic: String, ts: u64, data: Vec<PublicTrade> },
+    Ticker { topic: String, kind: UpdateKind, ts: u64, data: Box<TickerData> },
+    // option orderbooks share OrderbookData, only their tickers differ
+    OptionTicker { topic: String, ts: u64, data: Box<OptionTickerData> },
+    Kline { topic: String, ts: u64, data: Vec<KlineData> },
+    // unmodelled topics, and modelled ones that failed to decode under DecodePolicy::Raw
+    Raw { topic: String, kind: Option<String>, ts: Option<u64>, data: serde_json::Value },
+    Subscription(SubscriptionEvent<Topic>),
+}
+
+// Public market data over a caller supplied connection to Environment::public_ws_url,
+// topics can be added and removed at any point and are confirmed through Subscription events
+pub struct PublicWsClient<S> {
+    socket: Socket<S>,
+    subscriptions: Subscriptions<Topic>,
+    policies: DecodePolicies,
+    decode_failures: u64,
+}
+
+impl<S: WsConnection> PublicWsClient<S> {
+    pub fn new(conn: S) -> Self {
+        Self {
+            socket: Socket::new(conn),
+            subscriptions: Subscriptions::new(),
+            policies: DecodePolicies::default(),
+            decode_failures: 0,
+        }
+    }
+
+    pub fn with_decode_policies(mut self, policies: DecodePolicies) -> Self {
+        self.policies = policies;
+        self
+    }
+
+    pub fn with_ping_interval(mut self, interval: Duration) -> Self {
+        self.socket.set_ping_interval(interval);
+        self
+    }
+
+    // closes the connection and ends the stream when the client is shut down
+    pub fn with_shutdown(mut self, shutdown: &Shutdown) -> Self {
+        self.socket.set_shutdown(shutdown);
+        self
+    }
+
+    pub fn topics(&self) -> &[Topic] {
+        self.subscriptions.active()
+    }
+
+    pub fn decode_failures(&self) -> u64 {
+        self.decode_failures
+    }
+
+    // spot accepts at most 10 topics per request
+    pub async fn subscribe(&mut self, topics: Vec<Topic>) -> crate::Result<String> {
+        let req_id = self.socket.send("subscribe", Subscriptions::args(&topics)).await?;
+        self.subscriptions.request(req_id.clone(), "subscribe", topics);
+        Ok(req_id)
+    }
+
+    pub async fn unsubscribe(&mut self, topics: Vec<Topic>) -> crate::Result<String> {
+        let req_id = self.socket.send("unsubscribe", Subscriptions::args(&topics)).await?;
+        self.subscriptions.request(req_id.clone(), "unsubscribe", topics);
+        Ok(req_id)
+    }
+
+    pub async fn next(&mut self) -> Option<crate::Result<PublicEvent>> {
+        loop {
+            let frame = match self.socket.next().await? {
+                Ok(frame) => frame,
+                Err(err) => return Some(Err(err)),
+            };
+            let event = match frame {
+                Frame::Control(control) => self.subscriptions.ack(control).map(|event| Ok(PublicEvent::Subscription(event))),
+                Frame::Data(message) => decode_public(&self.policies, &mut self.decode_failures, message),
+            };
+            if event.is_some() {
+                return event;
+            }
+        }
+    }
+
+    pub fn into_stream(self) -> impl Stream<Item = crate::Result<PublicEvent>> {
+        futures::stream::unfold(self, |mut client| async move {
+            let event = client.next().await?;
+            Some((event, client))
+        })
+    }
+}
+
+// shared with the replay reader so recorded data decodes exactly like the live stream
+pub(crate) fn decode_public(policies: &DecodePolicies, failures: &mut u64, message: DataMessage) -> Option<crate::Result<PublicEvent>> {
+    let kind = UpdateKind::parse(message.kind.as_deref());
+    let ts = message.ts.unwrap_or_default();
+    let prefix = message.topic.split('.').next().unwrap_or_default();
+    let event = match prefix {
+        "orderbook" => typed(&message).map(|data| PublicEvent::Orderbook { topic: message.topic.clone(), kind, ts, cts: message.cts, data }),
+        "publicTrade" => typed(&message).map(|data| PublicEvent::Trades { topic: message.topic.clone(), ts, data }),
+        "tickers" if is_option_symbol(message.topic.rsplit('.').next().unwrap_or_default()) => {
+            typed(&message).map(|data| PublicEvent::OptionTicker { topic: message.topic.clone(), ts, data })
+        }
+        "tickers" => typed(&message).map(|data| PublicEvent::Ticker { topic: message.topic.clone(), kind, ts, data }),
+        "kline" => typed(&message).map(|data| PublicEvent::Kline { topic: message.topic.clone(), ts, data }),
+        _ => return Some(Ok(raw(message))),
+    };
+    match event {
+        Ok(event) => Some(Ok(event)),
+        Err(err) => decode_failed(policies, failures, message, err).map(|message| message.map(raw)),
+    }
+}
+
+fn raw(message: DataMessage) -> PublicEvent {
+    PublicEvent::Raw { topic: message.topic, kind: message.kind, ts: message.ts, data: message.data }
+}
@@ -0,0 +1,361 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use futures::FutureExt;
+use rust_decimal::Decimal;
+
+use super::{ControlMessage, Frame, Socket, WsConnection};
+use crate::{
+    clock::{Clock, SystemClock},
+    shutdown::Shutdown,
+    sign::Credentials,
+    trade::{AmendOrderRequest, CancelOrderRequest, Order, OrderRef, PlaceOrderRequest, PlaceOrderResponse},
+    BybitError, Category, Client, Error,
+};
+
+// how long an order request may go unanswered before its state is treated as unknown
+pub const TRADE_TIMEOUT: Duration = Duration::from_secs(5);
+// timed out requests are remembered this long for a late ack
+const LATE_ACK_WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone)]
+pub enum TradeRequest {
+    Create(PlaceOrderRequest),
+    Amend(AmendOrderRequest),
+    Cancel(CancelOrderRequest),
+}
+
+impl TradeRequest {
+    pub fn op(&self) -> &'static str {
+        match self {
+            Self::Create(_) => "order.create",
+            Self::Amend(_) => "order.amend",
+            Self::Cancel(_) => "order.cancel",
+        }
+    }
+
+    pub fn category(&self) -> Category {
+        match self {
+            Self::Create(request) => request.category,
+            Self::Amend(request) => request.category,
+            Self::Cancel(request) => request.category,
+        }
+    }
+
+    // None for a create without an orderLinkId, TradeWsClient assigns one before sending
+    pub fn order(&self) -> Option<OrderRef> {
+        match self {
+            Self::Create(request) => request.order_link_id.clone().map(OrderRef::OrderLinkId),
+            Self::Amend(request) => Some(request.order.clone()),
+            Self::Cancel(request) => Some(request.order.clone()),
+        }
+    }
+
+    fn args(&self) -> crate::Result<serde_json::Value> {
+        let args = match self {
+            Self::Create(request) => serde_json::to_value(request),
+            Self::Amend(request) => serde_json::to_value(request),
+            Self::Cancel(request) => serde_json::to_value(request),
+        }
+        .map_err(|err| Error::Serialization(err.to_string()))?;
+        Ok(serde_json::Value::Array(vec![args]))
+    }
+
+    // creates are only safe to resend under an orderLinkId, a duplicate is then rejected instead of placed twice
+    fn with_order_link_id(mut self) -> Self {
+        if let Self::Create(request) = &mut self
+            && request.order_link_id.is_none()
+        {
+            request.order_link_id = Some(uuid::Uuid::new_v4().simple().to_string());
+        }
+        self
+    }
+
+    // whether the order as looked up shows this request took effect. An amend counts once every field it sets that
+    // the order reports back matches
+    fn applied(&self, order: &Order) -> bool {
+        match self {
+            Self::Create(_) => true,
+            Self::Amend(request) => [
+                (request.qty, &order.qty),
+                (request.price, &order.price),
+                (request.trigger_price, &order.trigger_price),
+                (request.take_profit, &order.take_profit),
+                (request.stop_loss, &order.stop_loss),
+            ]
+            .into_iter()
+            .all(|(wanted, actual)| wanted.is_none_or(|wanted| actual.parse::<Decimal>().is_ok_and(|actual| actual == wanted))),
+            Self::Cancel(_) => order.order_status.is_terminal(),
+        }
+    }
+
+    async fn send_rest<F, R, E>(&self, client: &Client, recv_window: &Duration, send: F) -> crate::Result<PlaceOrderResponse>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        match self {
+            Self::Create(request) => client.place_order(request, recv_window)?.send(send).await,
+            Self::Amend(request) => client.amend_order(request, recv_window)?.send(send).await,
+            Self::Cancel(request) => client.cancel_order(request, recv_window)?.send(send).await,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PendingTrade {
+    pub req_id: String,
+    pub request: TradeRequest,
+    pub sent_at: Instant,
+}
+
+#[derive(Debug)]
+pub enum TradeEvent {
+    // late acks for timed out requests come through here too
+    Ack { pending: PendingTrade, result: crate::Result<PlaceOrderResponse> },
+    // no ack within the timeout, the order's state is unknown until it's looked up over REST
+    TimedOut(PendingTrade),
+    // a timed out request looked up over REST, None when Bybit has no such order
+    Resolved { pending: PendingTrade, order: crate::Result<Option<Box<Order>>> },
+}
+
+// Order entry over a caller supplied connection to Environment::trade_ws_url. Requests are answered by req_id, any
+// request left unanswered for longer than the timeout comes out of next as TimedOut
+pub struct TradeWsClient<S> {
+    socket: Socket<S>,
+    pending: HashMap<String, PendingTrade>,
+    late: HashMap<String, PendingTrade>,
+    // events next read while execute waited for its own ack
+    buffered: VecDeque<TradeEvent>,
+    timeout: Duration,
+    recv_window: Duration,
+    clock: Arc<dyn Clock>,
+}
+
+impl<S: WsConnection> TradeWsClient<S> {
+    pub async fn connect(conn: S, api_key: &str, secret: &str) -> crate::Result<Self> {
+        Self::connect_with(conn, &Credentials::hmac(api_key, secret)).await
+    }
+
+    pub async fn connect_with(conn: S, credentials: &Credentials) -> crate::Result<Self> {
+        Self::connect_at(conn, credentials, Arc::new(SystemClock)).await
+    }
+
+    // auth and the timestamp header of every request are taken from clock rather than the system time
+    pub async fn connect_at(conn: S, credentials: &Credentials, clock: Arc<dyn Clock>) -> crate::Result<Self> {
+        let mut client = Self {
+            socket: Socket::new(conn),
+            pending: HashMap::new(),
+            late: HashMap::new(),
+            buffered: VecDeque::new(),
+            timeout: TRADE_TIMEOUT,
+            recv_window: Duration::from_secs(5),
+            clock,
+        };
+        let now = client.clock.now();
+        client.socket.authenticate(credentials, now).await?;
+        Ok(client)
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_recv_window(mut self, recv_window: Duration) -> Self {
+        self.recv_window = recv_window;
+        self
+    }
+
+    pub fn with_ping_interval(mut self, interval: Duration) -> Self {
+        self.socket.set_ping_interval(interval);
+        self
+    }
+
+    pub fn with_shutdown(mut self, shutdown: &Shutdown) -> Self {
+        self.socket.set_shutdown(shutdown);
+        self
+    }
+
+    pub fn pending(&self) -> impl Iterator<Item = &PendingTrade> {
+        self.pending.values()
+    }
+
+    // sends the request and returns its req_id, the outcome comes out of next
+    pub async fn submit(&mut self, request: TradeRequest) -> crate::Result<String> {
+        let request = request.with_order_link_id();
+        let req_id = self.socket.next_req_id();
+        let message = serde_json::json!({
+            "reqId": req_id,
+            "header": {
+                "X-BAPI-TIMESTAMP": self.clock.now().timestamp_millis().to_string(),
+                "X-BAPI-RECV-WINDOW": self.recv_window.as_millis().to_string(),
+            },
+            "op": request.op(),
+            "args": request.args()?,
+        });
+        self.socket.send_message(message).await?;
+        self.pending.insert(req_id.clone(), PendingTrade { req_id: req_id.clone(), request, sent_at: Instant::now() });
+        Ok(req_id)
+    }
+
+    pub async fn next(&mut self) -> Option<crate::Result<TradeEvent>> {
+        if let Some(event) = self.buffered.pop_front() {
+            return Some(Ok(event));
+        }
+        self.next_frame().await
+    }
+
+    // like next, but timed out requests are looked up over REST and come out as Resolved
+    pub async fn next_resolved<F, R, E>(&mut self, client: &Client, recv_window: &Duration, send: F) -> Option<crate::Result<TradeEvent>>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        match self.next().await? {
+            Ok(TradeEvent::TimedOut(pending)) => Some(Ok(resolve(pending, client, recv_window, &send).await)),
+            event => Some(event),
+        }
+    }
+
+    // Sends the request and waits for its ack, other events are kept for next. A request that times out is looked up
+    // over REST and, when Bybit never saw it, sent over REST instead
+    pub async fn execute<F, R, E>(&mut self, request: TradeRequest, client: &Client, recv_window: &Duration, send: F) -> crate::Result<PlaceOrderResponse>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let req_id = self.submit(request).await?;
+        loop {
+            let event = match self.next_frame().await {
+                Some(Ok(event)) => event,
+                Some(Err(err)) => return Err(err),
+                None => return Err(Error::WebSocket("trade connection closed".into())),
+            };
+            match event {
+                TradeEvent::Ack { pending, result } if pending.req_id == req_id => return result,
+                TradeEvent::TimedOut(pending) if pending.req_id == req_id => {
+                    self.late.remove(&req_id);
+                    return after_timeout(pending.request, client, recv_window, &send).await;
+                }
+                event => self.buffered.push_back(event),
+            }
+        }
+    }
+
+    async fn next_frame(&mut self) -> Option<crate::Result<TradeEvent>> {
+        loop {
+            if let Some(pending) = self.expire() {
+                return Some(Ok(TradeEvent::TimedOut(pending)));
+            }
+            let deadline = self.pending.values().map(|pending| pending.sent_at + self.timeout).min();
+            let frame = match deadline {
+                // a frame half read when the deadline fires is dropped with the future, the connection only
+                // yields whole frames so nothing is lost
+                Some(deadline) => futures::select_biased! {
+                    frame = self.socket.next().fuse() => frame,
+                    _ = futures_timer::Delay::new(deadline.saturating_duration_since(Instant::now())).fuse() => continue,
+                },
+                None => self.socket.next().await,
+            };
+            match frame? {
+                Ok(Frame::Control(control)) => {
+                    if let Some(event) = self.ack(control) {
+                        return Some(Ok(event));
+                    }
+                }
+                Ok(Frame::Data(_)) => {}
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+
+    fn expire(&mut self) -> Option<PendingTrade> {
+        let now = Instant::now();
+        self.late.retain(|_, pending| now.duration_since(pending.sent_at) < LATE_ACK_WINDOW);
+        let req_id = self.pending.values()
+            .filter(|pending| now.duration_since(pending.sent_at) >= self.timeout)
+            .min_by_key(|pending| pending.sent_at)?
+            .req_id
+            .clone();
+        let pending = self.pending.remove(&req_id)?;
+        self.late.insert(req_id, pending.clone());
+        Some(pending)
+    }
+
+    fn ack(&mut self, control: ControlMessage) -> Option<TradeEvent> {
+        let req_id = control.req_id.as_ref()?;
+        let pending = self.pending.remove(req_id).or_else(|| self.late.remove(req_id))?;
+        let result = match control.ret_code {
+            Some(0) => control.data
+                .ok_or_else(|| Error::Invalid("ack without data".into()))
+                .and_then(|data| serde_json::from_value(data).map_err(|err| Error::Serialization(err.to_string()))),
+            code => Err(Error::Api(BybitError::from_ack(code.unwrap_or(-1), control.ret_msg.clone(), control.trace_id()))),
+        };
+        Some(TradeEvent::Ack { pending, result })
+    }
+}
+
+async fn resolve<F, R, E>(pending: PendingTrade, client: &Client, recv_window: &Duration, send: F) -> TradeEvent
+where F: Fn(http::Request<String>) -> R,
+    R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+    E: Into<crate::BoxError>
+{
+    let order = match pending.request.order() {
+        Some(order) => client.find_order(pending.request.category(), order, recv_window, send).await.map(|order| order.map(Box::new)),
+        None => Ok(None),
+    };
+    TradeEvent::Resolved { pending, order }
+}
+
+// the lookup decides: a create Bybit never saw, or an amend or cancel that didn't take effect, is sent again over
+// REST, anything that did is reported from the order as it stands
+async fn after_timeout<F, R, E>(request: TradeRequest, client: &Client, recv_window: &Duration, send: F) -> crate::Result<PlaceOrderResponse>
+where F: Fn(http::Request<String>) -> R,
+    R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+    E: Into<crate::BoxError>
+{
+    let found = match request.order() {
+        Some(order) => client.find_order(request.category(), order, recv_window, &send).await?,
+        None => None,
+    };
+    match found {
+        Some(order) if request.applied(&order) => {
+            Ok(PlaceOrderResponse { order_id: order.order_id, order_link_id: order.order_link_id, extra: order.extra })
+        }
+        _ => request.send_rest(client, recv_window, &send).await,
+    }
+}
+
+impl Client {
+    pub async fn trade_ws<S: WsConnection>(&self, conn: S) -> crate::Result<TradeWsClient<S>> {
+        Ok(TradeWsClient::connect_at(conn, self.credentials()?, self.clock.clone()).await?.with_shutdown(&self.shutdown))
+    }
+
+    // WS first with REST as the fallback, pass None while the trade connection is down. Either way the result is
+    // the same PlaceOrderResponse
+    pub async fn route_order<S: WsConnection, F, R, E>(
+        &self,
+        ws: Option<&mut TradeWsClient<S>>,
+        request: TradeRequest,
+        recv_window: &Duration,
+        send: F,
+    ) -> crate::Result<PlaceOrderResponse>
+    where F: Fn(http::Request<String>) -> R,
+        R: std::future::Future<Output = Result<bytes::Bytes, E>>,
+        E: Into<crate::BoxError>
+    {
+        let request = request.with_order_link_id();
+        match ws {
+            Some(ws) => match ws.execute(request.clone(), self, recv_window, &send).await {
+                // the connection failed before an ack, the request may still have gone out
+                Err(Error::Transport(_) | Error::WebSocket(_)) => after_timeout(request, self, recv_window, &send).await,
+                result => result,
+            },
+            None => request.send_rest(self, recv_window, &send).await,
+        }
+    }
+}
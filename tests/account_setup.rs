@@ -0,0 +1,48 @@
+#![cfg(feature = "position")]
+
+use std::{sync::Mutex, time::Duration};
+
+use bybit_rs::{
+    position::{AccountSetup, SetupChange},
+    Client, MarginMode,
+};
+use bytes::Bytes;
+use futures::executor::block_on;
+
+fn account_info(margin_mode: &str) -> String {
+    format!(
+        r#"{{"retCode":0,"retMsg":"OK","result":{{"unifiedMarginStatus":5,"marginMode":"{margin_mode}","isMasterTrader":false,
+        "spotHedgingStatus":"OFF","dcpStatus":"OFF","timeWindow":10,"smpGroup":0,"updatedTime":"1700000000000"}},"time":0}}"#
+    )
+}
+
+fn ensure(current: &str) -> (Vec<SetupChange>, Vec<String>) {
+    let paths = Mutex::new(Vec::new());
+    let info = account_info(current);
+    let send = |request: http::Request<String>| {
+        paths.lock().unwrap().push(request.uri().path().to_string());
+        let body = match request.uri().path() {
+            "/v5/account/info" => info.clone(),
+            _ => r#"{"retCode":0,"retMsg":"OK","result":{"reasons":[]},"time":0}"#.to_string(),
+        };
+        async move { Ok::<_, std::io::Error>(Bytes::from(body)) }
+    };
+    let client = Client::new("key".to_string(), "secret".to_string());
+    let desired = AccountSetup::new().with_margin_mode(MarginMode::Cross);
+    let changes = block_on(client.ensure_account_setup(&desired, &Duration::from_secs(5), send)).unwrap();
+    (changes, paths.into_inner().unwrap())
+}
+
+#[test]
+fn switches_a_differing_margin_mode() {
+    let (changes, paths) = ensure("ISOLATED_MARGIN");
+    assert_eq!(changes, vec![SetupChange::MarginMode(MarginMode::Cross)]);
+    assert_eq!(paths, ["/v5/account/info", "/v5/account/set-margin-mode"]);
+}
+
+#[test]
+fn leaves_a_matching_margin_mode_alone() {
+    let (changes, paths) = ensure("REGULAR_MARGIN");
+    assert!(changes.is_empty());
+    assert_eq!(paths, ["/v5/account/info"]);
+}
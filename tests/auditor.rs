@@ -0,0 +1,31 @@
+#![cfg(all(feature = "ws", feature = "position"))]
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use bybit_rs::{
+    state::{AccountState, Auditor},
+    Client, Error,
+};
+use bytes::Bytes;
+use futures::{channel::mpsc, executor::block_on};
+
+#[test]
+fn a_poisoned_state_ends_the_run() {
+    let state = Arc::new(Mutex::new(AccountState::new()));
+    let poisoner = state.clone();
+    let panicked = std::thread::spawn(move || {
+        let _state = poisoner.lock().unwrap();
+        panic!("poison the state");
+    });
+    assert!(panicked.join().is_err());
+    assert!(state.is_poisoned());
+
+    let client = Client::new("key".to_string(), "secret".to_string());
+    let send = |_| async { Ok::<_, std::io::Error>(Bytes::from_static(br#"{"retCode":0,"retMsg":"OK","result":{"list":[]},"time":0}"#)) };
+    let (discrepancies, _) = mpsc::unbounded();
+    let result = block_on(Auditor::new(Vec::new(), Duration::ZERO).run(&client, &state, send, discrepancies));
+    assert!(matches!(result, Err(Error::Unexpected(_))));
+}
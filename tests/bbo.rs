@@ -0,0 +1,48 @@
+#![cfg(feature = "ws")]
+
+use bybit_rs::{
+    ws::{BboTracker, OrderbookData, PublicEvent, UpdateKind},
+    PriceLevel,
+};
+
+fn level(price: &str, size: &str) -> PriceLevel {
+    PriceLevel::from((price.to_string(), size.to_string()))
+}
+
+fn book(kind: UpdateKind, update_id: u64, bids: Vec<PriceLevel>, asks: Vec<PriceLevel>) -> PublicEvent {
+    PublicEvent::Orderbook {
+        topic: "orderbook.1.BTCUSDT".to_string(),
+        kind,
+        ts: update_id,
+        cts: None,
+        data: OrderbookData { symbol: "BTCUSDT".to_string(), bids, asks, update_id, seq: None },
+    }
+}
+
+#[test]
+fn snapshot_clears_an_empty_side() {
+    let mut tracker = BboTracker::new();
+    tracker.on_event(&book(UpdateKind::Snapshot, 1, vec![level("100", "1")], vec![level("101", "2")]));
+    let bbo = tracker.on_event(&book(UpdateKind::Snapshot, 2, vec![level("100", "3")], vec![])).unwrap();
+    assert_eq!(bbo.bid, Some(level("100", "3")));
+    assert_eq!(bbo.ask, None);
+}
+
+#[test]
+fn delta_deleting_the_top_clears_the_side() {
+    let mut tracker = BboTracker::new();
+    tracker.on_event(&book(UpdateKind::Snapshot, 1, vec![level("100", "1")], vec![level("101", "2")]));
+    let bbo = tracker.on_event(&book(UpdateKind::Delta, 2, vec![level("100", "0")], vec![])).unwrap();
+    assert_eq!(bbo.bid, None);
+    assert_eq!(bbo.ask, Some(level("101", "2")));
+}
+
+#[test]
+fn delta_keeps_sides_it_does_not_touch() {
+    let mut tracker = BboTracker::new();
+    tracker.on_event(&book(UpdateKind::Snapshot, 1, vec![level("100", "1")], vec![level("101", "2")]));
+    // deleting a level that isn't the current top leaves the top alone
+    let bbo = tracker.on_event(&book(UpdateKind::Delta, 2, vec![level("99", "0")], vec![level("101", "0"), level("102", "4")])).unwrap();
+    assert_eq!(bbo.bid, Some(level("100", "1")));
+    assert_eq!(bbo.ask, Some(level("102", "4")));
+}
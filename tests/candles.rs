@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use bybit_rs::aggregate::{Candle, CandleBuilder, CandleUpdate};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+const MINUTE: Duration = Duration::from_secs(60);
+
+fn at(seconds: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(1_700_000_040 + seconds, 0).unwrap()
+}
+
+fn dec(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
+
+fn closed(updates: &[CandleUpdate]) -> Vec<&Candle> {
+    updates.iter().filter_map(|update| match update {
+        CandleUpdate::Closed(candle) => Some(candle),
+        CandleUpdate::Forming(_) => None,
+    }).collect()
+}
+
+#[test]
+fn trades_build_an_epoch_aligned_candle() {
+    let mut builder = CandleBuilder::new(MINUTE);
+    builder.on_trade(at(1), dec("100"), dec("1"));
+    builder.on_trade(at(20), dec("105"), dec("2"));
+    let updates = builder.on_trade(at(59), dec("98"), dec("1"));
+
+    let [CandleUpdate::Forming(candle)] = &updates[..] else {
+        panic!("{updates:?}");
+    };
+    assert_eq!(candle.start, at(0));
+    assert_eq!(candle.end(), at(60));
+    assert_eq!((candle.open, candle.high, candle.low, candle.close), (dec("100"), dec("105"), dec("98"), dec("98")));
+    assert_eq!(candle.volume, dec("4"));
+    assert_eq!(candle.turnover, dec("408"));
+    assert_eq!(candle.trades, 3);
+}
+
+#[test]
+fn next_interval_closes_the_forming_candle() {
+    let mut builder = CandleBuilder::new(MINUTE);
+    builder.on_trade(at(10), dec("100"), dec("1"));
+    let updates = builder.on_trade(at(61), dec("101"), dec("1"));
+
+    assert_eq!(closed(&updates).len(), 1);
+    assert_eq!(closed(&updates)[0].close, dec("100"));
+    assert!(matches!(updates.last(), Some(CandleUpdate::Forming(candle)) if candle.start == at(60) && candle.open == dec("101")));
+}
+
+#[test]
+fn late_trades_are_ignored() {
+    let mut builder = CandleBuilder::new(MINUTE);
+    builder.on_trade(at(10), dec("100"), dec("1"));
+    builder.on_trade(at(70), dec("101"), dec("1"));
+    assert!(builder.on_trade(at(30), dec("90"), dec("5")).is_empty());
+    assert_eq!(builder.current().unwrap().low, dec("101"));
+}
+
+#[test]
+fn gaps_are_filled_at_the_previous_close_when_enabled() {
+    let mut builder = CandleBuilder::new(MINUTE).with_gap_filling();
+    builder.on_trade(at(10), dec("100"), dec("1"));
+    let updates = builder.on_trade(at(200), dec("110"), dec("1"));
+
+    let closed = closed(&updates);
+    assert_eq!(closed.iter().map(|candle| candle.start).collect::<Vec<_>>(), vec![at(0), at(60), at(120)]);
+    assert!(closed[1..].iter().all(|candle| candle.open == dec("100") && candle.close == dec("100") && candle.trades == 0));
+
+    let mut plain = CandleBuilder::new(MINUTE);
+    plain.on_trade(at(10), dec("100"), dec("1"));
+    assert_eq!(self::closed(&plain.on_trade(at(200), dec("110"), dec("1"))).len(), 1);
+}
+
+#[test]
+fn flush_closes_a_candle_without_new_trades() {
+    let mut builder = CandleBuilder::new(MINUTE);
+    builder.on_trade(at(10), dec("100"), dec("1"));
+    assert!(builder.flush(at(59)).is_empty());
+    assert_eq!(closed(&builder.flush(at(60))).len(), 1);
+    assert!(builder.current().is_none());
+}
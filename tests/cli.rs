@@ -0,0 +1,39 @@
+#![cfg(feature = "cli")]
+
+use std::process::{Command, Output};
+
+fn bybit(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_bybit"))
+        .args(args)
+        .env_remove("BYBIT_API_KEY")
+        .env_remove("BYBIT_API_SECRET")
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn every_operation_is_a_subcommand() {
+    let output = bybit(&["--help"]);
+    assert!(output.status.success());
+    let help = String::from_utf8(output.stdout).unwrap();
+    for command in ["balance", "positions", "orders", "place", "cancel", "klines"] {
+        assert!(help.contains(command), "{command} missing from {help}");
+    }
+}
+
+#[test]
+fn arguments_parse_like_the_api_names_them() {
+    let output = bybit(&["place", "--category", "linear", "--symbol", "BTCUSDT", "--side", "Hold", "--qty", "1"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr).unwrap().contains("unknown value \"Hold\""));
+
+    let output = bybit(&["cancel", "--category", "linear", "--symbol", "BTCUSDT"]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn signed_commands_need_credentials_before_anything_is_sent() {
+    let output = bybit(&["balance", "--environment", "testnet"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr).unwrap().contains("BYBIT_API_KEY is not set"));
+}
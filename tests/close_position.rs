@@ -0,0 +1,51 @@
+#![cfg(feature = "position")]
+
+use std::{cell::Cell, time::Duration};
+
+use bybit_rs::{position::ClosePosition, Category, Client, Error};
+use bytes::Bytes;
+use futures::executor::block_on;
+use rust_decimal::Decimal;
+
+const PLACED: &str = r#"{"retCode":0,"retMsg":"OK","result":{"orderId":"1","orderLinkId":""},"time":0}"#;
+
+fn position(side: &str, position_idx: i32) -> String {
+    format!(
+        r#"{{"symbol":"BTCUSDT","side":"{side}","size":"2","positionIdx":{position_idx},"avgPrice":"100","positionValue":"200",
+        "markPrice":"100","leverage":"10","liqPrice":"","positionIM":"20","positionMM":"1","takeProfit":"","stopLoss":"",
+        "trailingStop":"0","unrealisedPnl":"0","curRealisedPnl":"0","cumRealisedPnl":"0","positionStatus":"Normal",
+        "createdTime":"0","updatedTime":"0"}}"#
+    )
+}
+
+#[test]
+fn a_failed_second_side_returns_the_placed_first_side() {
+    let positions = format!(
+        r#"{{"retCode":0,"retMsg":"OK","result":{{"category":"linear","nextPageCursor":"","list":[{},{}]}},"time":0}}"#,
+        position("Buy", 1),
+        position("Sell", 2)
+    );
+    let creates = Cell::new(0);
+    let send = |request: http::Request<String>| {
+        let body = match request.uri().path() {
+            "/v5/position/list" => Ok(positions.clone()),
+            _ => {
+                creates.set(creates.get() + 1);
+                match creates.get() {
+                    1 => Ok(PLACED.to_string()),
+                    _ => Err(std::io::Error::other("connection reset")),
+                }
+            }
+        };
+        async move { body.map(Bytes::from) }
+    };
+    let client = Client::new("key".to_string(), "secret".to_string());
+    let options = ClosePosition::new(Category::Linear, "BTCUSDT", Decimal::ONE_HUNDRED);
+
+    let Err(Error::PartialClose(err)) = block_on(client.close_position(&options, &Duration::from_secs(5), send)) else {
+        panic!("expected a partial close");
+    };
+    assert_eq!(err.placed.len(), 1);
+    assert_eq!(err.placed[0].position_idx, 1);
+    assert!(matches!(err.source, Error::Transport(_)));
+}
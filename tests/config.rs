@@ -0,0 +1,17 @@
+use std::time::Duration;
+
+use bybit_rs::{config::BybitConfig, Client};
+
+#[test]
+fn debug_redacts_the_secret() {
+    let config = BybitConfig::new("key".to_string(), "very-secret".to_string());
+    let debug = format!("{config:?}");
+    assert!(debug.contains("key"));
+    assert!(!debug.contains("very-secret"));
+}
+
+#[test]
+fn from_config_keeps_recv_window() {
+    let config = BybitConfig::new("key".to_string(), "secret".to_string()).with_recv_window(Duration::from_millis(2500));
+    assert_eq!(Client::from_config(&config).recv_window(), &Duration::from_millis(2500));
+}
@@ -0,0 +1,106 @@
+#![cfg(feature = "market")]
+
+use std::{
+    cell::{Cell, RefCell},
+    time::{Duration, Instant},
+};
+
+use bybit_rs::{
+    download::{BulkDownload, Checkpoint, JobProgress, KlineJob},
+    Category, Client, Environment, Interval,
+};
+use bytes::Bytes;
+use futures::{executor::block_on, StreamExt};
+use serde_json::{json, Value};
+
+const START: i64 = 1_699_999_980_000;
+const MINUTE: i64 = 60_000;
+
+// one minute klines from START on, newest first and at most 1000 per page like the endpoint
+fn klines(request: &http::Request<String>) -> (String, i64, Value) {
+    let query = request.uri().query().unwrap_or_default().to_string();
+    let param = |key: &str| query.split('&').find_map(|pair| pair.strip_prefix(key)).unwrap().to_string();
+    let (symbol, start, end) = (param("symbol="), param("start=").parse::<i64>().unwrap(), param("end=").parse::<i64>().unwrap());
+    let list: Vec<Value> = (0..1000)
+        .map(|back| end - end.rem_euclid(MINUTE) - back * MINUTE)
+        .filter(|time| *time >= start)
+        .map(|time| json!([time.to_string(), "1", "1", "1", "1", "1", "1"]))
+        .collect();
+    let body = json!({ "retCode": 0, "retMsg": "OK", "result": { "category": "linear", "symbol": symbol, "list": list }, "retExtInfo": {}, "time": 0 });
+    (symbol, end, body)
+}
+
+fn respond(body: Value) -> std::future::Ready<Result<Bytes, std::io::Error>> {
+    std::future::ready(Ok(Bytes::from(body.to_string())))
+}
+
+fn jobs() -> Vec<KlineJob> {
+    vec![
+        KlineJob::new(Category::Linear, "BTCUSDT", Interval::Minute1, START..=START + 1499 * MINUTE),
+        KlineJob::new(Category::Linear, "ETHUSDT", Interval::Minute1, START..=START + 9 * MINUTE),
+    ]
+}
+
+#[test]
+fn every_job_is_paged_through_and_reported() {
+    let client = Client::public(Environment::Mainnet);
+    let send = |request: http::Request<String>| respond(klines(&request).2);
+
+    let events: Vec<_> = block_on(client.download(BulkDownload::new(jobs()).with_concurrency(2), &send).collect());
+    let events: Vec<_> = events.into_iter().map(Result::unwrap).collect();
+    let fetched = |symbol: &str| events.iter().filter(|event| event.job.symbol == symbol).map(|event| event.klines.len()).sum::<usize>();
+    assert_eq!(fetched("BTCUSDT"), 1500);
+    assert_eq!(fetched("ETHUSDT"), 10);
+
+    let last = events.last().unwrap();
+    assert_eq!((last.progress.jobs, last.progress.finished, last.progress.requests, last.progress.klines), (2, 2, 3, 1510));
+    assert!(last.checkpoint.0.values().all(|progress| *progress == JobProgress::Done));
+    assert_eq!(events.iter().filter(|event| event.finished).count(), 2);
+}
+
+#[test]
+fn an_interrupted_download_resumes_from_its_checkpoint() {
+    let client = Client::public(Environment::Mainnet);
+    let failed = Cell::new(false);
+    let requests = RefCell::new(Vec::new());
+    // the second BTCUSDT page fails once
+    let send = |request: http::Request<String>| {
+        let (symbol, end, body) = klines(&request);
+        requests.borrow_mut().push((symbol.clone(), end));
+        if symbol == "BTCUSDT" && end < *jobs()[0].range.end() && !failed.replace(true) {
+            return std::future::ready(Err(std::io::Error::other("connection reset")));
+        }
+        respond(body)
+    };
+
+    let events: Vec<_> = block_on(client.download(BulkDownload::new(jobs()), &send).collect());
+    assert_eq!(events.iter().filter(|event| event.is_err()).count(), 1);
+    let checkpoint: Checkpoint = events.iter().filter_map(|event| event.as_ref().ok()).last().unwrap().checkpoint.clone();
+    assert_eq!(checkpoint.0[&jobs()[0].key()], JobProgress::Before(START + 500 * MINUTE - 1));
+    assert_eq!(checkpoint.0[&jobs()[1].key()], JobProgress::Done);
+
+    // the checkpoint round trips through whatever it's persisted as
+    let checkpoint: Checkpoint = serde_json::from_str(&serde_json::to_string(&checkpoint).unwrap()).unwrap();
+    requests.borrow_mut().clear();
+    let events: Vec<_> = block_on(client.download(BulkDownload::new(jobs()).with_checkpoint(checkpoint), &send).collect());
+    assert_eq!(*requests.borrow(), vec![("BTCUSDT".to_string(), START + 500 * MINUTE - 1)]);
+    let event = events.into_iter().next().unwrap().unwrap();
+    assert_eq!(event.klines.len(), 500);
+    assert_eq!((event.progress.finished, event.progress.jobs), (2, 2));
+}
+
+#[test]
+fn jobs_share_one_request_budget() {
+    let client = Client::public(Environment::Mainnet);
+    let send = |request: http::Request<String>| respond(klines(&request).2);
+    let jobs: Vec<_> = ["BTCUSDT", "ETHUSDT", "SOLUSDT"]
+        .into_iter()
+        .map(|symbol| KlineJob::new(Category::Linear, symbol, Interval::Minute1, START..=START + MINUTE))
+        .collect();
+
+    let started = Instant::now();
+    let download = BulkDownload::new(jobs).with_concurrency(3).with_budget(2, Duration::from_millis(200));
+    let events: Vec<_> = block_on(client.download(download, &send).collect());
+    assert_eq!(events.len(), 3);
+    assert!(started.elapsed() >= Duration::from_millis(200));
+}
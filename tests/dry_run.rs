@@ -0,0 +1,100 @@
+#![cfg(all(feature = "trade", feature = "market"))]
+
+use std::time::Duration;
+
+use bybit_rs::{
+    dryrun::DryRun,
+    market::SymbolRules,
+    trade::{BatchItemError, CancelOrderRequest, OrderRef, PlaceOrderRequest},
+    Category, Client, Environment, Error, KnownErrorCode, OrderStatus, Side,
+};
+use bytes::Bytes;
+use futures::executor::block_on;
+use rust_decimal::Decimal;
+
+fn dec(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
+
+fn rules() -> SymbolRules {
+    SymbolRules {
+        symbol: "BTCUSDT".to_string(),
+        tick_size: dec("0.1"),
+        qty_step: dec("0.001"),
+        min_qty: dec("0.001"),
+        max_qty: dec("100"),
+        max_market_qty: dec("10"),
+        min_price: dec("0.1"),
+        max_price: dec("1000000"),
+        min_notional: dec("5"),
+    }
+}
+
+// a dry run must never reach the transport
+fn unreachable(_: http::Request<String>) -> std::future::Ready<Result<Bytes, std::io::Error>> {
+    panic!("a dry run sent a request")
+}
+
+fn recv_window() -> Duration {
+    Duration::from_secs(5)
+}
+
+fn limit(qty: &str, price: &str) -> PlaceOrderRequest {
+    PlaceOrderRequest::limit(Category::Linear, "BTCUSDT", Side::Buy, dec(qty), dec(price))
+}
+
+fn api_code(result: bybit_rs::Result<impl std::fmt::Debug>) -> KnownErrorCode {
+    match result {
+        Err(Error::Api(err)) => err.known_code(),
+        other => panic!("expected a simulated api error, got {other:?}"),
+    }
+}
+
+#[test]
+fn orders_are_acked_locally_without_credentials() {
+    let dry_run = DryRun::new().with_rules(rules());
+    let client = Client::public(Environment::Mainnet).with_dry_run(dry_run.clone());
+
+    let request = client.place_order(&limit("0.01", "1000").with_order_link_id("a"), &recv_window()).unwrap();
+    assert!(request.is_simulated());
+    let placed = block_on(request.send(unreachable)).unwrap();
+    assert_eq!(placed.order_link_id, "a");
+    let orders = dry_run.open_orders();
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0].order_id, placed.order_id);
+
+    let cancel = CancelOrderRequest::new(Category::Linear, "BTCUSDT", OrderRef::OrderLinkId("a".to_string()));
+    block_on(client.cancel_order(&cancel, &recv_window()).unwrap().send(unreachable)).unwrap();
+    assert_eq!(dry_run.orders()[0].status, OrderStatus::Cancelled);
+    assert_eq!(api_code(block_on(client.cancel_order(&cancel, &recv_window()).unwrap().send(unreachable))), KnownErrorCode::OrderNotFound);
+}
+
+#[test]
+fn orders_breaking_the_rules_or_the_balance_are_rejected_as_bybit_would() {
+    let dry_run = DryRun::new().with_rules(rules()).with_balance(dec("1500"));
+    let client = Client::new("key".to_string(), "secret".to_string()).with_dry_run(dry_run.clone());
+    let place = |request: PlaceOrderRequest| block_on(client.place_order(&request, &recv_window()).unwrap().send(unreachable));
+
+    assert_eq!(api_code(place(limit("0.0105", "1000"))), KnownErrorCode::ParamError);
+    place(limit("1", "1000")).unwrap();
+    // 1000 of the 1500 are tied up by the first order
+    assert_eq!(api_code(place(limit("1", "600"))), KnownErrorCode::InsufficientBalance);
+    place(limit("1", "500")).unwrap();
+    // market orders are valued at the price set for their symbol
+    dry_run.set_price("BTCUSDT", dec("100"));
+    let market = PlaceOrderRequest::market(Category::Linear, "BTCUSDT", Side::Sell, dec("1"));
+    assert_eq!(api_code(place(market.clone())), KnownErrorCode::InsufficientBalance);
+    place(market.reduce_only()).unwrap();
+    assert_eq!(dry_run.open_orders().len(), 3);
+}
+
+#[test]
+fn batch_items_are_checked_one_by_one() {
+    let client = Client::public(Environment::Mainnet).with_dry_run(DryRun::new().with_rules(rules()));
+    let orders = [limit("0.01", "1000"), limit("0.01", "1000.05"), limit("0.01", "1000")];
+
+    let results = block_on(client.place_orders(&orders, Duration::ZERO, &recv_window(), unreachable));
+    assert!(results[0].is_ok());
+    assert!(matches!(&results[1], Err(BatchItemError::Rejected { code: 10001, .. })));
+    assert!(results[2].is_ok());
+}
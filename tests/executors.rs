@@ -0,0 +1,303 @@
+#![cfg(all(feature = "ws", feature = "trade"))]
+
+use std::{sync::Mutex, time::Duration};
+
+use bybit_rs::{
+    execution::{Grid, GridExecutor, GridSpacing, Iceberg, IcebergExecutor, IcebergState, Oco, OcoExecutor, OcoLeg, OcoState, Twap, TwapExecutor, TwapState},
+    number::Precision,
+    trade::{PlaceOrderRequest, TriggerBy, TriggerDirection},
+    ws::{ExecutionUpdate, OrderUpdate, PrivateEvent},
+    Category, Client, Error, Side,
+};
+use bytes::Bytes;
+use futures::{executor::block_on, stream};
+use rust_decimal::Decimal;
+
+const ACK: &str = r#"{"retCode":0,"retMsg":"OK","result":{"orderId":"1","orderLinkId":""},"time":0}"#;
+
+fn dec(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
+
+fn order(order_link_id: &str, status: &str, qty: &str, cum_exec_qty: &str) -> PrivateEvent {
+    let update: OrderUpdate = serde_json::from_value(serde_json::json!({
+        "category": "linear", "orderId": format!("id-{order_link_id}"), "orderLinkId": order_link_id, "symbol": "BTCUSDT",
+        "side": "Buy", "orderType": "Limit", "price": "100", "qty": qty, "orderStatus": status, "timeInForce": "GTC",
+        "positionIdx": 0, "avgPrice": "100", "leavesQty": "0", "cumExecQty": cum_exec_qty, "cumExecValue": "0",
+        "cumExecFee": "0", "rejectReason": "EC_NoError", "cancelType": "UNKNOWN", "reduceOnly": false,
+        "createdTime": "1700000000000", "updatedTime": "1700000000000"
+    }))
+    .unwrap();
+    PrivateEvent::Order { topic: "order".to_string(), creation_time: 1700000000000, data: vec![update] }
+}
+
+fn execution(order_link_id: &str, exec_id: &str, exec_qty: &str) -> PrivateEvent {
+    let update: ExecutionUpdate = serde_json::from_value(serde_json::json!({
+        "category": "linear", "symbol": "BTCUSDT", "orderId": format!("id-{order_link_id}"), "orderLinkId": order_link_id,
+        "side": "Buy", "orderPrice": "100", "orderQty": "3", "leavesQty": "0", "orderType": "Limit", "execId": exec_id,
+        "execPrice": "100", "execQty": exec_qty, "execValue": "0", "execFee": "0", "execType": "Trade",
+        "execTime": "1700000000000", "feeRate": "0", "isMaker": true, "markPrice": "100"
+    }))
+    .unwrap();
+    PrivateEvent::Fill { topic: "execution".to_string(), creation_time: 1700000000000, data: vec![update] }
+}
+
+// every item of a create-batch body acknowledged
+fn batch_ack(body: &serde_json::Value) -> String {
+    let count = body["request"].as_array().map_or(0, Vec::len);
+    let list = vec![serde_json::json!({"symbol": "BTCUSDT", "orderId": "1", "orderLinkId": ""}); count];
+    let ext = vec![serde_json::json!({"code": 0, "msg": "OK"}); count];
+    serde_json::json!({"retCode": 0, "retMsg": "OK", "result": {"list": list}, "retExtInfo": {"list": ext}, "time": 0}).to_string()
+}
+
+// every request the executor sent as (path, body), all of them acknowledged
+struct Exchange {
+    sent: Mutex<Vec<(String, serde_json::Value)>>,
+}
+
+impl Exchange {
+    fn new() -> Self {
+        Self { sent: Mutex::new(Vec::new()) }
+    }
+
+    fn send(&self) -> impl Fn(http::Request<String>) -> std::future::Ready<Result<Bytes, std::io::Error>> + '_ {
+        |request| {
+            let body: serde_json::Value = serde_json::from_str(request.body()).unwrap_or_default();
+            let ack = match request.uri().path() {
+                "/v5/order/create-batch" => Bytes::from(batch_ack(&body)),
+                _ => Bytes::from_static(ACK.as_bytes()),
+            };
+            self.sent.lock().unwrap().push((request.uri().path().to_string(), body));
+            std::future::ready(Ok(ack))
+        }
+    }
+
+    fn paths(&self) -> Vec<String> {
+        self.sent.lock().unwrap().iter().map(|(path, _)| path.clone()).collect()
+    }
+
+    // the field of every order in each create-batch call
+    fn batches(&self, field: &str) -> Vec<Vec<String>> {
+        self.sent
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|(_, body)| body["request"].as_array())
+            .map(|orders| orders.iter().filter_map(|order| order[field].as_str().map(str::to_string)).collect())
+            .collect()
+    }
+
+    fn field(&self, field: &str) -> Vec<String> {
+        self.sent.lock().unwrap().iter().filter_map(|(_, body)| body[field].as_str().map(str::to_string)).collect()
+    }
+}
+
+fn client() -> Client {
+    Client::new("key".to_string(), "secret".to_string())
+}
+
+fn recv_window() -> Duration {
+    Duration::from_secs(5)
+}
+
+fn twap(duration: Duration) -> TwapExecutor {
+    let template = PlaceOrderRequest::market(Category::Linear, "BTCUSDT", Side::Buy, Decimal::ZERO);
+    TwapExecutor::new(Twap::new(dec("9"), duration, 3, client().now()), template).with_link_id_prefix("twap")
+}
+
+#[test]
+fn twap_places_due_slices_and_completes_on_fills() {
+    let exchange = Exchange::new();
+    let mut executor = twap(Duration::ZERO);
+    let mut events = stream::iter(["twap-0", "twap-1", "twap-2"].map(|id| Ok(order(id, "Filled", "3", "3"))));
+
+    block_on(executor.run(&client(), &mut events, &recv_window(), exchange.send())).unwrap();
+    assert_eq!(exchange.paths(), ["/v5/order/create"; 3]);
+    assert_eq!(exchange.field("qty"), ["3", "3", "3"]);
+    assert_eq!(exchange.field("orderLinkId"), ["twap-0", "twap-1", "twap-2"]);
+    assert_eq!(executor.twap().state(), TwapState::Completed);
+}
+
+#[test]
+fn twap_counts_a_fill_seen_on_both_streams_once() {
+    let exchange = Exchange::new();
+    let mut executor = twap(Duration::ZERO);
+    let mut events = stream::iter([
+        Ok(execution("twap-0", "e1", "3")),
+        Ok(order("twap-0", "Filled", "3", "3")),
+        Ok(execution("twap-0", "e1", "3")),
+        Ok(order("twap-1", "Filled", "3", "3")),
+        Ok(order("twap-2", "Filled", "3", "3")),
+    ]);
+
+    block_on(executor.run(&client(), &mut events, &recv_window(), exchange.send())).unwrap();
+    assert_eq!(executor.twap().filled(), dec("9"));
+}
+
+#[test]
+fn cancelling_a_twap_cancels_its_working_children() {
+    let exchange = Exchange::new();
+    let mut executor = twap(Duration::from_secs(3600));
+    executor.handle().cancel();
+    let mut events = stream::iter([Ok(order("twap-0", "PartiallyFilledCanceled", "3", "1"))]);
+
+    block_on(executor.run(&client(), &mut events, &recv_window(), exchange.send())).unwrap();
+    assert_eq!(exchange.paths(), ["/v5/order/create", "/v5/order/cancel"]);
+    assert_eq!(executor.twap().state(), TwapState::Cancelled);
+    assert_eq!(executor.twap().filled(), dec("1"));
+}
+
+#[test]
+fn twap_fails_when_the_stream_ends_early() {
+    let exchange = Exchange::new();
+    let mut executor = twap(Duration::ZERO);
+    let mut events = stream::iter([Ok(order("twap-0", "Filled", "3", "3"))]);
+
+    assert!(block_on(executor.run(&client(), &mut events, &recv_window(), exchange.send())).is_err());
+    assert_eq!(executor.twap().filled(), dec("3"));
+}
+
+fn iceberg() -> IcebergExecutor {
+    let template = PlaceOrderRequest::limit(Category::Linear, "BTCUSDT", Side::Buy, Decimal::ZERO, dec("100"));
+    let iceberg = Iceberg::new(dec("10"), dec("4"), Precision::new(Decimal::ZERO, dec("1"))).unwrap();
+    IcebergExecutor::new(iceberg, template).with_link_id_prefix("ice")
+}
+
+#[test]
+fn iceberg_replenishes_the_visible_child_as_it_fills() {
+    let exchange = Exchange::new();
+    let mut executor = iceberg();
+    let mut events = stream::iter([
+        Ok(execution("ice-0", "e1", "1")),
+        Ok(order("ice-0", "Filled", "4", "4")),
+        Ok(order("ice-1", "Filled", "4", "4")),
+        Ok(order("ice-2", "Filled", "2", "2")),
+    ]);
+
+    block_on(executor.run(&client(), &mut events, &recv_window(), exchange.send())).unwrap();
+    assert_eq!(exchange.field("qty"), ["4", "4", "2"]);
+    assert_eq!(exchange.field("orderLinkId"), ["ice-0", "ice-1", "ice-2"]);
+    assert_eq!(executor.iceberg().state(), IcebergState::Completed);
+}
+
+#[test]
+fn iceberg_puts_back_what_a_cancelled_child_left() {
+    let exchange = Exchange::new();
+    let mut executor = iceberg();
+    let mut events = stream::iter([
+        Ok(order("ice-0", "PartiallyFilledCanceled", "4", "1")),
+        Ok(order("ice-1", "Filled", "4", "4")),
+        Ok(order("ice-2", "Filled", "4", "4")),
+        Ok(order("ice-3", "Filled", "1", "1")),
+    ]);
+
+    block_on(executor.run(&client(), &mut events, &recv_window(), exchange.send())).unwrap();
+    assert_eq!(exchange.field("qty"), ["4", "4", "4", "1"]);
+    assert_eq!(executor.iceberg().filled(), dec("10"));
+    assert_eq!(executor.iceberg().state(), IcebergState::Completed);
+}
+
+#[test]
+fn a_paused_iceberg_holds_back_its_next_child() {
+    let exchange = Exchange::new();
+    let mut executor = iceberg();
+    executor.handle().pause();
+    let mut events = stream::iter([Ok(order("ice-0", "Filled", "4", "4"))]);
+    assert!(block_on(executor.run(&client(), &mut events, &recv_window(), exchange.send())).is_err());
+    assert_eq!(exchange.field("orderLinkId"), ["ice-0"]);
+
+    executor.handle().resume();
+    executor.handle().cancel();
+    let mut events = stream::iter([Ok(order("ice-1", "Cancelled", "4", "0"))]);
+    block_on(executor.run(&client(), &mut events, &recv_window(), exchange.send())).unwrap();
+    assert_eq!(exchange.paths(), ["/v5/order/create", "/v5/order/create", "/v5/order/cancel"]);
+    assert_eq!(executor.iceberg().state(), IcebergState::Cancelled);
+}
+
+fn oco() -> OcoExecutor {
+    let take_profit = PlaceOrderRequest::limit(Category::Spot, "BTCUSDT", Side::Sell, dec("1"), dec("110"));
+    let stop = PlaceOrderRequest::market(Category::Spot, "BTCUSDT", Side::Sell, dec("1"))
+        .with_trigger(dec("90"), TriggerDirection::Fall, TriggerBy::LastPrice);
+    OcoExecutor::new(take_profit, stop).unwrap().with_link_id_prefix("oco")
+}
+
+#[test]
+fn oco_updates_pick_the_sibling_to_cancel() {
+    let mut oco = Oco::new("tp".to_string(), "sl".to_string());
+    let PrivateEvent::Order { data, .. } = order("other", "Filled", "1", "1") else { unreachable!() };
+    assert_eq!(oco.on_update(&data[0]), None);
+    let PrivateEvent::Order { data, .. } = order("sl", "PartiallyFilled", "1", "0.5") else { unreachable!() };
+    assert_eq!(oco.on_update(&data[0]), Some("tp"));
+    assert_eq!(oco.state(), OcoState::Filled(OcoLeg::Stop));
+    let PrivateEvent::Order { data, .. } = order("tp", "Cancelled", "1", "0") else { unreachable!() };
+    assert_eq!(oco.on_update(&data[0]), None);
+}
+
+#[test]
+fn oco_legs_must_match() {
+    let take_profit = PlaceOrderRequest::limit(Category::Spot, "BTCUSDT", Side::Sell, dec("1"), dec("110"));
+    let untriggered = PlaceOrderRequest::market(Category::Spot, "BTCUSDT", Side::Sell, dec("1"));
+    assert!(OcoExecutor::new(take_profit.clone(), untriggered.clone()).is_err());
+    let other_side = PlaceOrderRequest { side: Side::Buy, ..untriggered.with_trigger(dec("90"), TriggerDirection::Fall, TriggerBy::LastPrice) };
+    assert!(OcoExecutor::new(take_profit, other_side).is_err());
+}
+
+#[test]
+fn oco_places_both_legs_and_cancels_the_survivor() {
+    let exchange = Exchange::new();
+    let mut executor = oco();
+    let mut events = stream::iter([Ok(order("oco-0", "PartiallyFilled", "1", "0.4")), Ok(order("oco-1", "Cancelled", "1", "0"))]);
+
+    block_on(executor.run(&client(), &mut events, &recv_window(), exchange.send())).unwrap();
+    assert_eq!(exchange.paths(), ["/v5/order/create", "/v5/order/create", "/v5/order/cancel"]);
+    assert_eq!(exchange.field("orderLinkId"), ["oco-0", "oco-1", "oco-1"]);
+    assert_eq!(executor.oco().unwrap().state(), OcoState::Filled(OcoLeg::TakeProfit));
+}
+
+#[test]
+fn oco_cancels_the_stop_when_the_take_profit_goes_away() {
+    let exchange = Exchange::new();
+    let mut executor = oco();
+    let mut events = stream::iter([Ok(order("oco-0", "Cancelled", "1", "0")), Ok(order("oco-1", "Deactivated", "1", "0"))]);
+
+    block_on(executor.run(&client(), &mut events, &recv_window(), exchange.send())).unwrap();
+    assert_eq!(exchange.field("orderLinkId"), ["oco-0", "oco-1", "oco-1"]);
+    assert_eq!(executor.oco().unwrap().state(), OcoState::Cancelled);
+}
+
+fn grid(levels: usize, upper: &str) -> GridExecutor {
+    let grid = Grid::new(dec("90"), dec(upper), levels, dec("1"), GridSpacing::Arithmetic).unwrap();
+    GridExecutor::new(grid, Category::Linear, "BTCUSDT", dec("100")).with_link_id_prefix("grid")
+}
+
+#[test]
+fn grid_levels_go_out_in_batches_of_the_endpoint_cap() {
+    let exchange = Exchange::new();
+    // levels 1 apart from 90 to 112, the one at 100 stays empty
+    let mut executor = grid(23, "112");
+    executor.handle().cancel();
+    let mut events = stream::iter((0..22).map(|n| Ok(order(&format!("grid-{n}"), "Cancelled", "1", "0"))));
+
+    block_on(executor.run(&client(), &mut events, &recv_window(), exchange.send())).unwrap();
+    let batches = exchange.batches("orderLinkId");
+    assert_eq!(batches.iter().map(Vec::len).collect::<Vec<_>>(), [20, 2]);
+    assert_eq!(exchange.paths().iter().filter(|path| *path == "/v5/order/cancel").count(), 22);
+    assert!((0..23).all(|level| executor.grid().armed(level).is_none()));
+}
+
+#[test]
+fn a_filled_grid_level_rearms_its_neighbour() {
+    let exchange = Exchange::new();
+    // 90, 95, 100, 105, 110: grid-0 and grid-1 buy at 90 and 95, grid-2 and grid-3 sell at 105 and 110
+    let mut executor = grid(5, "110");
+    let mut events = stream::iter([Ok(order("grid-1", "Filled", "1", "1"))]);
+
+    let result = block_on(executor.run(&client(), &mut events, &recv_window(), exchange.send()));
+    assert!(matches!(result, Err(Error::WebSocket(_))));
+    assert_eq!(exchange.paths(), ["/v5/order/create-batch"; 2]);
+    assert_eq!(exchange.batches("orderLinkId")[1], ["grid-4"]);
+    assert_eq!(exchange.batches("side")[1], ["Sell"]);
+    assert_eq!(exchange.batches("price")[1], ["100"]);
+    assert_eq!(executor.grid().armed(1), None);
+    assert_eq!(executor.grid().armed(2), Some(Side::Sell));
+}
@@ -0,0 +1,129 @@
+#![cfg(feature = "position")]
+
+use std::{cell::RefCell, time::Duration};
+
+use bybit_rs::{
+    export::{CsvExporter, ExportRow},
+    position::ClosedPnlQuery,
+    trade::{Execution, ExecutionQuery},
+    Category, Client, Interval,
+};
+use bytes::Bytes;
+use futures::executor::block_on;
+use serde_json::{json, Value};
+
+fn execution(exec_id: &str, order_link_id: &str) -> Value {
+    json!({
+        "symbol": "BTCUSDT", "orderId": "1", "orderLinkId": order_link_id, "side": "Sell", "orderPrice": "30000",
+        "orderQty": "1", "leavesQty": "0", "orderType": "Limit", "execId": exec_id, "execPrice": "30000",
+        "execQty": "0.5", "execValue": "15000", "execFee": "1.5", "execType": "Trade", "execTime": "1700000000000",
+        "feeRate": "0.0001", "isMaker": true, "markPrice": "30000"
+    })
+}
+
+fn respond(result: Value) -> std::future::Ready<Result<Bytes, std::io::Error>> {
+    let body = json!({ "retCode": 0, "retMsg": "OK", "result": result, "retExtInfo": {}, "time": 0 });
+    std::future::ready(Ok(Bytes::from(body.to_string())))
+}
+
+fn client() -> Client {
+    Client::new("key".to_string(), "secret".to_string())
+}
+
+fn recv_window() -> Duration {
+    Duration::from_secs(5)
+}
+
+#[test]
+fn executions_stream_page_by_page_into_csv() {
+    let requests = RefCell::new(0);
+    let send = |request: http::Request<String>| {
+        *requests.borrow_mut() += 1;
+        let query = request.uri().query().unwrap_or_default();
+        if query.contains("cursor=next") {
+            respond(json!({ "category": "linear", "list": [execution("b", "quoted \"link\", with a comma")], "nextPageCursor": "" }))
+        } else {
+            respond(json!({ "category": "linear", "list": [execution("a", "plain")], "nextPageCursor": "next" }))
+        }
+    };
+
+    let mut csv = CsvExporter::<Execution, _>::new(Vec::new()).unwrap();
+    let written = block_on(client().export_executions(&ExecutionQuery::new(Category::Linear), &recv_window(), &mut csv, send)).unwrap();
+    assert_eq!(written, 2);
+    assert_eq!(*requests.borrow(), 2);
+
+    let text = String::from_utf8(csv.finish().unwrap()).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], Execution::COLUMNS.join(","));
+    assert_eq!(lines[1], "BTCUSDT,1,plain,Sell,30000,1,0,Limit,a,30000,0.5,15000,1.5,Trade,1700000000000,0.0001,,true,30000,");
+    assert!(lines[2].starts_with(r#"BTCUSDT,1,"quoted ""link"", with a comma",Sell,"#));
+    assert_eq!(lines.len(), 3);
+}
+
+#[test]
+fn an_export_without_rows_still_has_its_header() {
+    let send = |_| respond(json!({ "category": "linear", "list": [], "nextPageCursor": "" }));
+    let mut csv = CsvExporter::new(Vec::new()).unwrap();
+    let written = block_on(client().export_closed_pnl(&ClosedPnlQuery::new(Category::Linear), &recv_window(), &mut csv, send)).unwrap();
+    assert_eq!(written, 0);
+    let text = String::from_utf8(csv.finish().unwrap()).unwrap();
+    assert!(text.starts_with("symbol,orderId,side,qty,"));
+    assert_eq!(text.lines().count(), 1);
+}
+
+#[test]
+fn klines_page_backwards_until_the_start_of_the_range() {
+    const START: i64 = 1_699_999_980_000;
+    let ends = RefCell::new(Vec::new());
+    let send = |request: http::Request<String>| {
+        let query = request.uri().query().unwrap_or_default().to_string();
+        let end: i64 = query.split('&').find_map(|pair| pair.strip_prefix("end=")).unwrap().parse().unwrap();
+        ends.borrow_mut().push(end);
+        // a full page of 1000 one minute klines ending at end, none before START
+        let list: Vec<Value> = (0..1000)
+            .map(|back| end - end % 60_000 - back * 60_000)
+            .filter(|start| *start >= START)
+            .map(|start| json!([start.to_string(), "1", "1", "1", "1", "1", "1"]))
+            .collect();
+        respond(json!({ "category": "linear", "symbol": "BTCUSDT", "list": list }))
+    };
+
+    let mut csv = CsvExporter::new(Vec::new()).unwrap();
+    let end = START + 1500 * 60_000;
+    let written = block_on(client().export_klines(Category::Linear, "BTCUSDT", Interval::Minute1, START..=end, &mut csv, send)).unwrap();
+    assert_eq!(written, 1501);
+    assert_eq!(*ends.borrow(), vec![end, START + 501 * 60_000 - 1]);
+    let text = String::from_utf8(csv.finish().unwrap()).unwrap();
+    assert_eq!(text.lines().nth(1).unwrap().split(',').next(), Some(end.to_string().as_str()));
+    assert_eq!(text.lines().last().unwrap().split(',').next(), Some(START.to_string().as_str()));
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn the_transaction_log_exports_to_parquet() {
+    use bybit_rs::{account::TransactionLogQuery, export::ParquetExporter};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    let entry = |id: &str| {
+        json!({
+            "id": id, "symbol": "BTCUSDT", "category": "linear", "side": "Buy", "transactionTime": "1700000000000",
+            "type": "SETTLEMENT", "qty": "1", "size": "1", "currency": "USDT", "tradePrice": "30000", "funding": "-0.3",
+            "fee": "", "cashFlow": "0", "change": "-0.3", "cashBalance": "999.7", "feeRate": "", "tradeId": "",
+            "orderId": "", "orderLinkId": ""
+        })
+    };
+    let send = |_| respond(json!({ "list": [entry("1"), entry("2")], "nextPageCursor": "" }));
+    let path = std::env::temp_dir().join(format!("bybit-rs-export-{}.parquet", std::process::id()));
+
+    let mut parquet = ParquetExporter::create(&path).unwrap();
+    let written = block_on(client().export_transaction_log(&TransactionLogQuery::new(), &recv_window(), &mut parquet, send)).unwrap();
+    parquet.finish().unwrap();
+    assert_eq!(written, 2);
+
+    let reader = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&path).unwrap()).unwrap();
+    assert_eq!(reader.schema().field(0).name(), "id");
+    assert_eq!(reader.schema().fields().len(), 19);
+    let rows: usize = reader.build().unwrap().map(|batch| batch.unwrap().num_rows()).sum();
+    assert_eq!(rows, 2);
+    std::fs::remove_file(path).unwrap();
+}
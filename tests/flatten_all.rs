@@ -0,0 +1,55 @@
+#![cfg(feature = "position")]
+
+use std::time::Duration;
+
+use bybit_rs::{
+    position::{FlattenAll, FlattenOutcome, FlattenStep},
+    Category, Client,
+};
+use bytes::Bytes;
+use futures::executor::block_on;
+
+fn instrument(symbol: &str, settle_coin: &str) -> String {
+    format!(
+        r#"{{"symbol":"{symbol}","status":"Trading","baseCoin":"BTC","quoteCoin":"{settle_coin}","settleCoin":"{settle_coin}",
+        "priceFilter":{{"tickSize":"0.1"}},"lotSizeFilter":{{"minOrderQty":"0.001","maxOrderQty":"100","qtyStep":"0.001"}}}}"#
+    )
+}
+
+fn instruments(category: &str, list: &[String]) -> String {
+    format!(r#"{{"retCode":0,"retMsg":"OK","result":{{"category":"{category}","nextPageCursor":"","list":[{}]}},"time":0}}"#, list.join(","))
+}
+
+#[test]
+fn settle_coins_are_discovered_from_instruments() {
+    let linear = instruments("linear", &[instrument("BTCUSDT", "USDT"), instrument("BTCPERP", "USDC"), instrument("ETHUSDT", "USDT")]);
+    let inverse = instruments("inverse", &[instrument("BTCUSD", "BTC")]);
+    let send = |request: http::Request<String>| {
+        let query = request.uri().query().unwrap_or_default().to_string();
+        let body = match request.uri().path() {
+            "/v5/market/instruments-info" if query.contains("category=linear") => linear.clone(),
+            "/v5/market/instruments-info" => inverse.clone(),
+            _ => r#"{"retCode":0,"retMsg":"OK","result":{"category":"linear","nextPageCursor":"","list":[]},"time":0}"#.to_string(),
+        };
+        async move { Ok::<_, std::io::Error>(Bytes::from(body)) }
+    };
+    let client = Client::new("key".to_string(), "secret".to_string());
+    let options = FlattenAll::new().only(&[Category::Linear, Category::Inverse]).dry_run();
+    let report = block_on(client.flatten_all(&options, &Duration::from_secs(5), send));
+
+    let discovered: Vec<_> = report.actions.iter()
+        .filter_map(|(step, outcome)| match (step, outcome) {
+            (FlattenStep::DiscoverSettleCoins { category }, FlattenOutcome::Discovered(coins)) => Some((*category, coins.clone())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(discovered, [(Category::Linear, vec!["USDC".to_string(), "USDT".to_string()]), (Category::Inverse, vec!["BTC".to_string()])]);
+    let cancels: Vec<_> = report.actions.iter()
+        .filter_map(|(step, _)| match step {
+            FlattenStep::CancelOrders { category, settle_coin } => Some((*category, settle_coin.clone().unwrap())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(cancels, [(Category::Linear, "USDC".to_string()), (Category::Linear, "USDT".to_string()), (Category::Inverse, "BTC".to_string())]);
+    assert!(report.is_complete());
+}
@@ -0,0 +1,43 @@
+use bybit_rs::{
+    execution::{Grid, GridSpacing},
+    Side,
+};
+use rust_decimal::Decimal;
+
+fn dec(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
+
+#[test]
+fn rejects_invalid_bounds_and_levels() {
+    for (lower, upper, levels) in [("0", "100", 5), ("-1", "100", 5), ("100", "100", 5), ("200", "100", 5), ("90", "110", 1)] {
+        for spacing in [GridSpacing::Arithmetic, GridSpacing::Geometric] {
+            assert!(Grid::new(dec(lower), dec(upper), levels, Decimal::ONE, spacing).is_err(), "{lower} {upper} {levels} {spacing:?}");
+        }
+    }
+}
+
+#[test]
+fn arithmetic_levels_are_evenly_spaced() {
+    let grid = Grid::new(dec("90"), dec("110"), 5, Decimal::ONE, GridSpacing::Arithmetic).unwrap();
+    assert_eq!(grid.prices(), [dec("90"), dec("95"), dec("100"), dec("105"), dec("110")]);
+}
+
+#[test]
+fn geometric_levels_share_a_ratio() {
+    let grid = Grid::new(dec("100"), dec("400"), 3, Decimal::ONE, GridSpacing::Geometric).unwrap().with_tick_size(dec("0.01"));
+    assert_eq!(grid.prices(), [dec("100"), dec("200"), dec("400")]);
+}
+
+#[test]
+fn fill_rearms_the_neighbour_on_the_other_side() {
+    let mut grid = Grid::new(dec("90"), dec("110"), 5, Decimal::ONE, GridSpacing::Arithmetic).unwrap();
+    let orders = grid.initial_orders(dec("100"));
+    assert_eq!(orders.len(), 4);
+    assert_eq!(grid.armed(2), None);
+
+    let rearmed = grid.on_fill(1).unwrap();
+    assert_eq!((rearmed.level, rearmed.side, rearmed.price), (2, Side::Sell, dec("100")));
+    // the filled level is free, a second fill report for it is ignored
+    assert_eq!(grid.on_fill(1), None);
+}
@@ -0,0 +1,50 @@
+use bybit_rs::{
+    execution::{Iceberg, IcebergState},
+    number::Precision,
+    Error,
+};
+use rust_decimal::Decimal;
+
+fn dec(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
+
+#[test]
+fn rejects_display_quantities_that_show_nothing() {
+    for display in ["0", "-1", "0.05"] {
+        let result = Iceberg::new(dec("10"), dec(display), Precision::new(Decimal::ZERO, dec("0.1")));
+        assert!(matches!(result, Err(Error::Invalid(_))), "{display}");
+    }
+    assert!(matches!(Iceberg::new(Decimal::ZERO, Decimal::ONE, Precision::default()), Err(Error::Invalid(_))));
+}
+
+#[test]
+fn children_are_rounded_to_the_qty_step() {
+    let mut iceberg = Iceberg::new(dec("1.05"), dec("0.47"), Precision::new(Decimal::ZERO, dec("0.1"))).unwrap();
+    assert_eq!(iceberg.replenish(), Some(dec("0.4")));
+    assert_eq!(iceberg.on_fill(dec("0.4")), Some(dec("0.4")));
+    assert_eq!(iceberg.on_fill(dec("0.4")), Some(dec("0.2")));
+    // 0.05 is left, below one step
+    assert_eq!(iceberg.on_fill(dec("0.2")), None);
+    assert_eq!(iceberg.state(), IcebergState::Completed);
+    assert_eq!(iceberg.remaining(), dec("0.05"));
+}
+
+#[test]
+fn released_size_goes_back_to_the_hidden_pool() {
+    let mut iceberg = Iceberg::new(dec("10"), dec("4"), Precision::default()).unwrap();
+    assert_eq!(iceberg.replenish(), Some(dec("4")));
+    assert_eq!(iceberg.replenish(), None);
+    assert_eq!(iceberg.on_fill(dec("1")), None);
+    assert_eq!(iceberg.on_release(), Some(dec("4")));
+    assert_eq!(iceberg.hidden(), dec("5"));
+}
+
+#[test]
+fn fills_after_a_cancel_leave_it_cancelled() {
+    let mut iceberg = Iceberg::new(dec("4"), dec("4"), Precision::default()).unwrap();
+    iceberg.replenish();
+    iceberg.cancel();
+    assert_eq!(iceberg.on_fill(dec("4")), None);
+    assert_eq!(iceberg.state(), IcebergState::Cancelled);
+}
@@ -0,0 +1,50 @@
+use bybit_rs::{
+    execution::{Inconsistency, OrderTracker},
+    OrderStatus,
+};
+use rust_decimal::Decimal;
+
+fn tracker(qty: i64) -> OrderTracker {
+    let mut tracker = OrderTracker::new();
+    tracker.submit("link".to_string(), Decimal::from(qty)).unwrap();
+    tracker.ack("link", "order".to_string()).unwrap();
+    tracker
+}
+
+#[test]
+fn fills_move_to_partially_filled_then_filled() {
+    let mut tracker = tracker(10);
+    assert_eq!(tracker.on_fill("order", Decimal::from(4)).unwrap().status, Some(OrderStatus::PartiallyFilled));
+    let order = tracker.on_fill("link", Decimal::from(6)).unwrap();
+    assert_eq!(order.filled, Decimal::from(10));
+    assert_eq!(order.status, Some(OrderStatus::Filled));
+}
+
+#[test]
+fn overfill_leaves_state_unchanged() {
+    let mut tracker = tracker(10);
+    tracker.on_fill("order", Decimal::from(7)).unwrap();
+    let err = tracker.on_fill("order", Decimal::from(5)).unwrap_err();
+    assert_eq!(err, Inconsistency::Overfill { id: "order".to_string(), filled: Decimal::from(12), qty: Decimal::from(10) });
+
+    let order = tracker.get("order").unwrap();
+    assert_eq!(order.filled, Decimal::from(7));
+    assert_eq!(order.status, Some(OrderStatus::PartiallyFilled));
+    // the remaining qty still fills normally
+    assert_eq!(tracker.on_fill("order", Decimal::from(3)).unwrap().status, Some(OrderStatus::Filled));
+}
+
+#[test]
+fn duplicate_submit_and_unknown_order_are_flagged() {
+    let mut tracker = tracker(1);
+    assert_eq!(tracker.submit("link".to_string(), Decimal::ONE).unwrap_err(), Inconsistency::DuplicateSubmit("link".to_string()));
+    assert_eq!(tracker.on_fill("other", Decimal::ONE).unwrap_err(), Inconsistency::UnknownOrder("other".to_string()));
+}
+
+#[test]
+fn status_change_after_terminal_is_flagged() {
+    let mut tracker = tracker(1);
+    tracker.on_status("order", OrderStatus::Cancelled).unwrap();
+    let err = tracker.on_status("order", OrderStatus::New).unwrap_err();
+    assert_eq!(err, Inconsistency::AfterTerminal { id: "order".to_string(), from: OrderStatus::Cancelled, to: OrderStatus::New });
+}
@@ -0,0 +1,59 @@
+use bybit_rs::metrics::RequestMetrics;
+use bytes::Bytes;
+use futures::{channel::mpsc, executor::block_on, StreamExt};
+
+fn request(path: &str) -> http::Request<String> {
+    http::Request::get(format!("https://api.bybit.com{path}")).body(String::new()).unwrap()
+}
+
+#[test]
+fn summarises_status_and_ret_code_per_endpoint() {
+    let metrics = RequestMetrics::new();
+    let send = |request: http::Request<String>| async move {
+        match request.uri().path() {
+            "/v5/market/time" => Ok(http::Response::new(Bytes::from_static(br#"{"retCode":0,"retMsg":"OK","result":{},"time":0}"#))),
+            "/v5/order/create" => Ok(http::Response::builder().status(403).body(Bytes::from_static(br#"{"retCode":10006,"retMsg":"Too many visits"}"#)).unwrap()),
+            _ => Err(std::io::Error::other("connection reset")),
+        }
+    };
+    let send = metrics.wrap(&send);
+    block_on(async {
+        send(request("/v5/market/time")).await.unwrap();
+        send(request("/v5/market/time")).await.unwrap();
+        send(request("/v5/order/create")).await.unwrap();
+        send(request("/v5/position/list")).await.unwrap_err();
+    });
+
+    let time = metrics.summary("/v5/market/time").unwrap();
+    assert_eq!(time.requests, 2);
+    assert_eq!(time.statuses.get(&200), Some(&2));
+    assert_eq!(time.ret_codes.get(&0), Some(&2));
+    assert!(time.mean_latency().unwrap() <= time.max_latency);
+
+    let create = metrics.summary("/v5/order/create").unwrap();
+    assert_eq!(create.statuses.get(&403), Some(&1));
+    assert_eq!(create.ret_codes.get(&10006), Some(&1));
+
+    let positions = metrics.summary("/v5/position/list").unwrap();
+    assert_eq!(positions.transport_errors, 1);
+    assert!(positions.statuses.is_empty());
+
+    metrics.reset();
+    assert!(metrics.summaries().is_empty());
+}
+
+#[test]
+fn hook_receives_every_outcome() {
+    let (hook, outcomes) = mpsc::unbounded();
+    {
+        let metrics = RequestMetrics::new().with_hook(hook);
+        let send = |_| async { Ok::<_, std::io::Error>(http::Response::new(Bytes::from_static(br#"{"retCode":110007,"retMsg":"insufficient balance"}"#))) };
+        block_on(metrics.wrap(&send)(request("/v5/order/create"))).unwrap();
+    }
+
+    let outcomes: Vec<_> = block_on(outcomes.collect());
+    assert_eq!(outcomes.len(), 1);
+    assert_eq!(outcomes[0].path, "/v5/order/create");
+    assert_eq!(outcomes[0].status, Some(http::StatusCode::OK));
+    assert_eq!(outcomes[0].ret_code, Some(110007));
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "asset")]
+
+use std::{sync::Mutex, time::Duration};
+
+use bybit_rs::{
+    asset::{TransferError, Wallet},
+    AccountType, Client, Error,
+};
+use bytes::Bytes;
+use futures::executor::block_on;
+use rust_decimal::Decimal;
+
+const BALANCE: &str = r#"{"retCode":0,"retMsg":"OK","result":{"accountType":"FUND","memberId":"","balance":[
+    {"coin":"USDT","transferBalance":"100","walletBalance":"100","bonus":"0"}
+]},"time":0}"#;
+const CREATED: &str = r#"{"retCode":0,"retMsg":"OK","result":{"transferId":"t-1","status":"SUCCESS"},"time":0}"#;
+const RECORDS: &str = r#"{"retCode":0,"retMsg":"OK","result":{"nextPageCursor":"","list":[
+    {"transferId":"t-1","coin":"USDT","amount":"10","fromAccountType":"FUND","toAccountType":"UNIFIED","timestamp":"0","status":"SUCCESS"}
+]},"time":0}"#;
+
+fn move_funds(from: Wallet, to: Wallet) -> (bybit_rs::Result<()>, Vec<String>) {
+    let client = Client::new("key".to_string(), "secret".to_string());
+    let paths = Mutex::new(Vec::new());
+    let send = |request: http::Request<String>| {
+        let path = request.uri().path().to_string();
+        let body = match path.as_str() {
+            "/v5/asset/transfer/query-account-coins-balance" => BALANCE,
+            "/v5/asset/transfer/inter-transfer" | "/v5/asset/transfer/universal-transfer" => CREATED,
+            _ => RECORDS,
+        };
+        paths.lock().unwrap().push(path);
+        async move { Ok::<_, std::io::Error>(Bytes::from_static(body.as_bytes())) }
+    };
+    let result = block_on(client.move_funds("USDT", Decimal::TEN, from, to, &Duration::from_secs(5), send)).map(|_| ());
+    (result, paths.into_inner().unwrap())
+}
+
+#[test]
+fn own_wallets_use_an_internal_transfer() {
+    let (result, paths) = move_funds(Wallet::own(AccountType::FUND), Wallet::own(AccountType::UNIFIED));
+    result.unwrap();
+    assert!(paths.contains(&"/v5/asset/transfer/inter-transfer".to_string()), "{paths:?}");
+    assert!(paths.contains(&"/v5/asset/transfer/query-inter-transfer-list".to_string()), "{paths:?}");
+}
+
+#[test]
+fn a_named_member_uses_a_universal_transfer() {
+    // the same member on both sides is still a master key acting on a sub account, which only universal covers
+    let (result, paths) = move_funds(Wallet::member(7, AccountType::FUND), Wallet::member(7, AccountType::UNIFIED));
+    result.unwrap();
+    assert!(paths.contains(&"/v5/asset/transfer/universal-transfer".to_string()), "{paths:?}");
+    assert!(paths.contains(&"/v5/asset/transfer/query-universal-transfer-list".to_string()), "{paths:?}");
+}
+
+#[test]
+fn a_universal_transfer_needs_both_member_ids() {
+    let (result, paths) = move_funds(Wallet::own(AccountType::FUND), Wallet::member(7, AccountType::UNIFIED));
+    assert!(matches!(result, Err(Error::Transfer(TransferError::MissingMemberId))));
+    assert!(!paths.iter().any(|path| path.ends_with("-transfer")), "{paths:?}");
+}
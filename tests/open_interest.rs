@@ -0,0 +1,75 @@
+#![cfg(feature = "market")]
+
+use std::{cell::RefCell, time::Duration};
+
+use bybit_rs::{analytics::align, market::OpenInterestInterval, Category, Client, Environment};
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures::executor::block_on;
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+
+const T0: i64 = 1_699_920_000_000;
+const MINUTE: i64 = 60_000;
+
+fn respond(result: Value) -> std::future::Ready<Result<Bytes, std::io::Error>> {
+    let body = json!({ "retCode": 0, "retMsg": "OK", "result": result, "retExtInfo": {}, "time": 0 });
+    std::future::ready(Ok(Bytes::from(body.to_string())))
+}
+
+fn time(millis: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(millis).unwrap()
+}
+
+fn oi(minutes: i64, value: &str) -> Value {
+    json!({ "openInterest": value, "timestamp": (T0 + minutes * MINUTE).to_string() })
+}
+
+fn rate(minutes: i64, value: &str) -> Value {
+    json!({ "symbol": "BTCUSDT", "fundingRate": value, "fundingRateTimestamp": (T0 + minutes * MINUTE).to_string() })
+}
+
+#[test]
+fn open_interest_and_funding_end_up_on_one_resampled_series() {
+    let queries = RefCell::new(Vec::new());
+    let send = |request: http::Request<String>| {
+        let query = request.uri().query().unwrap_or_default().to_string();
+        queries.borrow_mut().push(format!("{}?{query}", request.uri().path()));
+        match request.uri().path() {
+            "/v5/market/open-interest" if query.contains("cursor=p2") => {
+                respond(json!({ "category": "linear", "symbol": "BTCUSDT", "list": [oi(0, "110"), oi(-30, "100")], "nextPageCursor": "" }))
+            }
+            "/v5/market/open-interest" => {
+                respond(json!({ "category": "linear", "symbol": "BTCUSDT", "list": [oi(90, "140"), oi(60, "130"), oi(30, "120")], "nextPageCursor": "p2" }))
+            }
+            _ => respond(json!({ "category": "linear", "list": [rate(60, "0.0002"), rate(-480, "0.0001")] })),
+        }
+    };
+
+    let client = Client::public(Environment::Mainnet);
+    let range = time(T0)..=time(T0 + 180 * MINUTE);
+    let points = block_on(client.open_interest_funding(Category::Linear, "BTCUSDT", OpenInterestInterval::Minute5, Duration::from_secs(3600), range, send)).unwrap();
+
+    let queries = queries.borrow();
+    assert!(queries[0].contains("intervalTime=5min") && queries[0].contains(&format!("startTime={}", T0 - 5 * MINUTE)));
+    assert!(queries.iter().any(|query| query.starts_with("/v5/market/funding/history") && query.contains(&format!("startTime={}", T0 - 1440 * MINUTE))));
+    assert_eq!(queries.len(), 3);
+
+    let rows: Vec<_> = points.iter()
+        .map(|point| (point.start.timestamp_millis(), point.open_interest, point.open_interest_change, point.funding_rate, point.settled))
+        .collect();
+    let dec = |value: &str| Some(value.parse::<Decimal>().unwrap());
+    assert_eq!(rows, vec![
+        (T0, dec("120"), dec("20"), dec("0.0001"), false),
+        (T0 + 60 * MINUTE, dec("140"), dec("20"), dec("0.0002"), true),
+        (T0 + 120 * MINUTE, dec("140"), dec("0"), dec("0.0002"), false),
+        (T0 + 180 * MINUTE, dec("140"), dec("0"), dec("0.0002"), false),
+    ]);
+}
+
+#[test]
+fn buckets_before_the_first_observation_are_empty() {
+    let points = align(&[], &[], Duration::from_secs(900), time(T0 + 7 * MINUTE)..=time(T0 + 31 * MINUTE));
+    assert_eq!(points.iter().map(|point| point.start.timestamp_millis()).collect::<Vec<_>>(), vec![T0, T0 + 15 * MINUTE, T0 + 30 * MINUTE]);
+    assert!(points.iter().all(|point| point.open_interest.is_none() && point.funding_rate.is_none() && !point.settled));
+}
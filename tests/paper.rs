@@ -0,0 +1,136 @@
+#![cfg(all(feature = "ws", feature = "trade"))]
+
+use std::time::Duration;
+
+use bybit_rs::{
+    execution::{Twap, TwapExecutor, TwapState},
+    paper::PaperExchange,
+    trade::{PlaceOrderRequest, TimeInForce},
+    ws::{OrderbookData, PrivateEvent, PublicEvent, PublicTrade, UpdateKind},
+    Category, Client, OrderStatus, PriceLevel, Side,
+};
+use futures::{channel::mpsc::UnboundedReceiver, executor::block_on};
+use rust_decimal::Decimal;
+
+fn dec(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
+
+fn book(bids: &[(&str, &str)], asks: &[(&str, &str)]) -> PublicEvent {
+    let levels = |levels: &[(&str, &str)]| levels.iter().map(|(price, size)| PriceLevel { price: price.to_string(), size: size.to_string() }).collect();
+    PublicEvent::Orderbook {
+        topic: "orderbook.50.BTCUSDT".to_string(),
+        kind: UpdateKind::Snapshot,
+        ts: 1700000000000,
+        cts: None,
+        data: OrderbookData { symbol: "BTCUSDT".to_string(), bids: levels(bids), asks: levels(asks), update_id: 1, seq: None },
+    }
+}
+
+fn trade(side: &str, price: &str, size: &str) -> PublicEvent {
+    let trade: PublicTrade = serde_json::from_value(serde_json::json!({
+        "T": 1700000001000u64, "s": "BTCUSDT", "S": side, "v": size, "p": price, "i": "t1", "BT": false
+    }))
+    .unwrap();
+    PublicEvent::Trades { topic: "publicTrade.BTCUSDT".to_string(), ts: 1700000001000, data: vec![trade] }
+}
+
+fn exchange() -> PaperExchange {
+    let exchange = PaperExchange::new(dec("-0.0001"), dec("0.001"));
+    exchange.on_public(&book(&[("99", "5")], &[("100", "1"), ("101", "2")]));
+    exchange
+}
+
+fn place(exchange: &PaperExchange, request: PlaceOrderRequest) -> String {
+    let client = Client::new("key".to_string(), "secret".to_string());
+    let placed = block_on(client.place_order(&request, &Duration::from_secs(5)).unwrap().send(|request| exchange.send(request))).unwrap();
+    placed.order_id
+}
+
+// everything published so far
+fn drain(events: &mut UnboundedReceiver<bybit_rs::Result<PrivateEvent>>) -> Vec<PrivateEvent> {
+    std::iter::from_fn(|| events.try_recv().ok()).map(Result::unwrap).collect()
+}
+
+fn statuses(events: &[PrivateEvent]) -> Vec<OrderStatus> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            PrivateEvent::Order { data, .. } => Some(data[0].order_status),
+            _ => None,
+        })
+        .collect()
+}
+
+fn fills(events: &[PrivateEvent]) -> Vec<(String, String, String, bool)> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            PrivateEvent::Fill { data, .. } => Some((data[0].exec_price.clone(), data[0].exec_qty.clone(), data[0].exec_fee.clone(), data[0].is_maker)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn a_market_order_walks_the_book_as_taker() {
+    let exchange = exchange();
+    let mut events = exchange.events();
+    place(&exchange, PlaceOrderRequest::market(Category::Linear, "BTCUSDT", Side::Buy, dec("2")));
+
+    let events = drain(&mut events);
+    assert_eq!(fills(&events), [("100".into(), "1".into(), "0.1".into(), false), ("101".into(), "1".into(), "0.101".into(), false)]);
+    let PrivateEvent::Order { data, .. } = events.last().unwrap() else { panic!("the order update comes last") };
+    assert_eq!(data[0].order_status, OrderStatus::Filled);
+    assert_eq!(data[0].avg_price, "100.5");
+}
+
+#[test]
+fn a_resting_limit_fills_from_trades_through_its_price() {
+    let exchange = exchange();
+    let mut events = exchange.events();
+    place(&exchange, PlaceOrderRequest::limit(Category::Linear, "BTCUSDT", Side::Buy, dec("2"), dec("98")));
+    // a trade above the limit or on the same side leaves it alone
+    exchange.on_public(&trade("Sell", "99", "10"));
+    exchange.on_public(&trade("Buy", "97", "10"));
+    exchange.on_public(&trade("Sell", "98", "0.5"));
+    exchange.on_public(&trade("Sell", "97", "10"));
+
+    let events = drain(&mut events);
+    assert_eq!(statuses(&events), [OrderStatus::New, OrderStatus::PartiallyFilled, OrderStatus::Filled]);
+    // filled at its own price as maker, the negative maker fee is a rebate
+    assert_eq!(fills(&events), [("98".into(), "0.5".into(), "-0.0049".into(), true), ("98".into(), "1.5".into(), "-0.0147".into(), true)]);
+}
+
+#[test]
+fn time_in_force_limits_what_takes_and_what_rests() {
+    let exchange = exchange();
+    let mut events = exchange.events();
+    let limit = |qty: &str, time_in_force| PlaceOrderRequest::limit(Category::Linear, "BTCUSDT", Side::Buy, dec(qty), dec("100")).with_time_in_force(time_in_force);
+
+    place(&exchange, limit("1", TimeInForce::PostOnly));
+    place(&exchange, limit("3", TimeInForce::FOK));
+    place(&exchange, limit("3", TimeInForce::IOC));
+    place(&exchange, limit("3", TimeInForce::GTC));
+
+    let events = drain(&mut events);
+    assert_eq!(
+        statuses(&events),
+        [OrderStatus::Cancelled, OrderStatus::Cancelled, OrderStatus::PartiallyFilledCanceled, OrderStatus::New]
+    );
+    // the IOC took the single lot at 100, the GTC found nothing left at its price and rests
+    assert_eq!(fills(&events).len(), 1);
+}
+
+#[test]
+fn executors_run_against_the_paper_exchange() {
+    let exchange = exchange();
+    let client = Client::new("key".to_string(), "secret".to_string());
+    let template = PlaceOrderRequest::market(Category::Linear, "BTCUSDT", Side::Buy, Decimal::ZERO);
+    let mut executor = TwapExecutor::new(Twap::new(dec("3"), Duration::ZERO, 3, client.now()), template);
+    let mut events = exchange.events();
+
+    block_on(executor.run(&client, &mut events, &Duration::from_secs(5), |request| exchange.send(request))).unwrap();
+    assert_eq!(executor.twap().state(), TwapState::Completed);
+    assert_eq!(executor.twap().filled(), dec("3"));
+}
@@ -0,0 +1,47 @@
+#![cfg(feature = "user")]
+
+use std::time::Duration;
+
+use bybit_rs::{
+    user::{Permission, PermissionError},
+    Client, Error,
+};
+use futures::executor::block_on;
+use serde_json::json;
+
+fn verify(read_only: u8, ips: &[&str], required: &[Permission]) -> bybit_rs::Result<()> {
+    let body = json!({
+        "retCode": 0, "retMsg": "", "retExtInfo": {}, "time": 0,
+        "result": {
+            "id": "1", "note": "bot", "apiKey": "key", "readOnly": read_only,
+            "permissions": {"ContractTrade": ["Order", "Position"], "Wallet": ["AccountTransfer"]},
+            "ips": ips, "type": 1, "deadlineDay": 80, "expiredAt": "2099-01-01T00:00:00Z", "createdAt": "2024-01-01T00:00:00Z",
+            "uta": 1, "userID": 1, "isMaster": true
+        }
+    });
+    let send = |_| {
+        let body = body.to_string();
+        async move { Ok::<_, std::io::Error>(bytes::Bytes::from(body)) }
+    };
+    let client = Client::new("key".to_string(), "secret".to_string());
+    block_on(client.verify_permissions(required, &Duration::from_secs(5), send)).map(|_| ())
+}
+
+#[test]
+fn ip_bound_keys_pass() {
+    assert!(verify(0, &["203.0.113.7"], &[Permission::ContractTrade, Permission::Wallet]).is_ok());
+}
+
+#[test]
+fn trading_keys_without_an_ip_whitelist_are_refused() {
+    for ips in [&["*"][..], &[]] {
+        assert!(matches!(verify(0, ips, &[Permission::ContractTrade]), Err(Error::Permission(PermissionError::Unrestricted))), "{ips:?}");
+    }
+}
+
+#[test]
+fn read_only_keys_may_be_unrestricted() {
+    assert!(verify(1, &["*"], &[Permission::Wallet]).is_ok());
+    // read only keys never hold trading permissions, whatever the permission map says
+    assert!(matches!(verify(1, &["*"], &[Permission::ContractTrade]), Err(Error::Permission(PermissionError::Missing(missing))) if missing == [Permission::ContractTrade]));
+}
@@ -0,0 +1,40 @@
+#![cfg(feature = "trade")]
+
+use std::{cell::Cell, time::Duration};
+
+use bybit_rs::{clock::FixedClock, ratelimit::RateLimiter, trade::PlaceOrderRequest, Category, Client, Side};
+use rust_decimal::Decimal;
+
+const BATCH: &str = r#"{"retCode":0,"retMsg":"OK","result":{"list":[
+    {"symbol":"BTCUSDT","orderId":"1","orderLinkId":"a"},
+    {"symbol":"BTCUSDT","orderId":"2","orderLinkId":"b"},
+    {"symbol":"BTCUSDT","orderId":"3","orderLinkId":"c"}
+]},"retExtInfo":{"list":[{"code":0,"msg":"OK"},{"code":0,"msg":"OK"},{"code":0,"msg":"OK"}]},"time":0}"#;
+
+#[test]
+fn batches_draw_on_the_order_create_budget() {
+    let clock = FixedClock::from_millis(1_700_000_000_000);
+    let limiter = RateLimiter::new().with_clock(clock);
+    let mut headers = http::HeaderMap::new();
+    headers.insert("X-Bapi-Limit", "10".parse().unwrap());
+    headers.insert("X-Bapi-Limit-Status", "5".parse().unwrap());
+    headers.insert("X-Bapi-Limit-Reset-Timestamp", "1700000060000".parse().unwrap());
+    limiter.observe("/v5/order/create", &headers);
+
+    let client = Client::new("key".to_string(), "secret".to_string()).with_clock(clock).with_rate_limiter(limiter.clone());
+    let orders: Vec<_> = [Category::Linear, Category::Spot, Category::Spot]
+        .into_iter()
+        .map(|category| PlaceOrderRequest::market(category, "BTCUSDT", Side::Buy, Decimal::ONE))
+        .collect();
+    let calls = Cell::new(0);
+    let send = |_| {
+        calls.set(calls.get() + 1);
+        async { Ok::<_, std::io::Error>(bytes::Bytes::from_static(BATCH.as_bytes())) }
+    };
+
+    // one batch per category, an hour of pacing between them would hang the test if it were still applied alongside the limiter
+    let results = futures::executor::block_on(client.place_orders(&orders, Duration::from_secs(3600), &Duration::from_secs(5), send));
+    assert!(results.iter().all(Result::is_ok));
+    assert_eq!(calls.get(), 2);
+    assert_eq!(limiter.status("/v5/order/create").unwrap().remaining, 2);
+}
@@ -0,0 +1,125 @@
+#![cfg(feature = "ws")]
+
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bybit_rs::{
+    clock::FixedClock,
+    shutdown::Shutdown,
+    ws::{PrivateEvent, PrivateWsClient},
+    Client,
+};
+use futures::{
+    channel::mpsc::{self, SendError, UnboundedReceiver, UnboundedSender},
+    executor::block_on,
+    Sink, Stream,
+};
+
+const AUTH_OK: &str = r#"{"op":"auth","success":true,"ret_msg":"","conn_id":"c1"}"#;
+const GREEKS: &str = r#"{"topic":"greeks","id":"1","creationTime":1700000000123,"data":[]}"#;
+
+// one end of an in-memory websocket, frames pushed into the sender are what Bybit would send
+struct Conn {
+    incoming: UnboundedReceiver<Result<String, SendError>>,
+    outgoing: UnboundedSender<String>,
+}
+
+impl Stream for Conn {
+    type Item = Result<String, SendError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.incoming).poll_next(cx)
+    }
+}
+
+impl Sink<String> for Conn {
+    type Error = SendError;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError>> {
+        Pin::new(&mut self.outgoing).poll_ready(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: String) -> Result<(), SendError> {
+        Pin::new(&mut self.outgoing).start_send(item)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError>> {
+        Pin::new(&mut self.outgoing).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError>> {
+        Pin::new(&mut self.outgoing).poll_close(cx)
+    }
+}
+
+fn connect(frames: &[&str]) -> (PrivateWsClient<Conn>, UnboundedSender<Result<String, SendError>>, UnboundedReceiver<String>) {
+    let (server, incoming) = mpsc::unbounded();
+    let (outgoing, sent) = mpsc::unbounded();
+    for frame in frames {
+        server.unbounded_send(Ok(frame.to_string())).unwrap();
+    }
+    let client = block_on(PrivateWsClient::connect(Conn { incoming, outgoing }, "key", "secret")).unwrap();
+    (client, server, sent)
+}
+
+#[test]
+fn a_dropped_connection_ends_with_disconnect() {
+    let (mut client, server, _sent) = connect(&[AUTH_OK, GREEKS]);
+    drop(server);
+
+    assert!(matches!(block_on(client.next()), Some(Ok(PrivateEvent::Raw { .. }))));
+    let Some(Ok(PrivateEvent::Disconnect { last_update })) = block_on(client.next()) else {
+        panic!("expected a disconnect");
+    };
+    assert_eq!(last_update, Some(1700000000123));
+    assert!(block_on(client.next()).is_none());
+}
+
+#[test]
+fn shutting_down_ends_without_disconnect() {
+    let (client, _server, _sent) = connect(&[AUTH_OK]);
+    let shutdown = Shutdown::new();
+    let mut client = client.with_shutdown(&shutdown);
+
+    let (_, event) = block_on(futures::future::join(shutdown.shutdown(), async move {
+        let event = client.next().await;
+        drop(client);
+        event
+    }));
+    assert!(event.is_none());
+}
+
+#[test]
+fn auth_expires_relative_to_the_client_clock() {
+    let (server, incoming) = mpsc::unbounded();
+    let (outgoing, mut sent) = mpsc::unbounded();
+    server.unbounded_send(Ok(AUTH_OK.to_string())).unwrap();
+    let client = Client::new("key".to_string(), "secret".to_string()).with_clock(FixedClock::from_millis(1700000000000));
+    block_on(client.private_ws(Conn { incoming, outgoing })).unwrap();
+
+    let auth: serde_json::Value = serde_json::from_str(&sent.try_recv().unwrap()).unwrap();
+    assert_eq!(auth["args"][1], 1700000010000_i64);
+}
+
+#[cfg(feature = "trade")]
+#[test]
+fn resync_is_delivered_before_the_next_frame() {
+    use bybit_rs::{
+        execution::{FillAggregator, OrderTracker},
+        trade::Resync,
+        Category,
+    };
+
+    let (mut client, _server, _sent) = connect(&[AUTH_OK, GREEKS]);
+    let resync = Resync { category: Category::Linear, open_orders: Vec::new(), executions: Vec::new() };
+    client.apply_resync(&resync, &mut OrderTracker::new(), &mut FillAggregator::new());
+
+    let Some(Ok(PrivateEvent::Resync { category, changes })) = block_on(client.next()) else {
+        panic!("expected the resync first");
+    };
+    assert_eq!(category, Category::Linear);
+    assert!(changes.is_empty());
+    assert!(matches!(block_on(client.next()), Some(Ok(PrivateEvent::Raw { .. }))));
+}
@@ -0,0 +1,13 @@
+#![cfg(all(feature = "market", feature = "account"))]
+
+use std::time::Duration;
+
+use bybit_rs::{AccountType, Client, Environment, Error};
+
+#[test]
+fn a_public_client_refuses_to_sign() {
+    let client = Client::public(Environment::Testnet);
+    assert!(!client.is_authenticated());
+    assert!(matches!(client.get_wallet_balance(AccountType::UNIFIED, Vec::new(), &Duration::from_secs(5)), Err(Error::Signing(_))));
+    assert!(client.get_server_time().is_ok());
+}
@@ -0,0 +1,54 @@
+use bybit_rs::query;
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct Params {
+    category: &'static str,
+    symbol: Vec<&'static str>,
+    #[serde(rename = "orderLinkId")]
+    order_link_id: Option<&'static str>,
+    #[serde(rename = "baseCoin")]
+    base_coin: Vec<&'static str>,
+    limit: u32,
+    cursor: Option<String>,
+}
+
+fn params() -> Params {
+    Params { category: "linear", symbol: vec!["BTCUSDT", "ETHUSDT"], order_link_id: None, base_coin: vec![], limit: 50, cursor: None }
+}
+
+#[test]
+fn sequences_are_comma_joined_and_empty_fields_skipped() {
+    assert_eq!(query::to_string(&params()).unwrap(), "category=linear&symbol=BTCUSDT,ETHUSDT&limit=50");
+}
+
+#[test]
+fn values_are_percent_encoded() {
+    let params = Params { order_link_id: Some("a b/c:d+é"), symbol: vec!["BTC-29JUL22-25000-C"], ..params() };
+    assert_eq!(
+        query::to_string(&params).unwrap(),
+        "category=linear&symbol=BTC-29JUL22-25000-C&orderLinkId=a%20b%2Fc%3Ad%2B%C3%A9&limit=50"
+    );
+}
+
+#[test]
+fn non_struct_params_are_rejected() {
+    assert_eq!(query::to_string(&()).unwrap(), "");
+    assert!(query::to_string(&["BTCUSDT"]).is_err());
+}
+
+#[test]
+fn decode_leaves_malformed_escapes_alone() {
+    assert_eq!(query::decode("123%3A1"), "123:1");
+    assert_eq!(query::decode("%E2%82%AC"), "€");
+    assert_eq!(query::decode("50%"), "50%");
+    assert_eq!(query::decode("%zz%4"), "%zz%4");
+}
+
+#[test]
+fn cursors_round_trip_without_double_encoding() {
+    let cursor = "page_args%3D1704067200000%26symbol%3D6%3A0";
+    let params = Params { cursor: Some(query::decode(cursor)), ..params() };
+    let encoded = query::to_string(&params).unwrap();
+    assert!(encoded.ends_with(&format!("&cursor={cursor}")), "{encoded}");
+}
@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+use bybit_rs::{clock::FixedClock, ratelimit::RateLimiter};
+
+const NOW: i64 = 1_700_000_000_000;
+
+fn headers(remaining: u32, reset_ms: i64) -> http::HeaderMap {
+    let mut headers = http::HeaderMap::new();
+    headers.insert("X-Bapi-Limit", "10".parse().unwrap());
+    headers.insert("X-Bapi-Limit-Status", remaining.to_string().parse().unwrap());
+    headers.insert("X-Bapi-Limit-Reset-Timestamp", reset_ms.to_string().parse().unwrap());
+    headers
+}
+
+fn limiter(now: i64) -> RateLimiter {
+    RateLimiter::new().with_clock(FixedClock::from_millis(now))
+}
+
+#[test]
+fn unknown_endpoints_go_straight_through() {
+    assert_eq!(limiter(NOW).try_acquire("/v5/order/create"), None);
+}
+
+#[test]
+fn budget_is_taken_until_exhausted() {
+    let limiter = limiter(NOW);
+    limiter.observe("/v5/order/create", &headers(2, NOW + 1500));
+    assert_eq!(limiter.try_acquire("/v5/order/create"), None);
+    assert_eq!(limiter.try_acquire("/v5/order/create"), None);
+    assert_eq!(limiter.status("/v5/order/create").unwrap().remaining, 0);
+    assert_eq!(limiter.try_acquire("/v5/order/create"), Some(Duration::from_millis(1500)));
+    // waiting doesn't take anything, the same wait comes back
+    assert_eq!(limiter.try_acquire("/v5/order/create"), Some(Duration::from_millis(1500)));
+}
+
+#[test]
+fn budget_resets_once_the_window_passed() {
+    let limiter = limiter(NOW);
+    limiter.observe("/v5/order/create", &headers(0, NOW));
+    assert_eq!(limiter.try_acquire("/v5/order/create"), None);
+    assert_eq!(limiter.status("/v5/order/create"), None);
+}
+
+#[test]
+fn grouped_paths_share_a_budget() {
+    let limiter = limiter(NOW).with_group("/v5/order/amend", "/v5/order/create");
+    limiter.observe("/v5/order/create", &headers(1, NOW + 1000));
+    assert_eq!(limiter.try_acquire("/v5/order/amend"), None);
+    assert_eq!(limiter.try_acquire("/v5/order/create"), Some(Duration::from_secs(1)));
+    assert_eq!(limiter.try_acquire("/v5/order/cancel"), None);
+}
+
+#[test]
+fn responses_without_limit_headers_are_ignored() {
+    let limiter = limiter(NOW);
+    limiter.observe("/v5/order/create", &http::HeaderMap::new());
+    assert_eq!(limiter.status("/v5/order/create"), None);
+}
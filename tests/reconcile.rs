@@ -0,0 +1,100 @@
+#![cfg(all(feature = "trade", feature = "account"))]
+
+use std::{cell::RefCell, time::Duration};
+
+use bybit_rs::{
+    export::{CsvExporter, ExportRow, ExportSink},
+    reconcile::ReconciliationRow,
+    Category, Client,
+};
+use bytes::Bytes;
+use chrono::DateTime;
+use futures::executor::block_on;
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+
+const T0: i64 = 1_699_920_000_000;
+const DAY: i64 = 24 * 60 * 60 * 1000;
+
+fn respond(result: Value) -> std::future::Ready<Result<Bytes, std::io::Error>> {
+    let body = json!({ "retCode": 0, "retMsg": "OK", "result": result, "retExtInfo": {}, "time": 0 });
+    std::future::ready(Ok(Bytes::from(body.to_string())))
+}
+
+fn entry(symbol: &str, kind: &str, fee: &str, funding: &str, cash_flow: &str, change: &str) -> Value {
+    json!({
+        "id": "1", "symbol": symbol, "category": "linear", "side": "Buy", "transactionTime": T0.to_string(), "type": kind,
+        "qty": "1", "size": "1", "currency": "USDT", "tradePrice": "30000", "funding": funding, "fee": fee, "cashFlow": cash_flow,
+        "change": change, "cashBalance": "1000", "feeRate": "", "tradeId": "", "orderId": "", "orderLinkId": ""
+    })
+}
+
+fn execution(symbol: &str, exec_type: &str, fee: &str) -> Value {
+    json!({
+        "symbol": symbol, "orderId": "1", "orderLinkId": "", "side": "Buy", "orderPrice": "30000", "orderQty": "1",
+        "leavesQty": "0", "orderType": "Limit", "execId": "a", "execPrice": "30000", "execQty": "1", "execValue": "30000",
+        "execFee": fee, "execType": exec_type, "execTime": T0.to_string(), "feeRate": "0.0001", "isMaker": false, "markPrice": "30000"
+    })
+}
+
+fn dec(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
+
+fn report() -> (Vec<ReconciliationRow>, Vec<String>) {
+    let requests = RefCell::new(Vec::new());
+    let send = |request: http::Request<String>| {
+        let query = request.uri().query().unwrap_or_default().to_string();
+        requests.borrow_mut().push(format!("{}?{query}", request.uri().path()));
+        let first_window = query.contains(&format!("startTime={T0}"));
+        match request.uri().path() {
+            "/v5/account/transaction-log" if first_window => respond(json!({ "list": [
+                entry("BTCUSDT", "TRADE", "1.5", "", "0", "-1.5"),
+                entry("BTCUSDT", "TRADE", "1.2", "", "100", "98.8"),
+                entry("BTCUSDT", "SETTLEMENT", "", "-0.3", "0", "-0.3"),
+                entry("", "TRANSFER_IN", "", "", "500", "500"),
+            ], "nextPageCursor": "" })),
+            "/v5/account/transaction-log" => respond(json!({ "list": [entry("ETHUSDT", "SETTLEMENT", "", "0.2", "0", "0.2")], "nextPageCursor": "" })),
+            _ if first_window => respond(json!({ "category": "linear", "list": [
+                execution("BTCUSDT", "Trade", "1.5"),
+                execution("BTCUSDT", "Trade", "1.2"),
+                execution("BTCUSDT", "Funding", "0.3"),
+            ], "nextPageCursor": "" })),
+            _ => respond(json!({ "category": "linear", "list": [execution("SOLUSDT", "Trade", "0.1")], "nextPageCursor": "" })),
+        }
+    };
+
+    let client = Client::new("key".to_string(), "secret".to_string());
+    let range = DateTime::from_timestamp_millis(T0).unwrap()..=DateTime::from_timestamp_millis(T0 + 10 * DAY).unwrap();
+    let rows = block_on(client.reconciliation_report(Category::Linear, range, &Duration::from_secs(5), send)).unwrap();
+    (rows, requests.into_inner())
+}
+
+#[test]
+fn fees_funding_and_pnl_are_summed_per_symbol() {
+    let (rows, requests) = report();
+    // two 7 day windows, the log and executions of each
+    assert_eq!(requests.len(), 4);
+    assert!(requests.iter().any(|request| request.contains(&format!("startTime={}&endTime={}", T0 + 7 * DAY, T0 + 10 * DAY))));
+
+    assert_eq!(rows.iter().map(|row| row.symbol.as_str()).collect::<Vec<_>>(), ["BTCUSDT", "ETHUSDT", "SOLUSDT"]);
+    let btc = &rows[0];
+    assert_eq!((btc.currency.as_str(), btc.executions), ("USDT", 2));
+    assert_eq!((btc.execution_fees, btc.logged_fees, btc.fee_difference()), (dec("2.7"), dec("2.7"), Decimal::ZERO));
+    assert_eq!((btc.funding_paid, btc.funding_received, btc.realized_pnl, btc.net), (dec("0.3"), Decimal::ZERO, dec("100"), dec("97")));
+    assert_eq!((rows[1].funding_received, rows[1].funding()), (dec("0.2"), dec("0.2")));
+    // fills without a transaction log entry show up as a fee difference
+    assert_eq!((rows[2].currency.as_str(), rows[2].fee_difference()), ("", dec("0.1")));
+}
+
+#[test]
+fn report_rows_export_like_fetched_ones() {
+    let (rows, _) = report();
+    let mut csv = CsvExporter::new(Vec::new()).unwrap();
+    csv.write(&rows).unwrap();
+    let text = String::from_utf8(csv.finish().unwrap()).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], ReconciliationRow::COLUMNS.join(","));
+    assert_eq!(lines[1], "BTCUSDT,USDT,2,2.7,2.7,0,0.3,0,100,97");
+    assert_eq!(lines.len(), 4);
+}
@@ -0,0 +1,103 @@
+#![cfg(feature = "ws")]
+
+use std::{
+    path::PathBuf,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bybit_rs::{
+    record::{Record, RecordReader, Recorder, Rotation, RotatingWriter},
+    ws::Topic,
+    Client, Environment,
+};
+use futures::{
+    channel::mpsc::{self, SendError, UnboundedReceiver, UnboundedSender},
+    executor::block_on,
+    Sink, Stream,
+};
+
+// replays incoming and then ends, whatever is sent goes to outgoing
+struct Conn {
+    incoming: UnboundedReceiver<Result<String, SendError>>,
+    outgoing: UnboundedSender<String>,
+}
+
+impl Stream for Conn {
+    type Item = Result<String, SendError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.incoming).poll_next(cx)
+    }
+}
+
+impl Sink<String> for Conn {
+    type Error = SendError;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError>> {
+        Pin::new(&mut self.outgoing).poll_ready(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: String) -> Result<(), SendError> {
+        Pin::new(&mut self.outgoing).start_send(item)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError>> {
+        Pin::new(&mut self.outgoing).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError>> {
+        Pin::new(&mut self.outgoing).poll_close(cx)
+    }
+}
+
+fn conn(messages: &[&str]) -> (Conn, UnboundedReceiver<String>) {
+    let (incoming_tx, incoming) = mpsc::unbounded();
+    let (outgoing, sent) = mpsc::unbounded();
+    for message in messages {
+        incoming_tx.unbounded_send(Ok(message.to_string())).unwrap();
+    }
+    (Conn { incoming, outgoing }, sent)
+}
+
+fn dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("bybit_rs-record-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+const TRADE: &str = r#"{"topic":"publicTrade.BTCUSDT","type":"snapshot","ts":1,"data":[]}"#;
+
+#[test]
+fn records_messages_and_the_session_end() {
+    let dir = dir("ndjson");
+    let mut recorder = Recorder::new(RotatingWriter::new(&dir, "ticks", Rotation::Daily), vec![Topic::Trade { symbol: "BTCUSDT".to_string() }]);
+    let (conn, _sent) = conn(&[TRADE, TRADE]);
+
+    block_on(recorder.run(&Client::public(Environment::Mainnet), conn)).unwrap();
+    // run returns after the writer thread flushed, the records are on disk
+    let records = RecordReader::open(&dir, "ticks").unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(records.len(), 3);
+    assert!(matches!(&records[0], Record::Message { topic, ts: Some(1), .. } if topic == "publicTrade.BTCUSDT"));
+    assert!(matches!(&records[2], Record::Gap { topic: None, reason, .. } if reason == "session ended"));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn parquet_files_hold_one_row_per_record() {
+    use bybit_rs::record::ParquetWriter;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    let dir = dir("parquet");
+    let mut recorder = Recorder::new(ParquetWriter::new(&dir, "ticks", Rotation::Daily), vec![Topic::Trade { symbol: "BTCUSDT".to_string() }]);
+    let (conn, _sent) = conn(&[TRADE, TRADE]);
+
+    block_on(recorder.run(&Client::public(Environment::Mainnet), conn)).unwrap();
+    let files = std::fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().path()).collect::<Vec<_>>();
+    assert_eq!(files.len(), 1);
+    let reader = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&files[0]).unwrap()).unwrap().build().unwrap();
+    let rows: usize = reader.map(|batch| batch.unwrap().num_rows()).sum();
+    assert_eq!(rows, 3);
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,91 @@
+#![cfg(feature = "trade")]
+
+use std::{sync::Mutex, time::Duration};
+
+use bybit_rs::{
+    trade::{OrderRef, PlaceOrderRequest, ReplaceOutcome},
+    Category, Client, Error, Side,
+};
+use bytes::Bytes;
+use futures::executor::block_on;
+use rust_decimal::Decimal;
+
+const CANCELLED: &str = r#"{"retCode":0,"retMsg":"OK","result":{"orderId":"1","orderLinkId":""},"time":0}"#;
+const CANCEL_REJECTED: &str = r#"{"retCode":110001,"retMsg":"order not exists or too late to cancel"}"#;
+const PLACED: &str = r#"{"retCode":0,"retMsg":"OK","result":{"orderId":"2","orderLinkId":""},"time":0}"#;
+
+fn dec(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
+
+fn page(status: &str, cum_exec_qty: &str) -> String {
+    format!(
+        r#"{{"retCode":0,"retMsg":"OK","result":{{"category":"linear","nextPageCursor":"","list":[{{
+            "orderId":"1","orderLinkId":"","symbol":"BTCUSDT","side":"Buy","orderType":"Limit","price":"100","qty":"10",
+            "orderStatus":"{status}","timeInForce":"GTC","positionIdx":0,"avgPrice":"100","leavesQty":"0",
+            "cumExecQty":"{cum_exec_qty}","cumExecValue":"0","cumExecFee":"0","cancelType":"UNKNOWN","rejectReason":"EC_NoError",
+            "stopOrderType":"","triggerPrice":"0","takeProfit":"0","stopLoss":"0","reduceOnly":false,
+            "createdTime":"1700000000000","updatedTime":"1700000000000"}}]}},"time":0}}"#
+    )
+}
+
+fn successor() -> PlaceOrderRequest {
+    PlaceOrderRequest::limit(Category::Linear, "BTCUSDT", Side::Buy, dec("3"), dec("99"))
+}
+
+fn replace(cancel: &'static str, lookup: Option<String>, created: &Mutex<Vec<String>>) -> bybit_rs::Result<ReplaceOutcome> {
+    let client = Client::new("key".to_string(), "secret".to_string());
+    let send = |request: http::Request<String>| {
+        let body = match request.uri().path() {
+            "/v5/order/cancel" => Ok(cancel.to_string()),
+            "/v5/order/create" => {
+                created.lock().unwrap().push(request.body().clone());
+                Ok(PLACED.to_string())
+            }
+            _ => lookup.clone().ok_or_else(|| std::io::Error::other("connection reset")),
+        };
+        async move { body.map(Bytes::from) }
+    };
+    block_on(client.replace_order(OrderRef::OrderId("1".to_string()), &successor(), &Duration::from_secs(5), send))
+}
+
+#[test]
+fn places_the_successor_as_given_and_reports_fills() {
+    let created = Mutex::new(Vec::new());
+    let outcome = replace(CANCELLED, Some(page("Cancelled", "4")), &created).unwrap();
+
+    let ReplaceOutcome::Replaced { filled, placed, .. } = outcome else {
+        panic!("expected the successor to be placed");
+    };
+    assert_eq!(filled, dec("4"));
+    assert_eq!(placed.order_id, "2");
+    let created = created.lock().unwrap();
+    assert_eq!(created.len(), 1);
+    assert!(created[0].contains(r#""qty":"3""#), "{}", created[0]);
+}
+
+#[test]
+fn reports_a_fill_that_beat_the_cancel() {
+    let created = Mutex::new(Vec::new());
+    let outcome = replace(CANCEL_REJECTED, Some(page("Filled", "10")), &created).unwrap();
+    assert!(matches!(outcome, ReplaceOutcome::Closed { .. }));
+    assert!(created.lock().unwrap().is_empty());
+}
+
+#[test]
+fn unparseable_fills_are_unexpected() {
+    let created = Mutex::new(Vec::new());
+    let err = replace(CANCELLED, Some(page("Cancelled", "n/a")), &created).unwrap_err();
+    assert!(matches!(err, Error::Unexpected(_)), "{err}");
+    assert!(created.lock().unwrap().is_empty());
+}
+
+#[test]
+fn a_failed_lookup_keeps_the_cancel_error() {
+    let created = Mutex::new(Vec::new());
+    let err = replace(CANCEL_REJECTED, None, &created).unwrap_err();
+    let Error::Api(err) = err else {
+        panic!("expected the cancel's api error, got {err}");
+    };
+    assert!(err.to_string().contains("too late to cancel"), "{err}");
+}
@@ -0,0 +1,40 @@
+#![cfg(feature = "ws")]
+
+use std::path::PathBuf;
+
+use bybit_rs::{
+    record::{Pacing, RecordReader, Replay},
+    ws::PublicEvent,
+};
+use futures::executor::block_on;
+
+fn recording(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("bybit_rs-replay-{name}-{}.ndjson", std::process::id()));
+    let lines = [
+        r#"{"type":"message","recv_ts":1700000000000,"topic":"greeks.BTC","kind":"snapshot","ts":1,"data":{}}"#,
+        r#"{"type":"message","recv_ts":1700000000001,"topic":"greeks.BTC","kind":"snapshot","ts":2,"data":{}}"#,
+    ];
+    std::fs::write(&path, lines.join("\n")).unwrap();
+    path
+}
+
+#[test]
+fn recorded_pacing_rejects_unusable_speeds() {
+    for speed in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        assert!(Pacing::recorded(speed).is_err(), "{speed}");
+    }
+    assert_eq!(Pacing::recorded(2.0).unwrap(), Pacing::Recorded { speed: 2.0 });
+}
+
+#[test]
+fn unrepresentable_gaps_replay_unpaced() {
+    for (name, speed) in [("tiny", f64::MIN_POSITIVE), ("nan", f64::NAN)] {
+        let path = recording(name);
+        let mut replay = Replay::new(RecordReader::from_files(vec![path.clone()])).with_pacing(Pacing::Recorded { speed });
+        for _ in 0..2 {
+            assert!(matches!(block_on(replay.next()), Some(Ok(PublicEvent::Raw { .. }))));
+        }
+        assert!(block_on(replay.next()).is_none());
+        std::fs::remove_file(path).unwrap();
+    }
+}
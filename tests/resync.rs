@@ -0,0 +1,96 @@
+#![cfg(feature = "trade")]
+
+use bybit_rs::{
+    execution::{FillAggregator, OrderTracker},
+    trade::{Execution, Order, Resync, ResyncChange},
+    Category, OrderStatus,
+};
+use rust_decimal::Decimal;
+use serde_json::json;
+
+fn order(link_id: &str, order_id: &str, status: &str) -> Order {
+    serde_json::from_value(json!({
+        "orderId": order_id, "orderLinkId": link_id, "symbol": "BTCUSDT", "side": "Buy", "orderType": "Limit",
+        "price": "30000", "qty": "10", "orderStatus": status, "timeInForce": "GTC", "positionIdx": 0,
+        "avgPrice": "", "leavesQty": "6", "cumExecQty": "4", "cumExecValue": "", "cumExecFee": "", "cancelType": "",
+        "rejectReason": "", "stopOrderType": "", "triggerPrice": "", "takeProfit": "", "stopLoss": "",
+        "reduceOnly": false, "createdTime": "0", "updatedTime": "0"
+    }))
+    .unwrap()
+}
+
+fn execution(exec_id: &str, link_id: &str, order_id: &str, qty: &str) -> Execution {
+    serde_json::from_value(json!({
+        "symbol": "BTCUSDT", "orderId": order_id, "orderLinkId": link_id, "side": "Buy", "orderPrice": "30000",
+        "orderQty": "10", "leavesQty": "6", "orderType": "Limit", "execId": exec_id, "execPrice": "30000",
+        "execQty": qty, "execValue": "", "execFee": "0", "execType": "Trade", "execTime": "0", "feeRate": "",
+        "isMaker": true, "markPrice": ""
+    }))
+    .unwrap()
+}
+
+fn tracked(link_ids: &[(&str, &str)]) -> OrderTracker {
+    let mut tracker = OrderTracker::new();
+    for (link_id, order_id) in link_ids {
+        tracker.submit(link_id.to_string(), Decimal::from(10)).unwrap();
+        tracker.ack(link_id, order_id.to_string()).unwrap();
+    }
+    tracker
+}
+
+#[test]
+fn missed_fills_are_applied_once() {
+    let mut tracker = tracked(&[("a", "1")]);
+    let mut fills = FillAggregator::new();
+    let resync = Resync {
+        category: Category::Linear,
+        open_orders: vec![order("a", "1", "PartiallyFilled")],
+        executions: vec![execution("x", "a", "1", "4")],
+    };
+
+    let changes = resync.reconcile(&mut tracker, &mut fills);
+    assert!(matches!(&changes[..], [ResyncChange::MissedFill(fill)] if fill.exec_id == "x"));
+    assert_eq!(tracker.get("a").unwrap().filled, Decimal::from(4));
+    assert_eq!(tracker.get("a").unwrap().status, Some(OrderStatus::PartiallyFilled));
+
+    // the same window fetched again changes nothing
+    assert!(resync.reconcile(&mut tracker, &mut fills).is_empty());
+    assert_eq!(tracker.get("a").unwrap().filled, Decimal::from(4));
+}
+
+#[test]
+fn status_changes_are_reported_and_applied() {
+    let mut tracker = tracked(&[("a", "1")]);
+    let resync = Resync { category: Category::Linear, open_orders: vec![order("a", "1", "Untriggered")], executions: vec![] };
+
+    let changes = resync.reconcile(&mut tracker, &mut FillAggregator::new());
+    assert!(matches!(
+        &changes[..],
+        [ResyncChange::StatusChanged { order_link_id, from: Some(OrderStatus::New), to: OrderStatus::Untriggered }] if order_link_id == "a"
+    ));
+    assert_eq!(tracker.get("1").unwrap().status, Some(OrderStatus::Untriggered));
+}
+
+#[test]
+fn untracked_and_vanished_orders_are_reported() {
+    let mut tracker = tracked(&[("a", "1"), ("b", "2")]);
+    tracker.submit("pending".to_string(), Decimal::from(10)).unwrap();
+    let resync = Resync { category: Category::Linear, open_orders: vec![order("a", "1", "New"), order("", "9", "New")], executions: vec![] };
+
+    let changes = resync.reconcile(&mut tracker, &mut FillAggregator::new());
+    assert_eq!(changes.len(), 2);
+    assert!(matches!(&changes[0], ResyncChange::UnknownOrder(order) if order.order_id == "9"));
+    // unacknowledged orders may still be in flight and aren't reported
+    assert!(matches!(&changes[1], ResyncChange::NoLongerOpen(id) if id == "b"));
+}
+
+#[test]
+fn refused_updates_are_reported_without_applying() {
+    let mut tracker = tracked(&[("a", "1")]);
+    let resync = Resync { category: Category::Linear, open_orders: vec![order("a", "1", "New")], executions: vec![execution("x", "a", "1", "11")] };
+
+    let changes = resync.reconcile(&mut tracker, &mut FillAggregator::new());
+    assert!(matches!(&changes[0], ResyncChange::Inconsistent(_)));
+    assert!(matches!(&changes[1], ResyncChange::MissedFill(_)));
+    assert_eq!(tracker.get("a").unwrap().filled, Decimal::ZERO);
+}
@@ -0,0 +1,52 @@
+use bybit_rs::retry::is_idempotent;
+
+fn request(method: http::Method, path: &str, body: &str) -> http::Request<String> {
+    http::Request::builder()
+        .method(method)
+        .uri(format!("https://api.bybit.com{path}"))
+        .body(body.to_string())
+        .unwrap()
+}
+
+fn post(path: &str, body: &str) -> http::Request<String> {
+    request(http::Method::POST, path, body)
+}
+
+#[test]
+fn gets_are_idempotent() {
+    assert!(is_idempotent(&request(http::Method::GET, "/v5/order/realtime?category=linear", "")));
+}
+
+#[test]
+fn order_create_needs_order_link_id() {
+    assert!(!is_idempotent(&post("/v5/order/create", r#"{"category":"linear","symbol":"BTCUSDT"}"#)));
+    assert!(!is_idempotent(&post("/v5/order/create", r#"{"symbol":"BTCUSDT","orderLinkId":""}"#)));
+    assert!(is_idempotent(&post("/v5/order/create", r#"{"symbol":"BTCUSDT","orderLinkId":"a"}"#)));
+}
+
+#[test]
+fn batch_create_needs_order_link_id_on_every_order() {
+    assert!(is_idempotent(&post("/v5/order/create-batch", r#"{"request":[{"orderLinkId":"a"},{"orderLinkId":"b"}]}"#)));
+    assert!(!is_idempotent(&post("/v5/order/create-batch", r#"{"request":[{"orderLinkId":"a"},{"symbol":"BTCUSDT"}]}"#)));
+    assert!(!is_idempotent(&post("/v5/order/create-batch", r#"{"request":[]}"#)));
+}
+
+#[test]
+fn withdraw_needs_request_id() {
+    assert!(!is_idempotent(&post("/v5/asset/withdraw/create", r#"{"coin":"USDT","amount":"10"}"#)));
+    assert!(is_idempotent(&post("/v5/asset/withdraw/create", r#"{"coin":"USDT","amount":"10","requestId":"r1"}"#)));
+}
+
+#[test]
+fn transfers_need_transfer_id() {
+    for path in ["/v5/asset/transfer/inter-transfer", "/v5/asset/transfer/universal-transfer"] {
+        assert!(!is_idempotent(&post(path, r#"{"coin":"USDT","amount":"10"}"#)), "{path}");
+        assert!(is_idempotent(&post(path, r#"{"coin":"USDT","amount":"10","transferId":"42c0cfb0-6bca-c242-bc76-4e6df6cbcb16"}"#)), "{path}");
+    }
+}
+
+#[test]
+fn other_writes_are_not_resent() {
+    assert!(!is_idempotent(&post("/v5/position/set-leverage", r#"{"symbol":"BTCUSDT","buyLeverage":"5"}"#)));
+    assert!(!is_idempotent(&post("/v5/order/create", "not json")));
+}
@@ -0,0 +1,142 @@
+#![cfg(feature = "trade")]
+
+use std::{cell::RefCell, time::Duration};
+
+use bybit_rs::{
+    clock::FixedClock,
+    number::Precision,
+    risk::{RiskPolicy, RiskViolation, SymbolLimits},
+    trade::{AmendOrderRequest, OrderRef, PlaceOrderRequest},
+    Category, Client, Error, Side,
+};
+use bytes::Bytes;
+use futures::executor::block_on;
+use rust_decimal::Decimal;
+
+const ACK: &str = r#"{"retCode":0,"retMsg":"OK","result":{"orderId":"1","orderLinkId":""},"retExtInfo":{},"time":0}"#;
+
+fn dec(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
+
+fn recv_window() -> Duration {
+    Duration::from_secs(5)
+}
+
+fn client(policy: RiskPolicy) -> Client {
+    Client::new("key".to_string(), "secret".to_string()).with_clock(FixedClock::from_millis(1_700_000_000_000)).with_risk_policy(policy)
+}
+
+fn limit(side: Side, qty: &str, price: &str) -> PlaceOrderRequest {
+    PlaceOrderRequest::limit(Category::Linear, "BTCUSDT", side, dec(qty), dec(price))
+}
+
+fn violation<T>(result: bybit_rs::Result<T>) -> RiskViolation {
+    match result {
+        Err(Error::Risk(violation)) => violation,
+        Err(err) => panic!("expected a risk violation, got {err}"),
+        Ok(_) => panic!("expected a risk violation, the order went through"),
+    }
+}
+
+// the body that went out, the order as the policy let it through
+fn sent(client: &Client, request: &PlaceOrderRequest) -> serde_json::Value {
+    let body = RefCell::new(String::new());
+    let send = |request: http::Request<String>| {
+        *body.borrow_mut() = request.into_body();
+        async { Ok::<_, std::io::Error>(Bytes::from_static(ACK.as_bytes())) }
+    };
+    block_on(client.place_order(request, &recv_window()).unwrap().send(send)).unwrap();
+    serde_json::from_str(&body.into_inner()).unwrap()
+}
+
+#[test]
+fn orders_above_the_notional_limit_are_refused_before_signing() {
+    let client = client(RiskPolicy::new(SymbolLimits::new().with_max_notional(dec("1000"))));
+
+    let refused = violation(client.place_order(&limit(Side::Buy, "1", "30000"), &recv_window()));
+    assert_eq!(refused, RiskViolation::NotionalTooLarge { symbol: "BTCUSDT".to_string(), notional: dec("30000"), max: dec("1000") });
+
+    // reduce only orders only ever shrink exposure
+    assert!(client.place_order(&limit(Side::Sell, "1", "30000").reduce_only(), &recv_window()).is_ok());
+    assert!(client.place_order(&limit(Side::Buy, "0.03", "30000"), &recv_window()).is_ok());
+}
+
+#[test]
+fn clamping_shrinks_the_order_to_the_limit() {
+    let limits = SymbolLimits::new().with_max_notional(dec("1000")).clamping(Precision::new(dec("0.1"), dec("0.001")));
+    let client = client(RiskPolicy::new(limits));
+
+    let body = sent(&client, &limit(Side::Buy, "1", "30000"));
+    assert_eq!(body["qty"], "0.033");
+    assert_eq!(body["price"], "30000");
+}
+
+#[test]
+fn the_position_limit_counts_what_is_already_held() {
+    let limits = SymbolLimits::new().with_max_position(dec("1")).clamping(Precision::new(dec("0.1"), dec("0.001")));
+    let policy = RiskPolicy::new(limits);
+    policy.set_position("BTCUSDT", 0, dec("0.75"));
+    let client = client(policy.clone());
+
+    assert_eq!(sent(&client, &limit(Side::Buy, "1", "30000"))["qty"], "0.25");
+    // selling first takes the long down, then builds a short up to the limit
+    assert_eq!(sent(&client, &limit(Side::Sell, "3", "30000"))["qty"], "1.75");
+
+    policy.set_position("BTCUSDT", 0, dec("1"));
+    let refused = violation(client.place_order(&limit(Side::Buy, "0.5", "30000"), &recv_window()));
+    assert_eq!(refused, RiskViolation::PositionTooLarge { symbol: "BTCUSDT".to_string(), position: dec("1.5"), max: dec("1") });
+}
+
+#[test]
+fn limit_prices_must_stay_within_the_band_around_the_mark() {
+    let policy = RiskPolicy::new(SymbolLimits::new()).with_symbol("BTCUSDT", SymbolLimits::new().with_price_band(dec("0.05")));
+    let client = client(policy.clone());
+
+    let refused = violation(client.place_order(&limit(Side::Buy, "0.01", "30000"), &recv_window()));
+    assert_eq!(refused, RiskViolation::NoMarkPrice { symbol: "BTCUSDT".to_string() });
+
+    policy.set_mark_price("BTCUSDT", dec("30000"));
+    assert!(client.place_order(&limit(Side::Buy, "0.01", "31000"), &recv_window()).is_ok());
+    assert!(matches!(violation(client.place_order(&limit(Side::Buy, "0.01", "32000"), &recv_window())), RiskViolation::OutsidePriceBand { .. }));
+
+    // other symbols fall back to the default limits, none here
+    let other = PlaceOrderRequest::limit(Category::Linear, "ETHUSDT", Side::Buy, dec("1"), dec("1"));
+    assert!(client.place_order(&other, &recv_window()).is_ok());
+
+    let amend = AmendOrderRequest::new(Category::Linear, "BTCUSDT", OrderRef::OrderId("1".to_string())).with_price(dec("25000"));
+    assert!(matches!(violation(client.amend_order(&amend, &recv_window())), RiskViolation::OutsidePriceBand { .. }));
+}
+
+#[test]
+fn clamped_prices_land_on_the_band_edge_inside_the_tick() {
+    let limits = SymbolLimits::new().with_price_band(dec("0.05")).clamping(Precision::new(dec("0.4"), dec("0.001")));
+    let policy = RiskPolicy::new(limits);
+    policy.set_mark_price("BTCUSDT", dec("100"));
+    let client = client(policy);
+
+    assert_eq!(sent(&client, &limit(Side::Buy, "1", "90"))["price"], "95.2");
+    assert_eq!(sent(&client, &limit(Side::Sell, "1", "110"))["price"], "104.8");
+}
+
+#[test]
+fn the_rate_cap_is_a_sliding_window_per_symbol() {
+    let policy = RiskPolicy::new(SymbolLimits::new().with_rate_cap(2, Duration::from_secs(1)));
+    let client = client(policy.clone());
+
+    assert!(client.place_order(&limit(Side::Buy, "0.01", "30000"), &recv_window()).is_ok());
+    assert!(client.place_order(&limit(Side::Buy, "0.01", "30000"), &recv_window()).is_ok());
+    let refused = violation(client.place_order(&limit(Side::Buy, "0.01", "30000"), &recv_window()));
+    assert_eq!(refused, RiskViolation::RateCapped { symbol: "BTCUSDT".to_string(), max: 2, per: Duration::from_secs(1) });
+
+    // a second later the window has moved on, the policy is shared with the first client
+    let later = Client::new("key".to_string(), "secret".to_string()).with_clock(FixedClock::from_millis(1_700_000_001_000)).with_risk_policy(policy);
+    assert!(later.place_order(&limit(Side::Buy, "0.01", "30000"), &recv_window()).is_ok());
+}
+
+#[test]
+fn a_refused_order_fails_the_whole_batch() {
+    let client = client(RiskPolicy::new(SymbolLimits::new().with_max_notional(dec("1000"))));
+    let orders = [limit(Side::Buy, "0.01", "30000"), limit(Side::Buy, "1", "30000")];
+    assert!(matches!(violation(client.create_batch_orders(Category::Linear, &orders, &recv_window())), RiskViolation::NotionalTooLarge { .. }));
+}
@@ -0,0 +1,61 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use bybit_rs::scheduler::{Priority, RequestScheduler};
+use bytes::Bytes;
+use futures::executor::block_on;
+
+fn request(method: &str, path: &str) -> http::Request<String> {
+    http::Request::builder().method(method).uri(format!("https://api.bybit.com{path}")).body(String::new()).unwrap()
+}
+
+#[test]
+fn order_mutations_are_told_apart_from_data_calls() {
+    assert_eq!(Priority::of(&request("POST", "/v5/order/cancel")), Priority::Order);
+    assert_eq!(Priority::of(&request("POST", "/v5/order/create-batch")), Priority::Order);
+    assert_eq!(Priority::of(&request("POST", "/v5/position/trading-stop")), Priority::Order);
+    assert_eq!(Priority::of(&request("GET", "/v5/order/realtime")), Priority::Data);
+    assert_eq!(Priority::of(&request("GET", "/v5/market/kline")), Priority::Data);
+
+    let scheduler = RequestScheduler::new(10, Duration::from_secs(1)).with_priority("/v5/asset/transfer/inter-transfer", Priority::Order);
+    assert_eq!(scheduler.priority(&request("POST", "/v5/asset/transfer/inter-transfer")), Priority::Order);
+}
+
+#[test]
+fn the_reserve_is_left_to_order_calls() {
+    let scheduler = RequestScheduler::new(2, Duration::from_millis(300)).with_reserve(1);
+    let sent = Mutex::new(Vec::new());
+    let started = Instant::now();
+    let send = |request: http::Request<String>| {
+        sent.lock().unwrap().push((request.uri().path().to_string(), started.elapsed()));
+        std::future::ready(Ok::<_, std::io::Error>(Bytes::new()))
+    };
+    let send = scheduler.wrap(&send);
+
+    // the second kline request would take the last request of the window, it waits while the cancel goes right away
+    let calls = [request("GET", "/v5/market/kline"), request("GET", "/v5/market/kline"), request("POST", "/v5/order/cancel")];
+    block_on(futures::future::join_all(calls.into_iter().map(&send)));
+
+    let sent = sent.lock().unwrap();
+    let paths: Vec<&str> = sent.iter().map(|(path, _)| path.as_str()).collect();
+    assert_eq!(paths, ["/v5/market/kline", "/v5/order/cancel", "/v5/market/kline"]);
+    assert!(sent[1].1 < Duration::from_millis(100));
+    assert!(sent[2].1 >= Duration::from_millis(300));
+}
+
+#[test]
+fn a_waiting_order_call_goes_before_waiting_data_calls() {
+    let scheduler = RequestScheduler::new(1, Duration::from_millis(100)).with_reserve(0);
+    let sent = Mutex::new(Vec::new());
+    let send = |request: http::Request<String>| {
+        sent.lock().unwrap().push(request.uri().path().to_string());
+        std::future::ready(Ok::<_, std::io::Error>(Bytes::new()))
+    };
+    let send = scheduler.wrap(&send);
+
+    let calls = [request("GET", "/v5/market/kline"), request("GET", "/v5/market/tickers"), request("POST", "/v5/order/amend")];
+    block_on(futures::future::join_all(calls.into_iter().map(&send)));
+    assert_eq!(*sent.lock().unwrap(), ["/v5/market/kline", "/v5/order/amend", "/v5/market/tickers"]);
+}
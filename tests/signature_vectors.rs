@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use bybit_rs::{sign, Params};
+use chrono::DateTime;
+use serde::Serialize;
+
+#[test]
+fn vectors_match_sign_payload() {
+    for vector in sign::VECTORS {
+        let signature = sign::sign_payload(vector.secret, &vector.timestamp(), vector.api_key, &vector.recv_window(), vector.payload);
+        assert_eq!(signature, vector.signature, "{}", vector.name);
+        assert!(vector.verify(), "{}", vector.name);
+    }
+}
+
+#[test]
+fn verify_rejects_tampered_payload() {
+    let vector = sign::VECTORS[0];
+    let payload = format!("{}&limit=1", vector.payload);
+    assert!(!sign::verify(vector.secret, &vector.timestamp(), vector.api_key, &vector.recv_window(), &payload, vector.signature));
+    assert!(!sign::verify(vector.secret, &vector.timestamp(), vector.api_key, &vector.recv_window(), vector.payload, "not hex"));
+}
+
+#[test]
+fn get_params_sign_like_vector() {
+    #[derive(Serialize)]
+    struct Query {
+        category: &'static str,
+        symbol: &'static str,
+    }
+    let vector = sign::VECTORS.iter().find(|vector| vector.name == "get_query").unwrap();
+    let params = Params::Get(Query { category: "option", symbol: "BTC-29JUL22-25000-C" });
+    let signature = sign(vector.secret, &vector.timestamp(), vector.api_key, &vector.recv_window(), &params).unwrap();
+    assert_eq!(signature, vector.signature);
+}
+
+#[test]
+fn post_params_sign_like_vector() {
+    #[derive(Serialize)]
+    struct Transfer {
+        #[serde(rename = "transferId")]
+        transfer_id: &'static str,
+        coin: &'static str,
+        amount: &'static str,
+        #[serde(rename = "fromAccountType")]
+        from_account_type: &'static str,
+        #[serde(rename = "toAccountType")]
+        to_account_type: &'static str,
+    }
+    let vector = sign::VECTORS.iter().find(|vector| vector.name == "post_transfer").unwrap();
+    let params = Params::Post(Transfer {
+        transfer_id: "42c0cfb0-6bca-c242-bc76-4e6df6cbcb16",
+        coin: "USDT",
+        amount: "10",
+        from_account_type: "UNIFIED",
+        to_account_type: "FUND",
+    });
+    let timestamp = DateTime::from_timestamp_millis(vector.timestamp_ms).unwrap();
+    let signature = sign(vector.secret, &timestamp, vector.api_key, &Duration::from_millis(vector.recv_window_ms), &params).unwrap();
+    assert_eq!(signature, vector.signature);
+}
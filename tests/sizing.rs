@@ -0,0 +1,24 @@
+use bybit_rs::{sizing::Contract, Category};
+use rust_decimal::Decimal;
+
+fn dec(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
+
+#[test]
+fn quantities_round_down_to_the_step() {
+    let linear = Contract::new(Category::Linear).with_qty_step(dec("0.001"));
+    assert_eq!(linear.qty_for_notional(dec("1000"), dec("30000")), Some(dec("0.033")));
+    assert_eq!(linear.qty_for_coin(dec("0.0339"), dec("30000")), Some(dec("0.033")));
+
+    let inverse = Contract::new(Category::Inverse).with_multiplier(Decimal::ONE).with_qty_step(dec("10"));
+    assert_eq!(inverse.qty_for_notional(dec("1999"), dec("30000")), Some(dec("1990")));
+    assert_eq!(inverse.qty_for_coin(dec("0.05"), dec("30000")), Some(dec("1500")));
+}
+
+#[test]
+fn no_step_leaves_quantities_alone() {
+    let contract = Contract::new(Category::Linear).with_qty_step(Decimal::ZERO);
+    assert_eq!(contract.qty_for_notional(dec("1000"), dec("30000")), Some(dec("1000") / dec("30000")));
+    assert_eq!(contract.qty_for_notional(dec("1000"), Decimal::ZERO), None);
+}
@@ -0,0 +1,154 @@
+#![cfg(feature = "storage")]
+
+use std::{cell::RefCell, collections::HashMap, time::Duration};
+
+use bybit_rs::{clock::FixedClock, storage::Store, Category, Client, Interval};
+use bytes::Bytes;
+use futures::executor::block_on;
+use serde_json::{json, Value};
+
+const T0: i64 = 1_700_000_000_000;
+const MINUTE: i64 = 60_000;
+const DAY: i64 = 24 * 60 * MINUTE;
+
+// history Bybit would answer with, served by time range like the real endpoints
+#[derive(Default)]
+struct History {
+    klines: Vec<i64>,
+    executions: Vec<(String, i64)>,
+    transactions: Vec<(String, i64)>,
+    // path, startTime and endTime of every request
+    requests: RefCell<Vec<(String, i64, i64)>>,
+}
+
+impl History {
+    fn send(&self, request: http::Request<String>) -> std::future::Ready<Result<Bytes, std::io::Error>> {
+        let params: HashMap<String, String> = request
+            .uri()
+            .query()
+            .unwrap_or_default()
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        let range = |key: &str| params.get(key).map(|value| value.parse::<i64>().unwrap());
+        let (start, end) = (range("startTime").or(range("start")).unwrap(), range("endTime").or(range("end")).unwrap());
+        let path = request.uri().path().to_string();
+        self.requests.borrow_mut().push((path.clone(), start, end));
+        let within = |time: &i64| (start..=end).contains(time);
+        let result = match path.as_str() {
+            "/v5/market/kline" => {
+                let list: Vec<Value> = self.klines.iter().rev().filter(|start| within(start)).map(|start| kline(*start)).collect();
+                json!({ "category": "linear", "symbol": "BTCUSDT", "list": list })
+            }
+            "/v5/execution/list" => {
+                let list: Vec<Value> = self.executions.iter().filter(|(_, time)| within(time)).map(|(id, time)| execution(id, *time)).collect();
+                json!({ "category": "linear", "list": list, "nextPageCursor": "" })
+            }
+            "/v5/account/transaction-log" => {
+                let list: Vec<Value> = self.transactions.iter().filter(|(_, time)| within(time)).map(|(id, time)| transaction(id, *time)).collect();
+                json!({ "list": list, "nextPageCursor": "" })
+            }
+            other => panic!("unexpected request to {other}"),
+        };
+        let body = json!({ "retCode": 0, "retMsg": "OK", "result": result, "retExtInfo": {}, "time": 0 });
+        std::future::ready(Ok(Bytes::from(body.to_string())))
+    }
+
+    fn requests(&self, path: &str) -> Vec<(i64, i64)> {
+        self.requests.borrow().iter().filter(|(requested, ..)| requested == path).map(|(_, start, end)| (*start, *end)).collect()
+    }
+}
+
+fn kline(start: i64) -> Value {
+    json!([start.to_string(), "100", "110", "90", "105", "1", "105"])
+}
+
+fn execution(exec_id: &str, time: i64) -> Value {
+    json!({
+        "symbol": "BTCUSDT", "orderId": "1", "orderLinkId": "", "side": "Buy", "orderPrice": "30000",
+        "orderQty": "1", "leavesQty": "0", "orderType": "Limit", "execId": exec_id, "execPrice": "30000",
+        "execQty": "1", "execValue": "30000", "execFee": "3", "execType": "Trade", "execTime": time.to_string(),
+        "feeRate": "0.0001", "isMaker": true, "markPrice": "30000"
+    })
+}
+
+fn transaction(id: &str, time: i64) -> Value {
+    json!({
+        "id": id, "symbol": "BTCUSDT", "category": "linear", "side": "Buy", "transactionTime": time.to_string(),
+        "type": "SETTLEMENT", "qty": "1", "size": "1", "currency": "USDT", "tradePrice": "30000", "funding": "-0.3",
+        "fee": "", "cashFlow": "0", "change": "-0.3", "cashBalance": "999.7", "feeRate": "", "tradeId": "",
+        "orderId": "", "orderLinkId": ""
+    })
+}
+
+fn client(now: i64) -> Client {
+    Client::new("key".to_string(), "secret".to_string()).with_clock(FixedClock::from_millis(now))
+}
+
+fn recv_window() -> Duration {
+    Duration::from_secs(5)
+}
+
+#[test]
+fn klines_are_only_fetched_from_the_last_synced_one_on() {
+    let store = Store::in_memory().unwrap();
+    let mut history = History { klines: (0..5).map(|minute| T0 + minute * MINUTE).collect(), ..History::default() };
+
+    let written = block_on(client(T0 + 5 * MINUTE).sync_klines(&store, Category::Linear, "BTCUSDT", Interval::Minute1, T0, |request| history.send(request))).unwrap();
+    assert_eq!(written, 5);
+
+    history.klines.extend((5..8).map(|minute| T0 + minute * MINUTE));
+    let written = block_on(client(T0 + 8 * MINUTE).sync_klines(&store, Category::Linear, "BTCUSDT", Interval::Minute1, T0, |request| history.send(request))).unwrap();
+    // the last kline of the first sync may have been forming, it's fetched again
+    assert_eq!(written, 4);
+    assert_eq!(history.requests("/v5/market/kline").last(), Some(&(T0 + 4 * MINUTE, T0 + 8 * MINUTE)));
+
+    let stored = store.klines(Category::Linear, "BTCUSDT", Interval::Minute1, T0, T0 + DAY).unwrap();
+    let starts: Vec<i64> = stored.iter().map(|kline| kline.start.parse().unwrap()).collect();
+    assert_eq!(starts, (0..8).map(|minute| T0 + minute * MINUTE).collect::<Vec<_>>());
+    assert!(store.klines(Category::Linear, "BTCUSDT", Interval::Minute5, T0, T0 + DAY).unwrap().is_empty());
+}
+
+#[test]
+fn executions_sync_in_week_windows_and_resume_where_they_stopped() {
+    let store = Store::in_memory().unwrap();
+    let history = History { executions: vec![("a".to_string(), T0 + DAY), ("b".to_string(), T0 + 9 * DAY)], ..History::default() };
+
+    let added = block_on(client(T0 + 10 * DAY).sync_executions(&store, Category::Linear, T0, &recv_window(), |request| history.send(request))).unwrap();
+    assert_eq!(added, 2);
+    assert_eq!(history.requests("/v5/execution/list"), vec![(T0, T0 + 7 * DAY - 1), (T0 + 7 * DAY, T0 + 10 * DAY)]);
+
+    history.requests.borrow_mut().clear();
+    let added = block_on(client(T0 + 11 * DAY).sync_executions(&store, Category::Linear, T0, &recv_window(), |request| history.send(request))).unwrap();
+    assert_eq!(added, 0);
+    assert_eq!(history.requests("/v5/execution/list"), vec![(T0 + 10 * DAY, T0 + 11 * DAY)]);
+
+    let stored = store.executions(Category::Linear, T0, T0 + 11 * DAY).unwrap();
+    assert_eq!(stored.iter().map(|execution| execution.exec_id.as_str()).collect::<Vec<_>>(), ["a", "b"]);
+    assert_eq!(stored[0].exec_fee, "3");
+    assert!(store.executions(Category::Spot, T0, T0 + 11 * DAY).unwrap().is_empty());
+}
+
+#[test]
+fn the_transaction_log_survives_reopening_the_store() {
+    let path = std::env::temp_dir().join(format!("bybit-rs-storage-{}.sqlite", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let history = History { transactions: vec![("1".to_string(), T0 + DAY), ("2".to_string(), T0 + 2 * DAY)], ..History::default() };
+
+    let store = Store::open(&path).unwrap();
+    let added = block_on(client(T0 + 3 * DAY).sync_transaction_log(&store, T0, &recv_window(), |request| history.send(request))).unwrap();
+    assert_eq!(added, 2);
+    drop(store);
+
+    let store = Store::open(&path).unwrap();
+    history.requests.borrow_mut().clear();
+    block_on(client(T0 + 4 * DAY).sync_transaction_log(&store, T0, &recv_window(), |request| history.send(request))).unwrap();
+    assert_eq!(history.requests("/v5/account/transaction-log"), vec![(T0 + 3 * DAY, T0 + 4 * DAY)]);
+
+    let entries = store.transaction_log(T0, T0 + 4 * DAY).unwrap();
+    assert_eq!(entries.iter().map(|entry| entry.id.as_str()).collect::<Vec<_>>(), ["1", "2"]);
+    assert_eq!(entries[1].funding, "-0.3");
+    assert_eq!(entries[1].kind, "SETTLEMENT");
+    std::fs::remove_file(&path).unwrap();
+}
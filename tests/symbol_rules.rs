@@ -0,0 +1,58 @@
+#![cfg(feature = "market")]
+
+use bybit_rs::market::{RuleViolation, SymbolRules};
+use rust_decimal::Decimal;
+
+fn dec(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
+
+fn rules(tick_size: &str, qty_step: &str) -> SymbolRules {
+    SymbolRules {
+        symbol: "BTCUSDT".to_string(),
+        tick_size: dec(tick_size),
+        qty_step: dec(qty_step),
+        min_qty: dec("0.001"),
+        max_qty: dec("100"),
+        max_market_qty: dec("10"),
+        min_price: dec("0.1"),
+        max_price: dec("1000000"),
+        min_notional: dec("5"),
+    }
+}
+
+#[test]
+fn qty_rounds_down_to_the_step() {
+    let rules = rules("0.1", "0.001");
+    assert_eq!(rules.round_qty(dec("1.23456")), dec("1.234"));
+    assert_eq!(rules.round_qty(dec("1.2349999")), dec("1.234"));
+    assert_eq!(rules.round_qty(dec("0.0009")), Decimal::ZERO);
+    assert_eq!(rules.round_qty(dec("2.000")).to_string(), "2");
+    assert_eq!(self::rules("0.5", "5").round_qty(dec("14.9")), dec("10"));
+}
+
+#[test]
+fn price_rounds_to_the_nearest_tick() {
+    let rules = rules("0.5", "0.001");
+    assert_eq!(rules.round_price(dec("100.24")), dec("100"));
+    assert_eq!(rules.round_price(dec("100.26")), dec("100.5"));
+    assert_eq!(rules.round_price(dec("100.74")), dec("100.5"));
+    assert_eq!(rules.round_price(dec("100.76")), dec("101"));
+    assert_eq!(rules.round_price(dec("100.50")).to_string(), "100.5");
+}
+
+#[test]
+fn zero_steps_leave_values_alone() {
+    let rules = rules("0", "0");
+    assert_eq!(rules.round_qty(dec("1.23456")), dec("1.23456"));
+    assert_eq!(rules.round_price(dec("100.24")), dec("100.24"));
+}
+
+#[test]
+fn rounded_orders_pass_validation() {
+    let rules = rules("0.1", "0.001");
+    let (qty, price) = (rules.round_qty(dec("0.12345")), rules.round_price(dec("30000.04")));
+    assert_eq!(rules.validate(qty, Some(price), false), Ok(()));
+    assert_eq!(rules.validate(dec("0.12345"), Some(price), false), Err(RuleViolation::QtyStep { qty: dec("0.12345"), step: dec("0.001") }));
+    assert_eq!(rules.validate(dec("20"), None, true), Err(RuleViolation::QtyTooLarge { qty: dec("20"), max: dec("10") }));
+}
@@ -0,0 +1,60 @@
+use std::{sync::Mutex, time::Duration};
+
+use bybit_rs::{raw::RawEndpoint, retry::RetryPolicy, Client, CorrelationId, Error};
+use bytes::Bytes;
+use futures::executor::block_on;
+
+fn client() -> Client {
+    Client::new("key".to_string(), "secret".to_string())
+}
+
+fn response(status: u16, body: &'static str) -> http::Response<Bytes> {
+    http::Response::builder().status(status).header("Traceid", "a1b2c3").body(Bytes::from_static(body.as_bytes())).unwrap()
+}
+
+#[test]
+fn api_errors_carry_the_trace_and_correlation_ids() {
+    let request = client().raw::<_, serde_json::Value>(&RawEndpoint::get("/v5/order/realtime", ()), &Duration::from_secs(5)).unwrap();
+    let correlation_id = request.correlation_id().unwrap();
+    let seen = Mutex::new(None);
+    let send = |request: http::Request<String>| {
+        *seen.lock().unwrap() = CorrelationId::of(&request);
+        async { Ok::<_, std::io::Error>(response(200, r#"{"retCode":10001,"retMsg":"params error"}"#)) }
+    };
+
+    let Err(Error::Api(err)) = block_on(request.send_http(send)) else {
+        panic!("expected an api error");
+    };
+    assert_eq!(*seen.lock().unwrap(), Some(correlation_id));
+    assert_eq!(err.correlation_id(), Some(correlation_id));
+    assert_eq!(err.trace_id(), Some("a1b2c3"));
+    assert!(err.to_string().contains("a1b2c3"));
+}
+
+#[test]
+fn responses_expose_the_trace_id() {
+    let request = client().raw::<_, serde_json::Value>(&RawEndpoint::get("/v5/market/time", ()).unsigned(), &Duration::from_secs(5)).unwrap();
+    let correlation_id = request.correlation_id().unwrap();
+    let send = |_| async { Ok::<_, std::io::Error>(response(200, r#"{"retCode":0,"retMsg":"OK","result":{},"time":0}"#)) };
+
+    let response = block_on(request.send_http(send)).unwrap();
+    assert_eq!(response.correlation_id, correlation_id);
+    assert_eq!(response.trace_id(), Some("a1b2c3"));
+}
+
+#[test]
+fn retries_keep_the_correlation_id() {
+    let client = client().with_retry_policy(RetryPolicy::new(2).with_backoff(Duration::ZERO, Duration::ZERO));
+    let request = client.raw::<_, serde_json::Value>(&RawEndpoint::get("/v5/order/realtime", ()), &Duration::from_secs(5)).unwrap();
+    let correlation_id = request.correlation_id().unwrap();
+    let seen = Mutex::new(Vec::new());
+    let send = |request: http::Request<String>| {
+        let mut seen = seen.lock().unwrap();
+        seen.push(CorrelationId::of(&request));
+        let status = if seen.len() == 1 { 502 } else { 200 };
+        async move { Ok::<_, std::io::Error>(response(status, r#"{"retCode":0,"retMsg":"OK","result":{},"time":0}"#)) }
+    };
+
+    block_on(request.send(client.transport(&send))).unwrap();
+    assert_eq!(*seen.lock().unwrap(), vec![Some(correlation_id); 2]);
+}
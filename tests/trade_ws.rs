@@ -0,0 +1,149 @@
+#![cfg(all(feature = "ws", feature = "trade"))]
+
+use std::{
+    pin::Pin,
+    sync::Mutex,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use bybit_rs::{
+    clock::FixedClock,
+    trade::{AmendOrderRequest, CancelOrderRequest, OrderRef},
+    ws::{TradeRequest, TradeWsClient},
+    Category, Client,
+};
+use bytes::Bytes;
+use futures::{
+    channel::mpsc::{self, SendError, UnboundedReceiver, UnboundedSender},
+    executor::block_on,
+    Sink, Stream,
+};
+use rust_decimal::Decimal;
+
+const AUTH_OK: &str = r#"{"op":"auth","retCode":0,"retMsg":"OK","connId":"c1"}"#;
+const REST_ACK: &str = r#"{"retCode":0,"retMsg":"OK","result":{"orderId":"rest","orderLinkId":""},"time":0}"#;
+
+struct Conn {
+    incoming: UnboundedReceiver<Result<String, SendError>>,
+    outgoing: UnboundedSender<String>,
+}
+
+impl Stream for Conn {
+    type Item = Result<String, SendError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.incoming).poll_next(cx)
+    }
+}
+
+impl Sink<String> for Conn {
+    type Error = SendError;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError>> {
+        Pin::new(&mut self.outgoing).poll_ready(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: String) -> Result<(), SendError> {
+        Pin::new(&mut self.outgoing).start_send(item)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError>> {
+        Pin::new(&mut self.outgoing).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), SendError>> {
+        Pin::new(&mut self.outgoing).poll_close(cx)
+    }
+}
+
+fn dec(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
+
+fn page(status: &str, price: &str) -> String {
+    format!(
+        r#"{{"retCode":0,"retMsg":"OK","result":{{"category":"linear","nextPageCursor":"","list":[{{
+            "orderId":"1","orderLinkId":"","symbol":"BTCUSDT","side":"Buy","orderType":"Limit","price":"{price}","qty":"10",
+            "orderStatus":"{status}","timeInForce":"GTC","positionIdx":0,"avgPrice":"0","leavesQty":"10",
+            "cumExecQty":"0","cumExecValue":"0","cumExecFee":"0","cancelType":"UNKNOWN","rejectReason":"EC_NoError",
+            "stopOrderType":"","triggerPrice":"0","takeProfit":"0","stopLoss":"0","reduceOnly":false,
+            "createdTime":"1700000000000","updatedTime":"1700000000000"}}]}},"time":0}}"#
+    )
+}
+
+// the connection drops right after the request goes out, so its outcome has to be looked up
+fn route(request: TradeRequest, lookup: String) -> (String, Vec<String>) {
+    let (server, incoming) = mpsc::unbounded();
+    let (outgoing, _sent) = mpsc::unbounded();
+    server.unbounded_send(Ok(AUTH_OK.to_string())).unwrap();
+    let mut ws = block_on(TradeWsClient::connect(Conn { incoming, outgoing }, "key", "secret")).unwrap();
+    drop(server);
+
+    let resent = Mutex::new(Vec::new());
+    let send = |request: http::Request<String>| {
+        let path = request.uri().path().to_string();
+        let body = match path.as_str() {
+            "/v5/order/realtime" => lookup.clone(),
+            _ => {
+                resent.lock().unwrap().push(path);
+                REST_ACK.to_string()
+            }
+        };
+        async move { Ok::<_, std::io::Error>(Bytes::from(body)) }
+    };
+    let client = Client::new("key".to_string(), "secret".to_string());
+    let response = block_on(client.route_order(Some(&mut ws), request, &Duration::from_secs(5), send)).unwrap();
+    (response.order_id, resent.into_inner().unwrap())
+}
+
+fn cancel() -> TradeRequest {
+    TradeRequest::Cancel(CancelOrderRequest::new(Category::Linear, "BTCUSDT", OrderRef::OrderId("1".to_string())))
+}
+
+fn amend() -> TradeRequest {
+    TradeRequest::Amend(AmendOrderRequest::new(Category::Linear, "BTCUSDT", OrderRef::OrderId("1".to_string())).with_price(dec("99")))
+}
+
+#[test]
+fn an_applied_cancel_returns_the_found_order() {
+    let (order_id, resent) = route(cancel(), page("Cancelled", "100"));
+    assert_eq!(order_id, "1");
+    assert!(resent.is_empty());
+}
+
+#[test]
+fn a_cancel_that_did_not_apply_is_resent_over_rest() {
+    let (order_id, resent) = route(cancel(), page("New", "100"));
+    assert_eq!(order_id, "rest");
+    assert_eq!(resent, ["/v5/order/cancel"]);
+}
+
+#[test]
+fn an_applied_amend_returns_the_found_order() {
+    let (order_id, resent) = route(amend(), page("New", "99"));
+    assert_eq!(order_id, "1");
+    assert!(resent.is_empty());
+}
+
+#[test]
+fn an_amend_that_did_not_apply_is_resent_over_rest() {
+    let (order_id, resent) = route(amend(), page("New", "100"));
+    assert_eq!(order_id, "rest");
+    assert_eq!(resent, ["/v5/order/amend"]);
+}
+
+#[test]
+fn auth_and_request_timestamps_come_from_the_client_clock() {
+    let (server, incoming) = mpsc::unbounded();
+    let (outgoing, mut sent) = mpsc::unbounded();
+    server.unbounded_send(Ok(AUTH_OK.to_string())).unwrap();
+    let client = Client::new("key".to_string(), "secret".to_string()).with_clock(FixedClock::from_millis(1700000000000));
+    let mut ws = block_on(client.trade_ws(Conn { incoming, outgoing })).unwrap();
+    block_on(ws.submit(cancel())).unwrap();
+
+    let auth: serde_json::Value = serde_json::from_str(&sent.try_recv().unwrap()).unwrap();
+    assert_eq!(auth["args"][1], 1700000010000_i64);
+    let request: serde_json::Value = serde_json::from_str(&sent.try_recv().unwrap()).unwrap();
+    assert_eq!(request["header"]["X-BAPI-TIMESTAMP"], "1700000000000");
+}
@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use bybit_rs::execution::{Twap, TwapState};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+fn dec(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
+
+fn start() -> DateTime<Utc> {
+    DateTime::from_timestamp(1_700_000_000, 0).unwrap()
+}
+
+fn at(minutes: i64) -> DateTime<Utc> {
+    start() + chrono::Duration::minutes(minutes)
+}
+
+#[test]
+fn slices_follow_the_schedule() {
+    let mut twap = Twap::new(dec("9"), Duration::from_secs(180), 3, start());
+    let first = twap.poll(at(0)).unwrap();
+    assert_eq!((first.index, first.qty), (0, dec("3")));
+    assert!(twap.poll(at(0)).is_none());
+    assert_eq!(twap.next_due(), Some(at(1)));
+    twap.on_fill(dec("3"));
+    assert_eq!(twap.poll(at(1)).unwrap().qty, dec("3"));
+    twap.on_fill(dec("3"));
+    assert_eq!(twap.poll(at(2)).unwrap().qty, dec("3"));
+    twap.on_fill(dec("3"));
+    assert_eq!(twap.state(), TwapState::Completed);
+}
+
+#[test]
+fn remainder_below_one_step_completes() {
+    let mut twap = Twap::new(dec("10.5"), Duration::from_secs(180), 3, start()).with_qty_step(dec("1"));
+    let mut sent = Vec::new();
+    for minute in 0..3 {
+        let slice = twap.poll(at(minute)).unwrap();
+        twap.on_fill(slice.qty);
+        sent.push(slice.qty);
+    }
+    assert_eq!(sent, [dec("3"), dec("4"), dec("3")]);
+    assert_eq!(twap.state(), TwapState::Completed);
+    assert_eq!(twap.remaining(), dec("0.5"));
+    assert!(twap.poll(at(10)).is_none());
+}
+
+#[test]
+fn quantity_released_after_the_last_slice_is_resent() {
+    let mut twap = Twap::new(dec("9"), Duration::from_secs(180), 3, start()).with_min_gap(Duration::from_secs(30));
+    for minute in 0..3 {
+        twap.poll(at(minute)).unwrap();
+    }
+    twap.on_fill(dec("6"));
+    twap.on_release(dec("3"));
+    assert_eq!(twap.state(), TwapState::Running);
+    assert_eq!(twap.next_due(), Some(at(2) + chrono::Duration::seconds(30)));
+
+    let catch_up = twap.poll(at(5)).unwrap();
+    assert_eq!((catch_up.index, catch_up.qty), (3, dec("3")));
+    twap.on_fill(dec("3"));
+    assert_eq!(twap.state(), TwapState::Completed);
+}
+
+#[test]
+fn paused_twap_sends_nothing_until_resumed() {
+    let mut twap = Twap::new(dec("4"), Duration::from_secs(120), 2, start());
+    twap.pause();
+    assert!(twap.poll(at(5)).is_none());
+    twap.resume();
+    // missed slices go out back to back once resumed
+    assert_eq!(twap.poll(at(5)).unwrap().qty, dec("2"));
+    assert_eq!(twap.poll(at(5)).unwrap().qty, dec("2"));
+    twap.cancel();
+    assert_eq!(twap.state(), TwapState::Cancelled);
+}
+
+#[test]
+fn fills_after_a_cancel_leave_it_cancelled() {
+    let mut twap = Twap::new(dec("4"), Duration::from_secs(120), 2, start());
+    twap.poll(at(0)).unwrap();
+    twap.poll(at(1)).unwrap();
+    twap.cancel();
+    twap.on_fill(dec("4"));
+    assert_eq!(twap.state(), TwapState::Cancelled);
+    assert_eq!(twap.filled(), dec("4"));
+}
@@ -0,0 +1,40 @@
+#![cfg(feature = "asset")]
+
+use std::{sync::Mutex, time::Duration};
+
+use bybit_rs::{
+    asset::{WithdrawAccount, WithdrawRequest},
+    clock::FixedClock,
+    Client,
+};
+use bytes::Bytes;
+use chrono::DateTime;
+use futures::executor::block_on;
+use rust_decimal::Decimal;
+
+fn timestamp(request: &WithdrawRequest) -> serde_json::Value {
+    let body = Mutex::new(String::new());
+    let send = |request: http::Request<String>| {
+        *body.lock().unwrap() = request.body().clone();
+        async { Ok::<_, std::io::Error>(Bytes::from_static(br#"{"retCode":0,"retMsg":"OK","result":{"id":"1"},"time":0}"#)) }
+    };
+    let client = Client::new("key".to_string(), "secret".to_string()).with_clock(FixedClock::from_millis(1700000000000));
+    block_on(client.withdraw(request, &Duration::from_secs(5)).unwrap().send(send)).unwrap();
+    let body: serde_json::Value = serde_json::from_str(&body.into_inner().unwrap()).unwrap();
+    body["timestamp"].clone()
+}
+
+fn request() -> WithdrawRequest {
+    WithdrawRequest::new("USDT", "ETH", "0xabc", Decimal::TEN, WithdrawAccount::Fund)
+}
+
+#[test]
+fn an_unset_timestamp_comes_from_the_client_clock() {
+    assert_eq!(timestamp(&request()), 1700000000000_i64);
+}
+
+#[test]
+fn a_set_timestamp_is_kept_for_resends() {
+    let request = request().with_timestamp(DateTime::from_timestamp_millis(1600000000000).unwrap());
+    assert_eq!(timestamp(&request), 1600000000000_i64);
+}